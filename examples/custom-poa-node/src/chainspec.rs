@@ -3,19 +3,459 @@
 //! This module defines the chain specification for a POA network that maintains
 //! full compatibility with Ethereum mainnet's EVM and hardforks.
 
+use crate::{
+    config::PoaNodeConfigFile,
+    consensus::snapshot::Snapshot,
+    deposit::DEPOSIT_EVENT_TOPIC,
+    genesis::{compute_state_root, validate_genesis, GenesisConfig, GenesisValidationError},
+    presets,
+    sealing::{EXTRA_SEAL_LENGTH, EXTRA_VANITY_LENGTH},
+};
 use alloy_consensus::Header;
-use alloy_eips::eip7840::BlobParams;
-use alloy_genesis::Genesis;
-use alloy_primitives::{Address, B256, U256};
+use alloy_eips::{
+    eip1559::calculate_block_gas_limit, eip7840::BlobParams, eip7892::BlobScheduleBlobParams,
+};
+use alloy_genesis::{ChainConfig, Genesis};
+use alloy_primitives::{keccak256, Address, Selector, B256, B64, U256};
 use reth_chainspec::{
     BaseFeeParams, BaseFeeParamsKind, Chain, ChainHardforks, ChainSpec, DepositContract,
-    EthChainSpec, EthereumHardforks, ForkCondition, ForkFilter, ForkId, Hardfork, Hardforks, Head,
+    EthChainSpec, EthereumHardforks, ForkCondition, ForkFilter, ForkFilterKey, ForkId, Hardfork,
+    Hardforks, Head,
 };
 use reth_ethereum_forks::EthereumHardfork;
-use reth_network_peers::NodeRecord;
+use reth_network_peers::{NodeRecord, NodeRecordParseError};
 use reth_primitives_traits::SealedHeader;
-use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::error::Category;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::Path,
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// Errors from building a [`PoaChainSpec`] out of a chain definition
+#[derive(Debug, Error)]
+pub enum PoaChainSpecError {
+    /// The chain definition file could not be read
+    #[error("failed to read chain definition: {0}")]
+    Io(#[from] io::Error),
+
+    /// The chain definition is not well-formed JSON
+    #[error("chain definition is not valid JSON: {0}")]
+    Syntax(#[source] serde_json::Error),
+
+    /// The JSON does not describe a genesis or POA config, e.g. a field is missing or an address
+    /// is malformed
+    #[error("invalid chain definition: {0}")]
+    InvalidDefinition(#[source] serde_json::Error),
+
+    /// The genesis config has no `clique` section, as in a plain, non-POA genesis
+    #[error("genesis config has no clique section, so it is not a POA genesis")]
+    MissingClique,
+
+    /// A required field of the `clique` section is missing
+    #[error("clique config is missing `{0}`")]
+    MissingCliqueField(&'static str),
+
+    /// Genesis extra data is not vanity, signer addresses and seal
+    #[error(
+        "genesis extra data of {0} bytes is not a {EXTRA_VANITY_LENGTH} byte vanity, signer \
+         addresses and a {EXTRA_SEAL_LENGTH} byte seal"
+    )]
+    InvalidExtraData(usize),
+
+    /// A bootnode is not a valid `enode://` URL
+    #[error("invalid bootnode `{enode}`: {reason}")]
+    InvalidBootnode {
+        /// The offending bootnode
+        enode: String,
+        /// What is wrong with it
+        reason: String,
+    },
+
+    /// The POA config the definition describes is invalid
+    #[error(transparent)]
+    Config(#[from] PoaConfigError),
+}
+
+/// Longest block period [`PoaChainSpec::try_new`] accepts, in seconds
+pub const MAX_PERIOD: u64 = 3600;
+
+/// Invariants of a [`PoaConfig`] violated, as reported by [`PoaChainSpec::try_new`]
+#[derive(Debug, Error)]
+pub enum PoaConfigError {
+    /// No signers are listed
+    #[error("signers: at least one signer is required")]
+    NoSigners,
+
+    /// A signer is listed more than once
+    #[error("signers: {0} is listed more than once")]
+    DuplicateSigner(Address),
+
+    /// The zero address is listed as a signer
+    #[error("signers: the zero address can't sign blocks")]
+    ZeroSigner,
+
+    /// Epoch length of zero
+    #[error("epoch: must be greater than zero")]
+    ZeroEpoch,
+
+    /// Block period above [`MAX_PERIOD`]
+    #[error("period: {0} seconds exceeds the maximum of {MAX_PERIOD}")]
+    PeriodTooLong(u64),
+
+    /// A hardfork activates before one that precedes it
+    #[error("hardforks: {fork} activates before {previous}")]
+    HardforkOutOfOrder {
+        /// The hardfork scheduled too early
+        fork: EthereumHardfork,
+        /// The preceding hardfork it must not activate before
+        previous: EthereumHardfork,
+    },
+
+    /// A hardfork is enabled although one that precedes it never activates
+    #[error("hardforks: {fork} is enabled but the preceding {disabled} is disabled")]
+    HardforkAfterDisabled {
+        /// The enabled hardfork
+        fork: EthereumHardfork,
+        /// The preceding hardfork that never activates
+        disabled: EthereumHardfork,
+    },
+
+    /// An upgrade scheduled at or before the timestamp of the chain head
+    #[error("hardforks: {fork} at {timestamp} is not after the head at {head}")]
+    UpgradeNotInFuture {
+        /// The scheduled hardfork
+        fork: EthereumHardfork,
+        /// Its activation timestamp
+        timestamp: u64,
+        /// Timestamp of the chain head
+        head: u64,
+    },
+
+    /// An upgrade scheduled at or before the activation of another hardfork
+    #[error("hardforks: {fork} at {timestamp} is not after {previous} at {activation}")]
+    UpgradeNotAfterActivation {
+        /// The scheduled hardfork
+        fork: EthereumHardfork,
+        /// Its activation timestamp
+        timestamp: u64,
+        /// The hardfork activating at or after it
+        previous: EthereumHardfork,
+        /// Activation timestamp of `previous`
+        activation: u64,
+    },
+
+    /// Base fee parameters with a zero denominator or elasticity multiplier
+    #[error(
+        "baseFeeParams: max change denominator {max_change_denominator} and elasticity \
+         multiplier {elasticity_multiplier} must be non-zero"
+    )]
+    InvalidBaseFeeParams {
+        /// Configured max change denominator
+        max_change_denominator: u128,
+        /// Configured elasticity multiplier
+        elasticity_multiplier: u128,
+    },
+
+    /// Base fee parameters are tied to a hardfork that doesn't activate by timestamp
+    #[error(
+        "baseFeeParamsForks: {0} is not activated by timestamp, so its parameters never apply"
+    )]
+    BaseFeeForkNotTimestamp(EthereumHardfork),
+
+    /// Custom base fee parameters alongside the zero base fee mode
+    #[error("zeroBaseFee: can't be combined with custom base fee parameters")]
+    ZeroBaseFeeParams,
+
+    /// A blob count override with a target above its maximum
+    #[error("blobParams: {fork} blob target {target} exceeds the maximum of {max} blobs")]
+    InvalidBlobCounts {
+        /// The hardfork the override applies to
+        fork: EthereumHardfork,
+        /// Configured target blob count
+        target: u64,
+        /// Configured maximum blob count
+        max: u64,
+    },
+
+    /// A prune delete limit of zero, which would stop the pruner from making progress
+    #[error("advanced.pruneDeleteLimit: must be at least 1")]
+    ZeroPruneDeleteLimit,
+
+    /// A pre-activation total difficulty on a chain that was POA from genesis on
+    #[error("preActivationTtd: requires poaActivationBlock")]
+    TtdWithoutActivation,
+
+    /// The genesis doesn't match the config
+    #[error("genesis {0}")]
+    Genesis(#[from] GenesisValidationError),
+}
+
+/// Base fee parameters of the zero base fee mode: a gas target equal to the gas limit never
+/// raises a base fee of zero
+const ZERO_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams::new(8, 1);
+
+/// Activation condition of every Ethereum hardfork the chain enables.
+///
+/// Hardforks without an entry never activate. In a chain definition only the hardforks that
+/// differ from the default need to be listed, e.g. `{"Cancun": {"Timestamp": 1800000000}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct HardforkSchedule(BTreeMap<EthereumHardfork, ForkCondition>);
+
+impl HardforkSchedule {
+    /// Schedule `fork` at `condition`, replacing its previous activation
+    pub fn with_fork(mut self, fork: EthereumHardfork, condition: ForkCondition) -> Self {
+        self.0.insert(fork, condition);
+        self
+    }
+
+    /// The hardforks a geth chain config activates, the way reth reads them from a genesis.
+    ///
+    /// Frontier is active from genesis on; hardforks the config doesn't mention get no entry.
+    pub fn from_chain_config(config: &ChainConfig) -> Self {
+        let blocks = [
+            (EthereumHardfork::Homestead, config.homestead_block),
+            (EthereumHardfork::Dao, config.dao_fork_block),
+            (EthereumHardfork::Tangerine, config.eip150_block),
+            (EthereumHardfork::SpuriousDragon, config.eip155_block),
+            (EthereumHardfork::Byzantium, config.byzantium_block),
+            (EthereumHardfork::Constantinople, config.constantinople_block),
+            (EthereumHardfork::Petersburg, config.petersburg_block),
+            (EthereumHardfork::Istanbul, config.istanbul_block),
+            (EthereumHardfork::MuirGlacier, config.muir_glacier_block),
+            (EthereumHardfork::Berlin, config.berlin_block),
+            (EthereumHardfork::London, config.london_block),
+            (EthereumHardfork::ArrowGlacier, config.arrow_glacier_block),
+            (EthereumHardfork::GrayGlacier, config.gray_glacier_block),
+        ]
+        .map(|(fork, block)| (fork, block.map(ForkCondition::Block)));
+        let paris = config.terminal_total_difficulty.map(|total_difficulty| ForkCondition::TTD {
+            activation_block_number: config.merge_netsplit_block.unwrap_or_default(),
+            fork_block: config.merge_netsplit_block,
+            total_difficulty,
+        });
+        let timestamps = [
+            (EthereumHardfork::Shanghai, config.shanghai_time),
+            (EthereumHardfork::Cancun, config.cancun_time),
+            (EthereumHardfork::Prague, config.prague_time),
+            (EthereumHardfork::Osaka, config.osaka_time),
+        ]
+        .map(|(fork, timestamp)| (fork, timestamp.map(ForkCondition::Timestamp)));
+
+        let forks = std::iter::once((EthereumHardfork::Frontier, Some(ForkCondition::Block(0))))
+            .chain(blocks)
+            .chain([(EthereumHardfork::Paris, paris)])
+            .chain(timestamps)
+            .filter_map(|(fork, condition)| Some((fork, condition?)));
+        Self(forks.collect())
+    }
+
+    /// Activation condition of `fork`
+    pub fn fork(&self, fork: EthereumHardfork) -> ForkCondition {
+        self.0.get(&fork).copied().unwrap_or(ForkCondition::Never)
+    }
+
+    /// Hardforks with an entry, including the ones explicitly disabled
+    pub fn forks(&self) -> impl Iterator<Item = EthereumHardfork> + '_ {
+        self.0.keys().copied()
+    }
+
+    /// Check that no hardfork activates before one that precedes it.
+    ///
+    /// Block activations come before timestamp activations, and a fork that never activates may
+    /// only be followed by others that never activate, so e.g. a chain can stay on Shanghai by
+    /// disabling Cancun and Prague but not Cancun alone.
+    pub fn validate(&self) -> Result<(), PoaConfigError> {
+        let mut previous: Option<(EthereumHardfork, (u8, u64))> = None;
+        for (fork, condition) in &self.0 {
+            let key = match *condition {
+                ForkCondition::Block(block) |
+                ForkCondition::TTD { activation_block_number: block, .. } => (0, block),
+                ForkCondition::Timestamp(timestamp) => (1, timestamp),
+                ForkCondition::Never => (2, 0),
+            };
+            if let Some((previous, previous_key)) = previous &&
+                key < previous_key
+            {
+                return Err(if previous_key.0 == 2 {
+                    PoaConfigError::HardforkAfterDisabled { fork: *fork, disabled: previous }
+                } else {
+                    PoaConfigError::HardforkOutOfOrder { fork: *fork, previous }
+                });
+            }
+            previous = Some((*fork, key));
+        }
+        Ok(())
+    }
+
+    /// Hardforks in activation order, leaving out the ones that never activate
+    fn chain_hardforks(&self) -> ChainHardforks {
+        ChainHardforks::new(
+            self.0
+                .iter()
+                .filter(|(_, condition)| **condition != ForkCondition::Never)
+                .map(|(&fork, &condition)| (Box::new(fork) as Box<dyn Hardfork>, condition))
+                .collect(),
+        )
+    }
+}
+
+impl Default for HardforkSchedule {
+    /// Every hardfork up to Prague active at genesis, for full mainnet EVM compatibility
+    fn default() -> Self {
+        let forks = [
+            // Block-based hardforks (all at block 0)
+            (EthereumHardfork::Frontier, ForkCondition::Block(0)),
+            (EthereumHardfork::Homestead, ForkCondition::Block(0)),
+            (EthereumHardfork::Tangerine, ForkCondition::Block(0)),
+            (EthereumHardfork::SpuriousDragon, ForkCondition::Block(0)),
+            (EthereumHardfork::Byzantium, ForkCondition::Block(0)),
+            (EthereumHardfork::Constantinople, ForkCondition::Block(0)),
+            (EthereumHardfork::Petersburg, ForkCondition::Block(0)),
+            (EthereumHardfork::Istanbul, ForkCondition::Block(0)),
+            (EthereumHardfork::Berlin, ForkCondition::Block(0)),
+            (EthereumHardfork::London, ForkCondition::Block(0)),
+            // The Merge - we use TTD of 0 since POA doesn't have proof of work
+            (
+                EthereumHardfork::Paris,
+                ForkCondition::TTD {
+                    activation_block_number: 0,
+                    fork_block: None,
+                    total_difficulty: U256::ZERO,
+                },
+            ),
+            // Timestamp-based hardforks (all at timestamp 0)
+            (EthereumHardfork::Shanghai, ForkCondition::Timestamp(0)),
+            (EthereumHardfork::Cancun, ForkCondition::Timestamp(0)),
+            (EthereumHardfork::Prague, ForkCondition::Timestamp(0)),
+        ];
+        Self(forks.into_iter().collect())
+    }
+}
+
+impl<'de> Deserialize<'de> for HardforkSchedule {
+    /// Entries override the default schedule
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let overrides = BTreeMap::<EthereumHardfork, ForkCondition>::deserialize(deserializer)?;
+        let mut schedule = Self::default();
+        schedule.0.extend(overrides);
+        Ok(schedule)
+    }
+}
+
+/// Where the authorized signer set comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SignerSetSource {
+    /// Signers vote accounts in and out through header nonces, like clique
+    #[default]
+    Votes,
+    /// A governance contract decides the signer set at every epoch block.
+    ///
+    /// The producer of an epoch block calls `selector` (a view function returning `address[]`,
+    /// such as `getValidators()`) on `address` against the parent state and writes the result into
+    /// the checkpoint extra data. Header votes are ignored.
+    Contract {
+        /// Address of the governance contract
+        address: Address,
+        /// Selector of the view function returning the signer set
+        selector: Selector,
+    },
+}
+
+/// Target and maximum number of blobs per block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobCounts {
+    /// Blobs per block the blob fee steers towards
+    pub target: u64,
+    /// Most blobs a block may carry, also the most a single transaction may carry
+    pub max: u64,
+}
+
+impl BlobCounts {
+    /// `params` with these counts instead of its own
+    pub const fn apply(self, mut params: BlobParams) -> BlobParams {
+        params.target_blob_count = self.target;
+        params.max_blob_count = self.max;
+        params.max_blobs_per_tx = self.max;
+        params
+    }
+}
+
+impl From<BlobParams> for BlobCounts {
+    fn from(params: BlobParams) -> Self {
+        Self { target: params.target_blob_count, max: params.max_blob_count }
+    }
+}
+
+/// Blob counts replacing Ethereum's for the blob-carrying hardforks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobScheduleOverride {
+    /// Blob counts from Cancun on, Ethereum's if unset
+    pub cancun: Option<BlobCounts>,
+    /// Blob counts from Prague on, Ethereum's if unset
+    pub prague: Option<BlobCounts>,
+}
+
+impl BlobScheduleOverride {
+    /// The blob counts the `blobSchedule` of a geth chain config sets, `None` if it has Ethereum's
+    /// for every fork.
+    ///
+    /// Only target and maximum are read, the update fraction is always Ethereum's.
+    pub fn from_chain_config(config: &ChainConfig) -> Option<Self> {
+        let counts = |fork: EthereumHardfork, ethereum: BlobParams| {
+            let counts = BlobCounts::from(*config.blob_schedule.get(&fork.name().to_lowercase())?);
+            (counts != ethereum.into()).then_some(counts)
+        };
+        let schedule = Self {
+            cancun: counts(EthereumHardfork::Cancun, BlobParams::cancun()),
+            prague: counts(EthereumHardfork::Prague, BlobParams::prague()),
+        };
+        (schedule != Self::default()).then_some(schedule)
+    }
+
+    /// Ethereum's blob schedule with the configured counts applied
+    fn blob_params(&self) -> Result<BlobScheduleBlobParams, PoaConfigError> {
+        let mut schedule = BlobScheduleBlobParams::default();
+        let forks = [
+            (EthereumHardfork::Cancun, self.cancun, &mut schedule.cancun),
+            (EthereumHardfork::Prague, self.prague, &mut schedule.prague),
+        ];
+        for (fork, counts, params) in forks {
+            let Some(counts) = counts else { continue };
+            if counts.target > counts.max {
+                return Err(PoaConfigError::InvalidBlobCounts {
+                    fork,
+                    target: counts.target,
+                    max: counts.max,
+                });
+            }
+            *params = counts.apply(*params);
+        }
+        Ok(schedule)
+    }
+}
+
+/// Tuning of the inner [`ChainSpec`] that rarely needs to change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AdvancedConfig {
+    /// Maximum number of entries the pruner deletes per run
+    pub prune_delete_limit: usize,
+    /// Block the chain merged at and its final total difficulty; POA chains are post-merge from
+    /// genesis on
+    pub paris_block_and_final_difficulty: Option<(u64, U256)>,
+}
+
+impl Default for AdvancedConfig {
+    fn default() -> Self {
+        Self { prune_delete_limit: 10000, paris_block_and_final_difficulty: Some((0, U256::ZERO)) }
+    }
+}
 
 /// POA-specific configuration that extends the standard chain config
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +467,88 @@ pub struct PoaConfig {
     pub epoch: u64,
     /// List of authorized signer addresses
     pub signers: Vec<Address>,
+    /// Require child timestamps to be exactly `parent + period` instead of at least that
+    #[serde(default)]
+    pub exact_period: bool,
+    /// How the signer set changes after genesis
+    #[serde(default)]
+    pub signer_set_source: SignerSetSource,
+    /// Share of the in-turn slots per signer, see
+    /// [`Snapshot::schedule`](crate::consensus::snapshot::Snapshot::schedule).
+    ///
+    /// Signers without an entry have weight one, so an empty list is plain round-robin.
+    #[serde(default)]
+    pub signers_weighted: Vec<(Address, u32)>,
+    /// When each Ethereum hardfork activates
+    #[serde(default)]
+    pub hardforks: HardforkSchedule,
+    /// EIP-1559 base fee parameters from genesis on, Ethereum's if unset
+    #[serde(default)]
+    pub base_fee_params: Option<BaseFeeParams>,
+    /// Base fee parameters taking over once a timestamp-activated hardfork is active
+    #[serde(default)]
+    pub base_fee_params_forks: BTreeMap<EthereumHardfork, BaseFeeParams>,
+    /// Pin the base fee to zero so transactions can be sent without paying for gas
+    #[serde(default)]
+    pub zero_base_fee: bool,
+    /// Blob counts per hardfork, Ethereum's if unset
+    #[serde(default)]
+    pub blob_params: Option<BlobScheduleOverride>,
+    /// Peers to connect to on startup, as `enode://` URLs
+    #[serde(default, deserialize_with = "deserialize_bootnodes")]
+    pub bootnodes: Vec<NodeRecord>,
+    /// Derive the [`ForkId`] like vanilla Ethereum clients do, without the
+    /// [`poa_params_digest`](PoaChainSpec::poa_params_digest), to peer with them
+    #[serde(default)]
+    pub compat_forkid: bool,
+    /// Deposit contract reported through [`EthChainSpec::deposit_contract`]; the topic defaults to
+    /// [`DEPOSIT_EVENT_TOPIC`] in a chain definition
+    #[serde(default, with = "serde_deposit_contract")]
+    pub deposit_contract: Option<DepositContract>,
+    /// Tuning of the inner chain spec
+    #[serde(default)]
+    pub advanced: AdvancedConfig,
+    /// Gas limit block production moves towards, the genesis gas limit if unset
+    #[serde(default)]
+    pub gas_limit_target: Option<u64>,
+    /// First block sealed under POA, for chains that ran another engine (such as proof of work)
+    /// before; POA from genesis on if unset
+    #[serde(default)]
+    pub poa_activation_block: Option<u64>,
+    /// Total difficulty the chain reached before
+    /// [`poa_activation_block`](Self::poa_activation_block), which becomes its terminal total
+    /// difficulty; zero if unset
+    #[serde(default)]
+    pub pre_activation_ttd: Option<U256>,
+}
+
+impl PoaConfig {
+    /// Check the invariants every chain must satisfy
+    pub fn validate(&self) -> Result<(), PoaConfigError> {
+        check_signers(&self.signers)?;
+        if self.epoch == 0 {
+            return Err(PoaConfigError::ZeroEpoch);
+        }
+        if self.period > MAX_PERIOD {
+            return Err(PoaConfigError::PeriodTooLong(self.period));
+        }
+        if self.advanced.prune_delete_limit == 0 {
+            return Err(PoaConfigError::ZeroPruneDeleteLimit);
+        }
+        if self.pre_activation_ttd.is_some() && self.poa_activation_block.is_none() {
+            return Err(PoaConfigError::TtdWithoutActivation);
+        }
+        self.hardforks.validate()
+    }
+
+    /// Block and total difficulty the chain merged at: the POA activation when there is one,
+    /// otherwise [`AdvancedConfig::paris_block_and_final_difficulty`]
+    pub fn paris_block_and_final_difficulty(&self) -> Option<(u64, U256)> {
+        match self.poa_activation_block {
+            Some(block) => Some((block, self.pre_activation_ttd.unwrap_or_default())),
+            None => self.advanced.paris_block_and_final_difficulty,
+        }
+    }
 }
 
 impl Default for PoaConfig {
@@ -35,6 +557,48 @@ impl Default for PoaConfig {
             period: 12, // 12 second block time like mainnet
             epoch: 30000,
             signers: vec![],
+            exact_period: false,
+            signer_set_source: SignerSetSource::Votes,
+            signers_weighted: vec![],
+            hardforks: HardforkSchedule::default(),
+            base_fee_params: None,
+            base_fee_params_forks: BTreeMap::new(),
+            zero_base_fee: false,
+            blob_params: None,
+            bootnodes: vec![],
+            compat_forkid: false,
+            deposit_contract: None,
+            advanced: AdvancedConfig::default(),
+            gas_limit_target: None,
+            poa_activation_block: None,
+            pre_activation_ttd: None,
+        }
+    }
+}
+
+/// Shape of the chain built by [`PoaChainSpec::dev_chain_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DevChainOptions {
+    /// Block period in seconds
+    pub block_period: u64,
+    /// Number of dev accounts that are signers
+    pub signers: usize,
+    /// Number of dev accounts that are prefunded
+    pub prefunded_accounts: usize,
+    /// Chain ID
+    pub chain_id: u64,
+    /// Gas limit of the genesis block
+    pub gas_limit: u64,
+}
+
+impl Default for DevChainOptions {
+    fn default() -> Self {
+        Self {
+            block_period: 2, // Fast 2-second blocks for dev
+            signers: 3,
+            prefunded_accounts: 20,
+            chain_id: 31337,
+            gas_limit: 30_000_000,
         }
     }
 }
@@ -46,76 +610,240 @@ pub struct PoaChainSpec {
     inner: Arc<ChainSpec>,
     /// POA-specific configuration
     poa_config: PoaConfig,
+    /// The genesis signers of `poa_config` as a set, for membership checks
+    signer_set: BTreeSet<Address>,
 }
 
 impl PoaChainSpec {
     /// Creates a new POA chain spec from genesis and POA config
+    ///
+    /// # Panics
+    ///
+    /// If the POA config is invalid, see [`try_new`](Self::try_new).
     pub fn new(genesis: Genesis, poa_config: PoaConfig) -> Self {
-        // Build hardforks - enable all Ethereum hardforks for mainnet compatibility
-        let hardforks = Self::mainnet_compatible_hardforks();
+        Self::try_new(genesis, poa_config).expect("invalid POA config")
+    }
+
+    /// Creates a new POA chain spec from genesis and POA config, rejecting a config that
+    /// violates one of the [`PoaConfigError`] invariants or doesn't match the genesis
+    /// ([`validate_genesis`])
+    ///
+    /// The signers are sorted ascending, as clique orders them in checkpoints and rotation, so
+    /// the order they were configured in doesn't matter.
+    ///
+    /// # Panics
+    ///
+    /// If the state root of the genesis header differs from [`compute_state_root`].
+    pub fn try_new(
+        mut genesis: Genesis,
+        mut poa_config: PoaConfig,
+    ) -> Result<Self, PoaConfigError> {
+        poa_config.signers.sort();
+        poa_config.validate()?;
+        let mut hardforks = poa_config.hardforks.chain_hardforks();
+        // Paris marks the switch to POA, which the fork id then reflects
+        if let Some(block) = poa_config.poa_activation_block {
+            let total_difficulty = poa_config.pre_activation_ttd.unwrap_or_default();
+            hardforks.insert(
+                EthereumHardfork::Paris,
+                ForkCondition::TTD {
+                    activation_block_number: block,
+                    fork_block: Some(block),
+                    total_difficulty,
+                },
+            );
+        }
+        let base_fee_params = base_fee_params_kind(&poa_config)?;
+        let blob_params = poa_config.blob_params.unwrap_or_default().blob_params()?;
+        validate_genesis(&genesis, &poa_config)?;
+        if poa_config.zero_base_fee {
+            genesis.base_fee_per_gas = Some(0);
+        }
 
         let genesis_header = reth_chainspec::make_genesis_header(&genesis, &hardforks);
+        assert_eq!(
+            genesis_header.state_root,
+            compute_state_root(&genesis),
+            "genesis state root doesn't commit to the alloc"
+        );
 
         let inner = ChainSpec {
             chain: Chain::from_id(genesis.config.chain_id),
             genesis_header: SealedHeader::seal_slow(genesis_header),
             genesis,
-            // Post-merge from the start (POA doesn't use proof of work)
-            paris_block_and_final_difficulty: Some((0, U256::ZERO)),
+            paris_block_and_final_difficulty: poa_config.paris_block_and_final_difficulty(),
             hardforks,
-            deposit_contract: None,
-            base_fee_params: BaseFeeParamsKind::Constant(BaseFeeParams::ethereum()),
-            prune_delete_limit: 10000,
-            blob_params: Default::default(),
+            deposit_contract: poa_config.deposit_contract,
+            base_fee_params,
+            prune_delete_limit: poa_config.advanced.prune_delete_limit,
+            blob_params,
+        };
+
+        let signer_set = poa_config.signers.iter().copied().collect();
+        Ok(Self { inner: Arc::new(inner), poa_config, signer_set })
+    }
+
+    /// Hash of the genesis block a chain spec built from `genesis` and `poa_config` would have,
+    /// without building it
+    pub fn compute_genesis_hash(genesis: &Genesis, poa_config: &PoaConfig) -> B256 {
+        let mut genesis = genesis.clone();
+        if poa_config.zero_base_fee {
+            genesis.base_fee_per_gas = Some(0);
+        }
+        let hardforks = poa_config.hardforks.chain_hardforks();
+        reth_chainspec::make_genesis_header(&genesis, &hardforks).hash_slow()
+    }
+
+    /// Creates a POA chain spec from a geth clique genesis.
+    ///
+    /// Period and epoch are read from `config.clique`, the signers from the genesis extra data
+    /// (32 byte vanity, signer addresses, 65 byte seal) and blob counts from `config.blobSchedule`
+    /// ([`BlobScheduleOverride::from_chain_config`]), the same file `geth init` accepts.
+    pub fn from_genesis(genesis: Genesis) -> Result<Self, PoaChainSpecError> {
+        let clique = genesis.config.clique.ok_or(PoaChainSpecError::MissingClique)?;
+        let period = clique.period.ok_or(PoaChainSpecError::MissingCliqueField("period"))?;
+        let epoch = clique.epoch.ok_or(PoaChainSpecError::MissingCliqueField("epoch"))?;
+        let signers = genesis_signers(&genesis.extra_data)?;
+        let blob_params = BlobScheduleOverride::from_chain_config(&genesis.config);
+        let poa_config = PoaConfig { period, epoch, signers, blob_params, ..Default::default() };
+        Ok(Self::try_new(genesis, poa_config)?)
+    }
+
+    /// Creates a POA chain spec from the `[poa]` settings of a node configuration file.
+    ///
+    /// If the file lists no signers, the ones in the genesis extra data are used.
+    pub fn from_config(
+        config: &PoaNodeConfigFile,
+        genesis: Genesis,
+    ) -> Result<Self, PoaChainSpecError> {
+        let settings = &config.poa;
+        let signers = if settings.signers.is_empty() {
+            genesis_signers(&genesis.extra_data)?
+        } else {
+            settings.signers.clone()
         };
 
-        Self { inner: Arc::new(inner), poa_config }
+        let poa_config = PoaConfig {
+            period: settings.period,
+            epoch: settings.epoch,
+            signers,
+            exact_period: settings.exact_period,
+            ..Default::default()
+        };
+        Ok(Self::try_new(genesis, poa_config)?)
+    }
+
+    /// Loads a POA chain spec from a JSON chain definition file, see
+    /// [`from_json_str`](Self::from_json_str)
+    pub fn from_json_file(path: &Path) -> Result<Self, PoaChainSpecError> {
+        Self::from_json_str(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a POA chain spec from a JSON chain definition.
+    ///
+    /// Accepts either a bare geth clique genesis, handled by [`from_genesis`](Self::from_genesis),
+    /// or a genesis with an additional top-level `poa` object holding the [`PoaConfig`], which
+    /// then takes precedence over the clique section and extra data.
+    pub fn from_json_str(json: &str) -> Result<Self, PoaChainSpecError> {
+        let mut definition: serde_json::Value =
+            serde_json::from_str(json).map_err(PoaChainSpecError::Syntax)?;
+        let poa = definition.as_object_mut().and_then(|definition| definition.remove("poa"));
+        let genesis: Genesis =
+            serde_json::from_value(definition).map_err(PoaChainSpecError::InvalidDefinition)?;
+        let Some(poa) = poa else { return Self::from_genesis(genesis) };
+
+        let config: PoaConfig =
+            serde_json::from_value(poa).map_err(PoaChainSpecError::InvalidDefinition)?;
+        Ok(Self::try_new(genesis, config)?)
+    }
+
+    /// Serializes the full effective chain definition: genesis, POA config and the resulting
+    /// hardforks, see [`from_json`](Self::from_json)
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Deserializes a chain definition written by [`to_json`](Self::to_json), reproducing the
+    /// same genesis hash and fork ids
+    pub fn from_json(json: &str) -> Result<Self, PoaChainSpecError> {
+        serde_json::from_str(json).map_err(|err| match err.classify() {
+            Category::Data => PoaChainSpecError::InvalidDefinition(err),
+            _ => PoaChainSpecError::Syntax(err),
+        })
+    }
+
+    /// Creates the chain of the preset registered under `name`, see [`presets`]
+    ///
+    /// # Panics
+    ///
+    /// If the registered preset is an invalid chain.
+    pub fn named(name: &str) -> Option<Self> {
+        let (genesis, poa_config) = presets::preset(name)?();
+        Some(Self::new(genesis, poa_config))
     }
 
     /// Creates a development POA chain with prefunded accounts
     pub fn dev_chain() -> Self {
-        let genesis = crate::genesis::create_dev_genesis();
+        Self::dev_chain_with(DevChainOptions::default())
+    }
+
+    /// Creates a development POA chain shaped by `options`, with the signers and prefunded
+    /// accounts taken from the start of the dev mnemonic's accounts
+    /// ([`dev_accounts_up_to`](crate::genesis::dev_accounts_up_to))
+    ///
+    /// # Panics
+    ///
+    /// If `options` asks for no signers.
+    pub fn dev_chain_with(options: DevChainOptions) -> Self {
+        let accounts =
+            crate::genesis::dev_accounts_up_to(options.signers.max(options.prefunded_accounts));
+        let signers = accounts[..options.signers].to_vec();
+        let balance = crate::genesis::default_prefund_balance();
+        let genesis = crate::genesis::create_genesis(GenesisConfig {
+            chain_id: options.chain_id,
+            gas_limit: options.gas_limit,
+            prefunded_accounts: accounts[..options.prefunded_accounts]
+                .iter()
+                .map(|account| (*account, balance))
+                .collect(),
+            ..GenesisConfig::dev()
+                .with_signers(signers.clone())
+                .with_block_period(options.block_period)
+        });
+        let poa_config =
+            PoaConfig { period: options.block_period, epoch: 30000, signers, ..Default::default() };
+        Self::new(genesis, poa_config)
+    }
+
+    /// Creates a throwaway development chain sealed by `signer_count` random signers, returned
+    /// with the [`SignerManager`](crate::signer::SignerManager) holding their keys
+    ///
+    /// Unlike [`dev_chain`](Self::dev_chain) no account has a publicly known key; the keys exist
+    /// in memory only, see [`SignerManager::ephemeral`](crate::signer::SignerManager::ephemeral).
+    /// Fails with [`PoaConfigError::NoSigners`] if `signer_count` is zero.
+    pub fn ephemeral_chain(
+        signer_count: usize,
+    ) -> Result<(Self, Arc<crate::signer::SignerManager>), PoaConfigError> {
+        let (manager, signers) = crate::signer::SignerManager::ephemeral(signer_count);
+        let config = GenesisConfig::dev_with_accounts(0).with_ephemeral_signers(&signers);
         let poa_config = PoaConfig {
-            period: 2, // Fast 2-second blocks for dev
-            epoch: 30000,
-            signers: crate::genesis::dev_signers(),
+            period: config.block_period,
+            epoch: config.epoch,
+            signers,
+            ..Default::default()
         };
-        Self::new(genesis, poa_config)
+        Ok((Self::try_new(crate::genesis::create_genesis(config), poa_config)?, manager))
     }
 
-    /// Creates hardforks configuration that matches Ethereum mainnet
-    /// This ensures full smart contract compatibility
-    fn mainnet_compatible_hardforks() -> ChainHardforks {
-        // Enable all hardforks at genesis (block 0 / timestamp 0)
-        // This gives you the latest Ethereum features immediately
-        ChainHardforks::new(vec![
-            // Block-based hardforks (all at block 0)
-            (EthereumHardfork::Frontier.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::Homestead.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::Tangerine.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::SpuriousDragon.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::Byzantium.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::Constantinople.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::Petersburg.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::Istanbul.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::Berlin.boxed(), ForkCondition::Block(0)),
-            (EthereumHardfork::London.boxed(), ForkCondition::Block(0)),
-            // The Merge - we use TTD of 0 since POA doesn't have proof of work
-            (
-                EthereumHardfork::Paris.boxed(),
-                ForkCondition::TTD {
-                    activation_block_number: 0,
-                    fork_block: None,
-                    total_difficulty: U256::ZERO,
-                },
-            ),
-            // Timestamp-based hardforks (all at timestamp 0)
-            (EthereumHardfork::Shanghai.boxed(), ForkCondition::Timestamp(0)),
-            (EthereumHardfork::Cancun.boxed(), ForkCondition::Timestamp(0)),
-            (EthereumHardfork::Prague.boxed(), ForkCondition::Timestamp(0)),
-            // Future hardforks can be added here with specific timestamps
-            // (EthereumHardfork::Osaka.boxed(), ForkCondition::Timestamp(OSAKA_TIMESTAMP)),
-        ])
+    /// The same chain under another chain id
+    ///
+    /// The inner spec is rebuilt from the updated genesis. The genesis header doesn't commit to
+    /// the chain id, so the genesis hash stays the same; transactions signed for the old id are no
+    /// longer valid.
+    pub fn with_chain_id(self, chain_id: u64) -> Self {
+        let mut genesis = self.inner.genesis.clone();
+        genesis.config.chain_id = chain_id;
+        Self::new(genesis, self.poa_config)
     }
 
     /// Returns the inner ChainSpec
@@ -128,7 +856,7 @@ impl PoaChainSpec {
         &self.poa_config
     }
 
-    /// Returns the list of authorized signers
+    /// Returns the list of authorized signers, in ascending order
     pub fn signers(&self) -> &[Address] {
         &self.poa_config.signers
     }
@@ -143,12 +871,109 @@ impl PoaChainSpec {
         self.poa_config.epoch
     }
 
-    /// Check if an address is an authorized signer
+    /// Returns `true` if blocks must be spaced exactly one period apart
+    pub fn exact_period(&self) -> bool {
+        self.poa_config.exact_period
+    }
+
+    /// Returns `true` if the base fee is pinned to zero, so the pool must accept zero fee
+    /// transactions
+    pub const fn is_zero_base_fee(&self) -> bool {
+        self.poa_config.zero_base_fee
+    }
+
+    /// Returns where the signer set comes from
+    pub const fn signer_set_source(&self) -> &SignerSetSource {
+        &self.poa_config.signer_set_source
+    }
+
+    /// Configured signer weights, weights below one counting as one
+    pub fn signer_weights(&self) -> BTreeMap<Address, u32> {
+        self.poa_config
+            .signers_weighted
+            .iter()
+            .map(|(signer, weight)| (*signer, (*weight).max(1)))
+            .collect()
+    }
+
+    /// Timestamp to stamp on the next block given its parent's timestamp and the wall clock.
+    ///
+    /// Under `exact_period` this is always `parent + period`, even when the parent is more than a
+    /// period behind the wall clock; otherwise it is the later of the two.
+    pub fn next_block_timestamp(&self, parent_timestamp: u64, now: u64) -> u64 {
+        let earliest = parent_timestamp + self.block_period();
+        if self.exact_period() {
+            earliest
+        } else {
+            earliest.max(now)
+        }
+    }
+
+    /// First block sealed under POA; blocks before it were sealed by another engine
+    pub fn poa_activation_block(&self) -> u64 {
+        self.poa_config.poa_activation_block.unwrap_or_default()
+    }
+
+    /// Gas limit block production aims for: the configured target, or the genesis gas limit
+    pub fn block_gas_limit(&self) -> u64 {
+        self.poa_config.gas_limit_target.unwrap_or(self.inner.genesis.gas_limit)
+    }
+
+    /// Gas limit to stamp on a block on top of a parent with `parent_gas_limit`.
+    ///
+    /// Moves towards [`block_gas_limit`](Self::block_gas_limit) by less than `parent / 1024` per
+    /// block, the same as reth's payload builder, so header validation always accepts the step.
+    pub fn next_block_gas_limit(&self, parent_gas_limit: u64) -> u64 {
+        calculate_block_gas_limit(parent_gas_limit, self.block_gas_limit())
+    }
+
+    /// Base fee of a block sealed at `timestamp` on top of `parent`, or `None` before London.
+    ///
+    /// Both header assembly and header validation use this, so they can't disagree on the fee
+    /// parameters.
+    pub fn next_block_base_fee(&self, parent: &Header, timestamp: u64) -> Option<u64> {
+        parent.next_block_base_fee(self.base_fee_params_at_timestamp(timestamp))
+    }
+
+    /// Total difficulty at `block_number` assuming every block since genesis was sealed in-turn.
+    ///
+    /// A healthy chain's actual total difficulty is at least this value; each out-of-turn block
+    /// adds one more.
+    pub fn expected_total_difficulty_at(&self, block_number: u64) -> U256 {
+        self.inner.genesis_header().difficulty + U256::from(block_number)
+    }
+
+    /// Check if an address is one of the genesis signers
     pub fn is_authorized_signer(&self, address: &Address) -> bool {
-        self.poa_config.signers.contains(address)
+        self.signer_set.contains(address)
+    }
+
+    /// Check if an address may seal block `block_number`
+    ///
+    /// `snapshot` is the snapshot at the parent block, whose signer set reflects every vote or
+    /// checkpoint up to it. Without one this falls back to the genesis signers, which is only right
+    /// as long as the signer set never changed. Nobody seals the genesis block.
+    pub fn is_authorized_signer_at(
+        &self,
+        address: &Address,
+        block_number: u64,
+        snapshot: Option<&Snapshot>,
+    ) -> bool {
+        if block_number == 0 {
+            return false;
+        }
+        match snapshot {
+            Some(snapshot) => snapshot.signers.contains(address),
+            None => self.is_authorized_signer(address),
+        }
     }
 
-    /// Get the expected signer for a given block number (round-robin)
+    /// Get the expected signer for a given block number (round-robin over the genesis signers).
+    ///
+    /// Only meaningful while the genesis signer set is unchanged and unweighted. Turn calculation
+    /// during validation and sealing goes through
+    /// [`Snapshot::inturn_signer`](crate::consensus::snapshot::Snapshot::inturn_signer), which
+    /// follows signer set changes.
     pub fn expected_signer(&self, block_number: u64) -> Option<&Address> {
         if self.poa_config.signers.is_empty() {
             return None;
@@ -156,6 +981,315 @@ impl PoaChainSpec {
         let index = (block_number as usize) % self.poa_config.signers.len();
         self.poa_config.signers.get(index)
     }
+
+    /// Whether the chain ever activates Cancun, without which blob transactions are invalid
+    pub fn supports_blob_transactions(&self) -> bool {
+        self.fork(EthereumHardfork::Cancun) != ForkCondition::Never
+    }
+
+    /// Digest of the consensus parameters every node of the chain must agree on, mixed into the
+    /// [`ForkId`] so nodes of POA chains that differ only in them don't peer.
+    ///
+    /// This is `keccak256(period ‖ epoch ‖ signers)`, with `period` and `epoch` as 8 byte big
+    /// endian integers and the signers in ascending order.
+    pub fn poa_params_digest(&self) -> B256 {
+        let mut encoded = Vec::with_capacity(16 + self.signers().len() * Address::len_bytes());
+        encoded.extend_from_slice(&self.block_period().to_be_bytes());
+        encoded.extend_from_slice(&self.epoch().to_be_bytes());
+        for signer in self.signers() {
+            encoded.extend_from_slice(signer.as_slice());
+        }
+        keccak256(encoded)
+    }
+
+    /// Short digest of the full hardfork schedule, for operators to compare out-of-band before an
+    /// upgrade: nodes that report the same digest activate every hardfork at the same time.
+    ///
+    /// These are the first 8 bytes of the keccak256 of every enabled hardfork in order, each
+    /// encoded as its name, a zero byte and its activation: `0` and the block, `1`, the block and
+    /// the total difficulty, or `2` and the timestamp, all as big endian integers.
+    pub fn upgrade_digest(&self) -> B64 {
+        let mut encoded = Vec::new();
+        for (fork, condition) in &self.poa_config.hardforks.0 {
+            match *condition {
+                ForkCondition::Block(block) => {
+                    encoded.extend_from_slice(fork.name().as_bytes());
+                    encoded.extend_from_slice(&[0, 0]);
+                    encoded.extend_from_slice(&block.to_be_bytes());
+                }
+                ForkCondition::TTD { activation_block_number, total_difficulty, .. } => {
+                    encoded.extend_from_slice(fork.name().as_bytes());
+                    encoded.extend_from_slice(&[0, 1]);
+                    encoded.extend_from_slice(&activation_block_number.to_be_bytes());
+                    encoded.extend_from_slice(&total_difficulty.to_be_bytes::<32>());
+                }
+                ForkCondition::Timestamp(timestamp) => {
+                    encoded.extend_from_slice(fork.name().as_bytes());
+                    encoded.extend_from_slice(&[0, 2]);
+                    encoded.extend_from_slice(&timestamp.to_be_bytes());
+                }
+                ForkCondition::Never => {}
+            }
+        }
+        B64::from_slice(&keccak256(encoded)[..8])
+    }
+
+    /// Hash the [`ForkId`] CRC starts from: the genesis hash xor-ed with the
+    /// [`poa_params_digest`](Self::poa_params_digest), or the plain genesis hash under
+    /// `compat_forkid`
+    fn fork_id_base_hash(&self) -> B256 {
+        if self.poa_config.compat_forkid {
+            self.genesis_hash()
+        } else {
+            self.genesis_hash() ^ self.poa_params_digest()
+        }
+    }
+
+    /// One-line summary of the POA parameters for log contexts; the [`Display`](fmt::Display)
+    /// impl gives the full picture
+    pub fn summary(&self) -> String {
+        format!(
+            "chain={} period={}s epoch={} signers={} gas_limit={} exact_period={} zero_base_fee={}",
+            self.inner.chain.id(),
+            self.block_period(),
+            self.epoch(),
+            self.signers().len(),
+            self.inner.genesis.gas_limit,
+            self.exact_period(),
+            self.is_zero_base_fee(),
+        )
+    }
+}
+
+impl fmt::Display for PoaChainSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+        writeln!(f, "POA chain {}", self.inner.chain.id())?;
+        writeln!(f, "  Block period:  {} seconds", self.block_period())?;
+        writeln!(f, "  Epoch:         {} blocks", self.epoch())?;
+        writeln!(f, "  Gas limit:     {}", self.inner.genesis.gas_limit)?;
+        writeln!(f, "  Exact period:  {}", on_off(self.exact_period()))?;
+        writeln!(f, "  Zero base fee: {}", on_off(self.is_zero_base_fee()))?;
+        write!(f, "  Signers ({}):", self.signers().len())?;
+        for signer in self.signers() {
+            write!(f, "\n    {signer}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds a [`PoaChainSpec`] out of a genesis and POA config, e.g. to schedule a hardfork on a
+/// running chain:
+///
+/// ```ignore
+/// let upgraded = PoaChainSpecBuilder::from(&chain)
+///     .with_head(head)
+///     .schedule_upgrade(EthereumHardfork::Osaka, 1_800_000_000)?
+///     .build()?;
+/// println!("schedule digest: {}", upgraded.upgrade_digest());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PoaChainSpecBuilder {
+    genesis: Genesis,
+    poa_config: PoaConfig,
+    head: Option<Head>,
+}
+
+impl PoaChainSpecBuilder {
+    /// Start from `genesis` and `poa_config`
+    pub const fn new(genesis: Genesis, poa_config: PoaConfig) -> Self {
+        Self { genesis, poa_config, head: None }
+    }
+
+    /// Current head of the chain, which upgrades must be scheduled after
+    pub const fn with_head(mut self, head: Head) -> Self {
+        self.head = Some(head);
+        self
+    }
+
+    /// Activate `fork` at `condition`, replacing its previous activation
+    pub fn activate_at(mut self, fork: EthereumHardfork, condition: ForkCondition) -> Self {
+        self.poa_config.hardforks = self.poa_config.hardforks.with_fork(fork, condition);
+        self
+    }
+
+    /// Activate `fork` at `timestamp`, which must be after the head, if one was supplied, and
+    /// after every other hardfork's activation
+    pub fn schedule_upgrade(
+        self,
+        fork: EthereumHardfork,
+        timestamp: u64,
+    ) -> Result<Self, PoaConfigError> {
+        if let Some(head) = &self.head &&
+            timestamp <= head.timestamp
+        {
+            return Err(PoaConfigError::UpgradeNotInFuture {
+                fork,
+                timestamp,
+                head: head.timestamp,
+            });
+        }
+        let latest = self
+            .poa_config
+            .hardforks
+            .0
+            .iter()
+            .filter(|(other, _)| **other != fork)
+            .filter_map(|(other, condition)| match condition {
+                ForkCondition::Timestamp(activation) => Some((*other, *activation)),
+                _ => None,
+            })
+            .max_by_key(|(_, activation)| *activation);
+        if let Some((previous, activation)) = latest &&
+            timestamp <= activation
+        {
+            return Err(PoaConfigError::UpgradeNotAfterActivation {
+                fork,
+                timestamp,
+                previous,
+                activation,
+            });
+        }
+        Ok(self.activate_at(fork, ForkCondition::Timestamp(timestamp)))
+    }
+
+    /// Build the chain spec, see [`PoaChainSpec::try_new`]
+    pub fn build(self) -> Result<PoaChainSpec, PoaConfigError> {
+        PoaChainSpec::try_new(self.genesis, self.poa_config)
+    }
+}
+
+impl From<&PoaChainSpec> for PoaChainSpecBuilder {
+    fn from(chain: &PoaChainSpec) -> Self {
+        Self::new(chain.inner.genesis.clone(), chain.poa_config.clone())
+    }
+}
+
+/// Parse the signer list out of genesis extra data, keeping its order
+pub(crate) fn genesis_signers(extra_data: &[u8]) -> Result<Vec<Address>, PoaChainSpecError> {
+    let invalid = || PoaChainSpecError::InvalidExtraData(extra_data.len());
+    let list = extra_data
+        .len()
+        .checked_sub(EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH)
+        .map(|len| &extra_data[EXTRA_VANITY_LENGTH..EXTRA_VANITY_LENGTH + len])
+        .ok_or_else(invalid)?;
+    if !list.len().is_multiple_of(Address::len_bytes()) {
+        return Err(invalid());
+    }
+
+    Ok(list.chunks_exact(Address::len_bytes()).map(Address::from_slice).collect())
+}
+
+/// Parse a bootnode given as `enode://<node id>@<ip>:<port>[?discport=<port>]`
+pub fn parse_bootnode(enode: &str) -> Result<NodeRecord, PoaChainSpecError> {
+    let invalid =
+        |reason: String| PoaChainSpecError::InvalidBootnode { enode: enode.to_string(), reason };
+    if !enode.starts_with("enode://") {
+        return Err(invalid("expected an enode:// URL".to_string()));
+    }
+    enode.parse().map_err(|err| match err {
+        NodeRecordParseError::InvalidId(reason) => invalid(format!("invalid node id: {reason}")),
+        other => invalid(other.to_string()),
+    })
+}
+
+/// Serde of [`PoaConfig::deposit_contract`], as `DepositContract` has none
+mod serde_deposit_contract {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct DepositContractDef {
+        address: Address,
+        #[serde(default)]
+        block: u64,
+        #[serde(default = "default_topic")]
+        topic: B256,
+    }
+
+    const fn default_topic() -> B256 {
+        DEPOSIT_EVENT_TOPIC
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        contract: &Option<DepositContract>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        contract
+            .map(|DepositContract { address, block, topic }| DepositContractDef {
+                address,
+                block,
+                topic,
+            })
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DepositContract>, D::Error> {
+        Ok(Option::<DepositContractDef>::deserialize(deserializer)?
+            .map(|def| DepositContract::new(def.address, def.block, def.topic)))
+    }
+}
+
+/// Deserialize `enode://` URLs, rejecting malformed ones with a [`parse_bootnode`] error
+fn deserialize_bootnodes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<NodeRecord>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|enode| parse_bootnode(enode).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+/// Base fee parameters of the inner chain spec: constant unless some apply from a hardfork on
+fn base_fee_params_kind(poa_config: &PoaConfig) -> Result<BaseFeeParamsKind, PoaConfigError> {
+    if poa_config.zero_base_fee {
+        if poa_config.base_fee_params.is_some() || !poa_config.base_fee_params_forks.is_empty() {
+            return Err(PoaConfigError::ZeroBaseFeeParams);
+        }
+        return Ok(BaseFeeParamsKind::Constant(ZERO_BASE_FEE_PARAMS));
+    }
+
+    let initial = poa_config.base_fee_params.unwrap_or_else(BaseFeeParams::ethereum);
+    for params in poa_config.base_fee_params_forks.values().chain([&initial]) {
+        let BaseFeeParams { max_change_denominator, elasticity_multiplier } = *params;
+        if max_change_denominator == 0 || elasticity_multiplier == 0 {
+            return Err(PoaConfigError::InvalidBaseFeeParams {
+                max_change_denominator,
+                elasticity_multiplier,
+            });
+        }
+    }
+    if poa_config.base_fee_params_forks.is_empty() {
+        return Ok(BaseFeeParamsKind::Constant(initial));
+    }
+
+    // London activates by block, so its entry is only ever the fallback before the first fork
+    let mut params: Vec<(Box<dyn Hardfork>, BaseFeeParams)> =
+        vec![(Box::new(EthereumHardfork::London), initial)];
+    for (&fork, &fork_params) in &poa_config.base_fee_params_forks {
+        if !matches!(poa_config.hardforks.fork(fork), ForkCondition::Timestamp(_)) {
+            return Err(PoaConfigError::BaseFeeForkNotTimestamp(fork));
+        }
+        params.push((Box::new(fork), fork_params));
+    }
+    Ok(BaseFeeParamsKind::Variable(params.into()))
+}
+
+/// Check that a configured signer list is non-empty and free of duplicates and the zero address
+fn check_signers(signers: &[Address]) -> Result<(), PoaConfigError> {
+    if signers.is_empty() {
+        return Err(PoaConfigError::NoSigners);
+    }
+    for (index, signer) in signers.iter().enumerate() {
+        if signer.is_zero() {
+            return Err(PoaConfigError::ZeroSigner);
+        }
+        if signers[..index].contains(signer) {
+            return Err(PoaConfigError::DuplicateSigner(*signer));
+        }
+    }
+    Ok(())
 }
 
 // Implement required traits to make PoaChainSpec work with Reth
@@ -170,15 +1304,23 @@ impl Hardforks for PoaChainSpec {
     }
 
     fn fork_id(&self, head: &Head) -> ForkId {
-        self.inner.fork_id(head)
+        self.fork_filter(*head).current()
     }
 
     fn latest_fork_id(&self) -> ForkId {
-        self.inner.latest_fork_id()
+        self.fork_id(&Head { number: u64::MAX, timestamp: u64::MAX, ..Default::default() })
     }
 
     fn fork_filter(&self, head: Head) -> ForkFilter {
-        self.inner.fork_filter(head)
+        // TTD forks without a known block don't show up in the fork id
+        let forks = self.forks_iter().filter_map(|(_, condition)| match condition {
+            ForkCondition::Block(block) | ForkCondition::TTD { fork_block: Some(block), .. } => {
+                Some(ForkFilterKey::Block(block))
+            }
+            ForkCondition::Timestamp(time) => Some(ForkFilterKey::Time(time)),
+            _ => None,
+        });
+        ForkFilter::new(head, self.fork_id_base_hash(), self.inner.genesis_timestamp(), forks)
     }
 }
 
@@ -222,7 +1364,11 @@ impl EthChainSpec for PoaChainSpec {
     }
 
     fn bootnodes(&self) -> Option<Vec<NodeRecord>> {
-        self.inner.bootnodes()
+        if self.poa_config.bootnodes.is_empty() {
+            self.inner.bootnodes()
+        } else {
+            Some(self.poa_config.bootnodes.clone())
+        }
     }
 
     fn final_paris_total_difficulty(&self) -> Option<U256> {
@@ -236,6 +1382,45 @@ impl EthereumHardforks for PoaChainSpec {
     }
 }
 
+/// Serialized form of a [`PoaChainSpec`]
+#[derive(Serialize, Deserialize)]
+struct PoaChainSpecDef {
+    genesis: Genesis,
+    poa: PoaConfig,
+    /// Activation of every enabled hardfork, for audit; must match what `poa` produces
+    hardforks: BTreeMap<String, ForkCondition>,
+}
+
+impl From<&PoaChainSpec> for PoaChainSpecDef {
+    fn from(chain: &PoaChainSpec) -> Self {
+        Self {
+            genesis: chain.inner.genesis.clone(),
+            poa: chain.poa_config.clone(),
+            hardforks: chain
+                .forks_iter()
+                .map(|(fork, condition)| (fork.name().to_string(), condition))
+                .collect(),
+        }
+    }
+}
+
+impl Serialize for PoaChainSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PoaChainSpecDef::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PoaChainSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let def = PoaChainSpecDef::deserialize(deserializer)?;
+        let chain = Self::try_new(def.genesis, def.poa).map_err(serde::de::Error::custom)?;
+        if PoaChainSpecDef::from(&chain).hardforks != def.hardforks {
+            return Err(serde::de::Error::custom("hardforks don't match the poa config"));
+        }
+        Ok(chain)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,6 +1432,141 @@ mod tests {
         assert_eq!(chain.block_period(), 2);
     }
 
+    #[tokio::test]
+    async fn test_ephemeral_chain() {
+        let (chain, manager) = PoaChainSpec::ephemeral_chain(2).unwrap();
+        let signers = chain.signers().to_vec();
+        assert_eq!(signers.len(), 2);
+        let dev_accounts = crate::genesis::dev_accounts();
+        assert!(signers.iter().all(|signer| !dev_accounts.contains(signer)), "{signers:?}");
+
+        let mut held = manager.signer_addresses().await;
+        held.sort();
+        assert_eq!(held, signers);
+        for signer in &signers {
+            assert!(chain.genesis().alloc.contains_key(signer));
+        }
+        assert!(dev_accounts.iter().all(|account| !chain.genesis().alloc.contains_key(account)));
+
+        // Every call makes new keys
+        let (other, _) = PoaChainSpec::ephemeral_chain(2).unwrap();
+        assert!(other.signers().iter().all(|signer| !signers.contains(signer)));
+
+        assert!(matches!(PoaChainSpec::ephemeral_chain(0), Err(PoaConfigError::NoSigners)));
+    }
+
+    #[test]
+    fn test_dev_chain_with_options() {
+        let single = PoaChainSpec::dev_chain_with(DevChainOptions {
+            block_period: 1,
+            signers: 1,
+            ..Default::default()
+        });
+        assert_eq!(single.signers(), [crate::genesis::dev_accounts()[0]]);
+        assert_eq!(single.block_period(), 1);
+
+        let large = PoaChainSpec::dev_chain_with(DevChainOptions {
+            signers: 10,
+            prefunded_accounts: 2,
+            chain_id: 4242,
+            gas_limit: 60_000_000,
+            ..Default::default()
+        });
+        assert_eq!(large.signers().len(), 10);
+        assert_eq!(large.block_period(), 2);
+        assert_eq!(large.chain().id(), 4242);
+        assert_eq!(large.genesis_header().gas_limit, 60_000_000);
+        assert_eq!(large.genesis().alloc.len(), 2);
+        assert_eq!(Snapshot::new_genesis(&large).signers.len(), 10);
+
+        // The defaults are the plain dev chain
+        assert_eq!(PoaChainSpec::dev_chain().genesis(), &crate::genesis::create_dev_genesis());
+    }
+
+    #[test]
+    fn test_with_chain_id() {
+        use alloy_consensus::{SignableTransaction, Transaction, TxLegacy};
+        use alloy_signer::SignerSync;
+
+        let dev = PoaChainSpec::dev_chain();
+        let chain = dev.clone().with_chain_id(4242);
+        assert_eq!(chain.chain().id(), 4242);
+        assert_eq!(chain.genesis().config.chain_id, 4242);
+        assert_eq!(chain.signers(), dev.signers());
+        // The header doesn't commit to the chain id
+        assert_eq!(chain.genesis_hash(), dev.genesis_hash());
+        assert_eq!(
+            chain.genesis_hash(),
+            PoaChainSpec::compute_genesis_hash(chain.genesis(), chain.poa_config())
+        );
+
+        // EIP-155 signatures commit to the new id
+        let key = crate::signer::dev::first_dev_signer();
+        let tx = TxLegacy {
+            chain_id: Some(chain.chain().id()),
+            gas_limit: 21_000,
+            ..Default::default()
+        };
+        let signature = key.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let signed = tx.into_signed(signature);
+        assert_eq!(signed.tx().chain_id(), Some(4242));
+        assert_eq!(signed.recover_signer().unwrap(), key.address());
+        let replayed = TxLegacy { chain_id: Some(dev.chain().id()), ..signed.tx().clone() };
+        assert_ne!(replayed.signature_hash(), signed.signature_hash());
+    }
+
+    #[test]
+    fn test_display_summary() {
+        let chain = PoaChainSpec::dev_chain();
+        assert_eq!(
+            chain.to_string(),
+            "\
+POA chain 31337
+  Block period:  2 seconds
+  Epoch:         30000 blocks
+  Gas limit:     30000000
+  Exact period:  off
+  Zero base fee: off
+  Signers (3):
+    0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC
+    0x70997970C51812dc3A010C7d01b50e0d17dc79C8
+    0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+        );
+        assert_eq!(
+            chain.summary(),
+            "chain=31337 period=2s epoch=30000 signers=3 gas_limit=30000000 exact_period=false \
+             zero_base_fee=false"
+        );
+    }
+
+    #[test]
+    fn test_fork_id_includes_poa_params() {
+        let chain = PoaChainSpec::dev_chain();
+        let fork_id = |config: PoaConfig| {
+            let chain = PoaChainSpec::new(crate::genesis::create_dev_genesis_for(&config), config);
+            (chain.latest_fork_id(), chain.fork_filter(Head::default()))
+        };
+
+        let (same, _) = fork_id(chain.poa_config().clone());
+        assert_eq!(same, chain.latest_fork_id());
+        assert_eq!(chain.fork_id(&Head::default()), chain.latest_fork_id());
+        assert!(chain.fork_filter(Head::default()).validate(same).is_ok());
+
+        // Replacing a single signer changes the fork id, so the nodes won't peer
+        let mut signers = chain.signers().to_vec();
+        signers[0] = crate::genesis::dev_accounts()[3];
+        let (other, other_filter) = fork_id(PoaConfig { signers, ..chain.poa_config().clone() });
+        assert_ne!(other.hash, same.hash);
+        assert!(other_filter.validate(same).is_err());
+        let (slower, _) = fork_id(PoaConfig { period: 3, ..chain.poa_config().clone() });
+        assert_ne!(slower.hash, same.hash);
+
+        // In compat mode the fork id is the vanilla one
+        let (compat, _) = fork_id(PoaConfig { compat_forkid: true, ..chain.poa_config().clone() });
+        assert_eq!(compat, chain.inner().latest_fork_id());
+        assert_ne!(compat, same);
+    }
+
     #[test]
     fn test_hardforks_enabled() {
         let chain = PoaChainSpec::dev_chain();
@@ -258,9 +1578,420 @@ mod tests {
         assert!(chain.fork(EthereumHardfork::Prague).active_at_timestamp(0));
     }
 
+    #[test]
+    fn test_hardfork_schedule() {
+        const CANCUN: u64 = 1_800_000_000;
+        let hardforks = HardforkSchedule::default()
+            .with_fork(EthereumHardfork::Cancun, ForkCondition::Timestamp(CANCUN))
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Never);
+        let poa_config = PoaConfig { hardforks, ..PoaChainSpec::dev_chain().poa_config().clone() };
+        let chain =
+            PoaChainSpec::try_new(crate::genesis::create_dev_genesis(), poa_config).unwrap();
+
+        let cancun = chain.fork(EthereumHardfork::Cancun);
+        assert!(!cancun.active_at_timestamp(CANCUN - 1));
+        assert!(cancun.active_at_timestamp(CANCUN));
+        assert!(chain.fork(EthereumHardfork::Shanghai).active_at_timestamp(0));
+        assert!(!chain.fork(EthereumHardfork::Prague).active_at_timestamp(u64::MAX));
+
+        // Prague can't activate at genesis while Cancun waits
+        let poa_config = PoaConfig {
+            hardforks: HardforkSchedule::default()
+                .with_fork(EthereumHardfork::Cancun, ForkCondition::Timestamp(CANCUN)),
+            ..PoaChainSpec::dev_chain().poa_config().clone()
+        };
+        assert!(matches!(
+            PoaChainSpec::try_new(crate::genesis::create_dev_genesis(), poa_config),
+            Err(PoaConfigError::HardforkOutOfOrder {
+                fork: EthereumHardfork::Prague,
+                previous: EthereumHardfork::Cancun,
+            })
+        ));
+
+        // A chain definition only lists the hardforks that differ from the default
+        let config: PoaConfig = serde_json::from_str(
+            r#"{"period":2,"epoch":100,"signers":[],
+            "hardforks":{"Cancun":{"Timestamp":1800000000},"Prague":"Never"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.hardforks.fork(EthereumHardfork::Cancun),
+            ForkCondition::Timestamp(CANCUN)
+        );
+        assert_eq!(config.hardforks.fork(EthereumHardfork::London), ForkCondition::Block(0));
+    }
+
+    #[test]
+    fn test_disabled_hardforks() {
+        // Staying on Shanghai means disabling every later fork
+        let hardforks = HardforkSchedule::default()
+            .with_fork(EthereumHardfork::Cancun, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Never);
+        let poa_config = PoaConfig { hardforks, ..PoaChainSpec::dev_chain().poa_config().clone() };
+        let chain = PoaChainSpec::try_new(crate::genesis::create_dev_genesis(), poa_config.clone())
+            .unwrap();
+        for timestamp in [0, 1_800_000_000, u64::MAX] {
+            assert!(chain.fork(EthereumHardfork::Shanghai).active_at_timestamp(timestamp));
+            assert!(!chain.fork(EthereumHardfork::Cancun).active_at_timestamp(timestamp));
+            assert!(!chain.inner().is_cancun_active_at_timestamp(timestamp));
+            assert!(chain.blob_params_at_timestamp(timestamp).is_none());
+        }
+        assert!(!chain.supports_blob_transactions());
+        assert!(PoaChainSpec::dev_chain().supports_blob_transactions());
+
+        // Prague can't activate without Cancun
+        let hardforks = poa_config
+            .hardforks
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Timestamp(1_800_000_000));
+        assert!(matches!(
+            PoaChainSpec::try_new(
+                crate::genesis::create_dev_genesis(),
+                PoaConfig { hardforks, ..poa_config }
+            ),
+            Err(PoaConfigError::HardforkAfterDisabled {
+                fork: EthereumHardfork::Prague,
+                disabled: EthereumHardfork::Cancun,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_schedule_upgrade() {
+        const PRAGUE: u64 = 1_800_000_000;
+        let chain = PoaChainSpecBuilder::from(&PoaChainSpec::dev_chain())
+            .activate_at(EthereumHardfork::Prague, ForkCondition::Timestamp(PRAGUE))
+            .build()
+            .unwrap();
+        let head = Head { number: 100, timestamp: PRAGUE - 1_000, ..Default::default() };
+        let upgrade = |timestamp| {
+            PoaChainSpecBuilder::from(&chain)
+                .with_head(head)
+                .schedule_upgrade(EthereumHardfork::Osaka, timestamp)
+        };
+
+        // Osaka goes after Prague
+        let osaka = upgrade(PRAGUE + 1_000).unwrap().build().unwrap();
+        assert_eq!(osaka.fork(EthereumHardfork::Osaka), ForkCondition::Timestamp(PRAGUE + 1_000));
+        assert!(osaka.is_osaka_active_at_timestamp(PRAGUE + 1_000));
+        assert!(!osaka.is_osaka_active_at_timestamp(PRAGUE + 999));
+
+        // Every operator building the same schedule gets the same digest
+        let again = upgrade(PRAGUE + 1_000).unwrap().build().unwrap();
+        assert_eq!(osaka.upgrade_digest(), again.upgrade_digest());
+        assert_ne!(osaka.upgrade_digest(), chain.upgrade_digest());
+        let later = upgrade(PRAGUE + 2_000).unwrap().build().unwrap();
+        assert_ne!(osaka.upgrade_digest(), later.upgrade_digest());
+
+        assert!(matches!(
+            upgrade(PRAGUE - 500),
+            Err(PoaConfigError::UpgradeNotAfterActivation {
+                fork: EthereumHardfork::Osaka,
+                previous: EthereumHardfork::Prague,
+                activation: PRAGUE,
+                ..
+            })
+        ));
+        assert!(matches!(
+            upgrade(PRAGUE - 2_000),
+            Err(PoaConfigError::UpgradeNotInFuture { head: 1_799_999_000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_deposit_contract() {
+        let address = Address::repeat_byte(0x42);
+        let genesis =
+            crate::genesis::create_genesis(GenesisConfig::dev().with_deposit_contract(address));
+        let deposit_contract = DepositContract::new(address, 0, DEPOSIT_EVENT_TOPIC);
+        let poa_config = PoaConfig {
+            deposit_contract: Some(deposit_contract),
+            ..PoaChainSpec::dev_chain().poa_config().clone()
+        };
+        let chain = PoaChainSpec::try_new(genesis, poa_config).unwrap();
+        assert_eq!(chain.deposit_contract(), Some(&deposit_contract));
+        assert_eq!(PoaChainSpec::dev_chain().deposit_contract(), None);
+
+        let account = &chain.genesis().alloc[&address];
+        assert_eq!(
+            account.code.as_deref().map(|code| &code[..]),
+            Some(crate::deposit::DEPOSIT_CONTRACT_CODE)
+        );
+        assert_eq!(account.storage, Some(crate::deposit::deposit_contract_storage()));
+
+        // The topic can be left out of a chain definition
+        let config: PoaConfig = serde_json::from_str(
+            r#"{"period":2,"epoch":100,"signers":[],
+            "depositContract":{"address":"0x4242424242424242424242424242424242424242"}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.deposit_contract, Some(deposit_contract));
+        let json = chain.to_json().unwrap();
+        assert_eq!(
+            PoaChainSpec::from_json(&json).unwrap().deposit_contract(),
+            Some(&deposit_contract)
+        );
+    }
+
+    #[test]
+    fn test_advanced_config() {
+        let dev_config = PoaChainSpec::dev_chain().poa_config().clone();
+        let advanced = AdvancedConfig {
+            prune_delete_limit: 100,
+            paris_block_and_final_difficulty: Some((0, U256::from(1))),
+        };
+        let chain = PoaChainSpec::try_new(
+            crate::genesis::create_dev_genesis(),
+            PoaConfig { advanced, ..dev_config.clone() },
+        )
+        .unwrap();
+        assert_eq!(chain.prune_delete_limit(), 100);
+        assert_eq!(chain.final_paris_total_difficulty(), Some(U256::from(1)));
+
+        // Leaving the section out keeps the defaults
+        assert_eq!(PoaChainSpec::dev_chain().prune_delete_limit(), 10000);
+        assert_eq!(PoaChainSpec::dev_chain().final_paris_total_difficulty(), Some(U256::ZERO));
+        let config: PoaConfig = serde_json::from_str(
+            r#"{"period":2,"epoch":100,"signers":[],"advanced":{"pruneDeleteLimit":5}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.advanced, AdvancedConfig { prune_delete_limit: 5, ..Default::default() });
+
+        let advanced = AdvancedConfig { prune_delete_limit: 0, ..Default::default() };
+        assert!(matches!(
+            PoaChainSpec::try_new(
+                crate::genesis::create_dev_genesis(),
+                PoaConfig { advanced, ..dev_config }
+            ),
+            Err(PoaConfigError::ZeroPruneDeleteLimit)
+        ));
+    }
+
+    #[test]
+    fn test_poa_activation() {
+        let dev = PoaChainSpec::dev_chain();
+        let ttd = U256::from(58_750_000_000_000_000_000_000u128);
+        let migrated = PoaChainSpec::new(
+            dev.genesis().clone(),
+            PoaConfig {
+                poa_activation_block: Some(100),
+                pre_activation_ttd: Some(ttd),
+                ..dev.poa_config().clone()
+            },
+        );
+        assert_eq!(migrated.poa_activation_block(), 100);
+        assert_eq!(migrated.inner().paris_block_and_final_difficulty, Some((100, ttd)));
+        assert_eq!(migrated.final_paris_total_difficulty(), Some(ttd));
+        assert!(!migrated.fork(EthereumHardfork::Paris).active_at_block(99));
+        assert!(migrated.fork(EthereumHardfork::Paris).active_at_block(100));
+
+        // Before the transition the fork id announces it, after it the transition is part of it
+        let head = |number| Head { number, ..Default::default() };
+        let before = migrated.fork_id(&head(99));
+        let after = migrated.fork_id(&head(100));
+        assert_eq!(before.next, 100);
+        assert_eq!(after.next, 0);
+        assert_ne!(before.hash, after.hash);
+        assert_eq!(migrated.fork_id(&head(1_000)), after);
+        assert_eq!(migrated.latest_fork_id(), after);
+        assert!(migrated.fork_filter(head(99)).validate(after).is_ok());
+        assert!(migrated.fork_filter(head(100)).validate(before).is_ok());
+        // A node unaware of the transition is rejected once it happened
+        let unaware = ForkId { next: 0, ..before };
+        assert!(migrated.fork_filter(head(100)).validate(unaware).is_err());
+        // A chain that was POA from genesis on has no transition to announce
+        assert_eq!(dev.fork_id(&head(99)).next, 0);
+        assert_eq!(dev.poa_activation_block(), 0);
+
+        assert!(matches!(
+            PoaChainSpec::try_new(
+                dev.genesis().clone(),
+                PoaConfig { pre_activation_ttd: Some(ttd), ..dev.poa_config().clone() }
+            ),
+            Err(PoaConfigError::TtdWithoutActivation)
+        ));
+    }
+
+    #[test]
+    fn test_base_fee_params() {
+        const CANCUN: u64 = 1_800_000_000;
+        // A full block at 1 gwei
+        let parent = Header {
+            gas_limit: 30_000_000,
+            gas_used: 30_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            ..Default::default()
+        };
+
+        let ethereum = PoaChainSpec::dev_chain();
+        assert_eq!(ethereum.next_block_base_fee(&parent, 0), Some(1_125_000_000));
+
+        let gentle = PoaChainSpec::new(
+            crate::genesis::create_dev_genesis(),
+            PoaConfig {
+                base_fee_params: Some(BaseFeeParams::new(50, 2)),
+                ..ethereum.poa_config().clone()
+            },
+        );
+        assert_eq!(gentle.base_fee_params_at_timestamp(0), BaseFeeParams::new(50, 2));
+        assert_eq!(gentle.next_block_base_fee(&parent, 0), Some(1_020_000_000));
+
+        // Switching to the gentle curve at Cancun
+        let poa_config = PoaConfig {
+            hardforks: HardforkSchedule::default()
+                .with_fork(EthereumHardfork::Cancun, ForkCondition::Timestamp(CANCUN))
+                .with_fork(EthereumHardfork::Prague, ForkCondition::Timestamp(CANCUN)),
+            base_fee_params_forks: BTreeMap::from([(
+                EthereumHardfork::Cancun,
+                BaseFeeParams::new(50, 2),
+            )]),
+            ..ethereum.poa_config().clone()
+        };
+        let forked = PoaChainSpec::new(crate::genesis::create_dev_genesis(), poa_config.clone());
+        assert_eq!(forked.next_block_base_fee(&parent, CANCUN - 1), Some(1_125_000_000));
+        assert_eq!(forked.next_block_base_fee(&parent, CANCUN), Some(1_020_000_000));
+
+        // London activates by block, so parameters tied to it would never apply
+        let mut poa_config = poa_config;
+        poa_config
+            .base_fee_params_forks
+            .insert(EthereumHardfork::London, BaseFeeParams::new(50, 2));
+        assert!(matches!(
+            PoaChainSpec::try_new(crate::genesis::create_dev_genesis(), poa_config),
+            Err(PoaConfigError::BaseFeeForkNotTimestamp(EthereumHardfork::London))
+        ));
+        let poa_config = PoaConfig {
+            base_fee_params: Some(BaseFeeParams::new(0, 2)),
+            ..ethereum.poa_config().clone()
+        };
+        assert!(matches!(
+            PoaChainSpec::try_new(crate::genesis::create_dev_genesis(), poa_config),
+            Err(PoaConfigError::InvalidBaseFeeParams { max_change_denominator: 0, .. })
+        ));
+        let poa_config = PoaConfig {
+            zero_base_fee: true,
+            base_fee_params: Some(BaseFeeParams::new(50, 2)),
+            ..ethereum.poa_config().clone()
+        };
+        assert!(matches!(
+            PoaChainSpec::try_new(crate::genesis::create_dev_genesis(), poa_config),
+            Err(PoaConfigError::ZeroBaseFeeParams)
+        ));
+    }
+
+    #[test]
+    fn test_blob_params_override() {
+        let config: PoaConfig = serde_json::from_str(
+            r#"{"period":2,"epoch":100,"signers":[],"blobParams":{"prague":{"target":2,"max":4}}}"#,
+        )
+        .unwrap();
+        let config = PoaConfig { signers: crate::genesis::dev_signers(), ..config };
+        let chain = PoaChainSpec::new(crate::genesis::create_dev_genesis_for(&config), config);
+        let params = chain.blob_params_at_timestamp(0).unwrap();
+        assert_eq!((params.target_blob_count, params.max_blob_count), (2, 4));
+        assert_eq!(params.max_blobs_per_tx, 4);
+        assert_eq!(chain.inner().blob_params.cancun, BlobParams::cancun());
+
+        let blob_params = BlobScheduleOverride {
+            cancun: Some(BlobCounts { target: 3, max: 2 }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            PoaChainSpec::try_new(
+                crate::genesis::create_dev_genesis(),
+                PoaConfig { blob_params: Some(blob_params), ..chain.poa_config().clone() }
+            ),
+            Err(PoaConfigError::InvalidBlobCounts { fork: EthereumHardfork::Cancun, .. })
+        ));
+    }
+
+    #[test]
+    fn test_bootnodes() {
+        const ENODE: &str = "enode://d860a01f9722d78051619d1e2351aba3f43f943f6f00718d1b9baa4101932a1f5011f16bb2b1bb35db20d6fe28fa0bf09636d26a87d31de9ec6203eeedb1f666@18.138.108.67:30303";
+        let record = parse_bootnode(ENODE).unwrap();
+        assert_eq!(record.tcp_port, 30303);
+
+        let mut definition = serde_json::to_value(PoaChainSpec::dev_chain().poa_config()).unwrap();
+        definition["bootnodes"] = serde_json::json!([ENODE]);
+        let config: PoaConfig = serde_json::from_value(definition).unwrap();
+        assert_eq!(config.bootnodes, [record]);
+        assert_eq!(serde_json::to_value(&config).unwrap()["bootnodes"], serde_json::json!([ENODE]));
+
+        let chain = PoaChainSpec::new(crate::genesis::create_dev_genesis(), config);
+        assert_eq!(chain.bootnodes(), Some(vec![record]));
+        assert_eq!(PoaChainSpec::dev_chain().bootnodes(), None);
+
+        let (id, host) = ENODE.trim_start_matches("enode://").split_once('@').unwrap();
+        for (enode, reason) in [
+            (format!("{id}@{host}"), "enode://"),
+            (format!("enode://{}@{host}", &id[2..]), "invalid node id"),
+            (format!("enode://{id}@18.138.108.67"), "no port"),
+        ] {
+            let err = parse_bootnode(&enode).unwrap_err();
+            assert!(err.to_string().contains(reason), "{err}");
+
+            let mut definition = serde_json::to_value(PoaChainSpec::dev_chain().genesis()).unwrap();
+            definition["poa"] = serde_json::json!({
+                "period": 2, "epoch": 100, "signers": crate::genesis::dev_signers(),
+                "bootnodes": [enode],
+            });
+            let err = PoaChainSpec::from_json_str(&definition.to_string()).unwrap_err();
+            assert!(err.to_string().contains(reason), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_chain_spec_json_round_trip() {
+        let dev = PoaChainSpec::dev_chain();
+        let json = dev.to_json().unwrap();
+        let chain = PoaChainSpec::from_json(&json).unwrap();
+        assert_eq!(chain.genesis_hash(), dev.genesis_hash());
+        assert_eq!(chain.latest_fork_id(), dev.latest_fork_id());
+        assert_eq!(chain.signers(), dev.signers());
+        assert_eq!(chain.to_json().unwrap(), json);
+
+        // Hand-edited hardforks that the POA config doesn't produce are rejected
+        let mut def: serde_json::Value = serde_json::from_str(&json).unwrap();
+        def["hardforks"]["Cancun"] = serde_json::json!({ "Timestamp": 1_800_000_000 });
+        assert!(matches!(
+            PoaChainSpec::from_json(&def.to_string()),
+            Err(PoaChainSpecError::InvalidDefinition(_))
+        ));
+        assert!(matches!(PoaChainSpec::from_json("{"), Err(PoaChainSpecError::Syntax(_))));
+    }
+
+    #[test]
+    fn test_config_invariants() {
+        let valid = PoaChainSpec::dev_chain().poa_config().clone();
+        let signer = valid.signers[0];
+        let try_new = |poa_config: PoaConfig| {
+            PoaChainSpec::try_new(crate::genesis::create_dev_genesis_for(&poa_config), poa_config)
+        };
+        assert!(try_new(PoaConfig { period: 0, ..valid.clone() }).is_ok());
+        assert!(try_new(PoaConfig { period: MAX_PERIOD, ..valid.clone() }).is_ok());
+
+        assert!(matches!(
+            try_new(PoaConfig { signers: vec![], ..valid.clone() }),
+            Err(PoaConfigError::NoSigners)
+        ));
+        assert!(matches!(
+            try_new(PoaConfig { signers: vec![signer, signer], ..valid.clone() }),
+            Err(PoaConfigError::DuplicateSigner(duplicate)) if duplicate == signer
+        ));
+        assert!(matches!(
+            try_new(PoaConfig { signers: vec![signer, Address::ZERO], ..valid.clone() }),
+            Err(PoaConfigError::ZeroSigner)
+        ));
+        assert!(matches!(
+            try_new(PoaConfig { epoch: 0, ..valid.clone() }),
+            Err(PoaConfigError::ZeroEpoch)
+        ));
+        let err = try_new(PoaConfig { period: MAX_PERIOD + 1, ..valid }).unwrap_err();
+        assert!(matches!(err, PoaConfigError::PeriodTooLong(3601)));
+        assert_eq!(err.to_string(), "period: 3601 seconds exceeds the maximum of 3600");
+    }
+
     #[test]
     fn test_round_robin_signer() {
-        let genesis = crate::genesis::create_dev_genesis();
         let poa_config = PoaConfig {
             period: 2,
             epoch: 30000,
@@ -269,7 +2000,9 @@ mod tests {
                 "0x0000000000000000000000000000000000000002".parse().unwrap(),
                 "0x0000000000000000000000000000000000000003".parse().unwrap(),
             ],
+            ..Default::default()
         };
+        let genesis = crate::genesis::create_dev_genesis_for(&poa_config);
         let chain = PoaChainSpec::new(genesis, poa_config);
 
         // Test round-robin assignment
@@ -290,4 +2023,204 @@ mod tests {
             Some(&"0x0000000000000000000000000000000000000001".parse().unwrap())
         );
     }
+
+    #[test]
+    fn test_signer_order_does_not_matter() {
+        let signers = crate::genesis::dev_signers();
+        let reversed: Vec<_> = signers.iter().rev().copied().collect();
+        let chain_with = |signers: Vec<Address>| {
+            let genesis = crate::genesis::create_genesis(GenesisConfig {
+                signers: signers.clone(),
+                ..GenesisConfig::dev()
+            });
+            PoaChainSpec::new(genesis, PoaConfig { period: 2, signers, ..Default::default() })
+        };
+        let (a, b) = (chain_with(signers), chain_with(reversed));
+
+        assert!(a.signers().is_sorted());
+        assert_eq!(a.signers(), b.signers());
+        assert_eq!(a.genesis_hash(), b.genesis_hash());
+        for number in 0..6 {
+            assert_eq!(a.expected_signer(number), b.expected_signer(number));
+        }
+
+        // The rotation is the one consensus enforces
+        let snapshot = Snapshot::new_genesis(&b);
+        for number in 1..6 {
+            assert_eq!(snapshot.inturn_signer(number).as_ref(), b.expected_signer(number));
+        }
+    }
+
+    #[test]
+    fn test_authorized_signer_lookup() {
+        let signers: Vec<_> = (1..=100u8).map(Address::repeat_byte).collect();
+        let config = PoaConfig { signers: signers.clone(), ..Default::default() };
+        let chain = PoaChainSpec::new(crate::genesis::create_dev_genesis_for(&config), config);
+        for signer in &signers {
+            assert!(chain.is_authorized_signer(signer));
+            assert!(chain.is_authorized_signer_at(signer, 1, None));
+        }
+        let outsider = Address::repeat_byte(0xff);
+        assert!(!chain.is_authorized_signer(&outsider));
+        assert!(!chain.is_authorized_signer_at(&signers[0], 0, None));
+
+        // A snapshot after the set changed takes precedence over the genesis signers
+        let snapshot = Snapshot::new(chain.epoch(), 10, B256::ZERO, [outsider, signers[1]]);
+        assert!(chain.is_authorized_signer_at(&outsider, 11, Some(&snapshot)));
+        assert!(chain.is_authorized_signer_at(&signers[1], 11, Some(&snapshot)));
+        assert!(!chain.is_authorized_signer_at(&signers[0], 11, Some(&snapshot)));
+    }
+
+    #[test]
+    fn test_expected_total_difficulty() {
+        let chain = PoaChainSpec::dev_chain();
+        assert_eq!(chain.expected_total_difficulty_at(0), U256::from(1));
+        assert_eq!(chain.expected_total_difficulty_at(100), U256::from(101));
+    }
+
+    #[test]
+    fn test_next_block_timestamp() {
+        let genesis = crate::genesis::create_dev_genesis();
+        let config =
+            PoaConfig { period: 2, signers: crate::genesis::dev_signers(), ..Default::default() };
+        let relaxed = PoaChainSpec::new(genesis.clone(), config.clone());
+        let exact = PoaChainSpec::new(genesis, PoaConfig { exact_period: true, ..config });
+
+        // Ahead of the wall clock both modes wait for the period
+        assert_eq!(relaxed.next_block_timestamp(100, 101), 102);
+        assert_eq!(exact.next_block_timestamp(100, 101), 102);
+
+        // Behind the wall clock only the relaxed mode catches up
+        assert_eq!(relaxed.next_block_timestamp(100, 110), 110);
+        assert_eq!(exact.next_block_timestamp(100, 110), 102);
+    }
+
+    #[test]
+    fn test_from_geth_clique_genesis() {
+        let genesis: Genesis =
+            serde_json::from_str(include_str!("../testdata/clique_genesis.json")).unwrap();
+        let chain = PoaChainSpec::from_genesis(genesis).unwrap();
+
+        assert_eq!(chain.inner().chain.id(), 5);
+        assert_eq!(chain.block_period(), 15);
+        assert_eq!(chain.epoch(), 30000);
+        assert_eq!(
+            chain.signers(),
+            [alloy_primitives::address!("e0a2bd4258d2768837baa26a28fe71dc079f84c7")]
+        );
+    }
+
+    #[test]
+    fn test_from_genesis_rejects_inconsistent_clique_data() {
+        let genesis: Genesis =
+            serde_json::from_str(include_str!("../testdata/clique_genesis.json")).unwrap();
+        let with_extra =
+            |extra_data: Vec<u8>| Genesis { extra_data: extra_data.into(), ..genesis.clone() };
+        let signer = [0x11; 20];
+
+        let mut no_clique = genesis.clone();
+        no_clique.config.clique = None;
+        assert!(matches!(
+            PoaChainSpec::from_genesis(no_clique),
+            Err(PoaChainSpecError::MissingClique)
+        ));
+
+        let mut no_epoch = genesis.clone();
+        no_epoch.config.clique.as_mut().unwrap().epoch = None;
+        assert!(matches!(
+            PoaChainSpec::from_genesis(no_epoch),
+            Err(PoaChainSpecError::MissingCliqueField("epoch"))
+        ));
+
+        let truncated = [[0; 32].as_slice(), &signer[..19], &[0; 65]].concat();
+        assert!(matches!(
+            PoaChainSpec::from_genesis(with_extra(truncated)),
+            Err(PoaChainSpecError::InvalidExtraData(116))
+        ));
+        assert!(matches!(
+            PoaChainSpec::from_genesis(with_extra(vec![0; 97])),
+            Err(PoaChainSpecError::Config(PoaConfigError::NoSigners))
+        ));
+
+        let duplicate = [[0; 32].as_slice(), &signer, &signer, &[0; 65]].concat();
+        assert!(matches!(
+            PoaChainSpec::from_genesis(with_extra(duplicate)),
+            Err(PoaChainSpecError::Config(PoaConfigError::DuplicateSigner(_)))
+        ));
+    }
+
+    #[test]
+    fn test_from_json_file_formats() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Bare geth genesis
+        let bare = dir.path().join("genesis.json");
+        fs::write(&bare, include_str!("../testdata/clique_genesis.json")).unwrap();
+        let chain = PoaChainSpec::from_json_file(&bare).unwrap();
+        assert_eq!(chain.inner().chain.id(), 5);
+        assert_eq!(chain.block_period(), 15);
+
+        // Genesis with a `poa` object
+        let dev = PoaChainSpec::dev_chain();
+        let mut definition = serde_json::to_value(dev.genesis()).unwrap();
+        definition["poa"] = serde_json::to_value(dev.poa_config()).unwrap();
+        let extended = dir.path().join("chain.json");
+        fs::write(&extended, definition.to_string()).unwrap();
+        let chain = PoaChainSpec::from_json_file(&extended).unwrap();
+        assert_eq!(chain.signers(), dev.signers());
+        assert_eq!(chain.block_period(), dev.block_period());
+        assert_eq!(chain.epoch(), dev.epoch());
+        assert_eq!(chain.genesis_hash(), dev.genesis_hash());
+
+        // The sample genesis shipped with the example is a bare genesis
+        let sample = PoaChainSpec::from_json_str(include_str!("../sample-genesis.json")).unwrap();
+        assert_eq!(sample.signers(), dev.signers());
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            PoaChainSpec::from_json_file(&dir.path().join("missing.json")),
+            Err(PoaChainSpecError::Io(_))
+        ));
+
+        assert!(matches!(
+            PoaChainSpec::from_json_str(r#"{"config": {"chainId": 1"#),
+            Err(PoaChainSpecError::Syntax(_))
+        ));
+
+        let mut definition = serde_json::to_value(PoaChainSpec::dev_chain().genesis()).unwrap();
+        definition["poa"] = serde_json::json!({ "period": 2, "epoch": 100, "signers": [] });
+        assert!(matches!(
+            PoaChainSpec::from_json_str(&definition.to_string()),
+            Err(PoaChainSpecError::Config(PoaConfigError::NoSigners))
+        ));
+
+        definition["poa"]["signers"] = serde_json::json!(["0x1234"]);
+        assert!(matches!(
+            PoaChainSpec::from_json_str(&definition.to_string()),
+            Err(PoaChainSpecError::InvalidDefinition(_))
+        ));
+    }
+
+    #[test]
+    fn test_signer_set_source_config() {
+        let votes: PoaConfig =
+            serde_json::from_str(r#"{"period":2,"epoch":100,"signers":[]}"#).unwrap();
+        assert_eq!(votes.signer_set_source, SignerSetSource::Votes);
+
+        let contract: PoaConfig = serde_json::from_str(
+            r#"{"period":2,"epoch":100,"signers":[],"signerSetSource":{"type":"contract",
+            "address":"0x0000000000000000000000000000000000001000","selector":"0xb7ab4db5"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            contract.signer_set_source,
+            SignerSetSource::Contract {
+                address: alloy_primitives::address!("0000000000000000000000000000000000001000"),
+                selector: Selector::new([0xb7, 0xab, 0x4d, 0xb5]),
+            }
+        );
+    }
 }
@@ -0,0 +1,83 @@
+//! Command line integration
+//!
+//! [`PoaChainSpecParser`] plugs POA chain specs into reth's `--chain` argument.
+
+use crate::{chainspec::PoaChainSpec, presets};
+use eyre::WrapErr;
+use reth_cli::chainspec::ChainSpecParser;
+use std::{path::Path, sync::Arc};
+
+/// Name of [`PoaChainSpec::dev_chain`] on the command line
+pub const POA_DEV: &str = "poa-dev";
+
+/// Chains known by name, the first being the default
+pub const SUPPORTED_CHAINS: &[&str] =
+    &[POA_DEV, presets::DEV, presets::LOCAL_FAST, presets::CONSORTIUM_TEMPLATE];
+
+/// Parses `--chain` values into [`PoaChainSpec`]s
+///
+/// A value is either [`POA_DEV`], the name of a registered preset, or the path to a chain
+/// definition file as read by [`PoaChainSpec::from_json_file`].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct PoaChainSpecParser;
+
+impl ChainSpecParser for PoaChainSpecParser {
+    type ChainSpec = PoaChainSpec;
+
+    const SUPPORTED_CHAINS: &'static [&'static str] = SUPPORTED_CHAINS;
+
+    fn parse(s: &str) -> eyre::Result<Arc<PoaChainSpec>> {
+        if s == POA_DEV {
+            return Ok(Arc::new(PoaChainSpec::dev_chain()));
+        }
+        if let Some(chain) = PoaChainSpec::named(s) {
+            return Ok(Arc::new(chain));
+        }
+        let chain = PoaChainSpec::from_json_file(Path::new(s))
+            .wrap_err_with(|| format!("failed to load chain definition {s}"))?;
+        Ok(Arc::new(chain))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_chainspec::EthChainSpec;
+    use std::fs;
+
+    #[test]
+    fn test_parse_named_chains() {
+        for &name in PoaChainSpecParser::SUPPORTED_CHAINS {
+            assert!(PoaChainSpecParser::parse(name).is_ok(), "{name}");
+        }
+        assert_eq!(PoaChainSpecParser::default_value(), Some(POA_DEV));
+
+        let dev = PoaChainSpecParser::parse(POA_DEV).unwrap();
+        assert_eq!(dev.genesis_hash(), PoaChainSpec::dev_chain().genesis_hash());
+        let fast = PoaChainSpecParser::parse(presets::LOCAL_FAST).unwrap();
+        assert_eq!(fast.block_period(), 1);
+    }
+
+    #[test]
+    fn test_parse_chain_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let dev = PoaChainSpec::dev_chain();
+        let mut definition = serde_json::to_value(dev.genesis()).unwrap();
+        definition["poa"] = serde_json::to_value(dev.poa_config()).unwrap();
+        let path = dir.path().join("mychain.json");
+        fs::write(&path, definition.to_string()).unwrap();
+
+        let chain = PoaChainSpecParser::parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(chain.genesis_hash(), dev.genesis_hash());
+        assert_eq!(chain.signers(), dev.signers());
+    }
+
+    #[test]
+    fn test_parse_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.json");
+        let err = PoaChainSpecParser::parse(path.to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("missing.json"), "{err}");
+    }
+}
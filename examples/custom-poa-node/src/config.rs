@@ -0,0 +1,280 @@
+//! Node configuration file
+//!
+//! Operators describe a node in a single TOML file (`poa.toml` by default) instead of environment
+//! variables and code edits:
+//!
+//! ```toml
+//! datadir = "custompoanode"
+//! keystore = "keys"
+//!
+//! [poa]
+//! period = 2
+//! epoch = 30000
+//! signers = ["0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"]
+//!
+//! [rpc]
+//! http = true
+//! http_addr = "127.0.0.1"
+//! http_port = 8545
+//! ```
+//!
+//! Every key is optional and falls back to the defaults below. Unknown keys are reported with a
+//! warning rather than silently dropped.
+
+use alloy_primitives::Address;
+use reth_tracing::tracing::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// File name the node looks for in its working directory
+pub const DEFAULT_CONFIG_FILE: &str = "poa.toml";
+
+/// Errors from loading a [`PoaNodeConfigFile`]
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The file could not be read
+    #[error("failed to read config file: {0}")]
+    Io(#[from] io::Error),
+
+    /// The file is not valid TOML or a value has the wrong type
+    #[error("failed to parse config file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    /// Epoch length of zero
+    #[error("poa.epoch must be greater than zero")]
+    ZeroEpoch,
+
+    /// A signer is listed more than once
+    #[error("signer {0} is listed more than once")]
+    DuplicateSigner(Address),
+}
+
+/// Consensus settings of the `[poa]` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoaSettings {
+    /// Block period in seconds
+    pub period: u64,
+    /// Number of blocks between checkpoints
+    pub epoch: u64,
+    /// Authorized signers; empty means the signers listed in the genesis extra data
+    pub signers: Vec<Address>,
+    /// Require child timestamps to be exactly `parent + period`
+    pub exact_period: bool,
+    #[serde(flatten, skip_serializing)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl Default for PoaSettings {
+    fn default() -> Self {
+        Self {
+            period: 2,
+            epoch: 30000,
+            signers: Vec::new(),
+            exact_period: false,
+            unknown: BTreeMap::new(),
+        }
+    }
+}
+
+/// HTTP RPC settings of the `[rpc]` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcSettings {
+    /// Serve the HTTP RPC
+    pub http: bool,
+    /// Address the HTTP RPC listens on
+    pub http_addr: IpAddr,
+    /// Port the HTTP RPC listens on
+    pub http_port: u16,
+    #[serde(flatten, skip_serializing)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl Default for RpcSettings {
+    fn default() -> Self {
+        Self {
+            http: true,
+            http_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            http_port: 8545,
+            unknown: BTreeMap::new(),
+        }
+    }
+}
+
+/// Contents of a node configuration file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoaNodeConfigFile {
+    /// Directory holding the chain data
+    pub datadir: PathBuf,
+    /// Directory holding the signer keys, if any
+    pub keystore: Option<PathBuf>,
+    /// Consensus settings
+    pub poa: PoaSettings,
+    /// RPC settings
+    pub rpc: RpcSettings,
+    #[serde(flatten, skip_serializing)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl Default for PoaNodeConfigFile {
+    fn default() -> Self {
+        Self {
+            datadir: PathBuf::from("custompoanode"),
+            keystore: None,
+            poa: PoaSettings::default(),
+            rpc: RpcSettings::default(),
+            unknown: BTreeMap::new(),
+        }
+    }
+}
+
+impl PoaNodeConfigFile {
+    /// Load and validate the configuration file at `path`
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        Self::from_toml_str(&fs::read_to_string(path)?)
+    }
+
+    /// Parse and validate a configuration, logging a warning that lists any unknown keys
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(toml)?;
+        config.validate()?;
+
+        let unknown = config.unknown_keys();
+        if !unknown.is_empty() {
+            warn!(target: "poa::config", keys = %unknown.join(", "), "Ignoring unknown config keys");
+        }
+        Ok(config)
+    }
+
+    /// Dotted paths of the keys that don't correspond to any setting
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let tables =
+            [("", &self.unknown), ("poa.", &self.poa.unknown), ("rpc.", &self.rpc.unknown)];
+        tables
+            .into_iter()
+            .flat_map(|(prefix, unknown)| unknown.keys().map(move |key| format!("{prefix}{key}")))
+            .collect()
+    }
+
+    /// Check the settings for values the node can't run with
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.poa.epoch == 0 {
+            return Err(ConfigError::ZeroEpoch);
+        }
+        for (index, signer) in self.poa.signers.iter().enumerate() {
+            if self.poa.signers[..index].contains(signer) {
+                return Err(ConfigError::DuplicateSigner(*signer));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::PoaChainSpec,
+        genesis::{self, GenesisConfig},
+    };
+
+    const EXAMPLE: &str = r#"
+        datadir = "/var/lib/poa"
+        keystore = "/etc/poa/keys"
+
+        [poa]
+        period = 5
+        epoch = 100
+        signers = [
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+        ]
+        exact_period = true
+
+        [rpc]
+        http = false
+        http_addr = "0.0.0.0"
+        http_port = 9545
+    "#;
+
+    #[test]
+    fn test_full_config() {
+        let config = PoaNodeConfigFile::from_toml_str(EXAMPLE).unwrap();
+        assert_eq!(config.datadir, PathBuf::from("/var/lib/poa"));
+        assert_eq!(config.keystore, Some(PathBuf::from("/etc/poa/keys")));
+        assert_eq!(config.poa.period, 5);
+        assert_eq!(config.poa.epoch, 100);
+        assert_eq!(config.poa.signers, genesis::dev_signers()[..2]);
+        assert!(config.poa.exact_period);
+        assert!(!config.rpc.http);
+        assert_eq!(config.rpc.http_addr, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(config.rpc.http_port, 9545);
+        assert!(config.unknown_keys().is_empty());
+
+        let genesis = genesis::create_genesis(
+            GenesisConfig::dev()
+                .with_signers(config.poa.signers.clone())
+                .with_block_period(5)
+                .with_epoch(100),
+        );
+        let chain = PoaChainSpec::from_config(&config, genesis).unwrap();
+        assert_eq!(chain.block_period(), 5);
+        assert_eq!(chain.epoch(), 100);
+        assert!(chain.exact_period());
+        assert_eq!(chain.signers(), [genesis::dev_signers()[1], genesis::dev_signers()[0]]);
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config = PoaNodeConfigFile::from_toml_str("[poa]\nperiod = 4\n").unwrap();
+        let defaults = PoaNodeConfigFile::default();
+        assert_eq!(config.poa.period, 4);
+        assert_eq!(config.poa.epoch, defaults.poa.epoch);
+        assert_eq!(config.datadir, defaults.datadir);
+        assert_eq!(config.rpc, defaults.rpc);
+        assert_eq!(PoaNodeConfigFile::from_toml_str("").unwrap(), defaults);
+
+        // Without signers the genesis extra data decides
+        let genesis = genesis::create_genesis(GenesisConfig::dev().with_block_period(4));
+        let chain = PoaChainSpec::from_config(&config, genesis).unwrap();
+        let mut signers = genesis::dev_signers();
+        signers.sort();
+        assert_eq!(chain.signers(), signers);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_reported() {
+        let config = PoaNodeConfigFile::from_toml_str(
+            "datadir = \"x\"\nblock_time = 3\n[poa]\nperoid = 5\n[rpc]\nws = true\n",
+        )
+        .unwrap();
+        assert_eq!(config.unknown_keys(), ["block_time", "poa.peroid", "rpc.ws"]);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(matches!(
+            PoaNodeConfigFile::from_toml_str("[poa]\nepoch = 0\n"),
+            Err(ConfigError::ZeroEpoch)
+        ));
+        assert!(matches!(
+            PoaNodeConfigFile::from_toml_str("[poa]\nperiod = \"fast\"\n"),
+            Err(ConfigError::Parse(_))
+        ));
+        let signer = genesis::dev_signers()[0];
+        assert!(matches!(
+            PoaNodeConfigFile::from_toml_str(&format!(
+                "[poa]\nsigners = [\"{signer}\", \"{signer}\"]\n"
+            )),
+            Err(ConfigError::DuplicateSigner(_))
+        ));
+    }
+}
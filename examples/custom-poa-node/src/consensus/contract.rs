@@ -0,0 +1,403 @@
+//! Contract-governed signer sets
+//!
+//! Under [`SignerSetSource::Contract`](crate::chainspec::SignerSetSource::Contract) the signer set
+//! is not voted on in headers. Instead, the producer of every epoch block calls a view function
+//! of a governance contract on top of the parent block and writes the returned addresses into the
+//! checkpoint extra data. Validators repeat the call and reject the block if the lists differ.
+//!
+//! The call runs in the environment of the parent block, so the contract sees the parent's
+//! `block.number` and `block.timestamp`, not those of the checkpoint. The producer has to make the
+//! call before its header exists, and the parent is all both sides know in common.
+//!
+//! Calls go through a [`SignerContractReader`], which [`StateSignerContractReader`] implements on
+//! top of the node's state providers.
+
+use alloy_consensus::Header;
+use alloy_primitives::{fixed_bytes, Address, Selector, B256};
+use reth_evm::{ConfigureEvm, Database, Evm};
+use reth_evm_ethereum::EthEvmConfig;
+use reth_revm::{context::result::ExecutionResult, database::StateProviderDatabase};
+use reth_storage_api::{errors::ProviderError, HeaderProvider, StateProviderFactory};
+use std::fmt::Debug;
+use thiserror::Error;
+
+/// Selector of `getValidators()`, the usual name of the signer set getter
+pub const GET_VALIDATORS_SELECTOR: Selector = fixed_bytes!("b7ab4db5");
+
+/// Errors from reading the signer set out of the governance contract
+#[derive(Debug, Error)]
+pub enum SignerContractError {
+    /// Consensus has no way to call the contract
+    #[error("no signer contract reader configured")]
+    Unavailable,
+
+    /// The block the call should run on top of is unknown
+    #[error("header {0} not found")]
+    MissingHeader(B256),
+
+    /// Accessing the state failed
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+
+    /// The EVM could not execute the call
+    #[error("failed to execute signer contract call: {0}")]
+    Execution(String),
+
+    /// The call reverted or halted
+    #[error("signer contract call did not succeed: {0}")]
+    Failed(String),
+
+    /// The call returned something other than an ABI encoded `address[]`
+    #[error("signer contract returned malformed output")]
+    MalformedOutput,
+}
+
+/// Reads the signer set reported by a governance contract
+pub trait SignerContractReader: Debug + Send + Sync {
+    /// Call `selector` on `contract` in the environment of block `parent`, against the state after
+    /// it, and decode the returned `address[]`
+    fn signers_at(
+        &self,
+        parent: B256,
+        contract: Address,
+        selector: Selector,
+    ) -> Result<Vec<Address>, SignerContractError>;
+}
+
+/// Call `selector` on `contract` in the environment of `parent`, reading the state after it from
+/// `db`
+pub fn call_signer_contract<DB: Database>(
+    evm_config: &EthEvmConfig,
+    db: DB,
+    parent: &Header,
+    contract: Address,
+    selector: Selector,
+) -> Result<Vec<Address>, SignerContractError> {
+    let Ok(mut evm) = evm_config.evm_for_block(db, parent);
+    let result = evm
+        .transact_system_call(Address::ZERO, contract, selector.into())
+        .map_err(|err| SignerContractError::Execution(err.to_string()))?
+        .result;
+
+    match result {
+        ExecutionResult::Success { output, .. } => decode_address_array(output.data()),
+        other => Err(SignerContractError::Failed(format!("{other:?}"))),
+    }
+}
+
+/// Decode the return data of a function returning `address[]`
+pub fn decode_address_array(data: &[u8]) -> Result<Vec<Address>, SignerContractError> {
+    let items = address_array_items(data).ok_or(SignerContractError::MalformedOutput)?;
+    items
+        .chunks_exact(32)
+        .map(|item| {
+            if item[..12].iter().any(|byte| *byte != 0) {
+                return Err(SignerContractError::MalformedOutput);
+            }
+            Ok(Address::from_slice(&item[12..]))
+        })
+        .collect()
+}
+
+/// The encoded items of an ABI encoded `address[]`, `None` if the offset or length is out of
+/// bounds
+///
+/// Both come from the contract, so every step is checked rather than trusted.
+fn address_array_items(data: &[u8]) -> Option<&[u8]> {
+    let word = |index: usize| -> Option<usize> {
+        let word = data.get(index..index.checked_add(32)?)?;
+        // Offsets and lengths beyond a machine word can never be valid
+        if word[..24].iter().any(|byte| *byte != 0) {
+            return None
+        }
+        usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+    };
+
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    data.get(start..start.checked_add(len.checked_mul(32)?)?)
+}
+
+/// [`SignerContractReader`] running calls against the node's historical state
+#[derive(Debug)]
+pub struct StateSignerContractReader<P> {
+    provider: P,
+    evm_config: EthEvmConfig,
+}
+
+impl<P> StateSignerContractReader<P> {
+    /// Create a reader calling the contract through `evm_config` against `provider`'s state
+    pub const fn new(provider: P, evm_config: EthEvmConfig) -> Self {
+        Self { provider, evm_config }
+    }
+}
+
+impl<P> SignerContractReader for StateSignerContractReader<P>
+where
+    P: StateProviderFactory + HeaderProvider<Header = Header> + Debug + Send + Sync,
+{
+    fn signers_at(
+        &self,
+        parent: B256,
+        contract: Address,
+        selector: Selector,
+    ) -> Result<Vec<Address>, SignerContractError> {
+        let header =
+            self.provider.header(parent)?.ok_or(SignerContractError::MissingHeader(parent))?;
+        let state = self.provider.state_by_block_hash(parent)?;
+        call_signer_contract(
+            &self.evm_config,
+            StateProviderDatabase::new(state),
+            &header,
+            contract,
+            selector,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::{PoaChainSpec, PoaConfig, SignerSetSource},
+        consensus::{
+            test_utils::{genesis_state, ChainHarness},
+            PoaConsensusError,
+        },
+        genesis::{self, GenesisConfig},
+        signer::dev,
+    };
+    use alloy_consensus::{transaction::Recovered, SignableTransaction, TxEip1559};
+    use alloy_primitives::{address, keccak256, Bytes, TxKind, U256};
+    use alloy_signer::SignerSync;
+    use parking_lot::Mutex;
+    use reth_revm::{
+        bytecode::Bytecode,
+        db::{CacheDB, EmptyDB},
+        state::AccountInfo,
+    };
+    use std::{collections::HashMap, sync::Arc};
+
+    const CONTRACT: Address = address!("0000000000000000000000000000000000001000");
+
+    /// EVM state after each block of a test chain, keyed by block hash
+    #[derive(Debug)]
+    struct InMemoryStates {
+        evm_config: EthEvmConfig,
+        states: Mutex<HashMap<B256, (Header, CacheDB<EmptyDB>)>>,
+    }
+
+    impl InMemoryStates {
+        /// Start with the genesis allocation
+        fn new(chain: &PoaChainSpec) -> Self {
+            let db = genesis_state(chain);
+            let genesis_header = chain.inner().genesis_header().clone();
+            let states = HashMap::from([(chain.inner().genesis_hash(), (genesis_header, db))]);
+            Self {
+                evm_config: EthEvmConfig::new(chain.inner().clone()),
+                states: Mutex::new(states),
+            }
+        }
+
+        /// Execute `txs` in `header` on top of its parent's state and record the result
+        fn execute(
+            &self,
+            header: &Header,
+            txs: Vec<Recovered<alloy_consensus::Signed<TxEip1559>>>,
+        ) {
+            let (_, mut db) = self.states.lock()[&header.parent_hash].clone();
+            // Test headers leave out the blob fields Cancun execution needs
+            let env_header = Header { excess_blob_gas: Some(0), ..header.clone() };
+            let Ok(mut evm) = self.evm_config.evm_for_block(&mut db, &env_header);
+            for tx in txs {
+                assert!(evm.transact_commit(tx).unwrap().is_success());
+            }
+            drop(evm);
+            self.states.lock().insert(header.hash_slow(), (env_header, db));
+        }
+    }
+
+    impl SignerContractReader for InMemoryStates {
+        fn signers_at(
+            &self,
+            parent: B256,
+            contract: Address,
+            selector: Selector,
+        ) -> Result<Vec<Address>, SignerContractError> {
+            let (header, db) = self
+                .states
+                .lock()
+                .get(&parent)
+                .cloned()
+                .ok_or(SignerContractError::MissingHeader(parent))?;
+            call_signer_contract(&self.evm_config, db, &header, contract, selector)
+        }
+    }
+
+    /// Signed `addValidator(validator)` call from the first dev account
+    fn add_validator_tx(validator: Address) -> Recovered<alloy_consensus::Signed<TxEip1559>> {
+        let mut input = keccak256("addValidator(address)")[..4].to_vec();
+        input.extend_from_slice(validator.into_word().as_slice());
+        let tx = TxEip1559 {
+            chain_id: 31337,
+            nonce: 0,
+            gas_limit: 100_000,
+            max_fee_per_gas: 10_000_000_000,
+            to: TxKind::Call(CONTRACT),
+            input: Bytes::from(input),
+            ..Default::default()
+        };
+        let key = dev::first_dev_signer();
+        let signature = key.sign_hash_sync(&tx.signature_hash()).unwrap();
+        Recovered::new_unchecked(tx.into_signed(signature), key.address())
+    }
+
+    /// ABI encoding of an `address[]` return value
+    fn encode(addresses: &[Address]) -> Vec<u8> {
+        let mut out = vec![0u8; 64];
+        out[31] = 0x20;
+        out[56..].copy_from_slice(&(addresses.len() as u64).to_be_bytes());
+        for address in addresses {
+            out.extend_from_slice(address.into_word().as_slice());
+        }
+        out
+    }
+
+    #[test]
+    fn test_get_validators_selector() {
+        assert_eq!(GET_VALIDATORS_SELECTOR, keccak256("getValidators()")[..4]);
+    }
+
+    #[test]
+    fn test_call_sees_parent_block() {
+        // Returns `[address(block.number)]`
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x20, 0x60, 0x00, 0x52, // mstore(0, 0x20)
+            0x60, 0x01, 0x60, 0x20, 0x52, // mstore(0x20, 1)
+            0x43, 0x60, 0x40, 0x52, // mstore(0x40, number())
+            0x60, 0x60, 0x60, 0x00, 0xf3, // return(0, 0x60)
+        ]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(CONTRACT, AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code));
+
+        let chain = PoaChainSpec::dev_chain();
+        let evm_config = EthEvmConfig::new(chain.inner().clone());
+        let parent = Header {
+            number: 7,
+            gas_limit: 30_000_000,
+            excess_blob_gas: Some(0),
+            ..Default::default()
+        };
+        let signers =
+            call_signer_contract(&evm_config, db, &parent, CONTRACT, GET_VALIDATORS_SELECTOR)
+                .unwrap();
+        assert_eq!(signers, [Address::with_last_byte(7)]);
+    }
+
+    #[test]
+    fn test_decode_address_array() {
+        let addresses = [Address::repeat_byte(1), Address::repeat_byte(2)];
+        assert_eq!(decode_address_array(&encode(&addresses)).unwrap(), addresses);
+        assert_eq!(decode_address_array(&encode(&[])).unwrap(), Vec::<Address>::new());
+
+        // Truncated items, dirty address padding and no data at all are rejected
+        let encoded = encode(&addresses);
+        assert!(decode_address_array(&encoded[..encoded.len() - 1]).is_err());
+        let mut dirty = encoded;
+        dirty[64] = 1;
+        assert!(decode_address_array(&dirty).is_err());
+        assert!(decode_address_array(&[]).is_err());
+
+        // Offsets and lengths that would overflow are rejected instead of panicking
+        for bytes in [0..32, 24..32, 56..64] {
+            let mut huge = encode(&addresses);
+            huge[bytes.clone()].fill(0xff);
+            let err = decode_address_array(&huge).unwrap_err();
+            assert!(matches!(err, SignerContractError::MalformedOutput), "{bytes:?}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_contract_governed_signer_set_follows_contract() {
+        let config = PoaConfig {
+            period: 2,
+            epoch: 4,
+            signers: genesis::dev_signers(),
+            signer_set_source: SignerSetSource::Contract {
+                address: CONTRACT,
+                selector: GET_VALIDATORS_SELECTOR,
+            },
+            ..Default::default()
+        };
+        let genesis = genesis::create_genesis(
+            GenesisConfig::dev().with_poa_config(&config).with_validator_set_contract(CONTRACT),
+        );
+        let chain = Arc::new(PoaChainSpec::new(genesis, config));
+        let manager = dev::setup_dev_signers().await;
+        let newcomer = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[5]).await.unwrap();
+        let dropped = genesis::dev_signers()[2];
+
+        let states = Arc::new(InMemoryStates::new(&chain));
+        let mut harness = ChainHarness::new(chain, manager);
+        harness.consensus = harness.consensus.clone().with_signer_contract_reader(states.clone());
+
+        for number in 1..=4 {
+            let mut builder = harness.builder();
+            if let Some(signers) =
+                harness.consensus.contract_signers(number, harness.parent.hash()).unwrap()
+            {
+                builder = builder.with_checkpoint_signers(signers);
+            } else {
+                // Header votes carry no weight under contract governance
+                builder = builder.with_vote(dropped, false);
+            }
+            let header = builder.build().await;
+
+            // The newcomer is added to the contract in the first block
+            let txs = if number == 1 { vec![add_validator_tx(newcomer)] } else { vec![] };
+            states.execute(header.header(), txs);
+
+            if number == 4 {
+                // A checkpoint that ignores the contract is rejected after execution
+                let stale =
+                    harness.builder().with_checkpoint_signers(genesis::dev_signers()).build().await;
+                states.execute(stale.header(), vec![]);
+                assert!(matches!(
+                    harness.consensus.validate_checkpoint_signers(&stale),
+                    Err(PoaConsensusError::CheckpointSignersMismatch { .. })
+                ));
+            }
+
+            harness.consensus.validate_checkpoint_signers(&header).unwrap();
+            harness.extend(header).unwrap();
+            assert_eq!(harness.snapshot.signers.contains(&newcomer), number == 4, "block {number}");
+            assert!(harness.snapshot.signers.contains(&dropped));
+        }
+
+        // The newcomer may seal right after the checkpoint
+        let header = harness.builder().with_signer(newcomer).build().await;
+        harness.extend(header).unwrap();
+    }
+
+    #[test]
+    fn test_missing_reader_rejects_checkpoints() {
+        let config = PoaConfig {
+            epoch: 4,
+            signers: genesis::dev_signers(),
+            signer_set_source: SignerSetSource::Contract {
+                address: CONTRACT,
+                selector: GET_VALIDATORS_SELECTOR,
+            },
+            ..Default::default()
+        };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis_for(&config), config));
+        let consensus = crate::consensus::PoaConsensus::new(chain);
+
+        assert_eq!(consensus.contract_signers(3, B256::ZERO).unwrap(), None);
+        assert!(matches!(
+            consensus.contract_signers(4, B256::ZERO),
+            Err(PoaConsensusError::SignerContract { source: SignerContractError::Unavailable, .. })
+        ));
+    }
+}
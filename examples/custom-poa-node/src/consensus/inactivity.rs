@@ -0,0 +1,223 @@
+//! Signer inactivity tracking
+//!
+//! A signer whose node crashed or lost its key stops sealing, and the chain keeps going with
+//! out-of-turn blocks from the others. The [`InactivityTracker`] watches canonical headers, counts
+//! the in-turn slots each signer missed over a sliding window and raises an [`InactivityAlert`]
+//! once a signer misses more than the configured number of them.
+
+use super::snapshot::Snapshot;
+use crate::chainspec::PoaChainSpec;
+use alloy_consensus::Header;
+use alloy_primitives::Address;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Number of epochs the sliding window covers by default
+const DEFAULT_WINDOW_EPOCHS: u64 = 2;
+
+/// Activity of a single signer, as reported by [`InactivityTracker::stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerActivity {
+    /// Last canonical block sealed by the signer, if any was seen
+    pub last_sealed: Option<u64>,
+    /// In-turn slots the signer missed within the window
+    pub missed: u64,
+}
+
+/// Raised when a signer misses more in-turn slots than allowed within the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InactivityAlert {
+    /// The inactive signer
+    pub signer: Address,
+    /// Block whose missed slot crossed the threshold
+    pub block: u64,
+    /// In-turn slots missed within the window
+    pub missed: u64,
+    /// Last canonical block sealed by the signer, if any was seen
+    pub last_sealed: Option<u64>,
+}
+
+/// Per-signer bookkeeping
+#[derive(Debug, Default)]
+struct SignerRecord {
+    last_sealed: Option<u64>,
+    /// Block numbers of the missed in-turn slots within the window, oldest first
+    missed: VecDeque<u64>,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    signers: BTreeMap<Address, SignerRecord>,
+    /// Signers above the threshold that were already reported
+    alerted: BTreeSet<Address>,
+}
+
+/// Tracks missed in-turn slots per signer over a sliding window of canonical blocks.
+///
+/// Feed it every canonical header through [`on_canonical_header`](Self::on_canonical_header).
+/// An alert is sent once when a signer crosses the threshold; it is re-armed after the signer's
+/// misses drop back to the threshold or below.
+#[derive(Debug)]
+pub struct InactivityTracker {
+    /// Number of most recent blocks misses are counted over
+    window: u64,
+    /// Misses within the window tolerated before alerting
+    max_missed: u64,
+    alerts: Option<UnboundedSender<InactivityAlert>>,
+    state: Mutex<TrackerState>,
+}
+
+impl InactivityTracker {
+    /// Create a tracker counting misses over the last two epochs, alerting when a signer misses
+    /// more than `max_missed` in-turn slots
+    pub fn new(chain_spec: &PoaChainSpec, max_missed: u64) -> Self {
+        Self {
+            window: chain_spec.epoch().saturating_mul(DEFAULT_WINDOW_EPOCHS).max(1),
+            max_missed,
+            alerts: None,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Count misses over the last `blocks` blocks instead of two epochs
+    pub fn with_window(mut self, blocks: u64) -> Self {
+        self.window = blocks.max(1);
+        self
+    }
+
+    /// Send an [`InactivityAlert`] to `alerts` whenever a signer crosses the threshold
+    pub fn with_alerts(mut self, alerts: UnboundedSender<InactivityAlert>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    /// Record a canonical header sealed by `signer` on top of the `parent` snapshot.
+    ///
+    /// If `signer` was not the in-turn signer, the in-turn one missed its slot. Signers that are
+    /// no longer authorized are forgotten.
+    pub fn on_canonical_header(&self, header: &Header, signer: Address, parent: &Snapshot) {
+        let number = header.number;
+        let mut state = self.state.lock();
+        state.signers.retain(|address, _| parent.signers.contains(address));
+        state.alerted.retain(|address| parent.signers.contains(address));
+        for address in &parent.signers {
+            state.signers.entry(*address).or_default();
+        }
+
+        state.signers.entry(signer).or_default().last_sealed = Some(number);
+        if let Some(inturn) = parent.inturn_signer(number).filter(|inturn| *inturn != signer) {
+            state.signers.entry(inturn).or_default().missed.push_back(number);
+        }
+
+        let TrackerState { signers, alerted } = &mut *state;
+        let oldest = number.saturating_sub(self.window - 1);
+        for (address, record) in signers.iter_mut() {
+            while record.missed.front().is_some_and(|missed| *missed < oldest) {
+                record.missed.pop_front();
+            }
+
+            let missed = record.missed.len() as u64;
+            if missed <= self.max_missed {
+                alerted.remove(address);
+            } else if alerted.insert(*address) &&
+                let Some(alerts) = &self.alerts
+            {
+                let alert = InactivityAlert {
+                    signer: *address,
+                    block: number,
+                    missed,
+                    last_sealed: record.last_sealed,
+                };
+                // Nobody listening is not an error for consensus
+                let _ = alerts.send(alert);
+            }
+        }
+    }
+
+    /// Activity of every currently authorized signer
+    pub fn stats(&self) -> BTreeMap<Address, SignerActivity> {
+        self.state
+            .lock()
+            .signers
+            .iter()
+            .map(|(address, record)| {
+                let activity = SignerActivity {
+                    last_sealed: record.last_sealed,
+                    missed: record.missed.len() as u64,
+                };
+                (*address, activity)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{consensus::test_utils::ChainHarness, genesis::dev_signers, signer::dev};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    /// Seal `n_blocks` on the harness without ever using `dead`, feeding every block to `trackers`
+    async fn grow_without(
+        harness: &mut ChainHarness,
+        dead: Address,
+        n_blocks: u64,
+        trackers: &[&InactivityTracker],
+    ) {
+        for _ in 0..n_blocks {
+            let number = harness.parent.number + 1;
+            let parent = harness.snapshot.clone();
+            let may_seal =
+                |signer: &Address| *signer != dead && !parent.is_recently_signed_at(number, signer);
+            let signer = parent
+                .inturn_signer(number)
+                .filter(may_seal)
+                .or_else(|| parent.signers.iter().copied().find(may_seal))
+                .unwrap();
+
+            let header = harness.builder().with_signer(signer).build().await;
+            harness.extend(header.clone()).unwrap();
+            for tracker in trackers {
+                tracker.on_canonical_header(header.header(), signer, &parent);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signer_that_never_seals_raises_alert() {
+        let chain = Arc::new(PoaChainSpec::dev_chain());
+        let mut harness = ChainHarness::new(chain.clone(), dev::setup_dev_signers().await);
+        let dead = dev_signers()[1];
+
+        let (alerts, mut alert_rx) = mpsc::unbounded_channel();
+        let tracker = InactivityTracker::new(&chain, 3).with_alerts(alerts);
+        let windowed = InactivityTracker::new(&chain, 3).with_window(6);
+        grow_without(&mut harness, dead, 12, &[&tracker, &windowed]).await;
+
+        // The dead signer is in turn every third block
+        let stats = tracker.stats();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[&dead], SignerActivity { last_sealed: None, missed: 4 });
+        // The other two must alternate, so the recent-signer limit costs them a slot now and then
+        for (signer, activity) in stats.iter().filter(|(signer, _)| **signer != dead) {
+            assert!(activity.missed <= 3, "{signer}");
+            assert!(activity.last_sealed.is_some_and(|last| last >= 11), "{signer}");
+        }
+
+        // Crossing the threshold alerts exactly once
+        let alert = alert_rx.try_recv().unwrap();
+        assert_eq!(alert.signer, dead);
+        assert_eq!(alert.missed, 4);
+        let genesis = Snapshot::new_genesis(&chain);
+        let slots: Vec<_> = (1..=12).filter(|number| genesis.inturn(*number, &dead)).collect();
+        assert_eq!(alert.block, slots[3]);
+        assert!(alert_rx.try_recv().is_err());
+
+        // Over a window of six blocks only two misses are ever counted
+        assert_eq!(windowed.stats()[&dead].missed, 2);
+    }
+}
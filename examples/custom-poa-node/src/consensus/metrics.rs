@@ -0,0 +1,75 @@
+//! Metrics for POA consensus validation
+
+use super::PoaConsensusError;
+use reth_consensus::ConsensusError;
+use reth_metrics::{
+    metrics::{counter, Counter, Histogram},
+    Metrics,
+};
+use std::time::Duration;
+
+/// Name of the labeled counter tracking rejected headers by reason
+const REJECTIONS: &str = "poa_consensus_rejections";
+
+/// Header validation metrics.
+///
+/// Handles are only registered the first time a header is validated, so library users that
+/// never install a recorder don't pay for them.
+#[derive(Metrics)]
+#[metrics(scope = "poa_consensus", separator = "_")]
+pub(crate) struct PoaConsensusMetrics {
+    /// Number of headers that passed validation
+    headers_validated: Counter,
+    /// Number of signer recoveries served from the cache
+    signer_cache_hits: Counter,
+    /// Number of signer recoveries that required ecrecover
+    signer_cache_misses: Counter,
+    /// Time spent validating a single header
+    header_validation_duration_seconds: Histogram,
+}
+
+impl PoaConsensusMetrics {
+    /// Record the outcome of a single header validation
+    pub(crate) fn record_validation(
+        &self,
+        elapsed: Duration,
+        result: &Result<(), PoaConsensusError>,
+    ) {
+        self.header_validation_duration_seconds.record(elapsed.as_secs_f64());
+        match result {
+            Ok(()) => self.headers_validated.increment(1),
+            Err(err) => Self::record_rejection(err),
+        }
+    }
+
+    /// Record a rejected header, labeled by the error variant
+    pub(crate) fn record_rejection(err: &PoaConsensusError) {
+        counter!(REJECTIONS, "reason" => err.kind()).increment(1);
+    }
+
+    /// Record a header rejected against its parent, including by the checks POA shares with
+    /// Ethereum, such as the gas limit and base fee rules
+    pub(crate) fn record_consensus_rejection(err: &ConsensusError) {
+        let reason = match err {
+            ConsensusError::Custom(err) => {
+                err.downcast_ref::<PoaConsensusError>().map_or("other", PoaConsensusError::kind)
+            }
+            ConsensusError::ParentBlockNumberMismatch { .. } => "parent_block_number_mismatch",
+            ConsensusError::ParentHashMismatch(_) => "parent_hash_mismatch",
+            ConsensusError::GasLimitInvalidIncrease { .. } |
+            ConsensusError::GasLimitInvalidDecrease { .. } => "gas_limit",
+            ConsensusError::BaseFeeMissing | ConsensusError::BaseFeeDiff(_) => "base_fee",
+            _ => "other",
+        };
+        counter!(REJECTIONS, "reason" => reason).increment(1);
+    }
+
+    /// Record a signer recovery cache lookup
+    pub(crate) fn record_signer_cache(&self, hit: bool) {
+        if hit {
+            self.signer_cache_hits.increment(1);
+        } else {
+            self.signer_cache_misses.increment(1);
+        }
+    }
+}
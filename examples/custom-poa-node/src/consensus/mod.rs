@@ -0,0 +1,1780 @@
+//! POA Consensus Implementation
+//!
+//! This module implements a Proof of Authority consensus mechanism that validates:
+//! - Block signers are authorized
+//! - Blocks are signed correctly
+//! - Timing constraints are respected
+//! - The signer rotation follows the expected pattern
+
+use crate::{
+    chainspec::{PoaChainSpec, SignerSetSource},
+    sealing::{seal_hash, ADDRESS_LENGTH, EXTRA_SEAL_LENGTH, EXTRA_VANITY_LENGTH},
+};
+use alloy_consensus::{BlockHeader, Header};
+use alloy_eips::eip4844::DATA_GAS_PER_BLOB;
+use alloy_primitives::{Address, Signature, B256};
+use contract::{SignerContractError, SignerContractReader};
+use metrics::PoaConsensusMetrics;
+use parking_lot::Mutex;
+use provider::{PoaStateProvider, SnapshotLookupError, SnapshotProvider};
+use reth_chainspec::EthChainSpec;
+use reth_consensus::{Consensus, ConsensusError, FullConsensus, HeaderValidator, ReceiptRootBloom};
+use reth_execution_types::BlockExecutionResult;
+use reth_primitives_traits::{Block, NodePrimitives, RecoveredBlock, SealedBlock, SealedHeader};
+use schnellru::{ByLength, LruMap};
+use snapshot::{Snapshot, NONCE_AUTH_VOTE, NONCE_DROP_VOTE};
+use std::{
+    sync::{Arc, OnceLock},
+    time::Instant,
+};
+use thiserror::Error;
+
+pub mod contract;
+pub mod inactivity;
+mod metrics;
+pub mod provider;
+pub mod snapshot;
+pub mod store;
+#[cfg(test)]
+pub mod test_utils;
+
+/// Number of recovered block signers to keep in memory (same as clique)
+const SIGNER_CACHE_SIZE: u32 = 4096;
+
+/// Identifies the block a [`PoaConsensusError`] refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockContext {
+    /// Block number
+    pub number: u64,
+    /// Block hash, when the header was already sealed
+    pub hash: Option<B256>,
+}
+
+impl BlockContext {
+    /// Context for a header whose hash is not known
+    pub const fn number(number: u64) -> Self {
+        Self { number, hash: None }
+    }
+
+    /// Context for a sealed header
+    pub const fn sealed(number: u64, hash: B256) -> Self {
+        Self { number, hash: Some(hash) }
+    }
+}
+
+impl From<&Header> for BlockContext {
+    fn from(header: &Header) -> Self {
+        Self::number(header.number)
+    }
+}
+
+impl From<&SealedHeader<Header>> for BlockContext {
+    fn from(header: &SealedHeader<Header>) -> Self {
+        Self::sealed(header.number, header.hash())
+    }
+}
+
+impl std::fmt::Display for BlockContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "block #{}", self.number)?;
+        if let Some(hash) = self.hash {
+            write!(f, " ({hash:#})")?;
+        }
+        Ok(())
+    }
+}
+
+/// POA-specific consensus errors
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum PoaConsensusError {
+    /// Block signer is not in the authorized signers list
+    #[error("{block}: block signer {signer} is not authorized")]
+    UnauthorizedSigner {
+        /// The offending block
+        block: BlockContext,
+        /// The unauthorized signer address
+        signer: Address,
+    },
+
+    /// Block signature is invalid or cannot be recovered
+    #[error("{block}: invalid block signature")]
+    InvalidSignature {
+        /// The offending block
+        block: BlockContext,
+    },
+
+    /// Extra data is too short to contain required POA information
+    #[error("{block}: extra data too short: expected at least {expected} bytes, got {got}")]
+    ExtraDataTooShort {
+        /// The offending block
+        block: BlockContext,
+        /// Expected minimum length
+        expected: usize,
+        /// Actual length
+        got: usize,
+    },
+
+    /// Block timestamp is earlier than allowed
+    #[error("{block}: block timestamp {timestamp} is before parent timestamp {parent_timestamp}")]
+    TimestampTooEarly {
+        /// The offending block
+        block: BlockContext,
+        /// Block timestamp
+        timestamp: u64,
+        /// Parent block timestamp
+        parent_timestamp: u64,
+    },
+
+    /// Block timestamp is not exactly one period after its parent
+    #[error("{block}: block timestamp {got} must be exactly {expected} (parent + period)")]
+    TimestampNotExact {
+        /// The offending block
+        block: BlockContext,
+        /// Required timestamp
+        expected: u64,
+        /// Actual block timestamp
+        got: u64,
+    },
+
+    /// Block timestamp is too far in the future
+    #[error("{block}: block timestamp {timestamp} is too far in the future")]
+    TimestampTooFarInFuture {
+        /// The offending block
+        block: BlockContext,
+        /// Block timestamp
+        timestamp: u64,
+    },
+
+    /// Signer sealed another block too recently
+    #[error("{block}: signer {signer} has signed too recently")]
+    RecentlySigned {
+        /// The offending block
+        block: BlockContext,
+        /// The signer that is still cooling down
+        signer: Address,
+    },
+
+    /// Block was signed by wrong signer (not in-turn)
+    #[error("{block}: wrong block signer: expected {expected}, got {got}")]
+    WrongSigner {
+        /// The offending block
+        block: BlockContext,
+        /// Expected signer
+        expected: Address,
+        /// Actual signer
+        got: Address,
+    },
+
+    /// Difficulty field has invalid value for POA
+    #[error("{block}: difficulty must be 1 for in-turn signer or 2 for out-of-turn")]
+    InvalidDifficulty {
+        /// The offending block
+        block: BlockContext,
+    },
+
+    /// Extra data of a non-epoch block carries bytes beyond vanity and seal
+    #[error("{block}: unexpected extra data: expected exactly {expected} bytes, got {got}")]
+    UnexpectedExtraData {
+        /// The offending block
+        block: BlockContext,
+        /// Required length
+        expected: usize,
+        /// Actual length
+        got: usize,
+    },
+
+    /// Signer list in epoch block is invalid
+    #[error("{block}: invalid signer list in epoch block")]
+    InvalidSignerList {
+        /// The offending block
+        block: BlockContext,
+    },
+
+    /// Nonce is neither an authorize nor a drop vote
+    #[error("{block}: invalid vote nonce")]
+    InvalidVote {
+        /// The offending block
+        block: BlockContext,
+    },
+
+    /// Headers handed to a snapshot do not extend it contiguously
+    #[error("{block}: header does not extend the voting chain")]
+    InvalidVotingChain {
+        /// The offending block
+        block: BlockContext,
+    },
+
+    /// Signer list of a checkpoint differs from the governance contract's signer set
+    #[error("{block}: checkpoint signers {got:?} do not match the signer contract's {expected:?}")]
+    CheckpointSignersMismatch {
+        /// The offending block
+        block: BlockContext,
+        /// Signer set reported by the contract
+        expected: Vec<Address>,
+        /// Signer list in the checkpoint extra data
+        got: Vec<Address>,
+    },
+
+    /// The governance contract could not be queried for the signer set
+    #[error("{block}: {source}")]
+    SignerContract {
+        /// The offending block
+        block: BlockContext,
+        /// Why the call failed
+        source: SignerContractError,
+    },
+
+    /// An ancestor header needed to reconstruct the parent snapshot is not available
+    #[error("{block}: unknown ancestor {ancestor}, the signer set can't be reconstructed")]
+    UnknownAncestor {
+        /// The offending block
+        block: BlockContext,
+        /// Hash of the first ancestor that could not be found
+        ancestor: B256,
+    },
+
+    /// Header at the trusted checkpoint height does not match the configured hash
+    #[error("{block}: checkpoint mismatch: expected {expected}, got {got}")]
+    CheckpointMismatch {
+        /// The offending block
+        block: BlockContext,
+        /// Configured checkpoint hash
+        expected: B256,
+        /// Hash of the header found at the checkpoint height
+        got: B256,
+    },
+}
+
+impl PoaConsensusError {
+    /// Short, stable name of the error variant, used as a metrics label
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::UnauthorizedSigner { .. } => "unauthorized_signer",
+            Self::InvalidSignature { .. } => "invalid_signature",
+            Self::ExtraDataTooShort { .. } => "extra_data_too_short",
+            Self::TimestampTooEarly { .. } => "timestamp_too_early",
+            Self::TimestampNotExact { .. } => "timestamp_not_exact",
+            Self::TimestampTooFarInFuture { .. } => "timestamp_too_far_in_future",
+            Self::RecentlySigned { .. } => "recently_signed",
+            Self::WrongSigner { .. } => "wrong_signer",
+            Self::InvalidDifficulty { .. } => "invalid_difficulty",
+            Self::UnexpectedExtraData { .. } => "unexpected_extra_data",
+            Self::InvalidSignerList { .. } => "invalid_signer_list",
+            Self::CheckpointMismatch { .. } => "checkpoint_mismatch",
+            Self::InvalidVote { .. } => "invalid_vote",
+            Self::InvalidVotingChain { .. } => "invalid_voting_chain",
+            Self::CheckpointSignersMismatch { .. } => "checkpoint_signers_mismatch",
+            Self::SignerContract { .. } => "signer_contract",
+            Self::UnknownAncestor { .. } => "unknown_ancestor",
+        }
+    }
+
+    /// The block this error refers to
+    pub const fn block(&self) -> &BlockContext {
+        match self {
+            Self::UnauthorizedSigner { block, .. } |
+            Self::InvalidSignature { block } |
+            Self::ExtraDataTooShort { block, .. } |
+            Self::TimestampTooEarly { block, .. } |
+            Self::TimestampNotExact { block, .. } |
+            Self::TimestampTooFarInFuture { block, .. } |
+            Self::RecentlySigned { block, .. } |
+            Self::WrongSigner { block, .. } |
+            Self::InvalidDifficulty { block } |
+            Self::UnexpectedExtraData { block, .. } |
+            Self::InvalidSignerList { block } |
+            Self::CheckpointMismatch { block, .. } |
+            Self::InvalidVote { block } |
+            Self::InvalidVotingChain { block } |
+            Self::CheckpointSignersMismatch { block, .. } |
+            Self::SignerContract { block, .. } |
+            Self::UnknownAncestor { block, .. } => block,
+        }
+    }
+
+    /// Attach the sealed block hash if the error was raised before it was known
+    pub const fn with_block_hash(mut self, hash: B256) -> Self {
+        let block = match &mut self {
+            Self::UnauthorizedSigner { block, .. } |
+            Self::InvalidSignature { block } |
+            Self::ExtraDataTooShort { block, .. } |
+            Self::TimestampTooEarly { block, .. } |
+            Self::TimestampNotExact { block, .. } |
+            Self::TimestampTooFarInFuture { block, .. } |
+            Self::RecentlySigned { block, .. } |
+            Self::WrongSigner { block, .. } |
+            Self::InvalidDifficulty { block } |
+            Self::UnexpectedExtraData { block, .. } |
+            Self::InvalidSignerList { block } |
+            Self::CheckpointMismatch { block, .. } |
+            Self::InvalidVote { block } |
+            Self::InvalidVotingChain { block } |
+            Self::CheckpointSignersMismatch { block, .. } |
+            Self::SignerContract { block, .. } |
+            Self::UnknownAncestor { block, .. } => block,
+        };
+        if block.hash.is_none() {
+            block.hash = Some(hash);
+        }
+        self
+    }
+}
+
+impl From<PoaConsensusError> for ConsensusError {
+    fn from(err: PoaConsensusError) -> Self {
+        ConsensusError::Custom(std::sync::Arc::new(err))
+    }
+}
+
+/// A block that is trusted without signature verification.
+///
+/// The checkpoint and its ancestors skip signer recovery and authorization checks, which speeds
+/// up initial sync of long chains. The header at `number` itself must hash to `hash`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedCheckpoint {
+    /// Checkpoint block number
+    pub number: u64,
+    /// Expected hash of the checkpoint block
+    pub hash: B256,
+}
+
+/// POA Consensus implementation
+#[derive(Debug, Clone)]
+pub struct PoaConsensus {
+    /// The chain specification with POA configuration
+    chain_spec: Arc<PoaChainSpec>,
+    /// Optional trusted checkpoint for fast sync
+    trusted_checkpoint: Option<TrustedCheckpoint>,
+    /// Height and parent hash of the lowest header proven to be an ancestor of the checkpoint
+    checkpoint_ancestor: Arc<Mutex<Option<(u64, B256)>>>,
+    /// Recently recovered block signers, keyed by block hash
+    signer_cache: Arc<Mutex<LruMap<B256, Address>>>,
+    /// Validation metrics, registered on first use
+    metrics: Arc<OnceLock<PoaConsensusMetrics>>,
+    /// Optional access to ancestor headers and the canonical chain
+    provider: Option<Arc<dyn PoaStateProvider>>,
+    /// Reconstructs parent snapshots from the provider's headers
+    snapshots: Option<Arc<SnapshotProvider>>,
+    /// Authorization state at genesis, used as the parent snapshot without a provider
+    genesis_snapshot: Arc<Snapshot>,
+    /// Access to the governance contract when the signer set is contract governed
+    signer_contract: Option<Arc<dyn SignerContractReader>>,
+}
+
+impl PoaConsensus {
+    /// Create a new POA consensus instance
+    pub fn new(chain_spec: Arc<PoaChainSpec>) -> Self {
+        Self {
+            genesis_snapshot: Arc::new(Snapshot::new_genesis(&chain_spec)),
+            chain_spec,
+            trusted_checkpoint: None,
+            checkpoint_ancestor: Arc::default(),
+            signer_cache: Arc::new(Mutex::new(LruMap::new(ByLength::new(SIGNER_CACHE_SIZE)))),
+            metrics: Arc::default(),
+            provider: None,
+            snapshots: None,
+            signer_contract: None,
+        }
+    }
+
+    /// Give consensus access to ancestor headers and snapshots.
+    ///
+    /// Checks that need more than the parent header (such as the recent-signer limit) are only
+    /// enforced when a provider is configured; without one validation stays stateless. Parent
+    /// snapshots the provider doesn't hold are reconstructed from its headers by a
+    /// [`SnapshotProvider`].
+    pub fn with_provider(mut self, provider: Arc<dyn PoaStateProvider>) -> Self {
+        self.snapshots =
+            Some(Arc::new(SnapshotProvider::new(self.chain_spec.clone(), provider.clone())));
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Query the governance contract through `reader` when the signer set is contract governed.
+    ///
+    /// Required to validate epoch blocks under [`SignerSetSource::Contract`].
+    pub fn with_signer_contract_reader(mut self, reader: Arc<dyn SignerContractReader>) -> Self {
+        self.signer_contract = Some(reader);
+        self
+    }
+
+    /// Trust all headers up to and including the given checkpoint.
+    ///
+    /// The checkpoint and the headers proven to be its ancestors only receive structural checks;
+    /// their seal is not recovered. Everything else is fully validated, including forks below
+    /// the checkpoint height and every newly produced block at the tip.
+    pub const fn with_trusted_checkpoint(mut self, number: u64, hash: B256) -> Self {
+        self.trusted_checkpoint = Some(TrustedCheckpoint { number, hash });
+        self
+    }
+
+    /// Returns the configured trusted checkpoint, if any
+    pub const fn trusted_checkpoint(&self) -> Option<&TrustedCheckpoint> {
+        self.trusted_checkpoint.as_ref()
+    }
+
+    /// Returns `true` if the header's seal should be verified.
+    ///
+    /// Only the checkpoint and headers proven to be its ancestors skip the check. A header is
+    /// proven if it is the parent of the lowest one proven so far, as during a backwards download
+    /// anchored at the checkpoint, or if the provider has both it and the checkpoint on the
+    /// canonical chain.
+    ///
+    /// Fails if the header sits exactly at the checkpoint height but has a different hash.
+    fn requires_seal_check(
+        &self,
+        header: &SealedHeader<Header>,
+    ) -> Result<bool, PoaConsensusError> {
+        let Some(checkpoint) = self.trusted_checkpoint else { return Ok(true) };
+        let (number, hash) = (header.number(), header.hash());
+
+        if number > checkpoint.number {
+            return Ok(true);
+        }
+        if number == checkpoint.number {
+            if hash != checkpoint.hash {
+                return Err(PoaConsensusError::CheckpointMismatch {
+                    block: BlockContext::sealed(number, hash),
+                    expected: checkpoint.hash,
+                    got: hash,
+                });
+            }
+            self.checkpoint_ancestor.lock().get_or_insert((number, header.parent_hash));
+            return Ok(false);
+        }
+
+        let mut lowest = self.checkpoint_ancestor.lock();
+        if let Some((lowest_number, parent_hash)) = *lowest &&
+            number + 1 == lowest_number &&
+            hash == parent_hash
+        {
+            *lowest = Some((number, header.parent_hash));
+            return Ok(false);
+        }
+        drop(lowest);
+
+        let is_canonical = |number, hash| {
+            self.provider.as_ref().and_then(|provider| provider.canonical_hash(number)) ==
+                Some(hash)
+        };
+        Ok(!(is_canonical(checkpoint.number, checkpoint.hash) && is_canonical(number, hash)))
+    }
+
+    /// Returns the validation metrics, registering them on first access
+    fn metrics(&self) -> &PoaConsensusMetrics {
+        self.metrics.get_or_init(PoaConsensusMetrics::default)
+    }
+
+    /// Recover the signer of a sealed header, consulting the signer cache first
+    pub fn recover_signer_cached(
+        &self,
+        header: &SealedHeader<Header>,
+    ) -> Result<Address, PoaConsensusError> {
+        self.recover_signer_with_hash(header.header(), header.hash())
+    }
+
+    /// Recover the signer of a header with a known hash, consulting the signer cache first
+    fn recover_signer_with_hash(
+        &self,
+        header: &Header,
+        hash: B256,
+    ) -> Result<Address, PoaConsensusError> {
+        let cached = self.signer_cache.lock().get(&hash).copied();
+        self.metrics().record_signer_cache(cached.is_some());
+        if let Some(signer) = cached {
+            return Ok(signer);
+        }
+
+        let signer = self.recover_signer(header).map_err(|err| err.with_block_hash(hash))?;
+        self.signer_cache.lock().insert(hash, signer);
+        Ok(signer)
+    }
+
+    /// Reject the header if its signer sealed one of the last `signers / 2` blocks.
+    ///
+    /// The recent signers come from the parent snapshot, so this does nothing without a provider.
+    fn validate_recent_signer(
+        &self,
+        header: &Header,
+        signer: &Address,
+        parent_snapshot: Option<&Snapshot>,
+    ) -> Result<(), PoaConsensusError> {
+        if parent_snapshot
+            .is_some_and(|snapshot| snapshot.is_recently_signed_at(header.number, signer))
+        {
+            return Err(PoaConsensusError::RecentlySigned { block: header.into(), signer: *signer });
+        }
+        Ok(())
+    }
+
+    /// Snapshot after the parent of `header`, reconstructed from ancestor headers if needed.
+    ///
+    /// `None` without a provider, in which case the caller falls back to the genesis signer set.
+    /// With a provider, missing ancestors fail the header rather than fall back: the genesis set
+    /// may still hold signers that were voted out since.
+    fn parent_snapshot(
+        &self,
+        header: &SealedHeader<Header>,
+    ) -> Result<Option<Snapshot>, PoaConsensusError> {
+        let Some(snapshots) = &self.snapshots else { return Ok(None) };
+        let unknown_ancestor =
+            |ancestor| PoaConsensusError::UnknownAncestor { block: header.into(), ancestor };
+        match snapshots.snapshot_at(header.parent_hash) {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(SnapshotLookupError::MissingHeader(ancestor)) => Err(unknown_ancestor(ancestor)),
+            Err(SnapshotLookupError::UnknownBlock(_)) => Err(unknown_ancestor(header.parent_hash)),
+            Err(SnapshotLookupError::Consensus(err)) => Err(err),
+        }
+    }
+
+    /// Check the blob gas a header claims against the chain's blob schedule
+    fn validate_blob_gas_used(&self, header: &Header) -> Result<(), ConsensusError> {
+        let (Some(blob_gas_used), Some(blob_params)) =
+            (header.blob_gas_used, self.chain_spec.blob_params_at_timestamp(header.timestamp))
+        else {
+            return Ok(());
+        };
+
+        if !blob_gas_used.is_multiple_of(DATA_GAS_PER_BLOB) {
+            return Err(ConsensusError::BlobGasUsedNotMultipleOfBlobGasPerBlob {
+                blob_gas_used,
+                blob_gas_per_blob: DATA_GAS_PER_BLOB,
+            });
+        }
+        let max_blob_gas_per_block = blob_params.max_blob_gas_per_block();
+        if blob_gas_used > max_blob_gas_per_block {
+            return Err(ConsensusError::BlobGasUsedExceedsMaxBlobGasPerBlock {
+                blob_gas_used,
+                max_blob_gas_per_block,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate the standalone POA rules of a header: extra data layout and seal
+    fn validate_header_seal(&self, header: &SealedHeader<Header>) -> Result<(), PoaConsensusError> {
+        // For POA, we validate:
+        // 1. The header is properly sealed by an authorized signer
+        // 2. The nonce is a valid vote marker
+
+        // The genesis block carries an empty seal, and blocks before the switch to POA were sealed
+        // by another engine
+        if header.number() == 0 || header.number() < self.chain_spec.poa_activation_block() {
+            return Ok(());
+        }
+
+        // Structural check: extra data must match the exact epoch / non-epoch layout
+        self.validate_extra_data(header.header())?;
+
+        if self.requires_seal_check(header)? {
+            let signer = self.recover_signer_cached(header)?;
+
+            // Signer set and turn order come from the parent snapshot, or genesis without provider
+            let parent_snapshot = self.parent_snapshot(header)?;
+            let snapshot = parent_snapshot.as_ref().unwrap_or(&self.genesis_snapshot);
+
+            self.validate_signer(header.header(), &signer, snapshot)?;
+            self.validate_recent_signer(header.header(), &signer, parent_snapshot.as_ref())?;
+            self.validate_difficulty(header.header(), &signer, snapshot)?;
+        }
+
+        // The nonce carries the vote direction and must be one of the two vote markers
+        if header.nonce != NONCE_AUTH_VOTE && header.nonce != NONCE_DROP_VOTE {
+            return Err(PoaConsensusError::InvalidVote { block: header.into() });
+        }
+
+        Ok(())
+    }
+
+    /// Signer set the checkpoint at `number` must carry, as reported by the governance contract at
+    /// the state after `parent_hash`.
+    ///
+    /// The contract is called in the environment of the parent, not of the checkpoint, so block
+    /// producers can call this before building their header.
+    ///
+    /// Returns `None` if `number` is not an epoch block or the signer set is voted on. The list is
+    /// sorted and deduplicated, the order in which checkpoints carry signers.
+    pub fn contract_signers(
+        &self,
+        number: u64,
+        parent_hash: B256,
+    ) -> Result<Option<Vec<Address>>, PoaConsensusError> {
+        let SignerSetSource::Contract { address, selector } = *self.chain_spec.signer_set_source()
+        else {
+            return Ok(None);
+        };
+        if number == 0 || !self.is_epoch_block(number) {
+            return Ok(None);
+        }
+
+        let reader = self.signer_contract.as_ref().ok_or(PoaConsensusError::SignerContract {
+            block: BlockContext::number(number),
+            source: SignerContractError::Unavailable,
+        })?;
+        let mut signers = reader.signers_at(parent_hash, address, selector).map_err(|source| {
+            PoaConsensusError::SignerContract { block: BlockContext::number(number), source }
+        })?;
+        signers.sort_unstable();
+        signers.dedup();
+        Ok(Some(signers))
+    }
+
+    /// Check a checkpoint's signer list against the governance contract.
+    ///
+    /// Needs the parent state, so it runs after execution rather than with the other header
+    /// checks. Does nothing unless the signer set is contract governed.
+    pub fn validate_checkpoint_signers(
+        &self,
+        header: &SealedHeader<Header>,
+    ) -> Result<(), PoaConsensusError> {
+        let Some(expected) = self
+            .contract_signers(header.number, header.parent_hash)
+            .map_err(|err| err.with_block_hash(header.hash()))?
+        else {
+            return Ok(());
+        };
+
+        let got = checkpoint_signers(header.header())
+            .map_err(|err| err.with_block_hash(header.hash()))?;
+        if got != expected {
+            return Err(PoaConsensusError::CheckpointSignersMismatch {
+                block: header.into(),
+                expected,
+                got,
+            });
+        }
+        Ok(())
+    }
+
+    /// Create an Arc-wrapped instance
+    pub fn arc(chain_spec: Arc<PoaChainSpec>) -> Arc<Self> {
+        Arc::new(Self::new(chain_spec))
+    }
+
+    /// Extract the signer address from the block's extra data
+    pub fn recover_signer(&self, header: &Header) -> Result<Address, PoaConsensusError> {
+        // Extra data must be exactly vanity + (signers on epoch blocks) + seal
+        self.validate_extra_data(header)?;
+        recover_header_signer(header)
+    }
+
+    /// Calculate the hash used for sealing (excludes the signature from extra data)
+    pub fn seal_hash(&self, header: &Header) -> B256 {
+        seal_hash(header)
+    }
+
+    /// Validate that the signer is authorized in the parent snapshot
+    fn validate_signer(
+        &self,
+        header: &Header,
+        signer: &Address,
+        snapshot: &Snapshot,
+    ) -> Result<(), PoaConsensusError> {
+        if !snapshot.signers.contains(signer) {
+            return Err(PoaConsensusError::UnauthorizedSigner {
+                block: header.into(),
+                signer: *signer,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validate the exact extra data layout of a header.
+    ///
+    /// Non-epoch blocks must carry exactly `vanity + seal`; epoch blocks must carry
+    /// `vanity + N * address + seal` with at least one signer. Anything else is rejected so that
+    /// no bytes can be smuggled between the vanity, the signer list and the seal.
+    pub fn validate_extra_data(&self, header: &Header) -> Result<(), PoaConsensusError> {
+        let len = header.extra_data.len();
+        let min_length = EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH;
+        if len < min_length {
+            return Err(PoaConsensusError::ExtraDataTooShort {
+                block: header.into(),
+                expected: min_length,
+                got: len,
+            });
+        }
+
+        let signers_len = len - min_length;
+        if !self.is_epoch_block(header.number) {
+            if signers_len != 0 {
+                return Err(PoaConsensusError::UnexpectedExtraData {
+                    block: header.into(),
+                    expected: min_length,
+                    got: len,
+                });
+            }
+        } else if signers_len == 0 || !signers_len.is_multiple_of(ADDRESS_LENGTH) {
+            return Err(PoaConsensusError::InvalidSignerList { block: header.into() });
+        }
+
+        Ok(())
+    }
+
+    /// Check if this is an epoch block (where signer list is updated)
+    pub fn is_epoch_block(&self, block_number: u64) -> bool {
+        block_number % self.chain_spec.epoch() == 0
+    }
+
+    /// Validate the difficulty field
+    /// In POA: difficulty 1 = in-turn signer, difficulty 2 = out-of-turn
+    fn validate_difficulty(
+        &self,
+        header: &Header,
+        signer: &Address,
+        snapshot: &Snapshot,
+    ) -> Result<(), PoaConsensusError> {
+        let is_in_turn = snapshot.inturn(header.number, signer);
+
+        let expected_difficulty = if is_in_turn { 1u64 } else { 2u64 };
+
+        if header.difficulty != U256::from(expected_difficulty) {
+            return Err(PoaConsensusError::InvalidDifficulty { block: header.into() });
+        }
+
+        Ok(())
+    }
+
+    /// Extract the signer list from an epoch block's extra data
+    pub fn extract_signers_from_epoch_block(
+        &self,
+        header: &Header,
+    ) -> Result<Vec<Address>, PoaConsensusError> {
+        // In epoch blocks, format is: vanity (32) + signers (N*20) + seal (65)
+        if !self.is_epoch_block(header.number) {
+            return Err(PoaConsensusError::InvalidSignerList { block: header.into() });
+        }
+        self.validate_extra_data(header)?;
+        checkpoint_signers(header)
+    }
+
+    /// Checks of [`HeaderValidator::validate_header_against_parent`], without the metrics
+    fn check_header_against_parent(
+        &self,
+        header: &SealedHeader<Header>,
+        parent: &SealedHeader<Header>,
+    ) -> Result<(), ConsensusError> {
+        // Validate block number
+        if header.header().number() != parent.header().number() + 1 {
+            return Err(ConsensusError::ParentBlockNumberMismatch {
+                parent_block_number: parent.header().number(),
+                block_number: header.header().number(),
+            });
+        }
+
+        // Validate parent hash
+        if header.header().parent_hash() != parent.hash() {
+            return Err(ConsensusError::ParentHashMismatch(
+                GotExpected { got: header.header().parent_hash(), expected: parent.hash() }.into(),
+            ));
+        }
+
+        // Blocks before the switch to POA follow the rules of the engine that sealed them
+        if header.header().number() < self.chain_spec.poa_activation_block() {
+            return Ok(());
+        }
+
+        // Validate timestamp (must be after parent + minimum period, or exactly that)
+        let min_timestamp = parent.header().timestamp() + self.chain_spec.block_period();
+        if self.chain_spec.exact_period() && header.header().timestamp() != min_timestamp {
+            let err = PoaConsensusError::TimestampNotExact {
+                block: header.into(),
+                expected: min_timestamp,
+                got: header.header().timestamp(),
+            };
+            return Err(err.into());
+        }
+        if header.header().timestamp() < min_timestamp {
+            let err = PoaConsensusError::TimestampTooEarly {
+                block: header.into(),
+                timestamp: header.header().timestamp(),
+                parent_timestamp: parent.header().timestamp(),
+            };
+            return Err(err.into());
+        }
+
+        // Validate difficulty progression: every POA block adds exactly 1 or 2
+        if !is_poa_difficulty(header.header().difficulty()) {
+            let err = PoaConsensusError::InvalidDifficulty { block: header.into() };
+            return Err(err.into());
+        }
+
+        // Validate gas limit changes (EIP-1559 compatible)
+        let parent_gas_limit = parent.header().gas_limit();
+        let current_gas_limit = header.header().gas_limit();
+        let max_change = parent_gas_limit / 1024;
+
+        if current_gas_limit > parent_gas_limit + max_change {
+            return Err(ConsensusError::GasLimitInvalidIncrease {
+                parent_gas_limit,
+                child_gas_limit: current_gas_limit,
+            });
+        }
+
+        if current_gas_limit < parent_gas_limit.saturating_sub(max_change) {
+            return Err(ConsensusError::GasLimitInvalidDecrease {
+                parent_gas_limit,
+                child_gas_limit: current_gas_limit,
+            });
+        }
+
+        // Validate the EIP-1559 base fee against the chain's fee parameters
+        if let Some(expected) =
+            self.chain_spec.next_block_base_fee(parent.header(), header.header().timestamp())
+        {
+            let got = header.header().base_fee_per_gas().ok_or(ConsensusError::BaseFeeMissing)?;
+            if got != expected {
+                return Err(ConsensusError::BaseFeeDiff(GotExpected { got, expected }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the signer list out of a checkpoint header's extra data.
+///
+/// Only the layout is checked, not whether the header actually sits on an epoch boundary.
+pub fn checkpoint_signers(header: &Header) -> Result<Vec<Address>, PoaConsensusError> {
+    let extra_data = &header.extra_data;
+    let min_length = EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH;
+    if extra_data.len() < min_length {
+        return Err(PoaConsensusError::ExtraDataTooShort {
+            block: header.into(),
+            expected: min_length,
+            got: extra_data.len(),
+        });
+    }
+
+    let signers_data = &extra_data[EXTRA_VANITY_LENGTH..extra_data.len() - EXTRA_SEAL_LENGTH];
+    if signers_data.is_empty() || !signers_data.len().is_multiple_of(ADDRESS_LENGTH) {
+        return Err(PoaConsensusError::InvalidSignerList { block: header.into() });
+    }
+
+    Ok(signers_data.chunks_exact(ADDRESS_LENGTH).map(Address::from_slice).collect())
+}
+
+/// Recover the address that sealed a header from the signature at the end of its extra data.
+///
+/// Only checks that a seal is present; use [`PoaConsensus::recover_signer`] to also enforce the
+/// extra data layout.
+pub fn recover_header_signer(header: &Header) -> Result<Address, PoaConsensusError> {
+    let extra_data = &header.extra_data;
+    if extra_data.len() < EXTRA_SEAL_LENGTH {
+        return Err(PoaConsensusError::ExtraDataTooShort {
+            block: header.into(),
+            expected: EXTRA_SEAL_LENGTH,
+            got: extra_data.len(),
+        });
+    }
+
+    // Extract the signature from the end of extra data
+    let signature_start = extra_data.len() - EXTRA_SEAL_LENGTH;
+    let signature_bytes = &extra_data[signature_start..];
+
+    // Parse signature (r, s, v format)
+    let signature = Signature::try_from(signature_bytes)
+        .map_err(|_| PoaConsensusError::InvalidSignature { block: header.into() })?;
+
+    // Recover the signer address from the seal hash (header hash without the signature)
+    signature
+        .recover_address_from_prehash(&seal_hash(header))
+        .map_err(|_| PoaConsensusError::InvalidSignature { block: header.into() })
+}
+
+use alloy_primitives::U256;
+use reth_primitives_traits::GotExpected;
+
+impl HeaderValidator<Header> for PoaConsensus {
+    fn validate_header(&self, header: &SealedHeader<Header>) -> Result<(), ConsensusError> {
+        let start = Instant::now();
+        let result =
+            self.validate_header_seal(header).map_err(|err| err.with_block_hash(header.hash()));
+        self.metrics().record_validation(start.elapsed(), &result);
+        result?;
+        self.validate_blob_gas_used(header.header())
+    }
+
+    fn validate_header_against_parent(
+        &self,
+        header: &SealedHeader<Header>,
+        parent: &SealedHeader<Header>,
+    ) -> Result<(), ConsensusError> {
+        self.check_header_against_parent(header, parent)
+            .inspect_err(PoaConsensusMetrics::record_consensus_rejection)
+    }
+}
+
+impl<B: Block<Header = Header>> Consensus<B> for PoaConsensus {
+    fn validate_body_against_header(
+        &self,
+        _body: &B::Body,
+        _header: &SealedHeader<B::Header>,
+    ) -> Result<(), ConsensusError> {
+        // Validate transaction root, etc.
+        // The base implementation handles most of this
+        Ok(())
+    }
+
+    fn validate_block_pre_execution(&self, _block: &SealedBlock<B>) -> Result<(), ConsensusError> {
+        // POA-specific pre-execution validation
+        // For now, we trust the header validation
+        Ok(())
+    }
+}
+
+impl<N: NodePrimitives<BlockHeader = Header>> FullConsensus<N> for PoaConsensus {
+    fn validate_block_post_execution(
+        &self,
+        block: &RecoveredBlock<N::Block>,
+        _result: &BlockExecutionResult<N::Receipt>,
+        _receipt_root_bloom: Option<ReceiptRootBloom>,
+    ) -> Result<(), ConsensusError> {
+        // Checkpoints of a contract governed signer set are checked against the parent state,
+        // which is only guaranteed to be available once the block is being executed
+        if let Err(err) = self.validate_checkpoint_signers(block.sealed_header()) {
+            PoaConsensusMetrics::record_rejection(&err);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if the difficulty is one POA can produce: 1 (in-turn) or 2 (out-of-turn)
+fn is_poa_difficulty(difficulty: U256) -> bool {
+    difficulty == U256::from(1) || difficulty == U256::from(2)
+}
+
+/// Total difficulty accumulated by a sequence of POA headers.
+///
+/// Unlike proof of work, POA difficulty only encodes whether a block was sealed in-turn (1) or
+/// out-of-turn (2), so total difficulty grows by one or two per block.
+pub fn poa_total_difficulty<'a>(headers: impl Iterator<Item = &'a Header>) -> U256 {
+    headers.fold(U256::ZERO, |total, header| total + header.difficulty)
+}
+
+/// Builder for POA consensus that integrates with Reth's node builder
+#[derive(Debug, Clone)]
+pub struct PoaConsensusBuilder {
+    chain_spec: Arc<PoaChainSpec>,
+}
+
+impl PoaConsensusBuilder {
+    /// Create a new consensus builder
+    pub fn new(chain_spec: Arc<PoaChainSpec>) -> Self {
+        Self { chain_spec }
+    }
+
+    /// Build the POA consensus instance
+    pub fn build(self) -> Arc<PoaConsensus> {
+        PoaConsensus::arc(self.chain_spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::{BlobCounts, BlobScheduleOverride, PoaConfig},
+        genesis,
+        signer::{dev, BlockSealer, ProposalsMap},
+    };
+    use alloy_consensus::{transaction::Recovered, SignableTransaction, TxEip1559};
+    use alloy_primitives::TxKind;
+    use alloy_signer::SignerSync;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use reth_chainspec::BaseFeeParams;
+    use reth_evm::{ConfigureEvm, Evm};
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_revm::db::{CacheDB, EmptyDB};
+    use std::collections::HashMap;
+    use test_utils::{build_chain, default_signer, ChainHarness, PoaHeaderBuilder};
+
+    async fn dev_setup() -> (Arc<PoaChainSpec>, Arc<crate::signer::SignerManager>) {
+        let chain = Arc::new(crate::chainspec::PoaChainSpec::dev_chain());
+        let manager = dev::setup_dev_signers().await;
+        // A key that is not part of the authorized signer set
+        manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[5]).await.unwrap();
+        (chain, manager)
+    }
+
+    fn outsider() -> Address {
+        crate::genesis::dev_accounts()[5]
+    }
+
+    /// In-turn signer of `number` under the genesis signer set
+    fn inturn(chain: &PoaChainSpec, number: u64) -> Address {
+        Snapshot::new_genesis(chain).inturn_signer(number).unwrap()
+    }
+
+    #[test]
+    fn test_consensus_creation() {
+        let chain = Arc::new(crate::chainspec::PoaChainSpec::dev_chain());
+        let consensus = PoaConsensus::new(chain);
+
+        // Basic sanity check
+        assert!(!consensus.chain_spec.signers().is_empty());
+    }
+
+    #[test]
+    fn test_epoch_block_detection() {
+        let chain = Arc::new(crate::chainspec::PoaChainSpec::dev_chain());
+        let consensus = PoaConsensus::new(chain.clone());
+
+        let epoch = chain.epoch();
+        assert!(consensus.is_epoch_block(0));
+        assert!(consensus.is_epoch_block(epoch));
+        assert!(consensus.is_epoch_block(epoch * 2));
+        assert!(!consensus.is_epoch_block(1));
+        assert!(!consensus.is_epoch_block(epoch + 1));
+    }
+
+    #[tokio::test]
+    async fn test_built_chain_is_valid() {
+        let (chain, manager) = dev_setup().await;
+        let headers = build_chain(chain.clone(), manager, 6).await;
+        let consensus = PoaConsensus::new(chain);
+
+        consensus.validate_header_range(&headers).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_gas_limit_converges_to_target() {
+        let config = PoaConfig {
+            signers: genesis::dev_signers(),
+            gas_limit_target: Some(40_000_000),
+            ..Default::default()
+        };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis_for(&config), config));
+        assert_eq!(chain.genesis_header().gas_limit, 30_000_000);
+        assert_eq!(chain.block_gas_limit(), 40_000_000);
+
+        let manager = dev::setup_dev_signers().await;
+        let headers = build_chain(chain.clone(), manager, 300).await;
+        let consensus = PoaConsensus::new(chain.clone());
+        let genesis = SealedHeader::seal_slow(chain.genesis_header().clone());
+        consensus.validate_header_against_parent(&headers[0], &genesis).unwrap();
+        consensus.validate_header_range(&headers).unwrap();
+
+        // Each step is just under 1/1024 of the parent's limit, so it takes 295 blocks
+        assert!(headers
+            .windows(2)
+            .all(|pair| pair[0].gas_limit < pair[1].gas_limit || pair[1].gas_limit == 40_000_000));
+        assert_eq!(headers[293].gas_limit, 39_970_987);
+        assert_eq!(headers[294].gas_limit, 40_000_000);
+        assert_eq!(headers[299].gas_limit, 40_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_built_checkpoint_round_trips_signers() {
+        let config = PoaConfig { epoch: 4, signers: genesis::dev_signers(), ..Default::default() };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis_for(&config), config));
+        let manager = dev::setup_dev_signers().await;
+        let headers = build_chain(chain.clone(), manager, 6).await;
+        let consensus = PoaConsensus::new(chain);
+        consensus.validate_header_range(&headers).unwrap();
+
+        let mut expected = genesis::dev_signers();
+        expected.sort();
+        let checkpoint = &headers[3];
+        assert_eq!(checkpoint.number, 4);
+        assert_eq!(consensus.extract_signers_from_epoch_block(checkpoint).unwrap(), expected);
+
+        for header in headers.iter().filter(|header| header.number != 4) {
+            assert_eq!(header.extra_data.len(), EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seal_checkpoint_header() {
+        let config = PoaConfig { epoch: 4, signers: genesis::dev_signers(), ..Default::default() };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis_for(&config), config));
+        let manager = dev::setup_dev_signers().await;
+        let sealer = BlockSealer::new(manager);
+        let consensus = PoaConsensus::new(chain);
+
+        let mut signers = genesis::dev_signers();
+        signers.sort();
+        let unsorted: Vec<_> = signers.iter().rev().copied().collect();
+        let vanity = BlockSealer::vanity_from_str("checkpoint").unwrap();
+        let header = Header { number: 4, ..Default::default() };
+        let sealed =
+            sealer.seal_checkpoint_header(header, &signers[0], &unsorted, &vanity).await.unwrap();
+
+        assert_eq!(consensus.extract_signers_from_epoch_block(&sealed).unwrap(), signers);
+        assert_eq!(sealed.extra_data[..EXTRA_VANITY_LENGTH], vanity);
+        assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), signers[0]);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_rotation() {
+        let mut signers = genesis::dev_signers();
+        signers.sort();
+        let config = PoaConfig {
+            signers: signers.clone(),
+            signers_weighted: vec![(signers[0], 2), (signers[1], 1), (signers[2], 1)],
+            ..Default::default()
+        };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis_for(&config), config));
+        let manager = dev::setup_dev_signers().await;
+        let mut harness = ChainHarness::new(chain.clone(), manager.clone());
+        for _ in 0..8 {
+            let header = harness.builder().build().await;
+            harness.extend(header).unwrap();
+        }
+
+        let (a, b, c) = (signers[0], signers[1], signers[2]);
+        let consensus = PoaConsensus::new(chain.clone());
+        let sealed: Vec<_> = (1..=8)
+            .map(|number| {
+                let hash = harness.provider.canonical_hash(number).unwrap();
+                let header = harness.provider.header_by_hash(&hash).unwrap();
+                (consensus.recover_signer(&header).unwrap(), header.difficulty)
+            })
+            .collect();
+        let one = U256::from(1);
+        assert_eq!(
+            sealed,
+            vec![(b, one), (a, one), (c, one), (a, one), (b, one), (a, one), (c, one), (a, one)]
+        );
+
+        // Sealing someone else's slot is out of turn for the heavy signer as well
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let header = PoaHeaderBuilder::new(chain, manager, genesis).with_signer(a).build().await;
+        assert_eq!(header.difficulty, U256::from(2));
+        consensus.validate_header(&header).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_out_of_turn_signer_uses_difficulty_two() {
+        let (chain, manager) = dev_setup().await;
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let out_of_turn = inturn(&chain, 2);
+        let header = PoaHeaderBuilder::new(chain.clone(), manager, genesis)
+            .with_signer(out_of_turn)
+            .build()
+            .await;
+
+        assert_eq!(header.difficulty, U256::from(2));
+        PoaConsensus::new(chain).validate_header(&header).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_difficulty_rejected() {
+        let (chain, manager) = dev_setup().await;
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let header =
+            PoaHeaderBuilder::new(chain.clone(), manager, genesis).with_difficulty(5).build().await;
+
+        let err = PoaConsensus::new(chain).validate_header(&header).unwrap_err();
+        assert!(err.to_string().contains("difficulty"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_signer_rejected() {
+        let (chain, manager) = dev_setup().await;
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let header = PoaHeaderBuilder::new(chain.clone(), manager, genesis)
+            .with_signer(outsider())
+            .build()
+            .await;
+
+        let err = PoaConsensus::new(chain).validate_header(&header).unwrap_err();
+        assert!(err.to_string().contains("not authorized"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_early_timestamp_rejected() {
+        let (chain, manager) = dev_setup().await;
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let header = PoaHeaderBuilder::new(chain.clone(), manager, genesis.clone())
+            .with_timestamp(genesis.timestamp)
+            .build()
+            .await;
+
+        let consensus = PoaConsensus::new(chain);
+        consensus.validate_header(&header).unwrap();
+        assert!(consensus.validate_header_against_parent(&header, &genesis).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_total_difficulty_of_mixed_chain() {
+        let (chain, manager) = dev_setup().await;
+        let consensus = PoaConsensus::new(chain.clone());
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+
+        // Blocks 2 and 4 are sealed out of turn
+        let mut headers = vec![genesis.clone()];
+        for number in 1..=5u64 {
+            let parent = headers.last().unwrap().clone();
+            let mut builder = PoaHeaderBuilder::new(chain.clone(), manager.clone(), parent);
+            if number % 2 == 0 {
+                builder = builder.with_signer(inturn(&chain, number + 1));
+            }
+            headers.push(builder.build().await);
+        }
+        consensus.validate_header_range(&headers).unwrap();
+
+        let total = poa_total_difficulty(headers.iter().map(|h| h.header()));
+        // genesis (1) + three in-turn (1) + two out-of-turn (2)
+        assert_eq!(total, U256::from(1 + 3 + 2 * 2));
+        assert!(total > chain.expected_total_difficulty_at(5));
+        assert_eq!(
+            poa_total_difficulty(headers.iter().take(2).map(|h| h.header())),
+            chain.expected_total_difficulty_at(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_difficulty_outside_poa_range_rejected() {
+        let (chain, manager) = dev_setup().await;
+        let consensus = PoaConsensus::new(chain.clone());
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+
+        for difficulty in [0, 3] {
+            let header = PoaHeaderBuilder::new(chain.clone(), manager.clone(), genesis.clone())
+                .with_difficulty(difficulty)
+                .build()
+                .await;
+            let err = consensus.validate_header_against_parent(&header, &genesis).unwrap_err();
+            assert!(err.to_string().contains("difficulty"), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_base_fee_follows_chain_params() {
+        let (dev_chain, manager) = dev_setup().await;
+        let config = PoaConfig {
+            base_fee_params: Some(BaseFeeParams::new(50, 2)),
+            ..dev_chain.poa_config().clone()
+        };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis(), config));
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+
+        let header =
+            PoaHeaderBuilder::new(chain.clone(), manager.clone(), genesis.clone()).build().await;
+        assert!(PoaConsensus::new(chain).validate_header_against_parent(&header, &genesis).is_ok());
+
+        // Under Ethereum's parameters the same block has the wrong base fee
+        let err = PoaConsensus::new(dev_chain)
+            .validate_header_against_parent(&header, &genesis)
+            .unwrap_err();
+        assert!(matches!(err, ConsensusError::BaseFeeDiff(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_zero_base_fee_chain_mines_free_transactions() {
+        let (dev_chain, manager) = dev_setup().await;
+        let config = PoaConfig { zero_base_fee: true, ..dev_chain.poa_config().clone() };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis(), config));
+        assert!(chain.is_zero_base_fee());
+        let consensus = PoaConsensus::new(chain.clone());
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        assert_eq!(genesis.base_fee_per_gas, Some(0));
+
+        // A full parent still leaves the base fee at zero
+        let full = Header { gas_used: genesis.gas_limit, ..genesis.header().clone() };
+        assert_eq!(chain.next_block_base_fee(&full, genesis.timestamp + 2), Some(0));
+
+        let header =
+            PoaHeaderBuilder::new(chain.clone(), manager.clone(), genesis.clone()).build().await;
+        assert_eq!(header.base_fee_per_gas, Some(0));
+        consensus.validate_header_against_parent(&header, &genesis).unwrap();
+
+        let mut charging = header.clone_header();
+        charging.base_fee_per_gas = Some(1);
+        let charging = SealedHeader::seal_slow(charging);
+        assert!(matches!(
+            consensus.validate_header_against_parent(&charging, &genesis),
+            Err(ConsensusError::BaseFeeDiff(_))
+        ));
+
+        // A transaction with `maxFeePerGas = 0` from an account without any balance is mined
+        let key = alloy_signer_local::PrivateKeySigner::random();
+        let tx = TxEip1559 {
+            chain_id: chain.inner().chain.id(),
+            gas_limit: 21_000,
+            max_fee_per_gas: 0,
+            to: TxKind::Call(Address::repeat_byte(0x42)),
+            ..Default::default()
+        };
+        let signature = key.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let tx = Recovered::new_unchecked(tx.into_signed(signature), key.address());
+
+        // Test headers leave out the blob fields Cancun execution needs
+        let env_header = Header { excess_blob_gas: Some(0), ..header.clone_header() };
+        let evm_config = EthEvmConfig::new(chain.inner().clone());
+        let Ok(mut evm) = evm_config.evm_for_block(CacheDB::new(EmptyDB::default()), &env_header);
+        assert!(evm.transact_commit(tx).unwrap().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_blob_gas_follows_blob_schedule() {
+        let (dev_chain, manager) = dev_setup().await;
+        let two_blobs = BlobCounts { target: 1, max: 2 };
+        let blob_params = BlobScheduleOverride { cancun: Some(two_blobs), prague: Some(two_blobs) };
+        let config = PoaConfig { blob_params: Some(blob_params), ..dev_chain.poa_config().clone() };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis(), config));
+        let consensus = PoaConsensus::new(chain.clone());
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+
+        let params = chain.blob_params_at_timestamp(genesis.timestamp).unwrap();
+        assert_eq!((params.target_blob_count, params.max_blob_count), (1, 2));
+
+        let header = PoaHeaderBuilder::new(chain.clone(), manager.clone(), genesis.clone())
+            .with_blob_gas_used(2 * DATA_GAS_PER_BLOB)
+            .build()
+            .await;
+        consensus.validate_header(&header).unwrap();
+
+        let header = PoaHeaderBuilder::new(chain, manager.clone(), genesis.clone())
+            .with_blob_gas_used(3 * DATA_GAS_PER_BLOB)
+            .build()
+            .await;
+        assert!(matches!(
+            consensus.validate_header(&header),
+            Err(ConsensusError::BlobGasUsedExceedsMaxBlobGasPerBlock { .. })
+        ));
+        // Ethereum's Prague schedule allows it
+        PoaConsensus::new(dev_chain).validate_header(&header).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_rules_under_both_period_modes() {
+        let manager = dev::setup_dev_signers().await;
+        for exact_period in [false, true] {
+            let config = crate::chainspec::PoaConfig {
+                period: 2,
+                signers: crate::genesis::dev_signers(),
+                exact_period,
+                ..Default::default()
+            };
+            let chain = Arc::new(PoaChainSpec::new(crate::genesis::create_dev_genesis(), config));
+            let consensus = PoaConsensus::new(chain.clone());
+            let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+
+            for (offset, relaxed_ok, exact_ok) in
+                [(1, false, false), (2, true, true), (3, true, false)]
+            {
+                let header = PoaHeaderBuilder::new(chain.clone(), manager.clone(), genesis.clone())
+                    .with_timestamp(genesis.timestamp + offset)
+                    .build()
+                    .await;
+                let expected_ok = if exact_period { exact_ok } else { relaxed_ok };
+                assert_eq!(
+                    consensus.validate_header_against_parent(&header, &genesis).is_ok(),
+                    expected_ok,
+                    "offset {offset}, exact_period {exact_period}"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poa_rules_apply_from_activation_block() {
+        let (dev, manager) = dev_setup().await;
+        let config = PoaConfig { poa_activation_block: Some(3), ..dev.poa_config().clone() };
+        let chain = Arc::new(PoaChainSpec::new(dev.genesis().clone(), config));
+        let consensus = PoaConsensus::new(chain.clone());
+        let headers = build_chain(chain.clone(), manager.clone(), 4).await;
+
+        // A block of the previous engine, with a proof of work difficulty and no POA seal
+        let legacy = SealedHeader::seal_slow(Header {
+            difficulty: U256::from(131_072),
+            extra_data: alloy_primitives::Bytes::from_static(b"legacy"),
+            ..headers[0].header().clone()
+        });
+        consensus.validate_header(&legacy).unwrap();
+        let child = PoaHeaderBuilder::new(chain.clone(), manager.clone(), legacy.clone())
+            .with_signer(outsider())
+            .with_difficulty(131_072)
+            .build()
+            .await;
+        consensus.validate_header(&child).unwrap();
+        consensus.validate_header_against_parent(&child, &legacy).unwrap();
+
+        // From the activation block on every POA rule applies
+        let forged = PoaHeaderBuilder::new(chain, manager, headers[1].clone())
+            .with_signer(outsider())
+            .build()
+            .await;
+        assert_eq!(forged.number, 3);
+        assert!(consensus.validate_header(&forged).is_err());
+        consensus.validate_header(&headers[2]).unwrap();
+        consensus.validate_header_against_parent(&headers[3], &headers[2]).unwrap();
+        assert!(PoaConsensus::new(dev).validate_header(&child).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trusted_checkpoint_skips_seal_check_up_to_checkpoint() {
+        let (chain, manager) = dev_setup().await;
+        let headers = build_chain(chain.clone(), manager.clone(), 8).await;
+        let checkpoint = &headers[4];
+        let seal_checked = |consensus: &PoaConsensus, header: &SealedHeader<Header>| {
+            consensus.signer_cache.lock().get(&header.hash()).is_some()
+        };
+
+        // Downloading backwards from the checkpoint, each parent is proven by its child
+        let consensus = PoaConsensus::new(chain.clone())
+            .with_trusted_checkpoint(checkpoint.number, checkpoint.hash());
+        for header in headers.iter().rev() {
+            consensus.validate_header(header).unwrap();
+        }
+        assert!(headers[..5].iter().all(|header| !seal_checked(&consensus, header)));
+        assert!(headers[5..].iter().all(|header| seal_checked(&consensus, header)));
+
+        // A fork below the checkpoint height is no ancestor, so its unauthorized seal is caught
+        let fork = PoaHeaderBuilder::new(chain.clone(), manager.clone(), headers[1].clone())
+            .with_signer(outsider())
+            .build()
+            .await;
+        assert!(fork.number < checkpoint.number);
+        let err = consensus.validate_header(&fork).unwrap_err();
+        assert!(err.to_string().contains("not authorized"), "{err}");
+
+        // Validating forwards, the canonical chain proves the ancestors instead
+        let provider = Arc::new(provider::InMemoryPoaStateProvider::new());
+        provider.insert_header(SealedHeader::seal_slow(chain.inner().genesis_header().clone()));
+        for header in &headers {
+            provider.insert_header(header.clone());
+        }
+        let consensus = PoaConsensus::new(chain.clone())
+            .with_provider(provider)
+            .with_trusted_checkpoint(checkpoint.number, checkpoint.hash());
+        for header in &headers {
+            consensus.validate_header(header).unwrap();
+        }
+        assert!(headers[..5].iter().all(|header| !seal_checked(&consensus, header)));
+        assert!(consensus.validate_header(&fork).is_err());
+
+        // Above the checkpoint the same forgery is rejected
+        let above = PoaHeaderBuilder::new(chain, manager, headers[5].clone())
+            .with_signer(outsider())
+            .build()
+            .await;
+        assert!(consensus.validate_header(&above).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trusted_checkpoint_hash_mismatch() {
+        let (chain, manager) = dev_setup().await;
+        let headers = build_chain(chain.clone(), manager, 6).await;
+        let consensus =
+            PoaConsensus::new(chain).with_trusted_checkpoint(4, B256::repeat_byte(0xab));
+
+        // Headers below the checkpoint are still accepted...
+        consensus.validate_header(&headers[1]).unwrap();
+
+        // ...but the header at the checkpoint height must match exactly
+        let err = consensus.validate_header(&headers[3]).unwrap_err();
+        assert!(err.to_string().contains("checkpoint mismatch"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_rejections() {
+        let (chain, manager) = dev_setup().await;
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let header = PoaHeaderBuilder::new(chain.clone(), manager.clone(), genesis.clone())
+            .with_signer(outsider())
+            .build()
+            .await;
+        // Well signed, but more than doubles the gas limit of its parent
+        let greedy = PoaHeaderBuilder::new(chain.clone(), manager, genesis.clone())
+            .with_gas_limit(genesis.gas_limit() * 2)
+            .build()
+            .await;
+        let consensus = PoaConsensus::new(chain);
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        ::metrics::with_local_recorder(&recorder, || {
+            assert!(consensus.validate_header(&header).is_err());
+            assert!(consensus.validate_header(&header).is_err());
+            assert!(consensus.validate_header_against_parent(&greedy, &genesis).is_err());
+        });
+
+        let rejections = snapshotter.snapshot().into_vec();
+        let count = |reason: &str| {
+            rejections
+                .iter()
+                .find_map(|(key, _, _, value)| {
+                    let key = key.key();
+                    let labeled = key.labels().any(|l| l.key() == "reason" && l.value() == reason);
+                    (key.name() == "poa_consensus_rejections" && labeled).then_some(value)
+                })
+                .unwrap_or_else(|| panic!("no {reason} rejections recorded"))
+                .clone()
+        };
+        assert_eq!(count("unauthorized_signer"), DebugValue::Counter(2));
+        assert_eq!(count("gas_limit"), DebugValue::Counter(1));
+    }
+
+    #[tokio::test]
+    async fn test_error_message_includes_block_context() {
+        let (chain, manager) = dev_setup().await;
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let header = PoaHeaderBuilder::new(chain.clone(), manager, genesis)
+            .with_signer(outsider())
+            .build()
+            .await;
+
+        let err = PoaConsensus::new(chain).validate_header_seal(&header).unwrap_err();
+        let err = err.with_block_hash(header.hash());
+        assert_eq!(err.block(), &BlockContext::sealed(1, header.hash()));
+
+        let message = err.to_string();
+        assert!(message.starts_with("block #1 (0x"), "{message}");
+        assert!(message.contains(&format!("block signer {} is not authorized", outsider())));
+    }
+
+    #[test]
+    fn test_extra_data_layout_is_exact() {
+        let chain = Arc::new(crate::chainspec::PoaChainSpec::dev_chain());
+        let consensus = PoaConsensus::new(chain.clone());
+        let min_length = EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH;
+
+        // Feed every length from the minimum to minimum + 200, filled with non-zero junk
+        for len in min_length..=min_length + 200 {
+            let extra: Vec<u8> = (0..len).map(|i| (i * 31 + len) as u8 | 1).collect();
+
+            let regular = Header {
+                number: chain.epoch() + 1,
+                extra_data: extra.clone().into(),
+                ..Default::default()
+            };
+            assert_eq!(consensus.validate_extra_data(&regular).is_ok(), len == min_length);
+            assert!(consensus.recover_signer(&regular).is_err() || len == min_length);
+
+            let checkpoint =
+                Header { number: chain.epoch(), extra_data: extra.into(), ..Default::default() };
+            let signers_len = len - min_length;
+            let valid = signers_len > 0 && signers_len % ADDRESS_LENGTH == 0;
+            assert_eq!(consensus.validate_extra_data(&checkpoint).is_ok(), valid, "len {len}");
+            match consensus.extract_signers_from_epoch_block(&checkpoint) {
+                Ok(signers) => {
+                    assert!(valid);
+                    assert_eq!(signers.len(), signers_len / ADDRESS_LENGTH);
+                }
+                Err(_) => assert!(!valid),
+            }
+        }
+
+        // Too short is reported as such rather than panicking
+        let short = Header {
+            number: chain.epoch(),
+            extra_data: vec![0u8; 40].into(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            consensus.extract_signers_from_epoch_block(&short),
+            Err(PoaConsensusError::ExtraDataTooShort { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_recent_signer_window_through_provider() {
+        // 20 signers allow each of them one block out of every 11
+        let manager = Arc::new(crate::signer::SignerManager::new());
+        let mut signers = Vec::new();
+        for _ in 0..20 {
+            let key = alloy_signer_local::PrivateKeySigner::random();
+            signers.push(manager.add_signer(key).await);
+        }
+        let config = crate::chainspec::PoaConfig { period: 2, signers, ..Default::default() };
+        let chain =
+            Arc::new(PoaChainSpec::new(crate::genesis::create_dev_genesis_for(&config), config));
+
+        let provider = Arc::new(provider::InMemoryPoaStateProvider::new());
+        provider.insert_header(SealedHeader::seal_slow(chain.inner().genesis_header().clone()));
+        let headers = build_chain(chain.clone(), manager.clone(), 12).await;
+        for header in &headers {
+            provider.insert_header(header.clone());
+        }
+        let consensus = PoaConsensus::new(chain.clone()).with_provider(provider);
+        let stateless = PoaConsensus::new(chain.clone());
+        let tip = headers.last().unwrap().clone();
+
+        // The signer of block 3 is still inside the 10-block window of block 13
+        let recent = PoaHeaderBuilder::new(chain.clone(), manager.clone(), tip.clone())
+            .with_signer(inturn(&chain, 3))
+            .build()
+            .await;
+        let err = consensus.validate_header_seal(&recent).unwrap_err();
+        assert!(matches!(err, PoaConsensusError::RecentlySigned { .. }), "{err}");
+        stateless.validate_header(&recent).unwrap();
+
+        // The signer of block 2 has left the window
+        let rested = PoaHeaderBuilder::new(chain.clone(), manager, tip)
+            .with_signer(inturn(&chain, 2))
+            .build()
+            .await;
+        consensus.validate_header(&rested).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rotation_follows_signer_set_changes() {
+        let (chain, manager) = dev_setup().await;
+        let provider = Arc::new(provider::InMemoryPoaStateProvider::new());
+        let consensus = PoaConsensus::new(chain.clone()).with_provider(provider.clone());
+        let genesis_snapshot = Snapshot::new_genesis(&chain);
+
+        let mut parent = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let mut snapshot = genesis_snapshot.clone();
+        provider.insert_snapshot(snapshot.clone());
+
+        // Blocks 1 and 2 vote the outsider in, blocks 3 to 6 are sealed by whoever is in turn
+        // (block 3 falls to a signer that sealed recently, so the outsider seals it out of turn)
+        let mut sealers = Vec::new();
+        for number in 1..=6u64 {
+            let mut builder = PoaHeaderBuilder::new(chain.clone(), manager.clone(), parent.clone())
+                .with_snapshot(snapshot.clone());
+            if number <= 2 {
+                builder = builder.with_vote(outsider(), true);
+            }
+            if number == 3 {
+                assert!(snapshot.is_recently_signed_at(3, &snapshot.inturn_signer(3).unwrap()));
+                builder = builder.with_signer(outsider());
+
+                // Sealing in turn according to the genesis rotation is no longer valid
+                let stale = PoaHeaderBuilder::new(chain.clone(), manager.clone(), parent.clone())
+                    .with_signer(inturn(&chain, 3))
+                    .with_difficulty(1)
+                    .build()
+                    .await;
+                assert_ne!(snapshot.inturn_signer(3), Some(inturn(&chain, 3)));
+                let err = consensus.validate_header_seal(&stale).unwrap_err();
+                assert!(matches!(err, PoaConsensusError::InvalidDifficulty { .. }), "{err}");
+            }
+
+            let header = builder.build().await;
+            consensus.validate_header(&header).unwrap();
+            sealers.push(recover_header_signer(&header).unwrap());
+
+            snapshot = snapshot.apply(std::slice::from_ref(header.header())).unwrap();
+            provider.insert_snapshot(snapshot.clone());
+            parent = header;
+        }
+
+        assert_eq!(snapshot.signers.len(), 4);
+        for number in 4..=6u64 {
+            assert_eq!(Some(sealers[number as usize - 1]), snapshot.inturn_signer(number));
+        }
+        assert!(sealers[3..].contains(&outsider()));
+        assert_ne!(
+            (4..=6).map(|n| snapshot.inturn_signer(n)).collect::<Vec<_>>(),
+            (4..=6).map(|n| genesis_snapshot.inturn_signer(n)).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signer_set_change_takes_effect_next_block() {
+        let (chain, manager) = dev_setup().await;
+        let mut harness = ChainHarness::new(chain, manager);
+        assert_eq!(harness.snapshot.signer_limit(), 2);
+
+        // Two of the three signers vote the outsider in...
+        for _ in 0..2 {
+            let header = harness.builder().with_vote(outsider(), true).build().await;
+            harness.extend(header).unwrap();
+        }
+        assert!(harness.snapshot.signers.contains(&outsider()));
+        assert_eq!(harness.snapshot.signer_limit(), 3);
+
+        // ...and it may seal the very next block
+        let header = harness.builder().with_signer(outsider()).build().await;
+        harness.extend(header).unwrap();
+
+        // The next three in-turn signers vote out the remaining one
+        let voters: Vec<_> = (4..=6).map(|n| harness.snapshot.inturn_signer(n).unwrap()).collect();
+        let removed = *harness.snapshot.signers.iter().find(|s| !voters.contains(s)).unwrap();
+        for _ in 0..3 {
+            let header = harness.builder().with_vote(removed, false).build().await;
+            harness.extend(header).unwrap();
+        }
+        assert!(!harness.snapshot.signers.contains(&removed));
+        assert_eq!(harness.snapshot.signer_limit(), 2);
+
+        // A freshly removed signer can no longer seal
+        let header = harness.builder().with_signer(removed).build().await;
+        let err = harness.consensus.validate_header_seal(&header).unwrap_err();
+        assert!(
+            matches!(err, PoaConsensusError::UnauthorizedSigner { signer, .. } if signer == removed),
+            "{err}"
+        );
+
+        // The shrunk recent-signer window lets the signer of block 5 seal block 7
+        let header = harness.builder().with_signer(voters[1]).build().await;
+        harness.extend(header).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_voted_out_signer_with_missing_ancestors() {
+        let (chain, manager) = dev_setup().await;
+        let mut harness = ChainHarness::new(chain.clone(), manager);
+
+        // The signers in turn for blocks 1 and 2 vote out the third
+        let voters = [inturn(&chain, 1), inturn(&chain, 2)];
+        let removed = *harness.snapshot.signers.iter().find(|s| !voters.contains(s)).unwrap();
+        for _ in 0..2 {
+            let header = harness.builder().with_vote(removed, false).build().await;
+            harness.extend(header).unwrap();
+        }
+        assert!(!harness.snapshot.signers.contains(&removed));
+        // In turn under the genesis rotation
+        assert_eq!(inturn(&chain, 3), removed);
+        let header = harness.builder().with_signer(removed).with_difficulty(1).build().await;
+
+        // Only the parent is known, so the vote that removed the signer can't be replayed
+        let provider = Arc::new(provider::InMemoryPoaStateProvider::new());
+        provider.insert_header(harness.parent.clone());
+        let consensus = PoaConsensus::new(chain.clone()).with_provider(provider);
+        let err = consensus.validate_header_seal(&header).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                PoaConsensusError::UnknownAncestor { ancestor, .. }
+                    if ancestor == harness.parent.parent_hash
+            ),
+            "{err}"
+        );
+
+        // With all ancestors the signer is rejected, without a provider genesis still admits it
+        let err = harness.consensus.validate_header_seal(&header).unwrap_err();
+        assert!(matches!(err, PoaConsensusError::UnauthorizedSigner { .. }), "{err}");
+        PoaConsensus::new(chain).validate_header_seal(&header).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_proposals_are_voted_in_automatically() {
+        let (chain, manager) = dev_setup().await;
+        let mut harness = ChainHarness::new(chain, manager);
+
+        // Two of the three signer nodes propose the outsider, each node sealing with its own map
+        let accounts = crate::genesis::dev_accounts();
+        let nodes: HashMap<Address, Arc<ProposalsMap>> =
+            [accounts[0], accounts[1], accounts[2], outsider()]
+                .into_iter()
+                .map(|signer| (signer, Arc::new(ProposalsMap::new())))
+                .collect();
+        nodes[&accounts[0]].propose(outsider(), true);
+        nodes[&accounts[1]].propose(outsider(), true);
+
+        for _ in 0..8 {
+            let number = harness.parent.number + 1;
+            let signer = default_signer(&harness.snapshot, number);
+            let voted = harness.snapshot.signers.contains(&outsider());
+            let header = harness.builder().with_proposals(nodes[&signer].clone()).build().await;
+
+            // Once the proposal passed, nobody embeds it any more
+            if voted {
+                assert_eq!(header.beneficiary, Address::ZERO, "block {number}");
+            }
+            harness.extend(header).unwrap();
+        }
+
+        assert!(harness.snapshot.signers.contains(&outsider()));
+        assert!(harness.snapshot.votes.is_empty());
+        assert!(harness.snapshot.tally.is_empty());
+    }
+}
@@ -0,0 +1,662 @@
+//! Access to chain state beyond a header and its parent
+//!
+//! [`HeaderValidator`](reth_consensus::HeaderValidator) only sees a header and its immediate
+//! parent, but several POA rules (recent-signer limit, dynamic signer sets, vote tallies) need
+//! ancestor headers. A [`PoaStateProvider`] supplies them to [`PoaConsensus`](super::PoaConsensus).
+//!
+//! The [`SnapshotProvider`] builds on top of it to reconstruct the [`Snapshot`] at any block,
+//! keeping recent results in a [`SnapshotCache`].
+
+use super::{checkpoint_signers, snapshot::Snapshot, store::SnapshotStore, PoaConsensusError};
+use crate::chainspec::PoaChainSpec;
+use alloy_consensus::Header;
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::B256;
+use parking_lot::{Mutex, RwLock};
+use reth_primitives_traits::SealedHeader;
+use reth_storage_api::{BlockNumReader, HeaderProvider};
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// Default number of recent snapshots kept in memory (same as clique)
+pub const DEFAULT_SNAPSHOT_CACHE_SIZE: u32 = 128;
+
+/// Provides ancestor headers and snapshots to POA consensus
+pub trait PoaStateProvider: Debug + Send + Sync {
+    /// Returns the header with the given hash, if known
+    fn header_by_hash(&self, hash: &B256) -> Option<Header>;
+
+    /// Returns the hash of the canonical block at `number`, if known
+    fn canonical_hash(&self, number: u64) -> Option<B256>;
+
+    /// Returns the number of the canonical tip, if known
+    fn best_block_number(&self) -> Option<u64>;
+
+    /// Returns the snapshot taken at the block with the given hash, if available
+    fn snapshot_at(&self, hash: &B256) -> Option<Snapshot>;
+}
+
+/// [`PoaStateProvider`] backed by reth's [`HeaderProvider`].
+///
+/// Snapshots are not stored in the reth database, so [`PoaStateProvider::snapshot_at`] always
+/// returns `None` and consensus reconstructs them from ancestor headers through a
+/// [`SnapshotProvider`].
+#[derive(Debug)]
+pub struct HeaderStateProvider<P> {
+    provider: P,
+}
+
+impl<P> HeaderStateProvider<P> {
+    /// Wrap a reth header provider
+    pub const fn new(provider: P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P> PoaStateProvider for HeaderStateProvider<P>
+where
+    P: HeaderProvider<Header = Header> + BlockNumReader + Sync + Debug,
+{
+    fn header_by_hash(&self, hash: &B256) -> Option<Header> {
+        self.provider.header(*hash).ok().flatten()
+    }
+
+    fn canonical_hash(&self, number: u64) -> Option<B256> {
+        self.provider.sealed_header(number).ok().flatten().map(|header| header.hash())
+    }
+
+    fn best_block_number(&self) -> Option<u64> {
+        self.provider.best_block_number().ok()
+    }
+
+    fn snapshot_at(&self, _hash: &B256) -> Option<Snapshot> {
+        None
+    }
+}
+
+/// In-memory [`PoaStateProvider`], mainly useful for tests
+#[derive(Debug, Default)]
+pub struct InMemoryPoaStateProvider {
+    headers: RwLock<HashMap<B256, Header>>,
+    canonical: RwLock<BTreeMap<u64, B256>>,
+    snapshots: RwLock<HashMap<B256, Snapshot>>,
+}
+
+impl InMemoryPoaStateProvider {
+    /// Create an empty provider
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a sealed header as the canonical block at its height
+    pub fn insert_header(&self, header: SealedHeader<Header>) {
+        self.canonical.write().insert(header.number, header.hash());
+        self.insert_sidechain_header(header);
+    }
+
+    /// Store a sealed header without making it canonical
+    pub fn insert_sidechain_header(&self, header: SealedHeader<Header>) {
+        let (header, hash) = header.split();
+        self.headers.write().insert(hash, header);
+    }
+
+    /// Store a snapshot under its block hash
+    pub fn insert_snapshot(&self, snapshot: Snapshot) {
+        self.snapshots.write().insert(snapshot.hash, snapshot);
+    }
+}
+
+impl PoaStateProvider for InMemoryPoaStateProvider {
+    fn header_by_hash(&self, hash: &B256) -> Option<Header> {
+        self.headers.read().get(hash).cloned()
+    }
+
+    fn canonical_hash(&self, number: u64) -> Option<B256> {
+        self.canonical.read().get(&number).copied()
+    }
+
+    fn best_block_number(&self) -> Option<u64> {
+        self.canonical.read().last_key_value().map(|(number, _)| *number)
+    }
+
+    fn snapshot_at(&self, hash: &B256) -> Option<Snapshot> {
+        self.snapshots.read().get(hash).cloned()
+    }
+}
+
+/// Errors from reconstructing a [`Snapshot`]
+#[derive(Debug, Error)]
+pub enum SnapshotLookupError {
+    /// The requested block could not be resolved to a hash
+    #[error("unknown block {0}")]
+    UnknownBlock(BlockId),
+
+    /// A header on the way back to the nearest known snapshot is missing
+    #[error("missing header {0}")]
+    MissingHeader(B256),
+
+    /// A header could not be applied to the snapshot
+    #[error(transparent)]
+    Consensus(#[from] PoaConsensusError),
+}
+
+/// Bounded cache of reconstructed snapshots, keyed by block hash.
+///
+/// Entries stay valid across reorgs because a hash pins the branch it belongs to. What does change
+/// is which block `latest` refers to: the cache tracks the canonical head reported by
+/// [`on_commit`](Self::on_commit) and [`on_reorg`](Self::on_reorg), and until either is called
+/// the [`SnapshotProvider`] asks its [`PoaStateProvider`] instead.
+#[derive(Debug)]
+pub struct SnapshotCache {
+    entries: Mutex<LruMap<B256, Snapshot>>,
+    head: RwLock<Option<B256>>,
+}
+
+impl SnapshotCache {
+    /// Create a cache holding at most `capacity` snapshots
+    pub fn new(capacity: u32) -> Self {
+        Self { entries: Mutex::new(LruMap::new(ByLength::new(capacity))), head: RwLock::new(None) }
+    }
+
+    /// Returns the cached snapshot taken at the block with the given hash
+    pub fn get(&self, hash: &B256) -> Option<Snapshot> {
+        self.entries.lock().get(hash).cloned()
+    }
+
+    /// Cache a snapshot under its block hash, evicting the least recently used one when full
+    pub fn insert(&self, snapshot: Snapshot) {
+        self.entries.lock().insert(snapshot.hash, snapshot);
+    }
+
+    /// Number of cached snapshots
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether no snapshot is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash of the canonical head, if one was reported
+    pub fn head(&self) -> Option<B256> {
+        *self.head.read()
+    }
+
+    /// The canonical chain was extended up to `tip_hash`
+    pub fn on_commit(&self, tip_hash: B256) {
+        *self.head.write() = Some(tip_hash);
+    }
+
+    /// The canonical chain switched branches and now ends at `new_tip_hash`.
+    ///
+    /// Snapshots of orphaned blocks are left in place, they are still correct for their own branch
+    /// and age out like any other entry. Only the head moves, so the next `latest` lookup resolves
+    /// the snapshot of the winning branch.
+    pub fn on_reorg(&self, new_tip_hash: B256) {
+        *self.head.write() = Some(new_tip_hash);
+    }
+}
+
+impl Default for SnapshotCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SNAPSHOT_CACHE_SIZE)
+    }
+}
+
+/// Reconstructs the [`Snapshot`] at arbitrary blocks.
+///
+/// Starting from the requested block, parent hashes are followed back to the nearest snapshot
+/// that is already known: one in the in-memory cache, one offered by the [`PoaStateProvider`], one
+/// persisted in the [`SnapshotStore`], an epoch block whose signer list can be trusted because
+/// its parent is unavailable, or genesis. The collected headers are then applied forward and the
+/// result is cached.
+///
+/// Because the walk follows parent hashes rather than block numbers, blocks on non-canonical
+/// forks resolve to the snapshot of their own branch.
+#[derive(Debug)]
+pub struct SnapshotProvider {
+    chain_spec: Arc<PoaChainSpec>,
+    state: Arc<dyn PoaStateProvider>,
+    store: Option<SnapshotStore>,
+    cache: SnapshotCache,
+}
+
+impl SnapshotProvider {
+    /// Create a provider reading headers and snapshots from `state`
+    pub fn new(chain_spec: Arc<PoaChainSpec>, state: Arc<dyn PoaStateProvider>) -> Self {
+        Self { chain_spec, state, store: None, cache: SnapshotCache::default() }
+    }
+
+    /// Keep up to `capacity` snapshots in memory instead of [`DEFAULT_SNAPSHOT_CACHE_SIZE`]
+    pub fn with_cache_capacity(mut self, capacity: u32) -> Self {
+        self.cache = SnapshotCache::new(capacity);
+        self
+    }
+
+    /// The snapshot cache, whose head must follow canonical chain notifications
+    pub const fn cache(&self) -> &SnapshotCache {
+        &self.cache
+    }
+
+    /// Also start from snapshots persisted in `store`
+    pub fn with_store(mut self, store: SnapshotStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Resolve a block id to a block hash.
+    ///
+    /// POA blocks are final once sealed, so every tag other than `earliest` resolves to the
+    /// canonical tip: the head tracked by the cache, or the state provider's best block.
+    fn resolve(&self, id: BlockId) -> Option<B256> {
+        match id {
+            BlockId::Hash(hash) => Some(hash.block_hash),
+            BlockId::Number(BlockNumberOrTag::Number(number)) => self.state.canonical_hash(number),
+            BlockId::Number(BlockNumberOrTag::Earliest) => self.state.canonical_hash(0),
+            BlockId::Number(_) => self
+                .cache
+                .head()
+                .or_else(|| self.state.canonical_hash(self.state.best_block_number()?)),
+        }
+    }
+
+    /// Returns the snapshot after the block identified by `id`.
+    ///
+    /// Numbers and tags are resolved to the hash of the current canonical block first, see
+    /// [`snapshot_at`](Self::snapshot_at).
+    pub fn snapshot_at_block(&self, id: BlockId) -> Result<Snapshot, SnapshotLookupError> {
+        let hash = self.resolve(id).ok_or(SnapshotLookupError::UnknownBlock(id))?;
+        self.snapshot_at(hash)
+    }
+
+    /// Returns the snapshot after the block with the given hash.
+    ///
+    /// Snapshots are only ever matched by hash, never by height alone: after a reorg, even one
+    /// that replaced an epoch block, the signer set, votes and recents come from the branch the
+    /// block belongs to.
+    pub fn snapshot_at(&self, hash: B256) -> Result<Snapshot, SnapshotLookupError> {
+        // Known snapshots don't need their header
+        if let Some(snapshot) = self.cache.get(&hash).or_else(|| self.state.snapshot_at(&hash)) {
+            return Ok(snapshot);
+        }
+
+        let header_by_hash = |hash: &B256| {
+            self.state.header_by_hash(hash).ok_or(SnapshotLookupError::MissingHeader(*hash))
+        };
+
+        // The height is needed to match persisted snapshots, which are keyed by number
+        let mut number = header_by_hash(&hash)?.number;
+        let mut cursor = hash;
+        let mut headers = Vec::new();
+
+        let base = loop {
+            if let Some(snapshot) = self.known_snapshot(&cursor, number) {
+                break snapshot;
+            }
+
+            let header = header_by_hash(&cursor)?;
+            if number == 0 {
+                break Snapshot::new_genesis(&self.chain_spec);
+            }
+
+            // Without the parent, an epoch block's signer list is the best starting point
+            if number.is_multiple_of(self.chain_spec.epoch()) &&
+                self.known_snapshot(&header.parent_hash, number - 1).is_none() &&
+                self.state.header_by_hash(&header.parent_hash).is_none()
+            {
+                let signers = checkpoint_signers(&header)?;
+                break Snapshot::new_checkpoint(&self.chain_spec, number, cursor, signers);
+            }
+
+            cursor = header.parent_hash;
+            number -= 1;
+            headers.push(header);
+        };
+
+        headers.reverse();
+        let snapshot = base.apply(&headers)?;
+        self.cache.insert(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Snapshot for block `number` with the given hash from the cache, the state provider or the
+    /// store
+    fn known_snapshot(&self, hash: &B256, number: u64) -> Option<Snapshot> {
+        if let Some(snapshot) = self.cache.get(hash) {
+            return Some(snapshot);
+        }
+        if let Some(snapshot) = self.state.snapshot_at(hash) {
+            return Some(snapshot);
+        }
+        // Persisted snapshots may belong to another branch at the same height
+        self.store
+            .as_ref()
+            .and_then(|store| store.load(number))
+            .filter(|snapshot| snapshot.hash == *hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::PoaConfig,
+        consensus::{
+            test_utils::{ChainHarness, PoaHeaderBuilder},
+            PoaConsensus,
+        },
+        genesis::{create_dev_genesis_for, dev_accounts, dev_signers},
+        signer::{dev, SignerManager},
+    };
+    use alloy_primitives::Address;
+    use reth_consensus::HeaderValidator;
+    use reth_ethereum::provider::test_utils::MockEthProvider;
+
+    /// Dev signers plus an outsider key that the tests vote in
+    async fn signers_and_outsider() -> (Arc<SignerManager>, Address) {
+        let manager = dev::setup_dev_signers().await;
+        manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[5]).await.unwrap();
+        (manager, dev_accounts()[5])
+    }
+
+    /// Extend the harness to `n_blocks`, voting the outsider in with the first two blocks
+    async fn grow(harness: &mut ChainHarness, outsider: Address, n_blocks: u64) {
+        while harness.parent.number < n_blocks {
+            let mut builder = harness.builder();
+            if harness.parent.number < 2 {
+                builder = builder.with_vote(outsider, true);
+            }
+            let header = builder.build().await;
+            harness.extend(header).unwrap();
+        }
+    }
+
+    /// A provider holding only the canonical headers of `harness` from `from` onwards
+    fn headers_from(harness: &ChainHarness, from: u64) -> Arc<InMemoryPoaStateProvider> {
+        let provider = Arc::new(InMemoryPoaStateProvider::new());
+        for number in from..=harness.parent.number {
+            let hash = harness.provider.canonical_hash(number).unwrap();
+            let header = harness.provider.header_by_hash(&hash).unwrap();
+            provider.insert_header(SealedHeader::new(header, hash));
+        }
+        provider
+    }
+
+    #[tokio::test]
+    async fn test_voted_in_signer_through_header_provider() {
+        let (manager, outsider) = signers_and_outsider().await;
+        let chain = Arc::new(PoaChainSpec::dev_chain());
+        let mut harness = ChainHarness::new(chain.clone(), manager.clone());
+        grow(&mut harness, outsider, 2).await;
+        assert!(harness.snapshot.signers.contains(&outsider));
+
+        // A reth database holds headers only, never snapshots
+        let headers = MockEthProvider::default();
+        for number in 0..=harness.parent.number {
+            let hash = harness.provider.canonical_hash(number).unwrap();
+            headers.add_header(hash, harness.provider.header_by_hash(&hash).unwrap());
+        }
+        let state = Arc::new(HeaderStateProvider::new(headers));
+        assert!(state.snapshot_at(&harness.parent.hash()).is_none());
+        let consensus = PoaConsensus::new(chain.clone()).with_provider(state);
+
+        // The outsider joined at block 2 and may seal block 3
+        let header = harness.builder().with_signer(outsider).build().await;
+        consensus.validate_header(&header).unwrap();
+
+        // Validated against genesis it would still be an outsider
+        let err = PoaConsensus::new(chain).validate_header(&header).unwrap_err();
+        assert!(err.to_string().contains("not authorized"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_far_past_persisted_checkpoint() {
+        let (manager, outsider) = signers_and_outsider().await;
+        let chain = Arc::new(PoaChainSpec::dev_chain());
+        let mut harness = ChainHarness::new(chain.clone(), manager);
+
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        grow(&mut harness, outsider, 10).await;
+        store.save(&harness.snapshot).unwrap();
+        grow(&mut harness, outsider, 510).await;
+
+        // Headers up to the persisted snapshot are gone, so it must start from there
+        let provider = SnapshotProvider::new(chain, headers_from(&harness, 11)).with_store(store);
+        let snapshot = provider.snapshot_at_block(BlockId::number(510)).unwrap();
+        assert_eq!(snapshot, harness.snapshot);
+        assert!(snapshot.signers.contains(&outsider));
+
+        // Latest resolves to the same block, now served from the cache
+        assert_eq!(provider.snapshot_at_block(BlockId::latest()).unwrap(), snapshot);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_from_checkpoint_signer_list() {
+        let (manager, outsider) = signers_and_outsider().await;
+        let config =
+            PoaConfig { period: 2, epoch: 8, signers: dev_signers(), ..Default::default() };
+        let chain = Arc::new(PoaChainSpec::new(create_dev_genesis_for(&config), config));
+        let mut harness = ChainHarness::new(chain.clone(), manager);
+        grow(&mut harness, outsider, 12).await;
+
+        // Nothing before the epoch block at 8 is available
+        let provider = SnapshotProvider::new(chain, headers_from(&harness, 8));
+        let snapshot = provider.snapshot_at_block(BlockId::number(12)).unwrap();
+        assert_eq!(snapshot.signers, harness.snapshot.signers);
+        assert_eq!(snapshot.hash, harness.parent.hash());
+
+        // Without a checkpoint to fall back on the lookup fails
+        let provider = SnapshotProvider::new(harness.chain.clone(), headers_from(&harness, 9));
+        assert!(matches!(
+            provider.snapshot_at_block(BlockId::number(12)),
+            Err(SnapshotLookupError::MissingHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_cache_is_bounded() {
+        let cache = SnapshotCache::default();
+        let signers: Vec<_> = (0..100u8).map(Address::repeat_byte).collect();
+        for number in 0..1000u64 {
+            let hash = B256::from(alloy_primitives::U256::from(number));
+            cache.insert(Snapshot::new(30_000, number, hash, signers.iter().copied()));
+            assert!(cache.len() <= DEFAULT_SNAPSHOT_CACHE_SIZE as usize);
+        }
+        assert_eq!(cache.len(), DEFAULT_SNAPSHOT_CACHE_SIZE as usize);
+
+        // Only the most recent snapshots survive
+        assert!(cache.get(&B256::from(alloy_primitives::U256::from(999))).is_some());
+        assert!(cache.get(&B256::ZERO).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_on_sidechain_follows_its_own_votes() {
+        let (manager, outsider) = signers_and_outsider().await;
+        let chain = Arc::new(PoaChainSpec::dev_chain());
+        let mut harness = ChainHarness::new(chain.clone(), manager.clone());
+
+        // Common history up to the fork point at block 4
+        for _ in 0..4 {
+            let header = harness.builder().build().await;
+            harness.extend(header).unwrap();
+        }
+        let fork_point = harness.parent.clone();
+        let fork_snapshot = harness.snapshot.clone();
+
+        // Canonical branch votes the outsider in
+        for _ in 0..2 {
+            let header = harness.builder().with_vote(outsider, true).build().await;
+            harness.extend(header).unwrap();
+        }
+        assert!(harness.snapshot.signers.contains(&outsider));
+
+        // Sibling branch seals the same heights without voting
+        let mut parent = fork_point;
+        let mut snapshot = fork_snapshot;
+        for _ in 0..2 {
+            let header = PoaHeaderBuilder::new(chain.clone(), manager.clone(), parent)
+                .with_snapshot(snapshot.clone())
+                .build()
+                .await;
+            snapshot = snapshot.apply(std::slice::from_ref(header.header())).unwrap();
+            harness.provider.insert_sidechain_header(header.clone());
+            parent = header;
+        }
+
+        let provider = SnapshotProvider::new(chain, harness.provider.clone());
+        let sidechain = provider.snapshot_at(parent.hash()).unwrap();
+        assert!(!sidechain.signers.contains(&outsider));
+        assert_eq!(sidechain, snapshot);
+
+        let canonical = provider.snapshot_at_block(BlockId::number(6)).unwrap();
+        assert!(canonical.signers.contains(&outsider));
+        assert_ne!(canonical.hash, sidechain.hash);
+    }
+
+    #[tokio::test]
+    async fn test_latest_snapshot_follows_reorg() {
+        let (manager, outsider) = signers_and_outsider().await;
+        let chain = Arc::new(PoaChainSpec::dev_chain());
+        let mut harness = ChainHarness::new(chain.clone(), manager.clone());
+        for _ in 0..2 {
+            let header = harness.builder().build().await;
+            harness.extend(header).unwrap();
+        }
+        let (fork_point, fork_snapshot) = (harness.parent.clone(), harness.snapshot.clone());
+
+        // The first branch votes the outsider in and becomes canonical
+        for _ in 0..2 {
+            let header = harness.builder().with_vote(outsider, true).build().await;
+            harness.extend(header).unwrap();
+        }
+        let provider = SnapshotProvider::new(chain.clone(), harness.provider.clone());
+        provider.cache().on_commit(harness.parent.hash());
+        assert!(provider.snapshot_at_block(BlockId::latest()).unwrap().signers.contains(&outsider));
+
+        // A longer branch without the votes wins
+        let mut parent = fork_point;
+        let mut snapshot = fork_snapshot;
+        for _ in 0..3 {
+            let header = PoaHeaderBuilder::new(chain.clone(), manager.clone(), parent)
+                .with_snapshot(snapshot.clone())
+                .build()
+                .await;
+            snapshot = snapshot.apply(std::slice::from_ref(header.header())).unwrap();
+            harness.provider.insert_sidechain_header(header.clone());
+            parent = header;
+        }
+        provider.cache().on_reorg(parent.hash());
+
+        let latest = provider.snapshot_at_block(BlockId::latest()).unwrap();
+        assert_eq!(latest, snapshot);
+        assert!(!latest.signers.contains(&outsider));
+
+        // The orphaned branch is still served from the cache by hash
+        let orphaned = provider.cache().get(&harness.parent.hash()).unwrap();
+        assert!(orphaned.signers.contains(&outsider));
+    }
+
+    #[tokio::test]
+    async fn test_reorg_across_epoch_boundary() {
+        let (manager, outsider) = signers_and_outsider().await;
+        let config =
+            PoaConfig { period: 2, epoch: 6, signers: dev_signers(), ..Default::default() };
+        let chain = Arc::new(PoaChainSpec::new(create_dev_genesis_for(&config), config));
+        let mut harness = ChainHarness::new(chain.clone(), manager.clone());
+        for _ in 0..2 {
+            let header = harness.builder().build().await;
+            harness.extend(header).unwrap();
+        }
+        let (fork_point, fork_snapshot) = (harness.parent.clone(), harness.snapshot.clone());
+
+        // Branch A votes the outsider in before its checkpoint at block 6
+        for number in 3..=7 {
+            let mut builder = harness.builder();
+            if number < 5 {
+                builder = builder.with_vote(outsider, true);
+            }
+            let header = builder.build().await;
+            harness.extend(header).unwrap();
+        }
+        let (a_tip, a_snapshot) = (harness.parent.clone(), harness.snapshot.clone());
+        assert!(a_snapshot.signers.contains(&outsider));
+
+        // Branch B replaces the same heights, checkpoint included, without any votes
+        let mut b_headers = Vec::new();
+        let mut b_snapshot = fork_snapshot;
+        let mut parent = fork_point;
+        for _ in 3..=7 {
+            let header = PoaHeaderBuilder::new(chain.clone(), manager.clone(), parent)
+                .with_snapshot(b_snapshot.clone())
+                .build()
+                .await;
+            harness.consensus.validate_header(&header).unwrap();
+            b_snapshot = b_snapshot.apply(std::slice::from_ref(header.header())).unwrap();
+            harness.provider.insert_sidechain_header(header.clone());
+            harness.provider.insert_snapshot(b_snapshot.clone());
+            b_headers.push(header.clone());
+            parent = header;
+        }
+        let b_tip = parent;
+        assert!(!b_snapshot.signers.contains(&outsider));
+
+        // The checkpoint of the losing branch was persisted before the reorg
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap().with_checkpoint_interval(6);
+        let a_checkpoint = harness.provider.canonical_hash(6).unwrap();
+        store.save(&harness.provider.snapshot_at(&a_checkpoint).unwrap()).unwrap();
+
+        // Reconstruct from headers alone, with both branches known
+        let replica = headers_from(&harness, 0);
+        for header in &b_headers {
+            replica.insert_sidechain_header(header.clone());
+        }
+        let provider =
+            SnapshotProvider::new(chain.clone(), replica.clone()).with_store(store.clone());
+        provider.cache().on_commit(a_tip.hash());
+        assert_eq!(provider.snapshot_at_block(BlockId::latest()).unwrap(), a_snapshot);
+
+        for header in &b_headers {
+            harness.provider.insert_header(header.clone());
+            replica.insert_header(header.clone());
+        }
+        provider.cache().on_reorg(b_tip.hash());
+        assert_eq!(provider.snapshot_at_block(BlockId::latest()).unwrap(), b_snapshot);
+        assert_eq!(
+            provider.snapshot_at_block(BlockId::number(6)).unwrap().hash,
+            b_headers[3].hash()
+        );
+
+        // Validation after the reorg follows the signer set of the winning branch
+        let on_b = PoaHeaderBuilder::new(chain.clone(), manager.clone(), b_tip.clone())
+            .with_snapshot(b_snapshot.clone())
+            .with_signer(outsider)
+            .build()
+            .await;
+        let err = harness.consensus.validate_header(&on_b).unwrap_err();
+        assert!(err.to_string().contains("not authorized"), "{err}");
+        let on_b = PoaHeaderBuilder::new(chain.clone(), manager.clone(), b_tip)
+            .with_snapshot(b_snapshot.clone())
+            .build()
+            .await;
+        harness.consensus.validate_header(&on_b).unwrap();
+
+        // Restoring the winning branch ignores the orphaned checkpoint and replaces it
+        let genesis = Snapshot::new_genesis(&chain);
+        let restored = store
+            .restore(genesis, 7, |number| {
+                let hash = replica.canonical_hash(number)?;
+                replica.header_by_hash(&hash)
+            })
+            .unwrap();
+        assert_eq!(restored, b_snapshot);
+        assert_eq!(store.load(6).unwrap().hash, b_headers[3].hash());
+    }
+}
@@ -0,0 +1,1311 @@
+//! POA signer snapshots
+//!
+//! A [`Snapshot`] captures the authorization state of the chain at a given block: the set of
+//! authorized signers, which of them sealed the most recent blocks and the votes cast since the
+//! last checkpoint. Snapshots are advanced with [`Snapshot::apply`], following the clique voting
+//! rules:
+//!
+//! - every block carries one vote: the beneficiary is the proposed account and the nonce picks the
+//!   direction ([`NONCE_AUTH_VOTE`] to add, [`NONCE_DROP_VOTE`] to remove)
+//! - each signer has a single live vote per account; voting again replaces the previous vote
+//! - a proposal passes once strictly more than half of the current signers voted for it, and votes
+//!   cast by a signer are discarded as soon as it is deauthorized
+//! - all pending votes are discarded at every epoch (checkpoint) block
+
+use super::{checkpoint_signers, recover_header_signer, PoaConsensusError};
+use crate::chainspec::{PoaChainSpec, SignerSetSource};
+use alloy_consensus::Header;
+use alloy_primitives::{Address, B256, B64};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Epoch assumed for deserialized snapshots, which don't carry one (same as clique's default)
+const DEFAULT_EPOCH: u64 = 30_000;
+
+/// Nonce voting to authorize the beneficiary as a signer
+pub const NONCE_AUTH_VOTE: B64 = B64::new([0xff; 8]);
+/// Nonce voting to deauthorize the beneficiary
+pub const NONCE_DROP_VOTE: B64 = B64::ZERO;
+
+/// A single vote cast by an authorized signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    /// Signer that cast the vote
+    #[serde(serialize_with = "serialize_checksummed")]
+    pub signer: Address,
+    /// Block number the vote was cast in
+    pub block: u64,
+    /// Account being voted on
+    #[serde(serialize_with = "serialize_checksummed")]
+    pub address: Address,
+    /// Whether to authorize or deauthorize the account
+    pub authorize: bool,
+}
+
+/// Running tally of the votes on a single account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tally {
+    /// Whether the votes are to authorize or deauthorize the account
+    pub authorize: bool,
+    /// Number of votes in favor of the proposal
+    pub votes: usize,
+}
+
+/// Direction of a signer proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VoteDirection {
+    /// Add the account to the signer set
+    Authorize,
+    /// Remove the account from the signer set
+    Deauthorize,
+}
+
+impl From<bool> for VoteDirection {
+    fn from(authorize: bool) -> Self {
+        if authorize {
+            Self::Authorize
+        } else {
+            Self::Deauthorize
+        }
+    }
+}
+
+/// How close a pending proposal is to passing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalStatus {
+    /// Votes cast in the direction of the proposal
+    pub votes_for: usize,
+    /// Votes cast in the opposite direction
+    pub votes_against: usize,
+    /// Number of votes needed for the proposal to pass
+    pub threshold: usize,
+    /// What the proposal would do to the account
+    pub direction: VoteDirection,
+}
+
+/// Authorization state at a specific block.
+///
+/// Serializes to the JSON returned by geth's `clique_getSnapshot`:
+///
+/// ```json
+/// {
+///   "number": 42,
+///   "hash": "0x…",
+///   "signers": { "0x…": {} },
+///   "recents": { "41": "0x…" },
+///   "votes": [{ "signer": "0x…", "block": 41, "address": "0x…", "authorize": true }],
+///   "tally": { "0x…": { "authorize": true, "votes": 1 } }
+/// }
+/// ```
+///
+/// The epoch is not part of that format, so deserialized snapshots get clique's default epoch
+/// until [`Snapshot::with_epoch`] sets the right one (the same goes for
+/// [`contract_governed`](Self::contract_governed) and [`weights`](Self::weights)). Addresses are
+/// written checksummed and read in any case, so geth's lowercase output loads as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Number of blocks after which pending votes are reset, taken from
+    /// [`PoaChainSpec::epoch`]
+    pub epoch: u64,
+    /// Block number the snapshot was taken at
+    pub number: u64,
+    /// Block hash the snapshot was taken at
+    pub hash: B256,
+    /// Authorized signers at this block
+    pub signers: BTreeSet<Address>,
+    /// Signers of the most recent blocks, keyed by block number
+    pub recents: BTreeMap<u64, Address>,
+    /// Votes cast since the last checkpoint, in chronological order
+    pub votes: Vec<Vote>,
+    /// Current vote tally per proposed account
+    pub tally: HashMap<Address, Tally>,
+    /// Take the signer set from checkpoint headers instead of votes, see
+    /// [`SignerSetSource::Contract`]
+    pub contract_governed: bool,
+    /// Slot weights of weighted signers, taken from [`PoaChainSpec::signer_weights`]; signers
+    /// without an entry have weight one
+    pub weights: BTreeMap<Address, u32>,
+}
+
+impl Snapshot {
+    /// Create a snapshot with the given signers and no recent signers or votes
+    pub fn new(
+        epoch: u64,
+        number: u64,
+        hash: B256,
+        signers: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        Self {
+            epoch,
+            number,
+            hash,
+            signers: signers.into_iter().collect(),
+            recents: BTreeMap::new(),
+            votes: Vec::new(),
+            tally: HashMap::new(),
+            contract_governed: false,
+            weights: BTreeMap::new(),
+        }
+    }
+
+    /// Set the epoch length used when applying headers
+    pub const fn with_epoch(mut self, epoch: u64) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Take the signer set from checkpoint headers instead of votes
+    pub const fn with_contract_governance(mut self, contract_governed: bool) -> Self {
+        self.contract_governed = contract_governed;
+        self
+    }
+
+    /// Give signers a larger share of the in-turn slots, see [`schedule`](Self::schedule)
+    pub fn with_weights(mut self, weights: BTreeMap<Address, u32>) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Create the snapshot at the genesis block of the given chain
+    pub fn new_genesis(chain_spec: &PoaChainSpec) -> Self {
+        Self::new_checkpoint(
+            chain_spec,
+            0,
+            chain_spec.inner().genesis_hash(),
+            chain_spec.signers().iter().copied(),
+        )
+    }
+
+    /// Create the snapshot at a checkpoint of the given chain from its signer list
+    pub fn new_checkpoint(
+        chain_spec: &PoaChainSpec,
+        number: u64,
+        hash: B256,
+        signers: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        let contract_governed =
+            matches!(chain_spec.signer_set_source(), SignerSetSource::Contract { .. });
+        Self::new(chain_spec.epoch(), number, hash, signers)
+            .with_contract_governance(contract_governed)
+            .with_weights(chain_spec.signer_weights())
+    }
+
+    /// Number of consecutive blocks out of which a signer may only seal one
+    pub fn signer_limit(&self) -> u64 {
+        self.signers.len() as u64 / 2 + 1
+    }
+
+    /// Returns `true` if `signer` sealed one of the blocks that prevent it from sealing
+    /// `number`
+    pub fn is_recently_signed_at(&self, number: u64, signer: &Address) -> bool {
+        let limit = self.signer_limit();
+        self.recents.iter().any(|(seen, recent)| recent == signer && *seen + limit > number)
+    }
+
+    /// Returns `true` if `signer` may not seal the block following this snapshot
+    pub fn is_recently_signed(&self, signer: &Address) -> bool {
+        self.is_recently_signed_at(self.number + 1, signer)
+    }
+
+    /// Drop the recent signer that falls out of the window once block `number` is sealed.
+    ///
+    /// Called before recording the signer of `number`, and again when the signer set shrinks so
+    /// the window shrinks with it instead of keeping a stale entry around.
+    fn trim_recents(&mut self, number: u64) {
+        let limit = self.signer_limit();
+        if number >= limit {
+            self.recents.remove(&(number - limit));
+        }
+    }
+
+    /// Number of votes a proposal needs to pass: a strict majority of the current signers
+    pub fn threshold(&self) -> usize {
+        self.signers.len() / 2 + 1
+    }
+
+    /// Status of the pending proposal on `target`, or `None` if nobody voted on it
+    pub fn proposal_status(&self, target: &Address) -> Option<ProposalStatus> {
+        let tally = self.tally.get(target)?;
+        let votes_against = self
+            .votes
+            .iter()
+            .filter(|vote| vote.address == *target && vote.authorize != tally.authorize)
+            .count();
+        Some(ProposalStatus {
+            votes_for: tally.votes,
+            votes_against,
+            threshold: self.threshold(),
+            direction: tally.authorize.into(),
+        })
+    }
+
+    /// The signer whose turn it is to seal block `number`, or `None` if there are no signers.
+    ///
+    /// Follows clique: signers take turns in ascending address order. With weights, turns follow
+    /// the repeating [`schedule`](Self::schedule) instead.
+    pub fn inturn_signer(&self, number: u64) -> Option<Address> {
+        if self.weights.is_empty() {
+            let len = self.signers.len() as u64;
+            if len == 0 {
+                return None;
+            }
+            return self.signers.iter().nth((number % len) as usize).copied();
+        }
+
+        let schedule = self.schedule();
+        if schedule.is_empty() {
+            return None;
+        }
+        schedule.get((number % schedule.len() as u64) as usize).copied()
+    }
+
+    /// Repeating turn order of the current signers, block `number` falling to entry
+    /// `number % len`.
+    ///
+    /// Every signer gets as many slots as its weight, spread as evenly as possible so that heavy
+    /// signers are not kept out by the recent-signer limit: heavier signers pick first, each slot
+    /// going to the first free one at or after its ideal position. Ties are broken by ascending
+    /// address, so without weights this is plain ascending order.
+    pub fn schedule(&self) -> Vec<Address> {
+        let weight = |signer: &Address| self.weights.get(signer).copied().unwrap_or(1).max(1);
+        let total = self.signers.iter().map(|signer| weight(signer) as usize).sum::<usize>();
+
+        // Stable sort, so equal weights stay in ascending address order
+        let mut by_weight: Vec<_> = self.signers.iter().copied().collect();
+        by_weight.sort_by_key(|signer| std::cmp::Reverse(weight(signer)));
+
+        let mut slots = vec![None; total];
+        for signer in by_weight {
+            let weight = weight(&signer) as usize;
+            for turn in 0..weight {
+                let ideal = turn * total / weight;
+                let slot = (ideal..total)
+                    .chain(0..ideal)
+                    .find(|slot| slots[*slot].is_none())
+                    .expect("one slot per unit of weight");
+                slots[slot] = Some(signer);
+            }
+        }
+        slots.into_iter().flatten().collect()
+    }
+
+    /// Returns `true` if `signer` is the in-turn signer for block `number`
+    pub fn inturn(&self, number: u64, signer: &Address) -> bool {
+        self.inturn_signer(number).as_ref() == Some(signer)
+    }
+
+    /// Whether a vote by `signer` on `address` would still count: the proposal is not settled yet
+    /// and `signer` has no live vote for it in the same direction.
+    pub fn is_vote_pending(&self, signer: &Address, address: &Address, authorize: bool) -> bool {
+        self.valid_vote(address, authorize) &&
+            !self.votes.iter().any(|vote| {
+                vote.signer == *signer && vote.address == *address && vote.authorize == authorize
+            })
+    }
+
+    /// Returns `true` if a vote would change the state of `address`
+    fn valid_vote(&self, address: &Address, authorize: bool) -> bool {
+        self.signers.contains(address) != authorize
+    }
+
+    /// Add a vote to the tally. Returns `false` if the vote is meaningless.
+    fn cast(&mut self, address: Address, authorize: bool) -> bool {
+        if !self.valid_vote(&address, authorize) {
+            return false;
+        }
+        self.tally.entry(address).or_insert(Tally { authorize, votes: 0 }).votes += 1;
+        true
+    }
+
+    /// Remove a previously cast vote from the tally. Returns `false` if there was nothing to undo.
+    fn uncast(&mut self, address: Address, authorize: bool) -> bool {
+        let Some(tally) = self.tally.get_mut(&address) else { return false };
+        if tally.authorize != authorize {
+            return false;
+        }
+        if tally.votes > 1 {
+            tally.votes -= 1;
+        } else {
+            self.tally.remove(&address);
+        }
+        true
+    }
+
+    /// Apply a contiguous run of headers on top of this snapshot, returning the new snapshot.
+    ///
+    /// Every header must be sealed by a currently authorized signer that did not seal one of the
+    /// last [`signer_limit`](Self::signer_limit) blocks, and carry a valid vote nonce.
+    ///
+    /// A proposal reaching majority changes the signer set right away: the following header is
+    /// already checked against the new set and the resized recent-signer window. When the snapshot
+    /// is [`contract_governed`](Self::contract_governed), votes are ignored and each checkpoint's
+    /// signer list takes effect in the same way.
+    pub fn apply(&self, headers: &[Header]) -> Result<Self, PoaConsensusError> {
+        let Some(last) = headers.last() else { return Ok(self.clone()) };
+
+        // Headers must extend the snapshot one block at a time
+        for (expected, header) in (self.number + 1..).zip(headers) {
+            if header.number != expected {
+                return Err(PoaConsensusError::InvalidVotingChain { block: header.into() });
+            }
+        }
+
+        let mut snap = self.clone();
+        for header in headers {
+            let number = header.number;
+
+            // Pending votes don't survive a checkpoint, so stale proposals can't linger forever
+            if number.is_multiple_of(snap.epoch) {
+                snap.votes.clear();
+                snap.tally.clear();
+            }
+
+            // Let the signer of the oldest block in the window sign again
+            snap.trim_recents(number);
+
+            let signer = recover_header_signer(header)?;
+            if !snap.signers.contains(&signer) {
+                return Err(PoaConsensusError::UnauthorizedSigner { block: header.into(), signer });
+            }
+            if snap.recents.values().any(|recent| *recent == signer) {
+                return Err(PoaConsensusError::RecentlySigned { block: header.into(), signer });
+            }
+            snap.recents.insert(number, signer);
+
+            if snap.contract_governed {
+                if number.is_multiple_of(snap.epoch) {
+                    snap.signers = checkpoint_signers(header)?.into_iter().collect();
+                    snap.trim_recents(number);
+                }
+                continue;
+            }
+
+            let authorize = if header.nonce == NONCE_AUTH_VOTE {
+                true
+            } else if header.nonce == NONCE_DROP_VOTE {
+                false
+            } else {
+                return Err(PoaConsensusError::InvalidVote { block: header.into() });
+            };
+
+            let address = header.beneficiary;
+
+            // A signer has one live vote per account: a new vote replaces the previous one, so
+            // repeating a vote leaves the tally unchanged instead of inflating it
+            if let Some(pos) =
+                snap.votes.iter().position(|vote| vote.signer == signer && vote.address == address)
+            {
+                let previous = snap.votes.remove(pos);
+                snap.uncast(previous.address, previous.authorize);
+            }
+
+            if snap.cast(address, authorize) {
+                snap.votes.push(Vote { signer, block: number, address, authorize });
+            }
+
+            // Once a majority agrees, update the signer list
+            let threshold = snap.threshold();
+            if let Some(tally) = snap.tally.get(&address).copied().filter(|t| t.votes >= threshold)
+            {
+                if tally.authorize {
+                    snap.signers.insert(address);
+                } else {
+                    snap.signers.remove(&address);
+
+                    // The signer list shrunk, so the recent-signer window did too
+                    snap.trim_recents(number);
+
+                    // Votes cast by the deauthorized signer no longer count. Like in clique, other
+                    // proposals are not re-checked against the lowered threshold here: one that
+                    // now has enough of the remaining votes passes the next time it is voted on
+                    let (dropped, kept) = std::mem::take(&mut snap.votes)
+                        .into_iter()
+                        .partition::<Vec<_>, _>(|vote| vote.signer == address);
+                    snap.votes = kept;
+                    for vote in dropped {
+                        snap.uncast(vote.address, vote.authorize);
+                    }
+                }
+
+                // Votes on the account are settled
+                snap.votes.retain(|vote| vote.address != address);
+                snap.tally.remove(&address);
+            }
+        }
+
+        snap.number = last.number;
+        snap.hash = last.hash_slow();
+        Ok(snap)
+    }
+}
+
+/// Value of each entry in geth's signer map, which is a set encoded as `{address: {}}`
+#[derive(Serialize, Deserialize)]
+struct Empty {}
+
+/// Address that serializes in its EIP-55 checksummed form
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Checksummed(Address);
+
+impl Serialize for Checksummed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_checksummed(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Checksummed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Address::deserialize(deserializer).map(Self)
+    }
+}
+
+fn serialize_checksummed<S: Serializer>(
+    address: &Address,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&address.to_checksum(None))
+}
+
+/// Wire format of a [`Snapshot`], matching geth's clique snapshot JSON
+#[derive(Serialize, Deserialize)]
+struct GethSnapshot {
+    number: u64,
+    hash: B256,
+    #[expect(clippy::zero_sized_map_values)]
+    signers: BTreeMap<Checksummed, Empty>,
+    recents: BTreeMap<u64, Checksummed>,
+    // geth encodes a snapshot without votes as `null`
+    #[serde(default, deserialize_with = "null_as_empty")]
+    votes: Vec<Vote>,
+    tally: BTreeMap<Checksummed, Tally>,
+}
+
+fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vote>, D::Error> {
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl Serialize for Snapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GethSnapshot {
+            number: self.number,
+            hash: self.hash,
+            signers: self.signers.iter().map(|signer| (Checksummed(*signer), Empty {})).collect(),
+            recents: self
+                .recents
+                .iter()
+                .map(|(number, signer)| (*number, Checksummed(*signer)))
+                .collect(),
+            votes: self.votes.clone(),
+            tally: self
+                .tally
+                .iter()
+                .map(|(address, tally)| (Checksummed(*address), *tally))
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Snapshot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshot = GethSnapshot::deserialize(deserializer)?;
+        Ok(Self {
+            epoch: DEFAULT_EPOCH,
+            number: snapshot.number,
+            hash: snapshot.hash,
+            signers: snapshot.signers.into_keys().map(|signer| signer.0).collect(),
+            recents: snapshot
+                .recents
+                .into_iter()
+                .map(|(number, signer)| (number, signer.0))
+                .collect(),
+            votes: snapshot.votes,
+            tally: snapshot.tally.into_iter().map(|(address, tally)| (address.0, tally)).collect(),
+            contract_governed: false,
+            weights: BTreeMap::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::PoaConfig,
+        genesis::create_dev_genesis_for,
+        sealing::{seal_hash, EXTRA_SEAL_LENGTH, EXTRA_VANITY_LENGTH},
+        signer::signature_to_bytes,
+    };
+    use alloy_primitives::keccak256;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+
+    /// Deterministic key for a named test account
+    fn key(name: &str) -> PrivateKeySigner {
+        PrivateKeySigner::from_bytes(&keccak256(name.as_bytes())).unwrap()
+    }
+
+    fn addr(name: &str) -> Address {
+        key(name).address()
+    }
+
+    fn addrs(names: &[&str]) -> BTreeSet<Address> {
+        names.iter().map(|name| addr(name)).collect()
+    }
+
+    /// A block sealed by `signer` voting on `voted` (no vote if empty)
+    #[derive(Clone, Copy)]
+    struct TestVote {
+        signer: &'static str,
+        voted: &'static str,
+        auth: bool,
+    }
+
+    const fn vote(signer: &'static str, voted: &'static str, auth: bool) -> TestVote {
+        TestVote { signer, voted, auth }
+    }
+
+    const fn seal(signer: &'static str) -> TestVote {
+        TestVote { signer, voted: "", auth: false }
+    }
+
+    /// Seal a header with the key of the named account
+    fn sign(mut header: Header, signer: &str) -> Header {
+        header.extra_data = vec![0u8; EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH].into();
+        let signature = key(signer).sign_hash_sync(&seal_hash(&header)).unwrap();
+        let mut extra = header.extra_data.to_vec();
+        extra[EXTRA_VANITY_LENGTH..].copy_from_slice(&signature_to_bytes(&signature));
+        header.extra_data = extra.into();
+        header
+    }
+
+    /// Build the sealed headers for a sequence of votes starting at block 1
+    fn headers(votes: &[TestVote]) -> Vec<Header> {
+        let mut parent_hash = B256::ZERO;
+        votes
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let header = Header {
+                    number: i as u64 + 1,
+                    parent_hash,
+                    beneficiary: if v.voted.is_empty() { Address::ZERO } else { addr(v.voted) },
+                    nonce: if v.auth { NONCE_AUTH_VOTE } else { NONCE_DROP_VOTE },
+                    ..Default::default()
+                };
+                let header = sign(header, v.signer);
+                parent_hash = header.hash_slow();
+                header
+            })
+            .collect()
+    }
+
+    fn run(
+        epoch: u64,
+        signers: &[&str],
+        votes: &[TestVote],
+    ) -> Result<Snapshot, PoaConsensusError> {
+        Snapshot::new(epoch, 0, B256::ZERO, addrs(signers)).apply(&headers(votes))
+    }
+
+    #[test]
+    fn test_single_signer_no_votes() {
+        let snap = run(30_000, &["A"], &[seal("A")]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A"]));
+        assert_eq!(snap.number, 1);
+        assert!(snap.votes.is_empty());
+    }
+
+    #[test]
+    fn test_single_signer_adds_signer() {
+        let snap = run(30_000, &["A"], &[vote("A", "B", true)]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B"]));
+        assert!(snap.tally.is_empty());
+    }
+
+    #[test]
+    fn test_two_signers_need_both_votes_to_add() {
+        let snap = run(30_000, &["A", "B"], &[vote("A", "C", true)]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B"]));
+        assert_eq!(snap.tally[&addr("C")], Tally { authorize: true, votes: 1 });
+
+        let snap = run(30_000, &["A", "B"], &[vote("A", "C", true), vote("B", "C", true)]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "C"]));
+        assert!(snap.votes.is_empty());
+    }
+
+    #[test]
+    fn test_single_signer_drops_itself() {
+        let snap = run(30_000, &["A"], &[vote("A", "A", false)]).unwrap();
+        assert!(snap.signers.is_empty());
+    }
+
+    #[test]
+    fn test_two_signers_drop_one() {
+        let snap = run(30_000, &["A", "B"], &[vote("A", "B", false)]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B"]));
+
+        let snap =
+            run(30_000, &["A", "B"], &[vote("A", "B", false), vote("B", "B", false)]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A"]));
+    }
+
+    #[test]
+    fn test_four_signers_tie_does_not_pass() {
+        let votes = [vote("A", "E", true), vote("B", "E", true)];
+        let snap = run(30_000, &["A", "B", "C", "D"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "C", "D"]));
+
+        let votes = [vote("A", "E", true), vote("B", "E", true), vote("C", "E", true)];
+        let snap = run(30_000, &["A", "B", "C", "D"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "C", "D", "E"]));
+    }
+
+    #[test]
+    fn test_redundant_votes_are_ignored() {
+        // Authorizing an existing signer or dropping an outsider changes nothing
+        let snap =
+            run(30_000, &["A", "B"], &[vote("A", "B", true), vote("B", "C", false)]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B"]));
+        assert!(snap.votes.is_empty());
+        assert!(snap.tally.is_empty());
+    }
+
+    #[test]
+    fn test_votes_reset_at_epoch() {
+        let votes = [vote("A", "C", true), seal("B"), seal("A"), vote("B", "C", true)];
+
+        // Without a checkpoint in between the two votes pass the proposal
+        let snap = run(30_000, &["A", "B"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "C"]));
+
+        // Block 3 is a checkpoint and discards the first vote
+        let snap = run(3, &["A", "B"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B"]));
+        assert_eq!(snap.tally[&addr("C")].votes, 1);
+    }
+
+    #[test]
+    fn test_unauthorized_signer_rejected() {
+        let err = run(30_000, &["A"], &[seal("B")]).unwrap_err();
+        assert!(
+            matches!(err, PoaConsensusError::UnauthorizedSigner { signer, .. } if signer == addr("B"))
+        );
+    }
+
+    #[test]
+    fn test_recent_signer_rejected() {
+        let err = run(30_000, &["A", "B"], &[seal("A"), seal("A")]).unwrap_err();
+        assert!(
+            matches!(err, PoaConsensusError::RecentlySigned { block, .. } if block.number == 2)
+        );
+
+        // A single signer may seal every block
+        let snap = run(30_000, &["A"], &[seal("A"), seal("A"), seal("A")]).unwrap();
+        assert_eq!(snap.recents, BTreeMap::from([(3, addr("A"))]));
+    }
+
+    #[test]
+    fn test_invalid_vote_nonce_rejected() {
+        let header = sign(Header { number: 1, nonce: B64::new([1; 8]), ..Default::default() }, "A");
+        let err = Snapshot::new(30_000, 0, B256::ZERO, addrs(&["A"])).apply(&[header]).unwrap_err();
+        assert!(matches!(err, PoaConsensusError::InvalidVote { .. }));
+    }
+
+    #[test]
+    fn test_non_contiguous_headers_rejected() {
+        let mut headers = headers(&[seal("A"), seal("B")]);
+        headers[1].number = 3;
+        let err =
+            Snapshot::new(30_000, 0, B256::ZERO, addrs(&["A", "B"])).apply(&headers).unwrap_err();
+        assert!(
+            matches!(err, PoaConsensusError::InvalidVotingChain { block } if block.number == 3)
+        );
+    }
+
+    #[test]
+    fn test_apply_updates_number_and_hash() {
+        let snap = Snapshot::new(30_000, 0, B256::ZERO, addrs(&["A", "B"]));
+        assert_eq!(snap.apply(&[]).unwrap(), snap);
+
+        let headers = headers(&[seal("A"), seal("B")]);
+        let next = snap.apply(&headers).unwrap();
+        assert_eq!(next.number, 2);
+        assert_eq!(next.hash, headers[1].hash_slow());
+        // The original snapshot is left untouched
+        assert_eq!(snap.number, 0);
+    }
+
+    /// A voting scenario ported from go-ethereum's `clique/snapshot_test.go`
+    struct CliqueTest {
+        name: &'static str,
+        epoch: u64,
+        signers: &'static [&'static str],
+        votes: &'static [TestVote],
+        /// Final signer set, or the [`PoaConsensusError::kind`] of the expected failure
+        result: Result<&'static [&'static str], &'static str>,
+    }
+
+    const fn add(signer: &'static str, voted: &'static str) -> TestVote {
+        vote(signer, voted, true)
+    }
+
+    const fn drop(signer: &'static str, voted: &'static str) -> TestVote {
+        vote(signer, voted, false)
+    }
+
+    const CLIQUE_TESTS: &[CliqueTest] = &[
+        CliqueTest {
+            name: "single signer, no votes cast",
+            epoch: 30_000,
+            signers: &["A"],
+            votes: &[seal("A")],
+            result: Ok(&["A"]),
+        },
+        CliqueTest {
+            name: "single signer, voting to add two others (only accept first, second needs 2 votes)",
+            epoch: 30_000,
+            signers: &["A"],
+            votes: &[add("A", "B"), seal("B"), add("A", "C")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "two signers, voting to add three others (only accept first two, third needs 3 votes already)",
+            epoch: 30_000,
+            signers: &["A", "B"],
+            votes: &[
+                add("A", "C"),
+                add("B", "C"),
+                add("A", "D"),
+                add("B", "D"),
+                seal("C"),
+                add("A", "E"),
+                add("B", "E"),
+            ],
+            result: Ok(&["A", "B", "C", "D"]),
+        },
+        CliqueTest {
+            name: "single signer, dropping itself",
+            epoch: 30_000,
+            signers: &["A"],
+            votes: &[drop("A", "A")],
+            result: Ok(&[]),
+        },
+        CliqueTest {
+            name: "two signers, actually needing mutual consent to drop either of them (not fulfilled)",
+            epoch: 30_000,
+            signers: &["A", "B"],
+            votes: &[drop("A", "B")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "two signers, actually needing mutual consent to drop either of them (fulfilled)",
+            epoch: 30_000,
+            signers: &["A", "B"],
+            votes: &[drop("A", "B"), drop("B", "B")],
+            result: Ok(&["A"]),
+        },
+        CliqueTest {
+            name: "three signers, two of them deciding to drop the third",
+            epoch: 30_000,
+            signers: &["A", "B", "C"],
+            votes: &[drop("A", "C"), drop("B", "C")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "four signers, consensus of two not being enough to drop anyone",
+            epoch: 30_000,
+            signers: &["A", "B", "C", "D"],
+            votes: &[drop("A", "C"), drop("B", "C")],
+            result: Ok(&["A", "B", "C", "D"]),
+        },
+        CliqueTest {
+            name: "four signers, consensus of three already being enough to drop someone",
+            epoch: 30_000,
+            signers: &["A", "B", "C", "D"],
+            votes: &[drop("A", "D"), drop("B", "D"), drop("C", "D")],
+            result: Ok(&["A", "B", "C"]),
+        },
+        CliqueTest {
+            name: "authorizations are counted once per signer per target",
+            epoch: 30_000,
+            signers: &["A", "B"],
+            votes: &[add("A", "C"), seal("B"), add("A", "C"), seal("B"), add("A", "C")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "authorizing multiple accounts concurrently is permitted",
+            epoch: 30_000,
+            signers: &["A", "B"],
+            votes: &[
+                add("A", "C"),
+                seal("B"),
+                add("A", "D"),
+                seal("B"),
+                seal("A"),
+                add("B", "D"),
+                seal("A"),
+                add("B", "C"),
+            ],
+            result: Ok(&["A", "B", "C", "D"]),
+        },
+        CliqueTest {
+            name: "deauthorizations are counted once per signer per target",
+            epoch: 30_000,
+            signers: &["A", "B"],
+            votes: &[drop("A", "B"), seal("B"), drop("A", "B"), seal("B"), drop("A", "B")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "deauthorizing multiple accounts concurrently is permitted",
+            epoch: 30_000,
+            signers: &["A", "B", "C", "D"],
+            votes: &[
+                drop("A", "C"),
+                seal("B"),
+                seal("C"),
+                drop("A", "D"),
+                seal("B"),
+                seal("C"),
+                seal("A"),
+                drop("B", "D"),
+                drop("C", "D"),
+                seal("A"),
+                drop("B", "C"),
+            ],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "votes from deauthorized signers are discarded immediately (deauth votes)",
+            epoch: 30_000,
+            signers: &["A", "B", "C"],
+            votes: &[drop("C", "B"), drop("A", "C"), drop("B", "C"), drop("A", "B")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "votes from deauthorized signers are discarded immediately (auth votes)",
+            epoch: 30_000,
+            signers: &["A", "B", "C"],
+            votes: &[add("C", "D"), drop("A", "C"), drop("B", "C"), add("A", "D")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "cascading changes are not allowed, only the account being voted on may change",
+            epoch: 30_000,
+            signers: &["A", "B", "C", "D"],
+            votes: &[
+                drop("A", "C"),
+                seal("B"),
+                seal("C"),
+                drop("A", "D"),
+                drop("B", "C"),
+                seal("C"),
+                seal("A"),
+                drop("B", "D"),
+                drop("C", "D"),
+            ],
+            result: Ok(&["A", "B", "C"]),
+        },
+        CliqueTest {
+            name: "changes reaching consensus out of bounds (via a deauth) execute on touch",
+            epoch: 30_000,
+            signers: &["A", "B", "C", "D"],
+            votes: &[
+                drop("A", "C"),
+                seal("B"),
+                seal("C"),
+                drop("A", "D"),
+                drop("B", "C"),
+                seal("C"),
+                seal("A"),
+                drop("B", "D"),
+                drop("C", "D"),
+                seal("A"),
+                add("C", "C"),
+            ],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "changes reaching consensus out of bounds (via a deauth) may go out of consensus on first touch",
+            epoch: 30_000,
+            signers: &["A", "B", "C", "D"],
+            votes: &[
+                drop("A", "C"),
+                seal("B"),
+                seal("C"),
+                drop("A", "D"),
+                drop("B", "C"),
+                seal("C"),
+                seal("A"),
+                drop("B", "D"),
+                drop("C", "D"),
+                seal("A"),
+                add("B", "C"),
+            ],
+            result: Ok(&["A", "B", "C"]),
+        },
+        CliqueTest {
+            // A signer is quickly added, removed and then re-added while one of the original
+            // voters is dropped. A stale cached vote would interfere with the final outcome.
+            name: "pending votes don't survive authorization status changes",
+            epoch: 30_000,
+            signers: &["A", "B", "C", "D", "E"],
+            votes: &[
+                add("A", "F"), // Authorize F, 3 votes needed
+                add("B", "F"),
+                add("C", "F"),
+                drop("D", "F"), // Deauthorize F, 4 votes needed (leave A's previous vote)
+                drop("E", "F"),
+                drop("B", "F"),
+                drop("C", "F"),
+                add("D", "F"), // Almost authorize F, 2/3 votes needed
+                add("E", "F"),
+                drop("B", "A"), // Deauthorize A, 3 votes needed
+                drop("C", "A"),
+                drop("D", "A"),
+                add("B", "F"), // Finish authorizing F, 3/3 votes needed
+            ],
+            result: Ok(&["B", "C", "D", "E", "F"]),
+        },
+        CliqueTest {
+            name: "epoch transitions reset all votes to allow chain checkpointing",
+            epoch: 3,
+            signers: &["A", "B"],
+            votes: &[add("A", "C"), seal("B"), seal("A"), add("B", "C")],
+            result: Ok(&["A", "B"]),
+        },
+        CliqueTest {
+            name: "an unauthorized signer should not be able to sign blocks",
+            epoch: 30_000,
+            signers: &["A"],
+            votes: &[seal("B")],
+            result: Err("unauthorized_signer"),
+        },
+        CliqueTest {
+            name: "an authorized signer that signed recently should not be able to sign again",
+            epoch: 30_000,
+            signers: &["A", "B"],
+            votes: &[seal("A"), seal("A")],
+            result: Err("recently_signed"),
+        },
+        CliqueTest {
+            name: "recent signatures should not reset on checkpoint blocks",
+            epoch: 3,
+            signers: &["A", "B", "C"],
+            votes: &[seal("A"), seal("B"), seal("A"), seal("A")],
+            result: Err("recently_signed"),
+        },
+    ];
+
+    #[test]
+    fn test_clique_vectors() {
+        for test in CLIQUE_TESTS {
+            match (run(test.epoch, test.signers, test.votes), test.result) {
+                (Ok(snap), Ok(expected)) => {
+                    assert_eq!(snap.signers, addrs(expected), "{}", test.name)
+                }
+                (Err(err), Err(kind)) => assert_eq!(err.kind(), kind, "{}", test.name),
+                (got, expected) => panic!("{}: got {got:?}, expected {expected:?}", test.name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_recents_survive_batch_boundary() {
+        // Rinkeby consensus split (go-ethereum#17593): the checkpoint block ends one import batch
+        // and the recent signer window must carry over into the next one
+        let headers = headers(&[seal("A"), seal("B"), seal("A"), seal("A")]);
+        let snap = Snapshot::new(3, 0, B256::ZERO, addrs(&["A", "B", "C"]));
+
+        let snap = snap.apply(&headers[..3]).unwrap();
+        let err = snap.apply(&headers[3..]).unwrap_err();
+        assert!(
+            matches!(err, PoaConsensusError::RecentlySigned { block, .. } if block.number == 4)
+        );
+    }
+
+    #[test]
+    fn test_new_vote_replaces_previous_one() {
+        let votes = [add("A", "C"), seal("B"), drop("A", "C")];
+        let snap = run(30_000, &["A", "B"], &votes).unwrap();
+        // Dropping an outsider is not a valid vote, so only the uncast remains
+        assert!(snap.votes.is_empty());
+        assert!(snap.tally.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_vote_does_not_inflate_tally() {
+        let snap = Snapshot::new(30_000, 0, B256::ZERO, addrs(&["A", "B", "C"]));
+        let headers = headers(&[add("A", "X"), seal("B"), add("A", "X"), seal("C"), add("A", "X")]);
+
+        let mut current = snap;
+        for header in &headers {
+            current = current.apply(std::slice::from_ref(header)).unwrap();
+            if header.beneficiary == addr("X") {
+                assert_eq!(current.tally[&addr("X")], Tally { authorize: true, votes: 1 });
+                assert_eq!(current.votes.len(), 1);
+                assert_eq!(current.votes[0].block, header.number);
+            }
+        }
+
+        // Two of three signers are needed, A alone can never get X in
+        assert!(!current.signers.contains(&addr("X")));
+    }
+
+    #[test]
+    fn test_changing_vote_direction_uncasts_previous_vote() {
+        // A proposes dropping B, then changes its mind (an invalid vote since B is a signer)
+        let votes = [drop("A", "B"), seal("B"), add("A", "B"), seal("B"), drop("C", "B")];
+        let snap = run(30_000, &["A", "B", "C"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "C"]));
+        assert_eq!(snap.tally[&addr("B")], Tally { authorize: false, votes: 1 });
+    }
+
+    #[test]
+    fn test_deauthorized_signer_votes_are_purged() {
+        let votes = [add("C", "D"), drop("A", "C"), drop("B", "C")];
+        let snap = run(30_000, &["A", "B", "C"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B"]));
+        assert!(!snap.tally.contains_key(&addr("D")));
+        assert!(snap.votes.iter().all(|vote| vote.signer != addr("C")));
+    }
+
+    #[test]
+    fn test_purged_vote_changes_concurrent_proposal() {
+        // A and C back E while A, B and D vote C out. Once C is gone the threshold drops to two,
+        // which E would meet if C's vote still counted
+        let votes = [add("A", "E"), add("C", "E"), drop("B", "C"), drop("D", "C"), drop("A", "C")];
+        let snap = run(30_000, &["A", "B", "C", "D"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "D"]));
+        let status = snap.proposal_status(&addr("E")).unwrap();
+        assert_eq!((status.votes_for, status.threshold), (1, 2));
+
+        // A touching the proposal again only replaces its own vote, so E stays out
+        let votes = [&votes[..], &[seal("B"), add("A", "E")]].concat();
+        let snap = run(30_000, &["A", "B", "C", "D"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "D"]));
+        assert_eq!(snap.tally[&addr("E")].votes, 1);
+
+        // A second remaining signer is still needed
+        let votes = [&votes[..], &[add("D", "E")]].concat();
+        let snap = run(30_000, &["A", "B", "C", "D"], &votes).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "D", "E"]));
+    }
+
+    #[test]
+    fn test_epoch_block_clears_votes_but_keeps_signers() {
+        let config =
+            PoaConfig { epoch: 4, signers: vec![addr("A"), addr("B")], ..Default::default() };
+        let chain_spec = PoaChainSpec::new(create_dev_genesis_for(&config), config);
+        let genesis = Snapshot::new_genesis(&chain_spec);
+        assert_eq!(genesis.epoch, 4);
+
+        let headers = headers(&[seal("A"), seal("B"), add("A", "C"), seal("B")]);
+
+        // Vote cast at epoch - 1 is pending
+        let before = genesis.apply(&headers[..3]).unwrap();
+        assert_eq!(before.votes.len(), 1);
+        assert_eq!(before.tally[&addr("C")], Tally { authorize: true, votes: 1 });
+
+        // The epoch block discards it
+        let after = before.apply(&headers[3..]).unwrap();
+        assert!(after.votes.is_empty());
+        assert!(after.tally.is_empty());
+        assert_eq!(after.signers, before.signers);
+    }
+
+    #[test]
+    fn test_threshold() {
+        for (signers, threshold) in [(1, 1), (2, 2), (3, 2), (4, 3), (5, 3), (6, 4)] {
+            let names = ["A", "B", "C", "D", "E", "F"];
+            let snap = Snapshot::new(30_000, 0, B256::ZERO, addrs(&names[..signers]));
+            assert_eq!(snap.threshold(), threshold, "{signers} signers");
+        }
+    }
+
+    #[test]
+    fn test_proposal_status() {
+        let votes = [add("A", "F"), add("B", "F"), drop("C", "E")];
+        let snap = run(30_000, &["A", "B", "C", "D", "E"], &votes).unwrap();
+        assert_eq!(
+            snap.proposal_status(&addr("F")),
+            Some(ProposalStatus {
+                votes_for: 2,
+                votes_against: 0,
+                threshold: 3,
+                direction: VoteDirection::Authorize
+            })
+        );
+        assert_eq!(
+            snap.proposal_status(&addr("E")),
+            Some(ProposalStatus {
+                votes_for: 1,
+                votes_against: 0,
+                threshold: 3,
+                direction: VoteDirection::Deauthorize
+            })
+        );
+        assert_eq!(snap.proposal_status(&addr("G")), None);
+    }
+
+    #[test]
+    fn test_proposal_status_mixed_votes() {
+        let mut snap = Snapshot::new(30_000, 0, B256::ZERO, addrs(&["A", "B", "C", "D"]));
+        let target = addr("X");
+        for (signer, authorize) in [("A", true), ("B", true), ("C", false)] {
+            snap.votes.push(Vote { signer: addr(signer), block: 1, address: target, authorize });
+        }
+        snap.tally.insert(target, Tally { authorize: true, votes: 2 });
+
+        let status = snap.proposal_status(&target).unwrap();
+        assert_eq!(status.votes_for, 2);
+        assert_eq!(status.votes_against, 1);
+        assert_eq!(status.threshold, 3);
+        assert_eq!(status.direction, VoteDirection::Authorize);
+    }
+
+    /// Apply blocks sealed by `sealers` one by one, checking the window after each block
+    fn assert_window(signers: &[&str], sealers: &[&'static str]) -> Snapshot {
+        let votes: Vec<_> = sealers.iter().map(|name| seal(name)).collect();
+        let headers = headers(&votes);
+        let mut snap = Snapshot::new(30_000, 0, B256::ZERO, addrs(signers));
+        let limit = snap.signer_limit();
+
+        for (i, header) in headers.iter().enumerate() {
+            snap = snap.apply(std::slice::from_ref(header)).unwrap();
+            assert!(snap.recents.len() as u64 <= limit);
+
+            // Exactly the last `limit - 1` sealers are blocked from the next block
+            let window = &sealers[(i + 2).saturating_sub(limit as usize)..=i];
+            for name in signers {
+                assert_eq!(
+                    snap.is_recently_signed(&addr(name)),
+                    window.contains(name),
+                    "{name} after block {}",
+                    i + 1
+                );
+            }
+        }
+        snap
+    }
+
+    #[test]
+    fn test_recents_window_two_signers() {
+        // limit 2: signers must alternate
+        let snap = assert_window(&["A", "B"], &["A", "B", "A", "B"]);
+        assert_eq!(snap.recents, BTreeMap::from([(3, addr("A")), (4, addr("B"))]));
+        assert!(run(30_000, &["A", "B"], &[seal("A"), seal("B"), seal("B")]).is_err());
+    }
+
+    #[test]
+    fn test_recents_window_three_signers() {
+        // limit 2: a signer may seal again after one other block
+        assert_window(&["A", "B", "C"], &["A", "B", "A", "C", "B", "A"]);
+        assert!(run(30_000, &["A", "B", "C"], &[seal("A"), seal("A")]).is_err());
+    }
+
+    #[test]
+    fn test_recents_window_seven_signers() {
+        // limit 4: a signer must wait for three other blocks
+        let names = ["A", "B", "C", "D", "E", "F", "G"];
+        let snap = assert_window(&names, &["A", "B", "C", "D", "A", "E", "B", "F", "G"]);
+        assert_eq!(snap.recents.len(), 4);
+        assert!(!snap.is_recently_signed(&addr("A")));
+        let err = run(30_000, &names, &[seal("A"), seal("B"), seal("C"), seal("A")]).unwrap_err();
+        assert!(
+            matches!(err, PoaConsensusError::RecentlySigned { block, .. } if block.number == 4)
+        );
+    }
+
+    #[test]
+    fn test_recents_shrink_with_signer_set() {
+        // Dropping D shrinks the window from 3 to 2 blocks, so A may seal block 4
+        let votes = [drop("A", "D"), drop("B", "D"), drop("C", "D"), seal("A")];
+        let snap = run(30_000, &["A", "B", "C", "D"], &votes[..3]).unwrap();
+        assert_eq!(snap.signers, addrs(&["A", "B", "C"]));
+        assert_eq!(snap.recents, BTreeMap::from([(2, addr("B")), (3, addr("C"))]));
+        assert!(!snap.is_recently_signed(&addr("A")));
+
+        let snap = run(30_000, &["A", "B", "C", "D"], &votes).unwrap();
+        assert_eq!(snap.recents, BTreeMap::from([(3, addr("C")), (4, addr("A"))]));
+    }
+
+    /// Snapshot matching `testdata/snapshot_round_trip.json`
+    fn golden_snapshot() -> Snapshot {
+        let votes = [add("A", "D"), drop("B", "C"), add("C", "D")];
+        let mut snap = run(30_000, &["A", "B", "C", "E"], &votes).unwrap();
+        snap.hash = B256::repeat_byte(0x11);
+        snap
+    }
+
+    /// The fixture was written by this serializer, not captured from geth: it pins the field
+    /// layout and guards against format drift, while geth's lowercase output is covered by
+    /// `test_geth_json_lowercase_and_null_votes`
+    #[test]
+    fn test_snapshot_json_round_trip() {
+        let golden: serde_json::Value =
+            serde_json::from_str(include_str!("../../testdata/snapshot_round_trip.json")).unwrap();
+        let snap = golden_snapshot();
+        assert_eq!(serde_json::to_value(&snap).unwrap(), golden);
+
+        let decoded: Snapshot = serde_json::from_value(golden).unwrap();
+        assert_eq!(decoded, snap);
+    }
+
+    #[test]
+    fn test_geth_json_lowercase_and_null_votes() {
+        // Shape of a fresh geth snapshot: lowercase addresses and no votes yet
+        let json = format!(
+            r#"{{"number":0,"hash":"{:#x}","signers":{{"{:#x}":{{}}}},"recents":{{}},"votes":null,"tally":{{}}}}"#,
+            B256::ZERO,
+            addr("A"),
+        );
+        let snap: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snap, Snapshot::new(DEFAULT_EPOCH, 0, B256::ZERO, [addr("A")]));
+        assert_eq!(snap.with_epoch(10).epoch, 10);
+    }
+
+    #[test]
+    fn test_inturn() {
+        let snap = Snapshot::new(30_000, 0, B256::ZERO, addrs(&["A", "B", "C"]));
+        let sorted: Vec<_> = snap.signers.iter().copied().collect();
+        for number in 0..9 {
+            for (offset, signer) in sorted.iter().enumerate() {
+                assert_eq!(snap.inturn(number, signer), number as usize % 3 == offset);
+            }
+        }
+        assert!(!snap.inturn(0, &addr("D")));
+        assert_eq!(snap.inturn_signer(4), Some(sorted[1]));
+        assert_eq!(Snapshot::new(30_000, 0, B256::ZERO, []).inturn_signer(4), None);
+    }
+
+    #[test]
+    fn test_weighted_schedule() {
+        let snap = Snapshot::new(30_000, 0, B256::ZERO, addrs(&["A", "B", "C"]));
+        let sorted: Vec<_> = snap.signers.iter().copied().collect();
+        assert_eq!(snap.schedule(), sorted);
+
+        // The heavy signer never gets two slots in a row, even across the wrap-around
+        let (a, b, c) = (sorted[0], sorted[1], sorted[2]);
+        let weighted = snap.clone().with_weights(BTreeMap::from([(b, 2)]));
+        assert_eq!(weighted.schedule(), vec![b, a, b, c]);
+        assert_eq!(weighted.inturn_signer(5), Some(a));
+        assert_eq!(weighted.inturn_signer(7), Some(c));
+
+        // Weights of signers outside the set don't take slots
+        let unknown = snap.with_weights(BTreeMap::from([(addr("D"), 5)]));
+        assert_eq!(unknown.schedule(), sorted);
+    }
+
+    #[test]
+    fn test_new_genesis() {
+        let chain_spec = PoaChainSpec::dev_chain();
+        let snap = Snapshot::new_genesis(&chain_spec);
+        assert_eq!(snap.number, 0);
+        assert_eq!(snap.hash, chain_spec.inner().genesis_hash());
+        assert_eq!(snap.epoch, chain_spec.epoch());
+        assert_eq!(snap.signers.len(), chain_spec.signers().len());
+    }
+}
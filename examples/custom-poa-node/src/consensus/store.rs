@@ -0,0 +1,514 @@
+//! On-disk persistence of POA snapshots
+//!
+//! Rebuilding a [`Snapshot`] from genesis means replaying every header of the chain, which is far
+//! too slow for long chains. The [`SnapshotStore`] keeps checkpoint snapshots as JSON files under
+//! `<datadir>/poa/snapshots/` so a restarted node only replays the headers after the most recent
+//! checkpoint.
+//!
+//! For disaster recovery the latest snapshot can also be [exported](SnapshotStore::export) to a
+//! single self-checking file and [imported](SnapshotStore::import) into a fresh data directory.
+
+use super::{snapshot::Snapshot, PoaConsensusError};
+use alloy_consensus::Header;
+use alloy_primitives::{keccak256, Address, Bytes, B256};
+use reth_tracing::tracing::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// Default number of blocks between persisted snapshots
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 1024;
+
+/// Version of the snapshot export format written by [`SnapshotStore::export`]
+pub const SNAPSHOT_EXPORT_VERSION: u32 = 1;
+
+/// Errors from loading, storing or replaying snapshots
+#[derive(Debug, Error)]
+pub enum SnapshotStoreError {
+    /// Filesystem access failed
+    #[error("snapshot store I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// Snapshot could not be encoded
+    #[error("failed to encode snapshot: {0}")]
+    Encode(#[from] serde_json::Error),
+
+    /// A header needed to replay the chain is not available
+    #[error("missing header #{0} while replaying snapshot")]
+    MissingHeader(u64),
+
+    /// A replayed header was rejected by the snapshot
+    #[error(transparent)]
+    Consensus(#[from] PoaConsensusError),
+
+    /// There is no persisted snapshot to export
+    #[error("no persisted snapshot to export")]
+    NothingToExport,
+
+    /// The export was written by an incompatible version
+    #[error("unsupported snapshot export version {0}")]
+    UnsupportedVersion(u32),
+
+    /// The export payload does not hash to the embedded checksum
+    #[error("snapshot export checksum mismatch: expected {expected}, got {got}")]
+    ChecksumMismatch {
+        /// Checksum embedded in the export
+        expected: B256,
+        /// Keccak of the payload actually found
+        got: B256,
+    },
+
+    /// The block an export refers to differs from the snapshot inside it
+    #[error("snapshot export is labelled #{number} ({hash}) but holds another block")]
+    InconsistentExport {
+        /// Block number the export is labelled with
+        number: u64,
+        /// Block hash the export is labelled with
+        hash: B256,
+    },
+
+    /// The block of an imported snapshot is not part of the local chain
+    #[error("header #{number} ({hash}) of the imported snapshot is not in the database")]
+    UnknownHeader {
+        /// Block number of the snapshot
+        number: u64,
+        /// Block hash of the snapshot
+        hash: B256,
+    },
+}
+
+/// File format of a persisted snapshot: geth's snapshot JSON next to the settings it lacks
+#[derive(Serialize, Deserialize)]
+struct StoredSnapshot<S = Snapshot> {
+    epoch: u64,
+    #[serde(default)]
+    contract_governed: bool,
+    #[serde(default)]
+    weights: BTreeMap<Address, u32>,
+    snapshot: S,
+}
+
+impl StoredSnapshot {
+    /// Encode a snapshot in the on-disk format
+    fn encode(snapshot: &Snapshot) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(&StoredSnapshot {
+            epoch: snapshot.epoch,
+            contract_governed: snapshot.contract_governed,
+            weights: snapshot.weights.clone(),
+            snapshot,
+        })
+    }
+
+    /// Decode a snapshot from the on-disk format
+    fn decode(bytes: &[u8]) -> Result<Snapshot, serde_json::Error> {
+        let stored = serde_json::from_slice::<Self>(bytes)?;
+        Ok(stored
+            .snapshot
+            .with_epoch(stored.epoch)
+            .with_contract_governance(stored.contract_governed)
+            .with_weights(stored.weights))
+    }
+}
+
+/// File format of a snapshot export.
+///
+/// The payload is a persisted snapshot file, kept as opaque bytes so that the checksum covers
+/// exactly what was written.
+#[derive(Serialize, Deserialize)]
+struct SnapshotExport {
+    version: u32,
+    number: u64,
+    hash: B256,
+    checksum: B256,
+    payload: Bytes,
+}
+
+/// Stores checkpoint snapshots in the node's data directory
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    /// Directory holding one file per persisted snapshot
+    dir: PathBuf,
+    /// Number of blocks between persisted snapshots
+    checkpoint_interval: u64,
+}
+
+impl SnapshotStore {
+    /// Open (and create if needed) the snapshot directory inside `datadir`
+    pub fn open(datadir: impl AsRef<Path>) -> Result<Self, SnapshotStoreError> {
+        let dir = datadir.as_ref().join("poa").join("snapshots");
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL })
+    }
+
+    /// Persist a snapshot every `interval` blocks instead of the default
+    pub fn with_checkpoint_interval(mut self, interval: u64) -> Self {
+        self.checkpoint_interval = interval.max(1);
+        self
+    }
+
+    /// Directory the snapshots are stored in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the file holding the snapshot at `number`
+    fn path(&self, number: u64) -> PathBuf {
+        // Zero padded so that lexical and numeric order agree
+        self.dir.join(format!("{number:020}.json"))
+    }
+
+    /// Write a snapshot to disk unconditionally.
+    ///
+    /// Call this on clean shutdown so the node restarts from its latest state. The file is
+    /// written next to its final location and then renamed, so a crash never leaves a partially
+    /// written snapshot behind.
+    pub fn save(&self, snapshot: &Snapshot) -> Result<(), SnapshotStoreError> {
+        write_atomic(&self.path(snapshot.number), &StoredSnapshot::encode(snapshot)?)
+    }
+
+    /// Write the snapshot if it sits on a checkpoint. Returns `true` if it was written.
+    pub fn save_if_checkpoint(&self, snapshot: &Snapshot) -> Result<bool, SnapshotStoreError> {
+        if snapshot.number == 0 || !snapshot.number.is_multiple_of(self.checkpoint_interval) {
+            return Ok(false);
+        }
+        self.save(snapshot)?;
+        Ok(true)
+    }
+
+    /// Block numbers of all persisted snapshots, in ascending order
+    fn stored_numbers(&self) -> Result<Vec<u64>, SnapshotStoreError> {
+        let mut numbers = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") &&
+                let Some(number) =
+                    path.file_stem().and_then(|stem| stem.to_str()?.parse::<u64>().ok())
+            {
+                numbers.push(number);
+            }
+        }
+        numbers.sort_unstable();
+        Ok(numbers)
+    }
+
+    /// Load the snapshot persisted at exactly `number`.
+    ///
+    /// Returns `None` if there is none or the file is unreadable, in which case a warning is
+    /// logged.
+    pub fn load(&self, number: u64) -> Option<Snapshot> {
+        let path = self.path(number);
+        if !path.exists() {
+            return None;
+        }
+        match fs::read(&path)
+            .map_err(SnapshotStoreError::from)
+            .and_then(|bytes| Ok(StoredSnapshot::decode(&bytes)?))
+        {
+            Ok(snapshot) if snapshot.number == number => return Some(snapshot),
+            Ok(snapshot) => warn!(
+                target: "poa::snapshot",
+                ?path,
+                found = snapshot.number,
+                "Snapshot file holds the wrong block, skipping"
+            ),
+            Err(err) => {
+                warn!(target: "poa::snapshot", ?path, %err, "Failed to read snapshot, skipping")
+            }
+        }
+        None
+    }
+
+    /// Load the most recent readable snapshot at or below `number`.
+    ///
+    /// Files that fail to decode are skipped with a warning, falling back to the previous
+    /// checkpoint.
+    pub fn load_latest(&self, number: u64) -> Result<Option<Snapshot>, SnapshotStoreError> {
+        Ok(self
+            .stored_numbers()?
+            .into_iter()
+            .rev()
+            .filter(|stored| *stored <= number)
+            .find_map(|stored| self.load(stored)))
+    }
+
+    /// Restore the snapshot at `number` of the chain served by `header_by_number`.
+    ///
+    /// Starts from the most recent persisted snapshot at or below `number` (or `genesis` if there
+    /// is none) and replays the remaining headers, persisting any checkpoints passed on the way.
+    /// Persisted snapshots whose hash no longer matches the header at their height were orphaned
+    /// by a reorg and are skipped.
+    pub fn restore(
+        &self,
+        genesis: Snapshot,
+        number: u64,
+        mut header_by_number: impl FnMut(u64) -> Option<Header>,
+    ) -> Result<Snapshot, SnapshotStoreError> {
+        let persisted = self
+            .stored_numbers()?
+            .into_iter()
+            .rev()
+            .filter(|stored| *stored <= number)
+            .filter_map(|stored| self.load(stored))
+            .find(|snapshot| {
+                let canonical = header_by_number(snapshot.number)
+                    .is_some_and(|header| header.hash_slow() == snapshot.hash);
+                if !canonical {
+                    warn!(
+                        target: "poa::snapshot",
+                        number = snapshot.number,
+                        hash = %snapshot.hash,
+                        "Persisted snapshot is not on the canonical chain, skipping"
+                    );
+                }
+                canonical
+            });
+        let mut snapshot = persisted.unwrap_or(genesis);
+
+        while snapshot.number < number {
+            let next = snapshot.number + 1;
+            let header = header_by_number(next).ok_or(SnapshotStoreError::MissingHeader(next))?;
+            snapshot = snapshot.apply(std::slice::from_ref(&header))?;
+            self.save_if_checkpoint(&snapshot)?;
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Export the most recent persisted snapshot to a single file at `path`.
+    ///
+    /// The file records the block the snapshot was taken at and a keccak checksum of the snapshot,
+    /// see [`import`](Self::import). Returns the exported snapshot.
+    pub fn export(&self, path: impl AsRef<Path>) -> Result<Snapshot, SnapshotStoreError> {
+        let snapshot = self.load_latest(u64::MAX)?.ok_or(SnapshotStoreError::NothingToExport)?;
+        let payload = StoredSnapshot::encode(&snapshot)?;
+        let export = SnapshotExport {
+            version: SNAPSHOT_EXPORT_VERSION,
+            number: snapshot.number,
+            hash: snapshot.hash,
+            checksum: keccak256(&payload),
+            payload: payload.into(),
+        };
+        write_atomic(path.as_ref(), &serde_json::to_vec_pretty(&export)?)?;
+        Ok(snapshot)
+    }
+
+    /// Import a snapshot exported with [`export`](Self::export) and persist it in this store.
+    ///
+    /// The snapshot is only trusted if its checksum matches and `header_by_number` returns the
+    /// header it was taken at, with the same hash.
+    pub fn import(
+        &self,
+        path: impl AsRef<Path>,
+        header_by_number: impl FnOnce(u64) -> Option<Header>,
+    ) -> Result<Snapshot, SnapshotStoreError> {
+        let export: SnapshotExport = serde_json::from_slice(&fs::read(path)?)?;
+        if export.version != SNAPSHOT_EXPORT_VERSION {
+            return Err(SnapshotStoreError::UnsupportedVersion(export.version));
+        }
+
+        let checksum = keccak256(&export.payload);
+        if checksum != export.checksum {
+            return Err(SnapshotStoreError::ChecksumMismatch {
+                expected: export.checksum,
+                got: checksum,
+            });
+        }
+
+        let (number, hash) = (export.number, export.hash);
+        let snapshot = StoredSnapshot::decode(&export.payload)?;
+        if snapshot.number != number || snapshot.hash != hash {
+            return Err(SnapshotStoreError::InconsistentExport { number, hash });
+        }
+
+        if header_by_number(number).is_none_or(|header| header.hash_slow() != hash) {
+            return Err(SnapshotStoreError::UnknownHeader { number, hash });
+        }
+
+        self.save(&snapshot)?;
+        Ok(snapshot)
+    }
+}
+
+/// Write `contents` next to `path` and rename it into place, so a crash never leaves a partially
+/// written file behind
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), SnapshotStoreError> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(tmp, path)?;
+    // The rename only survives a crash once the directory entry is on disk too
+    #[cfg(unix)]
+    {
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        fs::File::open(dir.unwrap_or(Path::new(".")))?.sync_all()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::PoaChainSpec, consensus::test_utils::build_chain, signer::dev::setup_dev_signers,
+    };
+    use std::sync::Arc;
+
+    /// Dev chain headers and the genesis snapshot they build on
+    async fn dev_headers(n: u64) -> (Snapshot, Vec<Header>) {
+        let chain_spec = Arc::new(PoaChainSpec::dev_chain());
+        let signer_manager = setup_dev_signers().await;
+        let headers = build_chain(chain_spec.clone(), signer_manager, n)
+            .await
+            .into_iter()
+            .map(|header| header.into_header())
+            .collect();
+        (Snapshot::new_genesis(&chain_spec), headers)
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_roundtrip() {
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        let (genesis, headers) = dev_headers(3).await;
+
+        let snapshot = genesis.apply(&headers).unwrap();
+        store.save(&snapshot).unwrap();
+
+        assert_eq!(store.load_latest(3).unwrap(), Some(snapshot.clone()));
+        assert_eq!(store.load_latest(10).unwrap(), Some(snapshot));
+        assert_eq!(store.load_latest(2).unwrap(), None);
+    }
+
+    #[test]
+    fn test_settings_survive_roundtrip() {
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        let snapshot = Snapshot::new(8, 5, Default::default(), [Address::repeat_byte(1)])
+            .with_contract_governance(true)
+            .with_weights(BTreeMap::from([(Address::repeat_byte(1), 3)]));
+
+        store.save(&snapshot).unwrap();
+        assert_eq!(store.load(5), Some(snapshot));
+    }
+
+    #[tokio::test]
+    async fn test_restart_resumes_from_persisted_snapshot() {
+        let datadir = tempfile::tempdir().unwrap();
+        let (genesis, headers) = dev_headers(10).await;
+
+        // First run: follow the chain block by block, persisting every 4 blocks
+        let store = SnapshotStore::open(datadir.path()).unwrap().with_checkpoint_interval(4);
+        let mut expected = genesis.clone();
+        for header in &headers {
+            expected = expected.apply(std::slice::from_ref(header)).unwrap();
+            store.save_if_checkpoint(&expected).unwrap();
+        }
+        drop(store);
+
+        // Restart: only the headers from the block 8 checkpoint on may be requested
+        let store = SnapshotStore::open(datadir.path()).unwrap().with_checkpoint_interval(4);
+        let mut requested = Vec::new();
+        let restored = store
+            .restore(genesis, 10, |number| {
+                requested.push(number);
+                headers.get(number as usize - 1).cloned()
+            })
+            .unwrap();
+
+        // Block 8 itself is only looked up to check the checkpoint is still canonical
+        assert_eq!(requested, vec![8, 9, 10]);
+        assert_eq!(restored, expected);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_snapshot_falls_back_to_previous_checkpoint() {
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap().with_checkpoint_interval(4);
+        let (genesis, headers) = dev_headers(8).await;
+
+        let at_4 = genesis.apply(&headers[..4]).unwrap();
+        let at_8 = at_4.apply(&headers[4..]).unwrap();
+        assert!(store.save_if_checkpoint(&at_4).unwrap());
+        assert!(store.save_if_checkpoint(&at_8).unwrap());
+
+        fs::write(store.path(8), b"not a snapshot").unwrap();
+        assert_eq!(store.load_latest(8).unwrap(), Some(at_4));
+
+        // Restoring replays from the intact checkpoint and rewrites the broken one
+        let restored =
+            store.restore(genesis, 8, |number| headers.get(number as usize - 1).cloned()).unwrap();
+        assert_eq!(restored, at_8);
+        assert_eq!(store.load_latest(8).unwrap(), Some(at_8));
+    }
+
+    /// Store in a fresh data directory holding the snapshot after `headers`, exported to a file
+    fn exported(headers: &[Header], genesis: Snapshot) -> (tempfile::TempDir, Snapshot) {
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        store.save(&genesis.apply(headers).unwrap()).unwrap();
+        let snapshot = store.export(datadir.path().join("export.json")).unwrap();
+        (datadir, snapshot)
+    }
+
+    #[tokio::test]
+    async fn test_export_import_roundtrip() {
+        let (genesis, headers) = dev_headers(6).await;
+        let (exported_dir, exported) = exported(&headers, genesis);
+        let file = exported_dir.path().join("export.json");
+        assert_eq!(exported.number, 6);
+
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        let imported =
+            store.import(&file, |number| headers.get(number as usize - 1).cloned()).unwrap();
+        assert_eq!(imported, exported);
+        assert_eq!(store.load(6), Some(exported));
+
+        // A database without that block, or with another one at its height, rejects it
+        assert!(matches!(
+            store.import(&file, |_| None),
+            Err(SnapshotStoreError::UnknownHeader { number: 6, .. })
+        ));
+        assert!(matches!(
+            store.import(&file, |_| headers.first().cloned()),
+            Err(SnapshotStoreError::UnknownHeader { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_export_is_rejected() {
+        let (genesis, headers) = dev_headers(6).await;
+        let (exported_dir, _) = exported(&headers, genesis);
+        let file = exported_dir.path().join("export.json");
+
+        let mut bytes = fs::read(&file).unwrap();
+        let payload = bytes.windows(9).position(|window| window == b"\"payload\"").unwrap();
+        bytes[payload + 40] ^= 1;
+        fs::write(&file, bytes).unwrap();
+
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        assert!(store.import(&file, |number| headers.get(number as usize - 1).cloned()).is_err());
+        assert_eq!(store.load(6), None);
+    }
+
+    #[test]
+    fn test_only_checkpoints_are_saved() {
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        let mut snapshot = Snapshot::new(30_000, 0, Default::default(), []);
+
+        for (number, saved) in [(0, false), (1, false), (1023, false), (1024, true), (2048, true)] {
+            snapshot.number = number;
+            assert_eq!(store.save_if_checkpoint(&snapshot).unwrap(), saved, "block {number}");
+        }
+        assert_eq!(store.stored_numbers().unwrap(), vec![1024, 2048]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Test utilities for POA consensus
+//!
+//! Provides a [`PoaHeaderBuilder`] that assembles correctly formed, signed POA headers so tests
+//! don't have to hand-build extra data and seals, and a [`ChainHarness`] that grows a validated
+//! chain on top of it.
+
+use super::{
+    provider::InMemoryPoaStateProvider,
+    snapshot::{Snapshot, NONCE_AUTH_VOTE, NONCE_DROP_VOTE},
+    PoaConsensus,
+};
+use crate::{
+    chainspec::PoaChainSpec,
+    sealing::EXTRA_VANITY_LENGTH,
+    signer::{BlockSealer, ProposalsMap, SignerManager},
+};
+use alloy_consensus::Header;
+use alloy_primitives::{Address, Bytes, U256};
+use reth_consensus::{ConsensusError, HeaderValidator};
+use reth_primitives_traits::SealedHeader;
+use reth_revm::{
+    bytecode::Bytecode,
+    db::{CacheDB, EmptyDB},
+    primitives::KECCAK_EMPTY,
+    state::AccountInfo,
+};
+use std::sync::Arc;
+
+/// Builds a signed child header on top of a parent.
+///
+/// By default the header is sealed by the in-turn signer of the parent snapshot (the genesis
+/// snapshot unless one is given, and the first signer allowed to seal if the in-turn one signed
+/// too recently), stamped `parent + period`, inherits the parent gas limit, casts
+/// no vote (unless given proposals to vote on) and carries the signer list in its extra data on
+/// epoch blocks. Each of these can be overridden to produce invalid headers for negative tests.
+#[derive(Debug)]
+pub struct PoaHeaderBuilder {
+    chain_spec: Arc<PoaChainSpec>,
+    signer_manager: Arc<SignerManager>,
+    parent: SealedHeader<Header>,
+    snapshot: Option<Snapshot>,
+    signer: Option<Address>,
+    vote: Option<(Address, bool)>,
+    proposals: Option<Arc<ProposalsMap>>,
+    checkpoint_signers: Option<Vec<Address>>,
+    difficulty: Option<u64>,
+    timestamp: Option<u64>,
+    gas_limit: Option<u64>,
+    blob_gas_used: Option<u64>,
+    extra_data: Option<Bytes>,
+}
+
+impl PoaHeaderBuilder {
+    /// Create a builder for the child of `parent`
+    pub const fn new(
+        chain_spec: Arc<PoaChainSpec>,
+        signer_manager: Arc<SignerManager>,
+        parent: SealedHeader<Header>,
+    ) -> Self {
+        Self {
+            chain_spec,
+            signer_manager,
+            parent,
+            snapshot: None,
+            signer: None,
+            vote: None,
+            proposals: None,
+            checkpoint_signers: None,
+            difficulty: None,
+            timestamp: None,
+            gas_limit: None,
+            blob_gas_used: None,
+            extra_data: None,
+        }
+    }
+
+    /// Determine the in-turn signer from this snapshot of the parent block
+    pub fn with_snapshot(mut self, snapshot: Snapshot) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Vote to authorize or deauthorize `address`
+    pub const fn with_vote(mut self, address: Address, authorize: bool) -> Self {
+        self.vote = Some((address, authorize));
+        self
+    }
+
+    /// Vote on the next pending proposal of this map, like a sealing node would, unless an
+    /// explicit vote is set
+    pub fn with_proposals(mut self, proposals: Arc<ProposalsMap>) -> Self {
+        self.proposals = Some(proposals);
+        self
+    }
+
+    /// Write these signers into an epoch block's extra data instead of the snapshot's
+    pub fn with_checkpoint_signers(mut self, signers: Vec<Address>) -> Self {
+        self.checkpoint_signers = Some(signers);
+        self
+    }
+
+    /// Seal with the given signer instead of the in-turn one
+    pub const fn with_signer(mut self, signer: Address) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Override the difficulty
+    pub const fn with_difficulty(mut self, difficulty: u64) -> Self {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    /// Override the timestamp
+    pub const fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Override the gas limit
+    pub const fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Claim this much blob gas used
+    pub const fn with_blob_gas_used(mut self, blob_gas_used: u64) -> Self {
+        self.blob_gas_used = Some(blob_gas_used);
+        self
+    }
+
+    /// Override the unsigned extra data (the seal is still appended when signing)
+    pub fn with_extra_data(mut self, extra_data: impl Into<Bytes>) -> Self {
+        self.extra_data = Some(extra_data.into());
+        self
+    }
+
+    /// Build the unsigned header and seal it with the selected signer
+    pub async fn build(self) -> SealedHeader<Header> {
+        let number = self.parent.number + 1;
+        let snapshot = self.snapshot.unwrap_or_else(|| Snapshot::new_genesis(&self.chain_spec));
+        let in_turn = snapshot.inturn_signer(number);
+        let signer = self.signer.unwrap_or_else(|| default_signer(&snapshot, number));
+        let difficulty =
+            self.difficulty.unwrap_or_else(|| if in_turn == Some(signer) { 1 } else { 2 });
+
+        let extra_data = self.extra_data.unwrap_or_else(|| {
+            let is_epoch_block = PoaConsensus::new(self.chain_spec.clone()).is_epoch_block(number);
+            let epoch_signers = is_epoch_block.then(|| {
+                self.checkpoint_signers
+                    .unwrap_or_else(|| snapshot.signers.iter().copied().collect())
+            });
+            BlockSealer::build_extra_data(&[0u8; EXTRA_VANITY_LENGTH], epoch_signers.as_deref())
+        });
+
+        let timestamp = self.timestamp.unwrap_or_else(|| {
+            self.chain_spec.next_block_timestamp(self.parent.timestamp, self.parent.timestamp)
+        });
+        let mut header = Header {
+            parent_hash: self.parent.hash(),
+            number,
+            timestamp,
+            gas_limit: self
+                .gas_limit
+                .unwrap_or_else(|| self.chain_spec.next_block_gas_limit(self.parent.gas_limit)),
+            base_fee_per_gas: self.chain_spec.next_block_base_fee(&self.parent, timestamp),
+            blob_gas_used: self.blob_gas_used,
+            difficulty: U256::from(difficulty),
+            beneficiary: self.vote.map(|(address, _)| address).unwrap_or_default(),
+            nonce: match self.vote {
+                Some((_, true)) => NONCE_AUTH_VOTE,
+                _ => NONCE_DROP_VOTE,
+            },
+            extra_data,
+            ..Default::default()
+        };
+
+        let mut sealer = BlockSealer::new(self.signer_manager);
+        if let Some(proposals) = self.proposals {
+            sealer = sealer.with_proposals(proposals);
+        }
+        if self.vote.is_none() {
+            sealer.prepare_vote(&mut header, &snapshot, &signer);
+        }
+
+        let sealed = sealer
+            .seal_header(header, &signer)
+            .await
+            .expect("signer manager holds the selected key");
+        SealedHeader::seal_slow(sealed)
+    }
+}
+
+/// Signer sealing block `number` on top of `snapshot`: the in-turn one, or the first signer
+/// allowed to seal if the in-turn one signed too recently (like a real sealer stepping aside)
+pub fn default_signer(snapshot: &Snapshot, number: u64) -> Address {
+    let may_seal = |signer: &Address| !snapshot.is_recently_signed_at(number, signer);
+    snapshot
+        .inturn_signer(number)
+        .filter(may_seal)
+        .or_else(|| snapshot.signers.iter().copied().find(may_seal))
+        .expect("chain has at least one signer")
+}
+
+/// Build a valid chain of `n_blocks` sealed headers on top of the chain's genesis, each sealed by
+/// the in-turn signer
+pub async fn build_chain(
+    chain_spec: Arc<PoaChainSpec>,
+    signer_manager: Arc<SignerManager>,
+    n_blocks: u64,
+) -> Vec<SealedHeader<Header>> {
+    let mut parent = SealedHeader::seal_slow(chain_spec.inner().genesis_header().clone());
+    let mut snapshot = Snapshot::new_genesis(&chain_spec);
+    let mut headers = Vec::with_capacity(n_blocks as usize);
+
+    for _ in 0..n_blocks {
+        let header = PoaHeaderBuilder::new(chain_spec.clone(), signer_manager.clone(), parent)
+            .with_snapshot(snapshot.clone())
+            .build()
+            .await;
+        snapshot = snapshot
+            .apply(std::slice::from_ref(header.header()))
+            .expect("in-turn signers never sign too recently");
+        headers.push(header.clone());
+        parent = header;
+    }
+
+    headers
+}
+
+/// EVM state holding the chain's genesis allocation
+pub fn genesis_state(chain_spec: &PoaChainSpec) -> CacheDB<EmptyDB> {
+    let mut db = CacheDB::new(EmptyDB::default());
+    for (address, account) in &chain_spec.inner().genesis.alloc {
+        let nonce = account.nonce.unwrap_or_default();
+        let info = match &account.code {
+            Some(code) => {
+                let code = Bytecode::new_raw(code.clone());
+                AccountInfo::new(account.balance, nonce, code.hash_slow(), code)
+            }
+            None => AccountInfo::new(account.balance, nonce, KECCAK_EMPTY, Bytecode::new()),
+        };
+        db.insert_account_info(*address, info);
+        for (slot, value) in account.storage.iter().flatten() {
+            db.insert_account_storage(*address, (*slot).into(), (*value).into()).unwrap();
+        }
+    }
+    db
+}
+
+/// Grows a chain block by block, validating each header against its parent snapshot.
+///
+/// Every accepted header and the snapshot after it are recorded in an in-memory state provider
+/// shared with the harness' consensus instance.
+#[derive(Debug)]
+pub struct ChainHarness {
+    /// Chain being extended
+    pub chain: Arc<PoaChainSpec>,
+    /// Keys of the signers sealing the chain
+    pub manager: Arc<SignerManager>,
+    /// Headers and snapshots of the chain so far
+    pub provider: Arc<InMemoryPoaStateProvider>,
+    /// Consensus validating every new header
+    pub consensus: PoaConsensus,
+    /// Current tip
+    pub parent: SealedHeader<Header>,
+    /// Snapshot at the current tip
+    pub snapshot: Snapshot,
+}
+
+impl ChainHarness {
+    /// Start at the chain's genesis
+    pub fn new(chain: Arc<PoaChainSpec>, manager: Arc<SignerManager>) -> Self {
+        let provider = Arc::new(InMemoryPoaStateProvider::new());
+        let genesis = SealedHeader::seal_slow(chain.inner().genesis_header().clone());
+        let snapshot = Snapshot::new_genesis(&chain);
+        provider.insert_header(genesis.clone());
+        provider.insert_snapshot(snapshot.clone());
+        Self {
+            consensus: PoaConsensus::new(chain.clone()).with_provider(provider.clone()),
+            parent: genesis,
+            chain,
+            manager,
+            provider,
+            snapshot,
+        }
+    }
+
+    /// Builder for the next block, in turn according to the current snapshot
+    pub fn builder(&self) -> PoaHeaderBuilder {
+        PoaHeaderBuilder::new(self.chain.clone(), self.manager.clone(), self.parent.clone())
+            .with_snapshot(self.snapshot.clone())
+    }
+
+    /// Validate the header and make it the new tip
+    pub fn extend(&mut self, header: SealedHeader<Header>) -> Result<(), ConsensusError> {
+        self.consensus.validate_header(&header)?;
+        self.snapshot = self.snapshot.apply(std::slice::from_ref(header.header()))?;
+        self.provider.insert_header(header.clone());
+        self.provider.insert_snapshot(self.snapshot.clone());
+        self.parent = header;
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+//! Ethereum deposit contract predeploy
+//!
+//! Chains that may move to proof of stake later, or whose tooling expects the deposit contract,
+//! can predeploy it at genesis through
+//! [`GenesisConfig::with_deposit_contract`](crate::genesis::GenesisConfig::with_deposit_contract)
+//! and announce it with [`PoaConfig::deposit_contract`](crate::chainspec::PoaConfig).
+
+use crate::genesis::StorageBuilder;
+use alloy_primitives::{b256, hex, B256};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Topic of the `DepositEvent` log the contract emits
+pub const DEPOSIT_EVENT_TOPIC: B256 =
+    b256!("649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5");
+
+/// Runtime code of the mainnet deposit contract
+pub const DEPOSIT_CONTRACT_CODE: &[u8] = &hex!(
+    "60806040526004361061003f5760003560e01c806301ffc9a71461004457806322895118146100a4578063621fd1"
+    "30146101ba578063c5f2892f14610244575b600080fd5b34801561005057600080fd5b5061009060048036036020"
+    "81101561006757600080fd5b50357fffffffff000000000000000000000000000000000000000000000000000000"
+    "001661026b565b604080519115158252519081900360200190f35b6101b8600480360360808110156100ba576000"
+    "80fd5b8101906020810181356401000000008111156100d557600080fd5b8201836020820111156100e757600080"
+    "fd5b8035906020019184600183028401116401000000008311171561010957600080fd5b91939092909160208101"
+    "903564010000000081111561012757600080fd5b82018360208201111561013957600080fd5b8035906020019184"
+    "600183028401116401000000008311171561015b57600080fd5b9193909290916020810190356401000000008111"
+    "1561017957600080fd5b82018360208201111561018b57600080fd5b803590602001918460018302840111640100"
+    "000000831117156101ad57600080fd5b919350915035610304565b005b3480156101c657600080fd5b506101cf61"
+    "10b5565b6040805160208082528351818301528351919283929083019185019080838360005b8381101561020957"
+    "81810151838201526020016101f1565b50505050905090810190601f168015610236578082038051600183602003"
+    "6101000a031916815260200191505b509250505060405180910390f35b34801561025057600080fd5b5061025961"
+    "10c7565b60408051918252519081900360200190f35b60007fffffffff0000000000000000000000000000000000"
+    "000000000000000000000082167f01ffc9a700000000000000000000000000000000000000000000000000000000"
+    "14806102fe57507fffffffff0000000000000000000000000000000000000000000000000000000082167f856409"
+    "0700000000000000000000000000000000000000000000000000000000145b92915050565b6030861461035d5760"
+    "40517f08c379a0000000000000000000000000000000000000000000000000000000008152600401808060200182"
+    "8103825260268152602001806118056026913960400191505060405180910390fd5b602084146103b6576040517f"
+    "08c379a0000000000000000000000000000000000000000000000000000000008152600401808060200182810382"
+    "52603681526020018061179c6036913960400191505060405180910390fd5b6060821461040f576040517f08c379"
+    "a0000000000000000000000000000000000000000000000000000000008152600401808060200182810382526029"
+    "8152602001806118786029913960400191505060405180910390fd5b670de0b6b3a7640000341015610470576040"
+    "517f08c379a000000000000000000000000000000000000000000000000000000000815260040180806020018281"
+    "03825260268152602001806118526026913960400191505060405180910390fd5b633b9aca003406156104cd5760"
+    "40517f08c379a0000000000000000000000000000000000000000000000000000000008152600401808060200182"
+    "8103825260338152602001806117d26033913960400191505060405180910390fd5b633b9aca00340467ffffffff"
+    "ffffffff811115610535576040517f08c379a0000000000000000000000000000000000000000000000000000000"
+    "00815260040180806020018281038252602781526020018061182b6027913960400191505060405180910390fd5b"
+    "6060610540826114ba565b90507f649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5"
+    "89898989858a8a6105756020546114ba565b6040805160a080825281018990529081906020820190820160608301"
+    "6080840160c085018e8e80828437600083820152601f017fffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffffffffffffffffe01690910187810386528c815260200190508c8c808284376000838201819052601f909101"
+    "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe01690920188810386528c518152"
+    "8c51602091820193918e019250908190849084905b83811015610648578181015183820152602001610630565b50"
+    "505050905090810190601f1680156106755780820380516001836020036101000a031916815260200191505b5086"
+    "810383528881526020018989808284376000838201819052601f9091017fffffffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffe0169092018881038452895181528951602091820193918b01925090819084"
+    "9084905b838110156106ef5781810151838201526020016106d7565b50505050905090810190601f16801561071c"
+    "5780820380516001836020036101000a031916815260200191505b509d5050505050505050505050505050604051"
+    "80910390a1600060028a8a600060801b604051602001808484808284377fffffffffffffffffffffffffffffffff"
+    "0000000000000000000000000000000090941691909301908152604080517fffffffffffffffffffffffffffffff"
+    "fffffffffffffffffffffffffffffffff08184030181526010909201908190528151919550935083925060208501"
+    "91508083835b602083106107fc57805182527fffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffffffe090920191602091820191016107bf565b51815160209384036101000a7fffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffffffffffff0180199092169116179052604051919093019450919250508083"
+    "0381855afa158015610859573d6000803e3d6000fd5b5050506040513d602081101561086e57600080fd5b505190"
+    "5060006002806108846040848a8c6116fe565b604051602001808383808284378083019250505092505050604051"
+    "6020818303038152906040526040518082805190602001908083835b602083106108f857805182527fffffffffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffe090920191602091820191016108bb565b518151"
+    "60209384036101000a7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff01801990"
+    "921691161790526040519190930194509192505080830381855afa158015610955573d6000803e3d6000fd5b5050"
+    "506040513d602081101561096a57600080fd5b5051600261097b896040818d6116fe565b60405160009060200180"
+    "84848082843791909101928352505060408051808303815260209283019182905280519094509092508291840190"
+    "8083835b602083106109f457805182527fffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffe090920191602091820191016109b7565b51815160209384036101000a7fffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffffffff01801990921691161790526040519190930194509192505080830381"
+    "855afa158015610a51573d6000803e3d6000fd5b5050506040513d6020811015610a6657600080fd5b5051604080"
+    "51602081810194909452808201929092528051808303820181526060909201908190528151919290918291840190"
+    "8083835b60208310610ada57805182527fffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffe09092019160209182019101610a9d565b51815160209384036101000a7fffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffffffff01801990921691161790526040519190930194509192505080830381"
+    "855afa158015610b37573d6000803e3d6000fd5b5050506040513d6020811015610b4c57600080fd5b5051604080"
+    "5160208101858152929350600092600292839287928f928f92018383808284378083019250505093505050506040"
+    "516020818303038152906040526040518082805190602001908083835b60208310610bd957805182527fffffffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffe09092019160209182019101610b9c565b5181"
+    "5160209384036101000a7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff018019"
+    "90921691161790526040519190930194509192505080830381855afa158015610c36573d6000803e3d6000fd5b50"
+    "50506040513d6020811015610c4b57600080fd5b5051604051865160029188916000918891602091820191829190"
+    "8601908083835b60208310610ca957805182527fffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffffffffe09092019160209182019101610c6c565b6001836020036101000a0380198251168184511680821785"
+    "525050505050509050018367ffffffffffffffff191667ffffffffffffffff191681526018018281526020019350"
+    "5050506040516020818303038152906040526040518082805190602001908083835b60208310610d4e5780518252"
+    "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe09092019160209182019101610d"
+    "11565b51815160209384036101000a7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffff01801990921691161790526040519190930194509192505080830381855afa158015610dab573d6000803e3d"
+    "6000fd5b5050506040513d6020811015610dc057600080fd5b505160408051602081810194909452808201929092"
+    "5280518083038201815260609092019081905281519192909182918401908083835b60208310610e345780518252"
+    "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe09092019160209182019101610d"
+    "f7565b51815160209384036101000a7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffff01801990921691161790526040519190930194509192505080830381855afa158015610e91573d6000803e3d"
+    "6000fd5b5050506040513d6020811015610ea657600080fd5b50519050858114610f02576040517f08c379a00000"
+    "00000000000000000000000000000000000000000000000000008152600401808060200182810382526054815260"
+    "2001806117486054913960600191505060405180910390fd5b60205463ffffffff11610f60576040517f08c379a0"
+    "00000000000000000000000000000000000000000000000000000000815260040180806020018281038252602181"
+    "52602001806117276021913960400191505060405180910390fd5b602080546001019081905560005b6020811015"
+    "6110a9578160011660011415610fa0578260008260208110610f9157fe5b0155506110ac95505050505050565b60"
+    "0260008260208110610faf57fe5b0154846040516020018083815260200182815260200192505050604051602081"
+    "8303038152906040526040518082805190602001908083835b6020831061102557805182527fffffffffffffffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffe09092019160209182019101610fe8565b518151602093"
+    "84036101000a7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff01801990921691"
+    "161790526040519190930194509192505080830381855afa158015611082573d6000803e3d6000fd5b5050506040"
+    "513d602081101561109757600080fd5b50519250600282049150600101610f6e565b50fe5b50505050505050565b"
+    "60606110c26020546114ba565b905090565b6020546000908190815b60208110156112f057816001166001141561"
+    "11e6576002600082602081106110f557fe5b01548460405160200180838152602001828152602001925050506040"
+    "516020818303038152906040526040518082805190602001908083835b6020831061116b57805182527fffffffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffe0909201916020918201910161112e565b5181"
+    "5160209384036101000a7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff018019"
+    "90921691161790526040519190930194509192505080830381855afa1580156111c8573d6000803e3d6000fd5b50"
+    "50506040513d60208110156111dd57600080fd5b505192506112e2565b600283602183602081106111f657fe5b01"
+    "54604051602001808381526020018281526020019250505060405160208183030381529060405260405180828051"
+    "90602001908083835b6020831061126b57805182527fffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffffffffffffe0909201916020918201910161122e565b51815160209384036101000a7fffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffff0180199092169116179052604051919093019450919250"
+    "5080830381855afa1580156112c8573d6000803e3d6000fd5b5050506040513d60208110156112dd57600080fd5b"
+    "505192505b6002820491506001016110d1565b506002826112ff6020546114ba565b600060401b60405160200180"
+    "84815260200183805190602001908083835b6020831061135a57805182527fffffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffffe0909201916020918201910161131d565b51815160209384036101000a7f"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff01801990921691161790527fffff"
+    "ffffffffffffffffffffffffffffffffffffffffffff000000000000000095909516920191825250604080518083"
+    "037ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff8018152601890920190819052"
+    "815191955093508392850191508083835b6020831061143f57805182527fffffffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffe09092019160209182019101611402565b51815160209384036101000a7fff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff018019909216911617905260405191"
+    "90930194509192505080830381855afa15801561149c573d6000803e3d6000fd5b5050506040513d602081101561"
+    "14b157600080fd5b50519250505090565b6040805160088082528183019092526060916020820181803683370190"
+    "5050905060c082901b8060071a60f81b826000815181106114f457fe5b60200101907effffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffffffffffff1916908160001a9053508060061a60f81b826001815181106115"
+    "3757fe5b60200101907effffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1916908160"
+    "001a9053508060051a60f81b8260028151811061157a57fe5b60200101907effffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffffffff1916908160001a9053508060041a60f81b826003815181106115bd57fe5b"
+    "60200101907effffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1916908160001a9053"
+    "508060031a60f81b8260048151811061160057fe5b60200101907effffffffffffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffff1916908160001a9053508060021a60f81b8260058151811061164357fe5b60200101"
+    "907effffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1916908160001a905350806001"
+    "1a60f81b8260068151811061168657fe5b60200101907effffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffffffffffffff1916908160001a9053508060001a60f81b826007815181106116c957fe5b60200101907effff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffff1916908160001a90535050919050565b60"
+    "00808585111561170d578182fd5b83861115611719578182fd5b505082019391909203915056fe4465706f736974"
+    "436f6e74726163743a206d65726b6c6520747265652066756c6c4465706f736974436f6e74726163743a20726563"
+    "6f6e7374727563746564204465706f7369744461746120646f6573206e6f74206d6174636820737570706c696564"
+    "206465706f7369745f646174615f726f6f744465706f736974436f6e74726163743a20696e76616c696420776974"
+    "6864726177616c5f63726564656e7469616c73206c656e6774684465706f736974436f6e74726163743a20646570"
+    "6f7369742076616c7565206e6f74206d756c7469706c65206f6620677765694465706f736974436f6e7472616374"
+    "3a20696e76616c6964207075626b6579206c656e6774684465706f736974436f6e74726163743a206465706f7369"
+    "742076616c756520746f6f20686967684465706f736974436f6e74726163743a206465706f7369742076616c7565"
+    "20746f6f206c6f774465706f736974436f6e74726163743a20696e76616c6964207369676e6174757265206c656e"
+    "677468a26469706673582212201dd26f37a621703009abf16e77e69c93dc50c79db7f6cc37543e3e0e3decdc9764"
+    "736f6c634300060b0033"
+);
+
+/// Depth of the contract's deposit Merkle tree
+const TREE_DEPTH: u64 = 32;
+
+/// Storage slot of `zero_hashes[0]`, after the 32 `branch` slots and `deposit_count`
+const ZERO_HASHES_SLOT: u64 = TREE_DEPTH + 1;
+
+/// Genesis storage of the deposit contract: the zero hashes of empty subtrees its constructor
+/// computes, which a predeploy skips
+pub fn deposit_contract_storage() -> BTreeMap<B256, B256> {
+    // `zero_hashes[0]` is the zero word, which is left out
+    let mut storage = StorageBuilder::new().slot(ZERO_HASHES_SLOT + 1);
+    let mut zero_hash = B256::ZERO;
+    for _ in 1..TREE_DEPTH {
+        zero_hash = B256::from_slice(
+            &Sha256::new().chain_update(zero_hash).chain_update(zero_hash).finalize(),
+        );
+        storage = storage.value_b256(zero_hash);
+    }
+    storage.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+
+    #[test]
+    fn test_deposit_contract_storage() {
+        // Same as the deposit contract in the Holesky genesis
+        let storage = deposit_contract_storage();
+        assert_eq!(storage.len(), 31);
+        assert_eq!(
+            storage[&B256::from(U256::from(0x22))],
+            b256!("f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b")
+        );
+        assert_eq!(
+            storage[&B256::from(U256::from(0x23))],
+            b256!("db56114e00fdd4c1f85c892bf35ac9a89289aaecb1ebd0a96cde606a748b5d71")
+        );
+        assert!(storage.contains_key(&B256::from(U256::from(0x40))));
+    }
+}
@@ -0,0 +1,445 @@
+//! Chain spec comparison
+//!
+//! Operators that end up with subtly different chain definitions, say a mistyped signer or Cancun
+//! at another timestamp, only notice that their nodes won't sync. [`PoaChainSpec::diff`] lists
+//! what differs so the definitions can be reconciled before launch.
+//!
+//! [`PoaChainSpec::mainnet_divergence`] compares the fork schedule against Ethereum mainnet's
+//! instead, to show whether the chain runs the same EVM.
+
+use crate::chainspec::PoaChainSpec;
+use alloy_eips::eip7840::BlobParams;
+use alloy_primitives::{Address, B256};
+use reth_chainspec::{BaseFeeParamsKind, EthChainSpec, EthereumHardforks, ForkCondition, MAINNET};
+use reth_ethereum_forks::EthereumHardfork;
+use std::{collections::BTreeSet, fmt};
+
+/// A difference between two chain specs, as seen from the first one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecDifference {
+    /// Different chain ids
+    ChainId {
+        /// Ours
+        ours: u64,
+        /// Theirs
+        theirs: u64,
+    },
+    /// Different genesis blocks
+    GenesisHash {
+        /// Ours
+        ours: B256,
+        /// Theirs
+        theirs: B256,
+    },
+    /// A hardfork activates at different times
+    Hardfork {
+        /// The hardfork
+        fork: EthereumHardfork,
+        /// Our activation
+        ours: ForkCondition,
+        /// Their activation
+        theirs: ForkCondition,
+    },
+    /// Different block periods
+    Period {
+        /// Ours
+        ours: u64,
+        /// Theirs
+        theirs: u64,
+    },
+    /// Different epoch lengths
+    Epoch {
+        /// Ours
+        ours: u64,
+        /// Theirs
+        theirs: u64,
+    },
+    /// Different genesis signer sets
+    Signers {
+        /// Signers only they have
+        added: Vec<Address>,
+        /// Signers only we have
+        removed: Vec<Address>,
+    },
+    /// Different base fee parameters
+    BaseFeeParams {
+        /// Ours
+        ours: BaseFeeParamsKind,
+        /// Theirs
+        theirs: BaseFeeParamsKind,
+    },
+    /// Different blob parameters for a hardfork
+    BlobParams {
+        /// The hardfork the parameters apply from
+        fork: EthereumHardfork,
+        /// Ours
+        ours: BlobParams,
+        /// Theirs
+        theirs: BlobParams,
+    },
+}
+
+impl fmt::Display for SpecDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChainId { ours, theirs } => write!(f, "chain id: {ours} vs {theirs}"),
+            Self::GenesisHash { ours, theirs } => write!(f, "genesis hash: {ours} vs {theirs}"),
+            Self::Hardfork { fork, ours, theirs } => {
+                write!(f, "{fork} activation: {ours:?} vs {theirs:?}")
+            }
+            Self::Period { ours, theirs } => {
+                write!(f, "block period: {ours} seconds vs {theirs} seconds")
+            }
+            Self::Epoch { ours, theirs } => write!(f, "epoch: {ours} blocks vs {theirs} blocks"),
+            Self::Signers { added, removed } => {
+                write!(f, "signers:")?;
+                for signer in added {
+                    write!(f, " +{signer}")?;
+                }
+                for signer in removed {
+                    write!(f, " -{signer}")?;
+                }
+                Ok(())
+            }
+            Self::BaseFeeParams { ours, theirs } => {
+                write!(f, "base fee params: {ours:?} vs {theirs:?}")
+            }
+            Self::BlobParams { fork, ours, theirs } => write!(
+                f,
+                "{fork} blob params: target {} max {} vs target {} max {}",
+                ours.target_blob_count,
+                ours.max_blob_count,
+                theirs.target_blob_count,
+                theirs.max_blob_count
+            ),
+        }
+    }
+}
+
+/// Mainnet hardforks that don't touch the EVM: an irregular state change and difficulty bomb delays
+const NON_EVM_FORKS: [EthereumHardfork; 4] = [
+    EthereumHardfork::Dao,
+    EthereumHardfork::MuirGlacier,
+    EthereumHardfork::ArrowGlacier,
+    EthereumHardfork::GrayGlacier,
+];
+
+/// How the activation of a hardfork compares to Ethereum mainnet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// Activates before it did on mainnet, including at genesis
+    Earlier,
+    /// Activates after it did on mainnet
+    Later,
+    /// Never activates, though mainnet has it
+    Disabled,
+    /// Activates, though mainnet doesn't have it
+    Extra,
+}
+
+impl fmt::Display for DivergenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Earlier => "earlier than mainnet",
+            Self::Later => "later than mainnet",
+            Self::Disabled => "disabled",
+            Self::Extra => "not on mainnet",
+        })
+    }
+}
+
+/// A hardfork that activates differently than on Ethereum mainnet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkDivergence {
+    /// The hardfork
+    pub fork: EthereumHardfork,
+    /// How it compares
+    pub kind: DivergenceKind,
+    /// Our activation
+    pub ours: ForkCondition,
+    /// Mainnet's activation
+    pub mainnet: ForkCondition,
+}
+
+impl fmt::Display for ForkDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({:?} vs {:?})", self.fork, self.kind, self.ours, self.mainnet)
+    }
+}
+
+impl PoaChainSpec {
+    /// Everything that differs between this chain spec and `other`
+    pub fn diff(&self, other: &Self) -> Vec<SpecDifference> {
+        let mut differences = Vec::new();
+        if self.chain().id() != other.chain().id() {
+            differences.push(SpecDifference::ChainId {
+                ours: self.chain().id(),
+                theirs: other.chain().id(),
+            });
+        }
+        if self.genesis_hash() != other.genesis_hash() {
+            differences.push(SpecDifference::GenesisHash {
+                ours: self.genesis_hash(),
+                theirs: other.genesis_hash(),
+            });
+        }
+
+        let (ours, theirs) = (&self.poa_config().hardforks, &other.poa_config().hardforks);
+        let forks: BTreeSet<_> = ours.forks().chain(theirs.forks()).collect();
+        for fork in forks {
+            if ours.fork(fork) != theirs.fork(fork) {
+                differences.push(SpecDifference::Hardfork {
+                    fork,
+                    ours: ours.fork(fork),
+                    theirs: theirs.fork(fork),
+                });
+            }
+        }
+
+        if self.block_period() != other.block_period() {
+            differences.push(SpecDifference::Period {
+                ours: self.block_period(),
+                theirs: other.block_period(),
+            });
+        }
+        if self.epoch() != other.epoch() {
+            differences.push(SpecDifference::Epoch { ours: self.epoch(), theirs: other.epoch() });
+        }
+        let added: Vec<_> = other
+            .signers()
+            .iter()
+            .filter(|signer| !self.signers().contains(signer))
+            .copied()
+            .collect();
+        let removed: Vec<_> = self
+            .signers()
+            .iter()
+            .filter(|signer| !other.signers().contains(signer))
+            .copied()
+            .collect();
+        if !added.is_empty() || !removed.is_empty() {
+            differences.push(SpecDifference::Signers { added, removed });
+        }
+
+        if self.inner().base_fee_params != other.inner().base_fee_params {
+            differences.push(SpecDifference::BaseFeeParams {
+                ours: self.inner().base_fee_params.clone(),
+                theirs: other.inner().base_fee_params.clone(),
+            });
+        }
+        let (ours, theirs) = (&self.inner().blob_params, &other.inner().blob_params);
+        let blob_params = [
+            (EthereumHardfork::Cancun, ours.cancun, theirs.cancun),
+            (EthereumHardfork::Prague, ours.prague, theirs.prague),
+            (EthereumHardfork::Osaka, ours.osaka, theirs.osaka),
+        ];
+        for (fork, ours, theirs) in blob_params {
+            if ours != theirs {
+                differences.push(SpecDifference::BlobParams { fork, ours, theirs });
+            }
+        }
+        differences
+    }
+
+    /// Whether nothing [`diff`](Self::diff) compares differs from `other`
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.diff(other).is_empty()
+    }
+
+    /// Every Ethereum hardfork that activates differently than on mainnet, as reth schedules it
+    pub fn mainnet_divergence(&self) -> Vec<ForkDivergence> {
+        EthereumHardfork::VARIANTS
+            .iter()
+            .filter_map(|&fork| {
+                let ours = self.ethereum_fork_activation(fork);
+                let mainnet = MAINNET.ethereum_fork_activation(fork);
+                let kind = self.divergence_kind(ours, mainnet)?;
+                Some(ForkDivergence { fork, kind, ours, mainnet })
+            })
+            .collect()
+    }
+
+    /// Whether every hardfork mainnet has activates here no later than on mainnet, so contracts
+    /// see at least mainnet's EVM
+    ///
+    /// The DAO fork and the difficulty bomb delays don't change the EVM and are left out.
+    pub fn is_evm_equivalent_to_mainnet(&self) -> bool {
+        self.mainnet_divergence().iter().all(|divergence| {
+            NON_EVM_FORKS.contains(&divergence.fork) ||
+                matches!(divergence.kind, DivergenceKind::Earlier | DivergenceKind::Extra)
+        })
+    }
+
+    fn divergence_kind(
+        &self,
+        ours: ForkCondition,
+        mainnet: ForkCondition,
+    ) -> Option<DivergenceKind> {
+        let earlier_if =
+            |earlier| if earlier { DivergenceKind::Earlier } else { DivergenceKind::Later };
+        Some(match (ours, mainnet) {
+            _ if ours == mainnet => return None,
+            (ForkCondition::Never, _) => DivergenceKind::Disabled,
+            (_, ForkCondition::Never) => DivergenceKind::Extra,
+            (ForkCondition::Block(ours), ForkCondition::Block(mainnet)) |
+            (ForkCondition::Timestamp(ours), ForkCondition::Timestamp(mainnet)) => {
+                earlier_if(ours < mainnet)
+            }
+            (
+                ForkCondition::TTD { total_difficulty: ours, .. },
+                ForkCondition::TTD { total_difficulty: mainnet, .. },
+            ) => earlier_if(ours < mainnet),
+            // Mainnet activated nothing at genesis but Frontier
+            _ => earlier_if(
+                ours.active_at_block(0) || ours.active_at_timestamp(self.genesis().timestamp),
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::{BlobCounts, BlobScheduleOverride, PoaConfig},
+        genesis,
+    };
+
+    #[test]
+    fn test_diff_reports_changed_signer() {
+        let chain = PoaChainSpec::dev_chain();
+        assert!(chain.diff(&PoaChainSpec::dev_chain()).is_empty());
+        assert!(chain.is_compatible(&PoaChainSpec::dev_chain()));
+
+        let (replaced, replacement) = (chain.signers()[0], genesis::dev_accounts()[3]);
+        let mut signers = chain.signers().to_vec();
+        signers[0] = replacement;
+        let config = PoaConfig { signers, ..chain.poa_config().clone() };
+        let other = PoaChainSpec::new(genesis::create_dev_genesis_for(&config), config);
+        let difference =
+            SpecDifference::Signers { added: vec![replacement], removed: vec![replaced] };
+        assert_eq!(difference.to_string(), format!("signers: +{replacement} -{replaced}"));
+        // The genesis extra data lists the signers, so the genesis differs too
+        let differences = chain.diff(&other);
+        assert_eq!(differences.len(), 2, "{differences:?}");
+        assert!(differences.contains(&difference));
+        assert!(matches!(differences[0], SpecDifference::GenesisHash { .. }));
+        assert!(!chain.is_compatible(&other));
+    }
+
+    #[test]
+    fn test_diff_reports_every_kind() {
+        let chain = PoaChainSpec::dev_chain();
+        let blob_params = BlobScheduleOverride {
+            cancun: Some(BlobCounts { target: 1, max: 2 }),
+            ..Default::default()
+        };
+        let other = PoaChainSpec::new(
+            genesis::create_genesis(
+                genesis::GenesisConfig::dev()
+                    .with_chain_id(4242)
+                    .with_block_period(5)
+                    .with_epoch(100),
+            ),
+            PoaConfig {
+                period: 5,
+                epoch: 100,
+                zero_base_fee: true,
+                blob_params: Some(blob_params),
+                hardforks: chain
+                    .poa_config()
+                    .hardforks
+                    .clone()
+                    .with_fork(EthereumHardfork::Osaka, ForkCondition::Timestamp(1_800_000_000)),
+                ..chain.poa_config().clone()
+            },
+        );
+
+        let differences = chain.diff(&other);
+        assert_eq!(differences.len(), 7, "{differences:?}");
+        for expected in [
+            SpecDifference::ChainId { ours: 31337, theirs: 4242 },
+            SpecDifference::Period { ours: 2, theirs: 5 },
+            SpecDifference::Epoch { ours: 30000, theirs: 100 },
+            SpecDifference::Hardfork {
+                fork: EthereumHardfork::Osaka,
+                ours: ForkCondition::Never,
+                theirs: ForkCondition::Timestamp(1_800_000_000),
+            },
+        ] {
+            assert!(differences.contains(&expected), "{expected}");
+        }
+        assert!(differences
+            .iter()
+            .any(|difference| matches!(difference, SpecDifference::GenesisHash { .. })));
+        assert!(differences.iter().any(|difference| matches!(
+            difference,
+            SpecDifference::BaseFeeParams { theirs: BaseFeeParamsKind::Constant(params), .. }
+                if params.elasticity_multiplier == 1
+        )));
+        assert!(differences.iter().any(|difference| matches!(
+            difference,
+            SpecDifference::BlobParams { fork: EthereumHardfork::Cancun, .. }
+        )));
+        assert_eq!(differences[0].to_string(), "chain id: 31337 vs 4242");
+    }
+
+    #[test]
+    fn test_mainnet_divergence() {
+        let dev = PoaChainSpec::dev_chain();
+        let divergence = dev.mainnet_divergence();
+        // Everything the dev chain has activates at genesis, ahead of mainnet
+        for fork in [EthereumHardfork::London, EthereumHardfork::Paris, EthereumHardfork::Cancun] {
+            let found = divergence.iter().find(|divergence| divergence.fork == fork).unwrap();
+            assert_eq!(found.kind, DivergenceKind::Earlier, "{found}");
+        }
+        assert!(divergence.iter().all(|divergence| divergence.fork != EthereumHardfork::Frontier));
+        // ...but it stops at Prague, which mainnet has moved past
+        let disabled: Vec<_> = divergence
+            .iter()
+            .filter(|divergence| {
+                divergence.kind != DivergenceKind::Earlier &&
+                    !NON_EVM_FORKS.contains(&divergence.fork)
+            })
+            .map(|divergence| (divergence.fork, divergence.kind))
+            .collect();
+        assert!(disabled.contains(&(EthereumHardfork::Osaka, DivergenceKind::Disabled)));
+        assert!(!dev.is_evm_equivalent_to_mainnet());
+
+        let hardforks =
+            disabled.iter().fold(dev.poa_config().hardforks.clone(), |hardforks, (fork, _)| {
+                hardforks.with_fork(*fork, ForkCondition::Timestamp(0))
+            });
+        let current = PoaChainSpec::new(
+            dev.genesis().clone(),
+            PoaConfig { hardforks, ..dev.poa_config().clone() },
+        );
+        assert!(current.is_evm_equivalent_to_mainnet(), "{:?}", current.mainnet_divergence());
+        assert!(current.mainnet_divergence().iter().all(|divergence| {
+            divergence.kind == DivergenceKind::Earlier || NON_EVM_FORKS.contains(&divergence.fork)
+        }));
+
+        // Without Cancun the chain lacks mainnet's blob and transient storage opcodes
+        let hardforks = dev
+            .poa_config()
+            .hardforks
+            .clone()
+            .with_fork(EthereumHardfork::Cancun, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Never);
+        let shanghai = PoaChainSpec::new(
+            dev.genesis().clone(),
+            PoaConfig { hardforks, ..dev.poa_config().clone() },
+        );
+        assert!(!shanghai.is_evm_equivalent_to_mainnet());
+        let cancun = shanghai
+            .mainnet_divergence()
+            .into_iter()
+            .find(|divergence| divergence.fork == EthereumHardfork::Cancun)
+            .unwrap();
+        assert_eq!(cancun.kind, DivergenceKind::Disabled);
+        assert!(
+            cancun.to_string().starts_with("Cancun: disabled (Never vs Timestamp("),
+            "{cancun}"
+        );
+    }
+}
@@ -0,0 +1,485 @@
+//! Prefunded accounts from an external file
+//!
+//! Launches that distribute a token to thousands of addresses keep the list in a file rather than
+//! in code. [`read_alloc_file`] reads either format, one entry at a time:
+//!
+//! - CSV, one `address,balance` row per account, optionally after an `address,...` header
+//! - JSON, a map of addresses to balances: `{"0x...": "1000 ETH"}`
+//!
+//! Balances are amounts as [`parse_amount`] reads them, in wei unless they name a unit:
+//! `1000000`, `0x3e8`, `30 gwei` or `1_234.5 ETH`.
+
+use super::GenesisError;
+use alloy_primitives::{Address, U256};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, Visitor};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// What is wrong with an alloc file entry
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AllocEntryError {
+    /// The address already has an entry
+    #[error("{0} is listed more than once")]
+    Duplicate(Address),
+
+    /// The address is not a 20 byte hex string
+    #[error("invalid address `{0}`")]
+    InvalidAddress(String),
+
+    /// The balance is not an amount [`parse_amount`] accepts
+    #[error("invalid balance `{balance}`: {reason}")]
+    InvalidBalance {
+        /// The balance as written
+        balance: String,
+        /// What is wrong with it
+        reason: AmountError,
+    },
+
+    /// A CSV row that isn't `address,balance`
+    #[error("expected `address,balance`")]
+    InvalidRow,
+
+    /// The JSON is malformed or not a map of strings
+    #[error("{0}")]
+    Json(String),
+}
+
+/// Why an amount can't be parsed, see [`parse_amount`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AmountError {
+    /// Not a number, optionally followed by a unit
+    #[error("expected a number of wei, gwei or ETH")]
+    Invalid,
+
+    /// A negative amount
+    #[error("amounts can't be negative")]
+    Negative,
+
+    /// More decimals than the unit has, which would have to be rounded away
+    #[error("{unit} has {max} decimals, got {decimals}")]
+    TooPrecise {
+        /// The unit of the amount
+        unit: Unit,
+        /// Decimals of the amount
+        decimals: usize,
+        /// Decimals of the unit
+        max: usize,
+    },
+
+    /// More than 2^256 - 1 wei
+    #[error("exceeds 2^256 - 1 wei")]
+    Overflow,
+}
+
+/// Unit of an amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// 1 wei
+    Wei,
+    /// 10^9 wei
+    Gwei,
+    /// 10^18 wei
+    Ether,
+}
+
+impl Unit {
+    /// Number of decimals in wei
+    pub const fn decimals(self) -> usize {
+        match self {
+            Self::Wei => 0,
+            Self::Gwei => 9,
+            Self::Ether => 18,
+        }
+    }
+
+    /// The unit an amount ends in, case insensitively, and the amount without it
+    fn strip_from(amount: &str) -> Option<(&str, Self)> {
+        let units = [
+            ("ether", Self::Ether),
+            ("eth", Self::Ether),
+            ("gwei", Self::Gwei),
+            ("wei", Self::Wei),
+        ];
+        let lowercase = amount.to_ascii_lowercase();
+        units.into_iter().find_map(|(suffix, unit)| {
+            lowercase.ends_with(suffix).then(|| (&amount[..amount.len() - suffix.len()], unit))
+        })
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Wei => "wei",
+            Self::Gwei => "gwei",
+            Self::Ether => "ETH",
+        })
+    }
+}
+
+/// Parse an amount like `10000`, `10000.5`, `1_000_000 ETH`, `30 gwei` or `0x3e8 wei` exactly,
+/// reading numbers without a unit in `default_unit`
+///
+/// Underscores may separate digits. Hex amounts are wei only, and an amount with more decimals
+/// than its unit is rejected rather than rounded.
+pub fn parse_amount(amount: &str, default_unit: Unit) -> Result<U256, AmountError> {
+    let amount = amount.trim();
+    let (number, unit) = match Unit::strip_from(amount) {
+        Some((number, unit)) => (number.trim_end(), unit),
+        None => (amount, default_unit),
+    };
+    if number.starts_with('-') {
+        return Err(AmountError::Negative)
+    }
+    if let Some(hex) = number.strip_prefix("0x") {
+        return match unit {
+            Unit::Wei => U256::from_str_radix(hex, 16).map_err(|_| AmountError::Invalid),
+            _ => Err(AmountError::Invalid),
+        }
+    }
+
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let digits = |part: &str| -> Result<String, AmountError> {
+        let bytes = part.as_bytes();
+        let separated = |index: usize| {
+            index > 0 &&
+                bytes[index - 1].is_ascii_digit() &&
+                bytes.get(index + 1).is_some_and(u8::is_ascii_digit)
+        };
+        let valid = bytes
+            .iter()
+            .enumerate()
+            .all(|(index, byte)| byte.is_ascii_digit() || (*byte == b'_' && separated(index)));
+        if !valid {
+            return Err(AmountError::Invalid)
+        }
+        Ok(part.replace('_', ""))
+    };
+    let (integer, fraction) = (digits(integer)?, digits(fraction)?);
+    if integer.is_empty() || (fraction.is_empty() && number.contains('.')) {
+        return Err(AmountError::Invalid)
+    }
+    if fraction.len() > unit.decimals() {
+        return Err(AmountError::TooPrecise { unit, decimals: fraction.len(), max: unit.decimals() })
+    }
+
+    // All digits, so parsing can only fail by overflowing
+    let scaled = format!("{integer}{fraction:0<width$}", width = unit.decimals());
+    U256::from_str_radix(&scaled, 10).map_err(|_| AmountError::Overflow)
+}
+
+/// Parse a balance as [`parse_amount`] does, in wei unless it names another unit
+pub fn parse_balance(balance: &str) -> Result<U256, AllocEntryError> {
+    parse_amount(balance, Unit::Wei).map_err(|reason| AllocEntryError::InvalidBalance {
+        balance: balance.trim().to_string(),
+        reason,
+    })
+}
+
+/// Read the prefunded accounts of an alloc file, JSON if the name ends in `.json` or the content
+/// starts with `{`, CSV otherwise
+pub fn read_alloc_file(path: &Path) -> Result<BTreeMap<Address, U256>, GenesisError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let is_json = path.extension().is_some_and(|extension| extension == "json") ||
+        reader.fill_buf()?.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'{');
+    if is_json {
+        read_json(reader)
+    } else {
+        read_csv(reader)
+    }
+}
+
+/// Add `address` to `accounts`, rejecting duplicates
+fn insert(
+    accounts: &mut BTreeMap<Address, U256>,
+    address: Address,
+    balance: U256,
+) -> Result<(), AllocEntryError> {
+    match accounts.insert(address, balance) {
+        Some(_) => Err(AllocEntryError::Duplicate(address)),
+        None => Ok(()),
+    }
+}
+
+fn read_csv(reader: impl BufRead) -> Result<BTreeMap<Address, U256>, GenesisError> {
+    let mut accounts = BTreeMap::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let invalid = |reason| GenesisError::InvalidAlloc { line: index + 1, reason };
+        let row = line.trim();
+        if row.is_empty() || (index == 0 && row.to_ascii_lowercase().starts_with("address")) {
+            continue
+        }
+
+        let (address, balance) =
+            row.split_once(',').ok_or_else(|| invalid(AllocEntryError::InvalidRow))?;
+        let address = address.trim();
+        let address = address
+            .parse()
+            .map_err(|_| invalid(AllocEntryError::InvalidAddress(address.to_string())))?;
+        let balance = parse_balance(balance).map_err(invalid)?;
+        insert(&mut accounts, address, balance).map_err(invalid)?;
+    }
+    Ok(accounts)
+}
+
+fn read_json(reader: impl std::io::Read) -> Result<BTreeMap<Address, U256>, GenesisError> {
+    let mut sink = JsonAllocSink::default();
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    (&mut sink).deserialize(&mut deserializer).and_then(|()| deserializer.end()).map_err(
+        |err| GenesisError::InvalidAlloc {
+            line: err.line(),
+            reason: sink.error.take().unwrap_or_else(|| AllocEntryError::Json(err.to_string())),
+        },
+    )?;
+    Ok(sink.accounts)
+}
+
+/// Collects the entries of a JSON alloc map as they are parsed, keeping the entry error that
+/// stopped parsing so it can be reported with its line
+#[derive(Debug, Default)]
+struct JsonAllocSink {
+    accounts: BTreeMap<Address, U256>,
+    error: Option<AllocEntryError>,
+}
+
+impl<'de> DeserializeSeed<'de> for &mut JsonAllocSink {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for &mut JsonAllocSink {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of addresses to balances")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some((address, balance)) = map.next_entry::<String, String>()? {
+            let entry = Address::from_str(&address)
+                .map_err(|_| AllocEntryError::InvalidAddress(address))
+                .and_then(|address| Ok((address, parse_balance(&balance)?)))
+                .and_then(|(address, balance)| insert(&mut self.accounts, address, balance));
+            if let Err(err) = entry {
+                let message = err.to_string();
+                self.error = Some(err);
+                return Err(de::Error::custom(message))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::io::Write;
+
+    const ETHER: u64 = 1_000_000_000_000_000_000;
+
+    fn write(dir: &tempfile::TempDir, name: &str, content: &str) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_balance() {
+        assert_eq!(parse_balance("1000"), Ok(U256::from(1000)));
+        assert_eq!(parse_balance("0x3e8"), Ok(U256::from(1000)));
+        assert_eq!(
+            parse_balance(" 1234.5 ETH"),
+            Ok(U256::from(1_234_500) * U256::from(ETHER / 1000))
+        );
+        assert_eq!(parse_balance("2ETH"), Ok(U256::from(2 * ETHER)));
+        assert_eq!(parse_balance("30 gwei"), Ok(U256::from(30_000_000_000u64)));
+        assert_eq!(parse_balance("0x10 wei"), Ok(U256::from(16)));
+        for invalid in ["", "0xzz", "1.5 BTC", "abc ETH", "1.", "_1", "1__0", "1_", "0x10 ETH"] {
+            assert_eq!(
+                parse_balance(invalid),
+                Err(AllocEntryError::InvalidBalance {
+                    balance: invalid.trim().to_string(),
+                    reason: AmountError::Invalid
+                }),
+                "{invalid}"
+            );
+        }
+        assert!(matches!(
+            parse_balance("1.5"),
+            Err(AllocEntryError::InvalidBalance { reason: AmountError::TooPrecise { .. }, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        let ether = |amount| parse_amount(amount, Unit::Ether);
+        assert_eq!(ether("10000"), Ok(U256::from(10_000) * U256::from(ETHER)));
+        assert_eq!(ether("10000.5"), Ok(U256::from(10_000_500) * U256::from(ETHER / 1000)));
+        assert_eq!(ether("1_000_000 ETH"), Ok(U256::from(1_000_000) * U256::from(ETHER)));
+        assert_eq!(ether("1 Ether"), Ok(U256::from(ETHER)));
+        assert_eq!(ether("0.000000000000000001"), Ok(U256::from(1)));
+        assert_eq!(ether("7 wei"), Ok(U256::from(7)));
+        assert_eq!(ether("1.5 gwei"), Ok(U256::from(1_500_000_000)));
+
+        assert_eq!(ether("-1"), Err(AmountError::Negative));
+        let err = ether("0.0000000000000000001").unwrap_err();
+        assert_eq!(err, AmountError::TooPrecise { unit: Unit::Ether, decimals: 19, max: 18 });
+        assert_eq!(err.to_string(), "ETH has 18 decimals, got 19");
+        assert_eq!(
+            ether("1.5 wei"),
+            Err(AmountError::TooPrecise { unit: Unit::Wei, decimals: 1, max: 0 })
+        );
+        assert_eq!(ether("0x10"), Err(AmountError::Invalid));
+        // 2^256 wei is about 1.16e59 ETH, so 1e60 ETH overflows
+        assert_eq!(ether(&format!("1{}", "0".repeat(60))), Err(AmountError::Overflow));
+        assert_eq!(parse_amount(&U256::MAX.to_string(), Unit::Wei), Ok(U256::MAX));
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_parse_amount_is_exact(
+            integer in any::<u128>(),
+            fraction in "[0-9]{0,18}",
+            unit in prop_oneof![Just(Unit::Wei), Just(Unit::Gwei), Just(Unit::Ether)],
+        ) {
+            let fraction = &fraction[..fraction.len().min(unit.decimals())];
+            let amount = if fraction.is_empty() {
+                format!("{integer} {unit}")
+            } else {
+                format!("{integer}.{fraction} {unit}")
+            };
+            let scale = |decimals: usize| U256::from(10).pow(U256::from(decimals));
+            let fraction_wei = if fraction.is_empty() {
+                U256::ZERO
+            } else {
+                U256::from_str_radix(fraction, 10).unwrap() * scale(unit.decimals() - fraction.len())
+            };
+            let expected = U256::from(integer) * scale(unit.decimals()) + fraction_wei;
+            prop_assert_eq!(parse_amount(&amount, Unit::Wei), Ok(expected));
+            // The unit given as the default reads the same
+            let bare = amount.trim_end_matches(&format!(" {unit}"));
+            prop_assert_eq!(parse_amount(bare, unit), Ok(expected));
+        }
+
+        #[test]
+        fn proptest_parse_amount_rejects_excess_decimals(
+            integer in any::<u64>(),
+            fraction in "[0-9]{19,30}",
+        ) {
+            let amount = format!("{integer}.{fraction}");
+            prop_assert_eq!(
+                parse_amount(&amount, Unit::Ether),
+                Err(AmountError::TooPrecise { unit: Unit::Ether, decimals: fraction.len(), max: 18 })
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_large_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alloc.csv");
+        let mut file = std::io::BufWriter::new(File::create(&path).unwrap());
+        writeln!(file, "address,balance_wei").unwrap();
+        for i in 1..=10_000u64 {
+            let address = Address::from_word(U256::from(i).into());
+            writeln!(file, "{address},{i}").unwrap();
+        }
+        drop(file);
+
+        let accounts = read_alloc_file(&path).unwrap();
+        assert_eq!(accounts.len(), 10_000);
+        assert_eq!(accounts.values().sum::<U256>(), U256::from(10_000 * 10_001 / 2));
+        assert_eq!(accounts[&Address::from_word(U256::from(10_000).into())], U256::from(10_000));
+    }
+
+    #[test]
+    fn test_read_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            &dir,
+            "alloc.txt",
+            r#"{
+                "0x0000000000000000000000000000000000000001": "1 ETH",
+                "0000000000000000000000000000000000000002": "0x10"
+            }"#,
+        );
+        let accounts = read_alloc_file(&path).unwrap();
+        assert_eq!(
+            accounts,
+            BTreeMap::from([
+                (Address::with_last_byte(1), U256::from(ETHER)),
+                (Address::with_last_byte(2), U256::from(16)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_alloc_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let one = Address::with_last_byte(1);
+
+        let duplicate = write(&dir, "duplicate.csv", &format!("{one},1\n\n{one},2\n"));
+        assert!(matches!(
+            read_alloc_file(&duplicate),
+            Err(GenesisError::InvalidAlloc { line: 3, reason: AllocEntryError::Duplicate(address) })
+                if address == one
+        ));
+        let json = format!("{{\n\"{one}\": \"1\",\n\"{one}\": \"2\",\n\"0x02\": \"3\"\n}}");
+        let duplicate = write(&dir, "duplicate.json", &json);
+        assert!(matches!(
+            read_alloc_file(&duplicate),
+            Err(GenesisError::InvalidAlloc { line: 3, reason: AllocEntryError::Duplicate(address) })
+                if address == one
+        ));
+
+        let malformed = write(&dir, "malformed.csv", &format!("{one},12 btc\n"));
+        let err = read_alloc_file(&malformed).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "alloc file line 1: invalid balance `12 btc`: expected a number of wei, gwei or ETH"
+        );
+        let malformed = write(&dir, "malformed.json", &format!(r#"{{"{one}": "1.5"}}"#));
+        assert!(matches!(
+            read_alloc_file(&malformed),
+            Err(GenesisError::InvalidAlloc {
+                reason: AllocEntryError::InvalidBalance {
+                    reason: AmountError::TooPrecise { .. },
+                    ..
+                },
+                ..
+            })
+        ));
+
+        let row = write(&dir, "row.csv", "0x01\n");
+        assert!(matches!(
+            read_alloc_file(&row),
+            Err(GenesisError::InvalidAlloc { line: 1, reason: AllocEntryError::InvalidRow })
+        ));
+        let address = write(&dir, "address.csv", "address,balance\n0x01,1\n");
+        assert!(matches!(
+            read_alloc_file(&address),
+            Err(GenesisError::InvalidAlloc { line: 2, reason: AllocEntryError::InvalidAddress(_) })
+        ));
+        let syntax = write(&dir, "syntax.json", "{\"0x01\": 1}");
+        assert!(matches!(
+            read_alloc_file(&syntax),
+            Err(GenesisError::InvalidAlloc { line: 1, reason: AllocEntryError::Json(_) })
+        ));
+        assert!(matches!(
+            read_alloc_file(&dir.path().join("missing.csv")),
+            Err(GenesisError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,427 @@
+//! Genesis comparison
+//!
+//! Two operators whose genesis hashes differ need to know why. [`diff`] lists what differs
+//! between two genesis files: chain config fields, header fields with the clique extra data split
+//! into vanity, signers and seal, and accounts. [`GenesisDiffSummary`] prints the list with the
+//! account differences capped, as a token distribution can differ in thousands of accounts.
+
+use crate::{
+    chainspec::genesis_signers,
+    sealing::{EXTRA_SEAL_LENGTH, EXTRA_VANITY_LENGTH},
+};
+use alloy_genesis::{Genesis, GenesisAccount};
+use alloy_primitives::{Address, Bytes, U256};
+use std::{collections::BTreeSet, fmt};
+
+/// How many account differences [`GenesisDiffSummary::new`] shows by default
+pub const DEFAULT_MAX_ACCOUNT_DIFFERENCES: usize = 20;
+
+/// A difference between two genesis blocks, as seen from the first one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenesisDifference {
+    /// A chain config field, by its JSON name, with its JSON values (`null` if absent)
+    ChainConfig {
+        /// The field, e.g. `londonBlock` or `clique.period`
+        field: String,
+        /// Ours
+        ours: serde_json::Value,
+        /// Theirs
+        theirs: serde_json::Value,
+    },
+    /// A header field other than the extra data, by its JSON name
+    Header {
+        /// The field, e.g. `gasLimit`
+        field: &'static str,
+        /// Ours
+        ours: String,
+        /// Theirs
+        theirs: String,
+    },
+    /// Different vanity bytes at the start of the extra data
+    Vanity {
+        /// Ours
+        ours: Bytes,
+        /// Theirs
+        theirs: Bytes,
+    },
+    /// Different signers in the extra data
+    Signers {
+        /// Signers only they have
+        added: Vec<Address>,
+        /// Signers only we have
+        removed: Vec<Address>,
+    },
+    /// Different seals at the end of the extra data
+    Seal {
+        /// Ours
+        ours: Bytes,
+        /// Theirs
+        theirs: Bytes,
+    },
+    /// Extra data that isn't vanity, signers and seal on at least one side
+    ExtraData {
+        /// Ours
+        ours: Bytes,
+        /// Theirs
+        theirs: Bytes,
+    },
+    /// An account only we have
+    OnlyOurs(Address),
+    /// An account only they have
+    OnlyTheirs(Address),
+    /// An account with different balances
+    Balance {
+        /// The account
+        address: Address,
+        /// Ours
+        ours: U256,
+        /// Theirs
+        theirs: U256,
+    },
+    /// An account with different nonces
+    Nonce {
+        /// The account
+        address: Address,
+        /// Ours
+        ours: u64,
+        /// Theirs
+        theirs: u64,
+    },
+    /// An account with different code
+    Code(Address),
+    /// An account with different storage
+    Storage(Address),
+}
+
+impl GenesisDifference {
+    /// Whether this is a difference in the alloc rather than in the config or header
+    pub const fn is_account(&self) -> bool {
+        matches!(
+            self,
+            Self::OnlyOurs(_) |
+                Self::OnlyTheirs(_) |
+                Self::Balance { .. } |
+                Self::Nonce { .. } |
+                Self::Code(_) |
+                Self::Storage(_)
+        )
+    }
+}
+
+impl fmt::Display for GenesisDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChainConfig { field, ours, theirs } => {
+                write!(f, "config.{field}: {ours} vs {theirs}")
+            }
+            Self::Header { field, ours, theirs } => write!(f, "{field}: {ours} vs {theirs}"),
+            Self::Vanity { ours, theirs } => write!(f, "extraData vanity: {ours} vs {theirs}"),
+            Self::Signers { added, removed } => {
+                write!(f, "extraData signers:")?;
+                for signer in added {
+                    write!(f, " +{signer}")?;
+                }
+                for signer in removed {
+                    write!(f, " -{signer}")?;
+                }
+                Ok(())
+            }
+            Self::Seal { ours, theirs } => write!(f, "extraData seal: {ours} vs {theirs}"),
+            Self::ExtraData { ours, theirs } => write!(f, "extraData: {ours} vs {theirs}"),
+            Self::OnlyOurs(address) => write!(f, "alloc.{address}: only ours"),
+            Self::OnlyTheirs(address) => write!(f, "alloc.{address}: only theirs"),
+            Self::Balance { address, ours, theirs } => {
+                write!(f, "alloc.{address}.balance: {ours} vs {theirs}")
+            }
+            Self::Nonce { address, ours, theirs } => {
+                write!(f, "alloc.{address}.nonce: {ours} vs {theirs}")
+            }
+            Self::Code(address) => write!(f, "alloc.{address}.code differs"),
+            Self::Storage(address) => write!(f, "alloc.{address}.storage differs"),
+        }
+    }
+}
+
+/// Everything that differs between `ours` and `theirs`, config first, then the header, then the
+/// accounts in address order
+pub fn diff(ours: &Genesis, theirs: &Genesis) -> Vec<GenesisDifference> {
+    let mut differences = Vec::new();
+    diff_chain_config(ours, theirs, &mut differences);
+
+    let optional =
+        |value: Option<u128>| value.map_or_else(|| "none".to_string(), |value| value.to_string());
+    let blob_field = |value: Option<u64>| optional(value.map(u128::from));
+    let header_fields = [
+        ("nonce", ours.nonce.to_string(), theirs.nonce.to_string()),
+        ("timestamp", ours.timestamp.to_string(), theirs.timestamp.to_string()),
+        ("gasLimit", ours.gas_limit.to_string(), theirs.gas_limit.to_string()),
+        ("difficulty", ours.difficulty.to_string(), theirs.difficulty.to_string()),
+        ("mixHash", ours.mix_hash.to_string(), theirs.mix_hash.to_string()),
+        ("coinbase", ours.coinbase.to_string(), theirs.coinbase.to_string()),
+        ("baseFeePerGas", optional(ours.base_fee_per_gas), optional(theirs.base_fee_per_gas)),
+        ("excessBlobGas", blob_field(ours.excess_blob_gas), blob_field(theirs.excess_blob_gas)),
+        ("blobGasUsed", blob_field(ours.blob_gas_used), blob_field(theirs.blob_gas_used)),
+    ];
+    for (field, ours, theirs) in header_fields {
+        if ours != theirs {
+            differences.push(GenesisDifference::Header { field, ours, theirs });
+        }
+    }
+    diff_extra_data(&ours.extra_data, &theirs.extra_data, &mut differences);
+
+    let addresses: BTreeSet<_> = ours.alloc.keys().chain(theirs.alloc.keys()).collect();
+    for address in addresses {
+        match (ours.alloc.get(address), theirs.alloc.get(address)) {
+            (Some(ours), Some(theirs)) => diff_account(*address, ours, theirs, &mut differences),
+            (Some(_), None) => differences.push(GenesisDifference::OnlyOurs(*address)),
+            (None, _) => differences.push(GenesisDifference::OnlyTheirs(*address)),
+        }
+    }
+    differences
+}
+
+/// Compare the chain configs field by field, in their JSON form so that every field geth knows
+/// is covered
+fn diff_chain_config(ours: &Genesis, theirs: &Genesis, differences: &mut Vec<GenesisDifference>) {
+    let fields = |genesis: &Genesis| {
+        let mut fields = serde_json::Map::new();
+        if let Ok(serde_json::Value::Object(config)) = serde_json::to_value(&genesis.config) {
+            flatten("", config, &mut fields);
+        }
+        fields
+    };
+    let (ours, theirs) = (fields(ours), fields(theirs));
+    let names: BTreeSet<_> = ours.keys().chain(theirs.keys()).collect();
+    for field in names {
+        let (ours, theirs) = (ours.get(field), theirs.get(field));
+        if ours != theirs {
+            differences.push(GenesisDifference::ChainConfig {
+                field: field.clone(),
+                ours: ours.cloned().unwrap_or_default(),
+                theirs: theirs.cloned().unwrap_or_default(),
+            });
+        }
+    }
+}
+
+/// Flatten nested objects such as `clique` into dotted field names
+fn flatten(
+    prefix: &str,
+    object: serde_json::Map<String, serde_json::Value>,
+    fields: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    for (name, value) in object {
+        let name = format!("{prefix}{name}");
+        match value {
+            serde_json::Value::Object(object) => flatten(&format!("{name}."), object, fields),
+            value => {
+                fields.insert(name, value);
+            }
+        }
+    }
+}
+
+fn diff_extra_data(ours: &Bytes, theirs: &Bytes, differences: &mut Vec<GenesisDifference>) {
+    let (Ok(our_signers), Ok(their_signers)) = (genesis_signers(ours), genesis_signers(theirs))
+    else {
+        if ours != theirs {
+            differences
+                .push(GenesisDifference::ExtraData { ours: ours.clone(), theirs: theirs.clone() });
+        }
+        return
+    };
+
+    let vanity = |extra_data: &Bytes| extra_data.slice(..EXTRA_VANITY_LENGTH);
+    if vanity(ours) != vanity(theirs) {
+        differences.push(GenesisDifference::Vanity { ours: vanity(ours), theirs: vanity(theirs) });
+    }
+    let added: Vec<_> =
+        their_signers.iter().filter(|signer| !our_signers.contains(signer)).copied().collect();
+    let removed: Vec<_> =
+        our_signers.iter().filter(|signer| !their_signers.contains(signer)).copied().collect();
+    if !added.is_empty() || !removed.is_empty() {
+        differences.push(GenesisDifference::Signers { added, removed });
+    }
+    let seal = |extra_data: &Bytes| extra_data.slice(extra_data.len() - EXTRA_SEAL_LENGTH..);
+    if seal(ours) != seal(theirs) {
+        differences.push(GenesisDifference::Seal { ours: seal(ours), theirs: seal(theirs) });
+    }
+}
+
+fn diff_account(
+    address: Address,
+    ours: &GenesisAccount,
+    theirs: &GenesisAccount,
+    differences: &mut Vec<GenesisDifference>,
+) {
+    if ours.balance != theirs.balance {
+        differences.push(GenesisDifference::Balance {
+            address,
+            ours: ours.balance,
+            theirs: theirs.balance,
+        });
+    }
+    let (our_nonce, their_nonce) =
+        (ours.nonce.unwrap_or_default(), theirs.nonce.unwrap_or_default());
+    if our_nonce != their_nonce {
+        differences.push(GenesisDifference::Nonce {
+            address,
+            ours: our_nonce,
+            theirs: their_nonce,
+        });
+    }
+    let code = |account: &GenesisAccount| account.code.clone().unwrap_or_default();
+    if code(ours) != code(theirs) {
+        differences.push(GenesisDifference::Code(address));
+    }
+    let storage = |account: &GenesisAccount| account.storage.clone().unwrap_or_default();
+    if storage(ours) != storage(theirs) {
+        differences.push(GenesisDifference::Storage(address));
+    }
+}
+
+/// Displays differences one per line, showing at most a number of account differences and how
+/// many more there are
+#[derive(Debug, Clone, Copy)]
+pub struct GenesisDiffSummary<'a> {
+    differences: &'a [GenesisDifference],
+    max_accounts: usize,
+}
+
+impl<'a> GenesisDiffSummary<'a> {
+    /// Summary showing up to [`DEFAULT_MAX_ACCOUNT_DIFFERENCES`] account differences
+    pub const fn new(differences: &'a [GenesisDifference]) -> Self {
+        Self { differences, max_accounts: DEFAULT_MAX_ACCOUNT_DIFFERENCES }
+    }
+
+    /// Show up to `max_accounts` account differences
+    pub const fn with_max_accounts(mut self, max_accounts: usize) -> Self {
+        self.max_accounts = max_accounts;
+        self
+    }
+}
+
+impl fmt::Display for GenesisDiffSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.differences.is_empty() {
+            return writeln!(f, "genesis blocks are identical")
+        }
+        let (accounts, other): (Vec<_>, Vec<_>) =
+            self.differences.iter().partition(|difference| difference.is_account());
+        writeln!(
+            f,
+            "{} differences, {} of them in accounts",
+            self.differences.len(),
+            accounts.len()
+        )?;
+        for difference in other.iter().chain(accounts.iter().take(self.max_accounts)) {
+            writeln!(f, "  {difference}")?;
+        }
+        if accounts.len() > self.max_accounts {
+            writeln!(f, "  +{} more", accounts.len() - self.max_accounts)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis::{create_dev_genesis, create_genesis, dev_signers, GenesisConfig};
+
+    #[test]
+    fn test_diff_reports_signer_and_balance() {
+        let ours = create_dev_genesis();
+        assert!(diff(&ours, &ours).is_empty());
+
+        let (funded, replacement) = (dev_signers()[0], Address::repeat_byte(0x42));
+        let mut signers = dev_signers();
+        let replaced = signers.pop().unwrap();
+        signers.push(replacement);
+        let dir = tempfile::tempdir().unwrap();
+        let (our_path, their_path) = (dir.path().join("ours.json"), dir.path().join("theirs.json"));
+        let theirs = create_genesis(
+            GenesisConfig::dev()
+                .with_signers(signers)
+                .with_prefunded_account(funded, U256::from(1)),
+        );
+        crate::genesis::write_genesis_file(&ours, &our_path, false).unwrap();
+        crate::genesis::write_genesis_file(&theirs, &their_path, false).unwrap();
+        let ours = crate::genesis::read_genesis_file(&our_path).unwrap();
+        let theirs = crate::genesis::read_genesis_file(&their_path).unwrap();
+
+        let differences = diff(&ours, &theirs);
+        assert_eq!(
+            differences,
+            [
+                GenesisDifference::Signers { added: vec![replacement], removed: vec![replaced] },
+                GenesisDifference::Balance {
+                    address: funded,
+                    ours: crate::genesis::default_prefund_balance(),
+                    theirs: U256::from(1),
+                },
+            ]
+        );
+        assert!(!differences[0].is_account());
+        assert!(differences[1].is_account());
+        assert_eq!(
+            differences[0].to_string(),
+            format!("extraData signers: +{replacement} -{replaced}")
+        );
+    }
+
+    #[test]
+    fn test_diff_config_header_and_accounts() {
+        let ours = create_dev_genesis();
+        let theirs = create_genesis(
+            GenesisConfig::dev()
+                .with_block_period(5)
+                .with_gas_limit(60_000_000)
+                .with_vanity([0x11; 32])
+                .with_predeploy(
+                    Address::repeat_byte(0x42),
+                    Bytes::from_static(&[0x00]),
+                    Default::default(),
+                    U256::ZERO,
+                    1,
+                ),
+        );
+        let differences = diff(&ours, &theirs);
+        assert_eq!(
+            differences[0],
+            GenesisDifference::ChainConfig {
+                field: "clique.period".to_string(),
+                ours: 2.into(),
+                theirs: 5.into()
+            }
+        );
+        assert_eq!(differences[1].to_string(), "gasLimit: 30000000 vs 60000000");
+        assert!(matches!(differences[2], GenesisDifference::Vanity { .. }));
+        assert_eq!(differences[3], GenesisDifference::OnlyTheirs(Address::repeat_byte(0x42)));
+        assert_eq!(differences.len(), 4, "{differences:?}");
+
+        let mut truncated = ours.clone();
+        truncated.extra_data = Bytes::from_static(&[0; 10]);
+        assert!(matches!(diff(&ours, &truncated)[..], [GenesisDifference::ExtraData { .. }]));
+    }
+
+    #[test]
+    fn test_summary_caps_accounts() {
+        let ours = create_dev_genesis();
+        let theirs = create_genesis(GenesisConfig::dev_with_accounts(5).with_chain_id(4242));
+        let differences = diff(&ours, &theirs);
+        assert_eq!(differences.iter().filter(|difference| difference.is_account()).count(), 15);
+
+        let summary = GenesisDiffSummary::new(&differences).with_max_accounts(10).to_string();
+        let lines: Vec<_> = summary.lines().collect();
+        assert_eq!(lines[0], "16 differences, 15 of them in accounts");
+        assert_eq!(lines[1], "  config.chainId: 31337 vs 4242");
+        assert_eq!(lines.len(), 13, "{summary}");
+        assert_eq!(lines[12], "  +5 more");
+        assert!(lines[2].ends_with(": only ours"), "{summary}");
+
+        let all = GenesisDiffSummary::new(&differences).to_string();
+        assert!(!all.contains("more"), "{all}");
+        assert_eq!(GenesisDiffSummary::new(&[]).to_string(), "genesis blocks are identical\n");
+    }
+}
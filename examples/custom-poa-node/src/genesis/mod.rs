@@ -0,0 +1,2461 @@
+//! Genesis Configuration for POA Chain
+//!
+//! This module provides utilities for creating genesis configurations
+//! that are compatible with Ethereum tooling while supporting POA consensus.
+
+use self::alloc::{AllocEntryError, AmountError, Unit};
+use crate::{
+    chainspec::{genesis_signers, BlobScheduleOverride, HardforkSchedule, PoaChainSpec, PoaConfig},
+    deposit::{deposit_contract_storage, DEPOSIT_CONTRACT_CODE},
+    sealing::{EXTRA_SEAL_LENGTH, EXTRA_VANITY_LENGTH},
+    signer::{dev, BlockSealer, SignerError},
+};
+use alloy_eips::eip7840::BlobParams;
+use alloy_genesis::{ChainConfig, CliqueConfig, Genesis, GenesisAccount};
+use alloy_primitives::{
+    address, hex, keccak256, utils::format_ether, Address, Bytes, B256, KECCAK_EMPTY, U256,
+};
+use alloy_signer_local::PrivateKeySigner;
+use reth_chainspec::{EthChainSpec, ForkCondition};
+use reth_ethereum_forks::EthereumHardfork;
+use reth_tracing::tracing::{info, warn};
+use reth_trie_common::{
+    root::{state_root_unhashed, storage_root_unhashed},
+    TrieAccount,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write as _},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+pub mod alloc;
+mod diff;
+pub mod presets;
+
+pub use self::diff::{
+    diff, GenesisDiffSummary, GenesisDifference, DEFAULT_MAX_ACCOUNT_DIFFERENCES,
+};
+
+/// Errors from reading a genesis back, see [`read_genesis_file`] and
+/// [`GenesisConfig::from_genesis`]
+#[derive(Debug, Error)]
+pub enum GenesisError {
+    /// The genesis file could not be read
+    #[error("failed to read genesis file: {0}")]
+    Io(#[from] io::Error),
+
+    /// The file is not a JSON genesis; the message names the offending field
+    #[error("invalid genesis: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// A field [`GenesisConfig`] needs is missing
+    #[error("genesis is missing `{0}`")]
+    MissingField(&'static str),
+
+    /// The extra data is not vanity, signer addresses and seal
+    #[error(
+        "extraData: {0} bytes are not a {EXTRA_VANITY_LENGTH} byte vanity, signer addresses and a \
+         {EXTRA_SEAL_LENGTH} byte seal"
+    )]
+    InvalidExtraData(usize),
+
+    /// An alloc entry with storage but no code
+    #[error("alloc.{0}: storage without code")]
+    UnsupportedAccount(Address),
+
+    /// An entry of an alloc file can't be used, see [`GenesisConfig::with_alloc_file`]
+    #[error("alloc file line {line}: {reason}")]
+    InvalidAlloc {
+        /// Line of the entry
+        line: usize,
+        /// What is wrong with it
+        reason: AllocEntryError,
+    },
+
+    /// A balance given to [`GenesisConfig::with_prefunded_account_eth`] can't be parsed
+    #[error("alloc.{address}: invalid amount `{amount}`: {reason}")]
+    InvalidAmount {
+        /// The account
+        address: Address,
+        /// The amount as given
+        amount: String,
+        /// What is wrong with it
+        reason: AmountError,
+    },
+
+    /// Vanity text that doesn't fit the vanity field
+    #[error("vanity: {0} bytes exceed the {EXTRA_VANITY_LENGTH} byte field")]
+    VanityTooLong(usize),
+}
+
+/// Mismatches between a genesis and the POA config it is used with, see [`validate_genesis`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GenesisValidationError {
+    /// The extra data is not vanity, signer addresses and seal
+    #[error(
+        "extraData: {0} bytes are not a {EXTRA_VANITY_LENGTH} byte vanity, signer addresses and a \
+         {EXTRA_SEAL_LENGTH} byte seal"
+    )]
+    InvalidExtraData(usize),
+
+    /// The extra data lists other signers than the config
+    #[error("extraData: lists signers {genesis:?}, but the config has {config:?}")]
+    SignerMismatch {
+        /// Signers in the extra data, sorted
+        genesis: Vec<Address>,
+        /// Signers in the config, sorted
+        config: Vec<Address>,
+    },
+
+    /// The genesis block carries a seal, which nobody could have produced
+    #[error("extraData: the seal of the genesis block must be all zeros")]
+    NonZeroSeal,
+
+    /// A difficulty other than 1
+    #[error("difficulty: must be 1, got {0}")]
+    InvalidDifficulty(U256),
+
+    /// The `clique` section has another block period than the config
+    #[error("config.clique.period: {genesis} differs from the configured {config}")]
+    PeriodMismatch {
+        /// Period in the genesis
+        genesis: u64,
+        /// Period in the config
+        config: u64,
+    },
+
+    /// The `clique` section has another epoch length than the config
+    #[error("config.clique.epoch: {genesis} differs from the configured {config}")]
+    EpochMismatch {
+        /// Epoch length in the genesis
+        genesis: u64,
+        /// Epoch length in the config
+        config: u64,
+    },
+}
+
+/// Why [`write_dev_accounts_file`] didn't write the dev keys
+#[derive(Debug, Error)]
+pub enum DevAccountsError {
+    /// The file could not be written
+    #[error("failed to write dev accounts file: {0}")]
+    Io(#[from] io::Error),
+
+    /// A chain that isn't a dev chain signs with or funds a dev account
+    #[error("preset `{chain}` (chain id {chain_id}) uses dev account {address}, refusing to publish its key")]
+    UsedOutsideDev {
+        /// Name of the chain
+        chain: String,
+        /// Its chain id
+        chain_id: u64,
+        /// The dev account it uses
+        address: Address,
+    },
+}
+
+/// Chain id of the dev genesis; chains under another id don't count as dev chains
+pub const DEV_CHAIN_ID: u64 = 31337;
+
+/// Chain id of the [`GenesisTemplate::PrivateTestnet`] template
+pub const PRIVATE_TESTNET_CHAIN_ID: u64 = 4242;
+
+/// Chain id of the [`GenesisTemplate::Consortium`] template and the
+/// [consortium preset](crate::presets::CONSORTIUM_TEMPLATE)
+pub const CONSORTIUM_CHAIN_ID: u64 = 1337;
+
+/// Base fee of the genesis block unless configured otherwise (0.875 gwei, what a 1 gwei initial
+/// base fee drops to after an empty block)
+pub const DEFAULT_INITIAL_BASE_FEE: u64 = 875_000_000;
+
+/// Default balance for prefunded accounts (10,000 ETH in wei)
+/// 10,000 ETH = 10,000 * 10^18 wei = 10,000,000,000,000,000,000,000 wei
+pub fn default_prefund_balance() -> U256 {
+    U256::from(10_000u64) * U256::from(10u64).pow(U256::from(18u64))
+}
+
+/// Runtime code of a minimal validator set contract for
+/// [`SignerSetSource::Contract`](crate::chainspec::SignerSetSource::Contract) chains.
+///
+/// Storage slot 0 holds the number of validators and slots `1..=n` the validators themselves. The
+/// contract exposes `getValidators() returns (address[])` and `addValidator(address)`, which
+/// appends without any access control, so it is only meant for development chains.
+pub const VALIDATOR_SET_CONTRACT_CODE: &[u8] = &hex!(
+    "60003560e01c8063b7ab4db5146100205780634d238c8e1461005757600080fd5b602060005260005480602052"
+    "60005b8181101561004c578060010154816020026040015260010161002f565b506020026040016000f35b6004"
+    "35600054806001018060005590505500"
+);
+
+/// Standard dev mnemonic accounts (derived from "test test test test test test test test test test test junk")
+pub fn dev_accounts() -> Vec<Address> {
+    vec![
+        address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"),
+        address!("70997970C51812dc3A010C7d01b50e0d17dc79C8"),
+        address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC"),
+        address!("90F79bf6EB2c4f870365E785982E1f101E93b906"),
+        address!("15d34AAf54267DB7D7c367839AAf71A00a2C6A65"),
+        address!("9965507D1a55bcC2695C58ba16FB37d819B0A4dc"),
+        address!("976EA74026E726554dB657fA54763abd0C3a0aa9"),
+        address!("14dC79964da2C08b23698B3D3cc7Ca32193d9955"),
+        address!("23618e81E3f5cdF7f54C3d65f7FBc0aBf5B21E8f"),
+        address!("a0Ee7A142d267C1f36714E4a8F75612F20a79720"),
+        address!("Bcd4042DE499D14e55001CcbB24a551F3b954096"),
+        address!("71bE63f3384f5fb98995898A86B02Fb2426c5788"),
+        address!("FABB0ac9d68B0B445fB7357272Ff202C5651694a"),
+        address!("1CBd3b2770909D4e10f157cABC84C7264073C9Ec"),
+        address!("dF3e18d64BC6A983f673Ab319CCaE4f1a57C7097"),
+        address!("cd3B766CCDd6AE721141F452C550Ca635964ce71"),
+        address!("2546BcD3c84621e976D8185a91A922aE77ECEc30"),
+        address!("bDA5747bFD65F08deb54cb465eB87D40e51B197E"),
+        address!("dD2FD4581271e230360230F9337D5c0430Bf44C0"),
+        address!("8626f6940E2eb28930eFb4CeF49B2d1F2C9C1199"),
+    ]
+}
+
+/// Addresses of the first `count` accounts derived from `mnemonic`, the account index appended to
+/// the derivation `path`
+///
+/// The usual Ethereum accounts are at [`DEV_DERIVATION_PATH`](dev::DEV_DERIVATION_PATH).
+pub fn accounts_from_mnemonic(
+    mnemonic: &str,
+    path: &str,
+    count: usize,
+) -> Result<Vec<Address>, SignerError> {
+    Ok(dev::keys_from_mnemonic(mnemonic, path, count)?.iter().map(|key| key.address()).collect())
+}
+
+/// The first `count` accounts of the dev mnemonic, derived only past the end of [`dev_accounts`]
+pub fn dev_accounts_up_to(count: usize) -> Vec<Address> {
+    let mut accounts = dev_accounts();
+    if count <= accounts.len() {
+        accounts.truncate(count);
+        return accounts
+    }
+    accounts_from_mnemonic(dev::DEV_MNEMONIC, dev::DEV_DERIVATION_PATH, count)
+        .expect("dev mnemonic is valid")
+}
+
+/// Default dev signers (first 3 accounts from dev mnemonic)
+pub fn dev_signers() -> Vec<Address> {
+    dev_accounts().into_iter().take(3).collect()
+}
+
+/// The dev accounts with their private keys, parsed from
+/// [`DEV_PRIVATE_KEYS`](dev::DEV_PRIVATE_KEYS) in the order of [`dev_accounts`]
+///
+/// The keys are public knowledge; whatever these accounts hold on a real network is lost.
+pub fn dev_accounts_with_keys() -> Vec<(Address, B256)> {
+    dev::DEV_PRIVATE_KEYS
+        .iter()
+        .map(|key| {
+            let signer: PrivateKeySigner = key.parse().expect("dev keys are valid");
+            (signer.address(), signer.to_bytes())
+        })
+        .collect()
+}
+
+/// A dev account in the file [`write_dev_accounts_file`] writes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevAccount {
+    /// The account
+    pub address: Address,
+    /// Its private key
+    pub private_key: B256,
+    /// Its balance in the dev genesis
+    pub balance: U256,
+}
+
+/// Write the dev accounts with their private keys and their balances in the
+/// [dev genesis](create_dev_genesis) to `path`, as a JSON array for tools like hardhat and foundry
+/// scripts
+///
+/// Refuses if a registered [preset](crate::presets) under another chain id than [`DEV_CHAIN_ID`]
+/// signs with or funds one of the accounts, as the keys would then secure a real chain.
+pub fn write_dev_accounts_file(path: &Path) -> Result<(), DevAccountsError> {
+    let chains = crate::presets::preset_names().into_iter().filter_map(|name| {
+        let (genesis, poa_config) = crate::presets::preset(&name)?();
+        Some((name, genesis, poa_config))
+    });
+    check_dev_accounts_unused(chains)?;
+
+    let genesis = create_dev_genesis();
+    let accounts: Vec<_> = dev_accounts_with_keys()
+        .into_iter()
+        .map(|(address, private_key)| DevAccount {
+            address,
+            private_key,
+            balance: genesis.alloc.get(&address).map(|account| account.balance).unwrap_or_default(),
+        })
+        .collect();
+    let json = serde_json::to_string_pretty(&accounts).expect("dev accounts serialize");
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Check that none of the named `chains` outside [`DEV_CHAIN_ID`] signs with or funds a dev
+/// account
+fn check_dev_accounts_unused(
+    chains: impl IntoIterator<Item = (String, Genesis, PoaConfig)>,
+) -> Result<(), DevAccountsError> {
+    let dev = dev_accounts();
+    for (chain, genesis, poa_config) in chains {
+        let chain_id = genesis.config.chain_id;
+        if chain_id == DEV_CHAIN_ID {
+            continue
+        }
+        let extra_data_signers = genesis_signers(&genesis.extra_data).unwrap_or_default();
+        let used = genesis
+            .alloc
+            .keys()
+            .chain(&poa_config.signers)
+            .chain(&extra_data_signers)
+            .find(|address| dev.contains(address));
+        if let Some(&address) = used {
+            return Err(DevAccountsError::UsedOutsideDev { chain, chain_id, address })
+        }
+    }
+    Ok(())
+}
+
+/// Create a development genesis configuration
+pub fn create_dev_genesis() -> Genesis {
+    create_genesis(GenesisConfig::dev())
+}
+
+/// Create a development genesis for a chain run under `poa_config`
+pub fn create_dev_genesis_for(poa_config: &PoaConfig) -> Genesis {
+    create_genesis(GenesisConfig::dev().with_poa_config(poa_config))
+}
+
+/// Builds the storage of a genesis contract the way Solidity lays it out
+///
+/// [`slot`](Self::slot) and [`mapping_slot`](Self::mapping_slot) pick the slot the next value goes
+/// to, the first value going to slot 0. Each value moves on to the slot after it, where the next
+/// field of a struct or element of a fixed size array lives.
+///
+/// ```ignore
+/// // uint256 totalSupply at slot 2, mapping(address => uint256) balanceOf at slot 3
+/// let storage = StorageBuilder::new()
+///     .slot(2)
+///     .value_u256(supply)
+///     .mapping_slot(holder, 3)
+///     .value_u256(supply)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageBuilder {
+    storage: BTreeMap<B256, B256>,
+    slot: U256,
+}
+
+impl StorageBuilder {
+    /// Empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the next value to slot `slot`, where a state variable of its own lives
+    pub fn slot(mut self, slot: u64) -> Self {
+        self.slot = U256::from(slot);
+        self
+    }
+
+    /// Write the next value to the entry for `key` of the `mapping(address => ...)` declared at
+    /// slot `base`, which is at `keccak256(key . base)` with both padded to 32 bytes
+    pub fn mapping_slot(mut self, key: Address, base: u64) -> Self {
+        let mut preimage = [0u8; 64];
+        preimage[..32].copy_from_slice(key.into_word().as_slice());
+        preimage[32..].copy_from_slice(&U256::from(base).to_be_bytes::<32>());
+        self.slot = U256::from_be_bytes(keccak256(preimage).0);
+        self
+    }
+
+    /// Write `value` to the current slot and move on to the next one
+    pub fn value_u256(self, value: U256) -> Self {
+        self.value_b256(value.into())
+    }
+
+    /// Write `address` to the current slot, right aligned like Solidity stores it, and move on to
+    /// the next one
+    pub fn value_address(self, address: Address) -> Self {
+        self.value_b256(address.into_word())
+    }
+
+    /// Write the raw word `value` to the current slot and move on to the next one
+    pub fn value_b256(mut self, value: B256) -> Self {
+        self.storage.insert(self.slot.into(), value);
+        self.slot = self.slot.wrapping_add(U256::from(1));
+        self
+    }
+
+    /// The storage, for [`GenesisAccount::storage`] or
+    /// [`with_predeploy`](GenesisConfig::with_predeploy)
+    pub fn build(self) -> BTreeMap<B256, B256> {
+        self.storage
+    }
+}
+
+/// Configuration for creating a genesis
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisConfig {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Gas limit for the genesis block
+    pub gas_limit: u64,
+    /// Accounts to prefund with their balances
+    pub prefunded_accounts: BTreeMap<Address, U256>,
+    /// POA signers (encoded in extra data in ascending order)
+    pub signers: Vec<Address>,
+    /// Block time in seconds
+    pub block_period: u64,
+    /// Epoch length for checkpoint blocks
+    pub epoch: u64,
+    /// Optional extra vanity data (32 bytes)
+    pub vanity: [u8; 32],
+    /// Where to predeploy [`VALIDATOR_SET_CONTRACT_CODE`], seeded with the signers
+    pub validator_set_contract: Option<Address>,
+    /// Where to predeploy the [deposit contract](crate::deposit::DEPOSIT_CONTRACT_CODE)
+    pub deposit_contract: Option<Address>,
+    /// Accounts with code, storage or a nonce, see [`with_predeploy`](Self::with_predeploy)
+    pub predeploys: BTreeMap<Address, GenesisAccount>,
+    /// Hardforks announced in the chain config; they decide which header fields the genesis has
+    pub hardforks: HardforkSchedule,
+    /// Blob counts listed in the `blobSchedule` of the chain config, Ethereum's if unset
+    pub blob_params: Option<BlobScheduleOverride>,
+    /// Base fee of the genesis block if London is active at genesis, the client's default if
+    /// `None`
+    pub initial_base_fee: Option<u64>,
+    /// Timestamp of the genesis block
+    pub timestamp: u64,
+    /// Nonce of the genesis block
+    pub nonce: u64,
+    /// Mix hash of the genesis block
+    pub mix_hash: B256,
+    /// Beneficiary of the genesis block
+    pub coinbase: Address,
+    /// Balance every signer is topped up to, see
+    /// [`with_signer_prefund`](Self::with_signer_prefund)
+    pub signer_prefund: Option<U256>,
+    /// Whether the genesis is a clique genesis, see [`poa_mode`](Self::poa_mode)
+    pub poa_mode: bool,
+}
+
+impl Default for GenesisConfig {
+    fn default() -> Self {
+        Self {
+            chain_id: 31337, // Common local dev chain ID
+            gas_limit: 30_000_000,
+            prefunded_accounts: BTreeMap::new(),
+            signers: vec![],
+            block_period: 12,
+            epoch: 30000,
+            vanity: [0u8; 32],
+            validator_set_contract: None,
+            deposit_contract: None,
+            predeploys: BTreeMap::new(),
+            hardforks: HardforkSchedule::default(),
+            blob_params: None,
+            initial_base_fee: Some(DEFAULT_INITIAL_BASE_FEE),
+            timestamp: 0,
+            nonce: 0,
+            mix_hash: B256::ZERO,
+            coinbase: Address::ZERO,
+            signer_prefund: None,
+            poa_mode: true,
+        }
+    }
+}
+
+/// Deployment shapes [`GenesisConfig::template`] starts a config from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenesisTemplate {
+    /// A chain on one machine: the [dev config](GenesisConfig::dev), signed by the dev signers
+    /// with 2 second blocks and all dev accounts funded
+    LocalDev,
+    /// A testnet shared by a team, with 5 second blocks under [`PRIVATE_TESTNET_CHAIN_ID`]
+    PrivateTestnet {
+        /// Signers sealing the blocks
+        signers: Vec<Address>,
+        /// Account holding the 1,000,000 test ETH handed out to users
+        faucet: Address,
+    },
+    /// A chain run by a group of organisations, with 3 second blocks and a 60M gas limit under
+    /// [`CONSORTIUM_CHAIN_ID`]
+    Consortium {
+        /// Signers sealing the blocks, one or more per member
+        signers: Vec<Address>,
+        /// Account holding the 100,000,000 ETH supply of the chain
+        treasury: Address,
+        /// Start the base fee at zero, for a chain run with
+        /// [`zero_base_fee`](PoaConfig::zero_base_fee) so transactions cost nothing
+        zero_fees: bool,
+    },
+}
+
+impl GenesisConfig {
+    /// Create a development configuration with prefunded accounts
+    pub fn dev() -> Self {
+        Self::dev_with_accounts(dev_accounts().len())
+    }
+
+    /// Create a development configuration with the first `accounts` accounts of the dev mnemonic
+    /// prefunded
+    pub fn dev_with_accounts(accounts: usize) -> Self {
+        let signers = dev_signers();
+
+        let balance = default_prefund_balance();
+        let prefunded =
+            dev_accounts_up_to(accounts).into_iter().map(|account| (account, balance)).collect();
+
+        Self {
+            chain_id: DEV_CHAIN_ID,
+            gas_limit: 30_000_000,
+            prefunded_accounts: prefunded,
+            signers,
+            block_period: 2, // Fast blocks for dev
+            epoch: 30000,
+            vanity: [0u8; 32],
+            validator_set_contract: None,
+            deposit_contract: None,
+            predeploys: BTreeMap::new(),
+            hardforks: HardforkSchedule::default(),
+            blob_params: None,
+            initial_base_fee: Some(DEFAULT_INITIAL_BASE_FEE),
+            timestamp: 0,
+            nonce: 0,
+            mix_hash: B256::ZERO,
+            coinbase: Address::ZERO,
+            signer_prefund: None,
+            poa_mode: true,
+        }
+    }
+
+    /// Create a mainnet-like configuration
+    pub fn mainnet_compatible(chain_id: u64, signers: Vec<Address>) -> Self {
+        Self {
+            chain_id,
+            gas_limit: 30_000_000,
+            prefunded_accounts: BTreeMap::new(),
+            signers,
+            block_period: 12, // Same as Ethereum mainnet
+            epoch: 30000,
+            vanity: [0u8; 32],
+            validator_set_contract: None,
+            deposit_contract: None,
+            predeploys: BTreeMap::new(),
+            hardforks: HardforkSchedule::default(),
+            blob_params: None,
+            initial_base_fee: Some(DEFAULT_INITIAL_BASE_FEE),
+            timestamp: 0,
+            nonce: 0,
+            mix_hash: B256::ZERO,
+            coinbase: Address::ZERO,
+            signer_prefund: None,
+            poa_mode: true,
+        }
+    }
+
+    /// Create the configuration of a common deployment shape, to adjust with the other builder
+    /// methods
+    ///
+    /// Every template installs the [standard predeploys](presets::STANDARD_PREDEPLOYS). The
+    /// testnet and consortium templates fund their signers with 100 ETH each, enough to send
+    /// votes and the odd transaction.
+    ///
+    /// ```ignore
+    /// let local = GenesisConfig::template(GenesisTemplate::LocalDev);
+    ///
+    /// let testnet =
+    ///     GenesisConfig::template(GenesisTemplate::PrivateTestnet { signers, faucet })
+    ///         .with_chain_id(31338);
+    ///
+    /// // A chain spec with `zero_base_fee` keeps the base fee at zero after genesis
+    /// let consortium = GenesisConfig::template(GenesisTemplate::Consortium {
+    ///     signers,
+    ///     treasury,
+    ///     zero_fees: true,
+    /// });
+    /// ```
+    pub fn template(template: GenesisTemplate) -> Self {
+        let ether = U256::from(10u64).pow(U256::from(18u64));
+        let signer_prefund = U256::from(100u64) * ether;
+        let config = match template {
+            GenesisTemplate::LocalDev => Self::dev(),
+            GenesisTemplate::PrivateTestnet { signers, faucet } => {
+                Self::mainnet_compatible(PRIVATE_TESTNET_CHAIN_ID, signers)
+                    .with_block_period(5)
+                    .with_prefunded_account(faucet, U256::from(1_000_000u64) * ether)
+                    .with_signer_prefund(signer_prefund)
+            }
+            GenesisTemplate::Consortium { signers, treasury, zero_fees } => {
+                let config = Self::mainnet_compatible(CONSORTIUM_CHAIN_ID, signers)
+                    .with_block_period(3)
+                    .with_gas_limit(60_000_000)
+                    .with_prefunded_account(treasury, U256::from(100_000_000u64) * ether)
+                    .with_signer_prefund(signer_prefund);
+                if zero_fees {
+                    config.with_initial_base_fee(Some(0))
+                } else {
+                    config
+                }
+            }
+        };
+        config.with_standard_predeploys()
+    }
+
+    /// Recover the config [`create_genesis`] built `genesis` from
+    ///
+    /// Signers come from the extra data, period and epoch from the clique section, hardforks and
+    /// blob counts from the chain config (see [`HardforkSchedule::from_chain_config`] and
+    /// [`BlobScheduleOverride::from_chain_config`]), prefunded accounts from the alloc and the rest
+    /// from the header fields. Signers end up in ascending order, the order
+    /// [`create_genesis`] writes them in.
+    pub fn from_genesis(genesis: &Genesis) -> Result<Self, GenesisError> {
+        let clique = genesis.config.clique.ok_or(GenesisError::MissingField("config.clique"))?;
+        let block_period =
+            clique.period.ok_or(GenesisError::MissingField("config.clique.period"))?;
+        let epoch = clique.epoch.ok_or(GenesisError::MissingField("config.clique.epoch"))?;
+        let extra_data = &genesis.extra_data;
+        let signers = genesis_signers(extra_data)
+            .map_err(|_| GenesisError::InvalidExtraData(extra_data.len()))?;
+        let mut vanity = [0u8; EXTRA_VANITY_LENGTH];
+        vanity.copy_from_slice(&extra_data[..EXTRA_VANITY_LENGTH]);
+
+        let mut config = Self {
+            chain_id: genesis.config.chain_id,
+            gas_limit: genesis.gas_limit,
+            prefunded_accounts: BTreeMap::new(),
+            signers,
+            block_period,
+            epoch,
+            vanity,
+            validator_set_contract: None,
+            deposit_contract: None,
+            predeploys: BTreeMap::new(),
+            hardforks: HardforkSchedule::from_chain_config(&genesis.config),
+            blob_params: BlobScheduleOverride::from_chain_config(&genesis.config),
+            initial_base_fee: genesis.base_fee_per_gas.map(|base_fee| base_fee as u64),
+            timestamp: genesis.timestamp,
+            nonce: genesis.nonce,
+            mix_hash: genesis.mix_hash,
+            coinbase: genesis.coinbase,
+            signer_prefund: None,
+            poa_mode: true,
+        };
+        for (address, account) in &genesis.alloc {
+            match account.code.as_ref().map(|code| &code[..]) {
+                Some(VALIDATOR_SET_CONTRACT_CODE) => config.validator_set_contract = Some(*address),
+                Some(DEPOSIT_CONTRACT_CODE) => config.deposit_contract = Some(*address),
+                Some(_) => {
+                    config.predeploys.insert(*address, account.clone());
+                }
+                None if account.storage.as_ref().is_some_and(|storage| !storage.is_empty()) => {
+                    return Err(GenesisError::UnsupportedAccount(*address))
+                }
+                None if account.nonce.unwrap_or_default() != 0 => {
+                    config.predeploys.insert(*address, account.clone());
+                }
+                None => {
+                    config.prefunded_accounts.insert(*address, account.balance);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read a clique genesis as geth writes and accepts it, see
+    /// [`from_genesis`](Self::from_genesis)
+    ///
+    /// Numbers may be hex or decimal, `extraData` may leave out the `0x` and the optional header
+    /// fields default to zero, so [`create_genesis`] reproduces the genesis hash geth computes.
+    pub fn from_geth_clique_json(json: &str) -> Result<Self, GenesisError> {
+        Self::from_genesis(&serde_json::from_str(json)?)
+    }
+
+    /// Builder method to add a prefunded account
+    pub fn with_prefunded_account(mut self, address: Address, balance: U256) -> Self {
+        self.prefunded_accounts.insert(address, balance);
+        self
+    }
+
+    /// Builder method to add a prefunded account with a balance like `"10000"`, `"10000.5"` or
+    /// `"1_000_000 ETH"`, in ether unless it names another unit (see [`alloc::parse_amount`])
+    pub fn with_prefunded_account_eth(
+        self,
+        address: Address,
+        amount: &str,
+    ) -> Result<Self, GenesisError> {
+        let balance = alloc::parse_amount(amount, Unit::Ether).map_err(|reason| {
+            GenesisError::InvalidAmount { address, amount: amount.to_string(), reason }
+        })?;
+        Ok(self.with_prefunded_account(address, balance))
+    }
+
+    /// Builder method to prefund the accounts listed in an alloc file, in the CSV or JSON format
+    /// [`read_alloc_file`](alloc::read_alloc_file) reads
+    ///
+    /// An address listed twice in the file is an error; entries replace the balance of accounts
+    /// prefunded before.
+    pub fn with_alloc_file(mut self, path: &Path) -> Result<Self, GenesisError> {
+        let accounts = alloc::read_alloc_file(path)?;
+        let count = accounts.len();
+        if self.prefunded_accounts.is_empty() {
+            // Large files would otherwise be copied over account by account
+            self.prefunded_accounts = accounts;
+        } else {
+            self.prefunded_accounts.extend(accounts);
+        }
+        info!(
+            target: "poa::genesis",
+            path = %path.display(),
+            accounts = count,
+            total_supply = %self.total_supply(),
+            "Loaded alloc file"
+        );
+        Ok(self)
+    }
+
+    /// Sum of the balances of all prefunded accounts and predeploys
+    pub fn total_supply(&self) -> U256 {
+        self.prefunded_accounts
+            .values()
+            .chain(self.predeploys.values().map(|account| &account.balance))
+            .sum()
+    }
+
+    /// Builder method to give every signer at least `balance` in the genesis
+    ///
+    /// Signers missing from the alloc are added, signers holding less are topped up and the
+    /// balance of signers holding more is left alone. Without it, [`create_genesis`] warns about
+    /// signers that start with nothing, as they couldn't pay for a single transaction.
+    pub const fn with_signer_prefund(mut self, balance: U256) -> Self {
+        self.signer_prefund = Some(balance);
+        self
+    }
+
+    /// Builder method to choose between a clique genesis, the default, and a plain one
+    ///
+    /// A plain genesis has empty extra data and no `clique` section in its chain config, for a
+    /// vanilla dev chain that still gets the prefunds, predeploys and hardforks of this config.
+    /// [`PoaChainSpec::from_genesis`] rejects it.
+    pub const fn poa_mode(mut self, poa_mode: bool) -> Self {
+        self.poa_mode = poa_mode;
+        self
+    }
+
+    /// Builder method to set signers
+    pub fn with_signers(mut self, signers: Vec<Address>) -> Self {
+        self.signers = signers;
+        self
+    }
+
+    /// Make the [ephemeral signers](crate::signer::SignerManager::ephemeral) the signers, each
+    /// prefunded like a dev account
+    pub fn with_ephemeral_signers(mut self, signers: &[Address]) -> Self {
+        let balance = default_prefund_balance();
+        self.prefunded_accounts.extend(signers.iter().map(|signer| (*signer, balance)));
+        self.with_signers(signers.to_vec())
+    }
+
+    /// Builder method to set chain ID
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Builder method to set block period
+    pub fn with_block_period(mut self, period: u64) -> Self {
+        self.block_period = period;
+        self
+    }
+
+    /// Builder method to set the epoch length
+    ///
+    /// # Panics
+    ///
+    /// If `epoch` is zero, as clique needs at least one block between checkpoints.
+    pub const fn with_epoch(mut self, epoch: u64) -> Self {
+        assert!(epoch > 0, "epoch must be at least one block");
+        self.epoch = epoch;
+        self
+    }
+
+    /// Builder method to set the genesis block gas limit
+    pub const fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Builder method to set the hardforks the chain config announces
+    pub fn with_hardforks(mut self, hardforks: HardforkSchedule) -> Self {
+        self.hardforks = hardforks;
+        self
+    }
+
+    /// Builder method to set the blob counts the chain config announces
+    pub const fn with_blob_params(mut self, blob_params: BlobScheduleOverride) -> Self {
+        self.blob_params = Some(blob_params);
+        self
+    }
+
+    /// Builder method to set the base fee of the genesis block, `None` for the client's default
+    pub const fn with_initial_base_fee(mut self, base_fee: Option<u64>) -> Self {
+        self.initial_base_fee = base_fee;
+        self
+    }
+
+    /// Builder method to set the timestamp of the genesis block
+    pub const fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Builder method to set the nonce of the genesis block
+    pub const fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Builder method to set the mix hash of the genesis block
+    pub const fn with_mix_hash(mut self, mix_hash: B256) -> Self {
+        self.mix_hash = mix_hash;
+        self
+    }
+
+    /// Builder method to set the beneficiary of the genesis block
+    pub const fn with_coinbase(mut self, coinbase: Address) -> Self {
+        self.coinbase = coinbase;
+        self
+    }
+
+    /// Builder method to take the signers, block period, epoch, hardforks and blob counts of
+    /// `poa_config`, so that the genesis passes [`validate_genesis`] against it
+    ///
+    /// A zero base fee config also starts the genesis at a base fee of zero. The config isn't
+    /// validated here, an invalid one is rejected when the chain spec is built.
+    pub fn with_poa_config(self, poa_config: &PoaConfig) -> Self {
+        let initial_base_fee =
+            if poa_config.zero_base_fee { Some(0) } else { self.initial_base_fee };
+        let blob_params = poa_config.blob_params;
+        Self { epoch: poa_config.epoch, initial_base_fee, blob_params, ..self }
+            .with_signers(poa_config.signers.clone())
+            .with_block_period(poa_config.period)
+            .with_hardforks(poa_config.hardforks.clone())
+    }
+
+    /// Builder method to set vanity data
+    pub fn with_vanity(mut self, vanity: [u8; 32]) -> Self {
+        self.vanity = vanity;
+        self
+    }
+
+    /// Builder method to set vanity data from text, see [`BlockSealer::vanity_from_str`]
+    pub fn with_vanity_str(self, vanity: &str) -> Result<Self, GenesisError> {
+        let vanity = BlockSealer::vanity_from_str(vanity)
+            .map_err(|_| GenesisError::VanityTooLong(vanity.len()))?;
+        Ok(self.with_vanity(vanity))
+    }
+
+    /// The vanity data as text, without the zero padding
+    pub fn vanity_as_string(&self) -> String {
+        BlockSealer::vanity_to_string(&self.vanity)
+    }
+
+    /// Builder method to predeploy the validator set contract at `address`
+    pub const fn with_validator_set_contract(mut self, address: Address) -> Self {
+        self.validator_set_contract = Some(address);
+        self
+    }
+
+    /// Builder method to predeploy the deposit contract at `address`
+    pub const fn with_deposit_contract(mut self, address: Address) -> Self {
+        self.deposit_contract = Some(address);
+        self
+    }
+
+    /// Builder method to deploy `code` at `address`, with `storage` written verbatim
+    ///
+    /// A prefunded account at the same address is merged into the predeploy, the balances adding
+    /// up.
+    ///
+    /// # Panics
+    ///
+    /// If `storage` is given without `code`, as nothing could ever read it.
+    pub fn with_predeploy(
+        mut self,
+        address: Address,
+        code: Bytes,
+        storage: BTreeMap<B256, B256>,
+        balance: U256,
+        nonce: u64,
+    ) -> Self {
+        assert!(
+            storage.is_empty() || !code.is_empty(),
+            "predeploy at {address} has storage but no code"
+        );
+        let account = GenesisAccount {
+            balance,
+            nonce: (nonce != 0).then_some(nonce),
+            code: (!code.is_empty()).then_some(code),
+            storage: (!storage.is_empty()).then_some(storage),
+            private_key: None,
+        };
+        self.predeploys.insert(address, account);
+        self
+    }
+
+    /// Builder method to install the [standard predeploys](presets::STANDARD_PREDEPLOYS)
+    pub fn with_standard_predeploys(self) -> Self {
+        presets::STANDARD_PREDEPLOYS.iter().fold(self, |config, predeploy| {
+            // Contracts created by a transaction start at nonce 1 (EIP-161)
+            config.with_predeploy(
+                predeploy.address,
+                Bytes::from_static(predeploy.code),
+                predeploy.storage.iter().copied().collect(),
+                U256::ZERO,
+                1,
+            )
+        })
+    }
+
+    /// Hash of the genesis block [`create_genesis`] builds from this config, for a chain with its
+    /// hardforks
+    ///
+    /// Operators can compare it before launch to confirm they all start from the same genesis.
+    pub fn genesis_hash(&self) -> B256 {
+        let poa_config = PoaConfig { hardforks: self.hardforks.clone(), ..Default::default() };
+        PoaChainSpec::compute_genesis_hash(&create_genesis(self.clone()), &poa_config)
+    }
+}
+
+/// Create a genesis configuration from the config
+pub fn create_genesis(config: GenesisConfig) -> Genesis {
+    let chain_config = chain_config(&config);
+    // geth only accepts the header fields of the forks active at genesis
+    let base_fee_per_gas = config
+        .initial_base_fee
+        .filter(|_| config.hardforks.fork(EthereumHardfork::London).active_at_block(0))
+        .map(u128::from);
+    let has_blob_fields =
+        config.hardforks.fork(EthereumHardfork::Cancun).active_at_timestamp(config.timestamp);
+
+    // Clique lists signers in ascending order, whatever order they were configured in
+    let mut signers = config.signers.clone();
+    signers.sort();
+
+    // Build the extra data field for POA:
+    // Format: [vanity (32 bytes)][signers (N*20 bytes)][signature (65 bytes, all zeros for genesis)]
+    let mut extra_data = Vec::with_capacity(32 + signers.len() * 20 + 65);
+    if config.poa_mode {
+        // Add vanity (32 bytes)
+        extra_data.extend_from_slice(&config.vanity);
+
+        // Add signer addresses
+        for signer in &signers {
+            extra_data.extend_from_slice(signer.as_slice());
+        }
+
+        // Add empty signature (65 bytes of zeros for genesis block)
+        extra_data.extend_from_slice(&[0u8; 65]);
+    }
+
+    // Convert prefunded accounts to genesis alloc format
+    let mut alloc = BTreeMap::new();
+    for (address, balance) in config.prefunded_accounts {
+        alloc.insert(
+            address,
+            GenesisAccount { balance, nonce: None, code: None, storage: None, private_key: None },
+        );
+    }
+
+    // Deploy the configured contracts, merging prefunded accounts at the same address into them
+    for (address, predeploy) in config.predeploys {
+        let account = match alloc.remove(&address) {
+            Some(prefunded) => {
+                warn!(
+                    target: "poa::genesis",
+                    %address,
+                    "Predeploy collides with a prefunded account, merging them"
+                );
+                GenesisAccount {
+                    balance: predeploy.balance.saturating_add(prefunded.balance),
+                    ..predeploy
+                }
+            }
+            None => predeploy,
+        };
+        alloc.insert(address, account);
+    }
+
+    // Predeploy the validator set contract, holding the genesis signers
+    if let Some(contract) = config.validator_set_contract {
+        let storage = signers.iter().fold(
+            StorageBuilder::new().slot(0).value_u256(U256::from(signers.len())),
+            |storage, signer| storage.value_address(*signer),
+        );
+        alloc.insert(
+            contract,
+            GenesisAccount::default()
+                .with_code(Some(VALIDATOR_SET_CONTRACT_CODE.into()))
+                .with_storage(Some(storage.build())),
+        );
+    }
+
+    // Predeploy the deposit contract with the storage its constructor would have written
+    if let Some(contract) = config.deposit_contract {
+        alloc.insert(
+            contract,
+            GenesisAccount::default()
+                .with_code(Some(DEPOSIT_CONTRACT_CODE.into()))
+                .with_storage(Some(deposit_contract_storage())),
+        );
+    }
+
+    // Make sure the signers can pay for their own transactions
+    match config.signer_prefund {
+        Some(prefund) => {
+            for signer in &signers {
+                let account = alloc.entry(*signer).or_default();
+                account.balance = account.balance.max(prefund);
+            }
+        }
+        None if config.poa_mode => {
+            let unfunded: Vec<_> = signers
+                .iter()
+                .filter(|signer| alloc.get(*signer).is_none_or(|account| account.balance.is_zero()))
+                .map(ToString::to_string)
+                .collect();
+            if !unfunded.is_empty() {
+                warn!(
+                    target: "poa::genesis",
+                    signers = %unfunded.join(", "),
+                    "Signers start without a balance, see `GenesisConfig::with_signer_prefund`"
+                );
+            }
+        }
+        None => {}
+    }
+
+    Genesis {
+        config: chain_config,
+        nonce: config.nonce,
+        timestamp: config.timestamp,
+        extra_data: extra_data.into(),
+        gas_limit: config.gas_limit,
+        difficulty: U256::from(1),
+        mix_hash: config.mix_hash,
+        coinbase: config.coinbase,
+        alloc,
+        number: None,
+        parent_hash: None,
+        base_fee_per_gas,
+        excess_blob_gas: has_blob_fields.then_some(0),
+        blob_gas_used: has_blob_fields.then_some(0),
+    }
+}
+
+/// Chain config of the genesis [`create_genesis`] builds: the hardforks of the config and, in
+/// [POA mode](GenesisConfig::poa_mode), a `clique` section, in the form `geth init` expects
+///
+/// geth refuses a genesis that schedules a fork with blobs without a `blobSchedule` entry for it,
+/// so every scheduled fork with blobs is listed, with the configured blob counts or mainnet's.
+fn chain_config(config: &GenesisConfig) -> ChainConfig {
+    let hardforks = &config.hardforks;
+    let activation = |fork| match hardforks.fork(fork) {
+        ForkCondition::Block(block) => Some(block),
+        ForkCondition::Timestamp(timestamp) => Some(timestamp),
+        ForkCondition::TTD { activation_block_number, .. } => Some(activation_block_number),
+        ForkCondition::Never => None,
+    };
+    let terminal_total_difficulty = match hardforks.fork(EthereumHardfork::Paris) {
+        ForkCondition::TTD { total_difficulty, .. } => Some(total_difficulty),
+        _ => None,
+    };
+    let blob_counts = config.blob_params.unwrap_or_default();
+    let blob_schedule = [
+        (EthereumHardfork::Cancun, BlobParams::cancun(), blob_counts.cancun),
+        (EthereumHardfork::Prague, BlobParams::prague(), blob_counts.prague),
+        (EthereumHardfork::Osaka, BlobParams::osaka(), None),
+    ]
+    .into_iter()
+    .filter(|(fork, ..)| activation(*fork).is_some())
+    .map(|(fork, params, counts)| {
+        (fork.name().to_lowercase(), counts.map_or(params, |counts| counts.apply(params)))
+    })
+    .collect();
+
+    ChainConfig {
+        chain_id: config.chain_id,
+        homestead_block: activation(EthereumHardfork::Homestead),
+        eip150_block: activation(EthereumHardfork::Tangerine),
+        eip155_block: activation(EthereumHardfork::SpuriousDragon),
+        eip158_block: activation(EthereumHardfork::SpuriousDragon),
+        byzantium_block: activation(EthereumHardfork::Byzantium),
+        constantinople_block: activation(EthereumHardfork::Constantinople),
+        petersburg_block: activation(EthereumHardfork::Petersburg),
+        istanbul_block: activation(EthereumHardfork::Istanbul),
+        muir_glacier_block: activation(EthereumHardfork::MuirGlacier),
+        berlin_block: activation(EthereumHardfork::Berlin),
+        london_block: activation(EthereumHardfork::London),
+        arrow_glacier_block: activation(EthereumHardfork::ArrowGlacier),
+        gray_glacier_block: activation(EthereumHardfork::GrayGlacier),
+        terminal_total_difficulty,
+        terminal_total_difficulty_passed: terminal_total_difficulty.is_some(),
+        shanghai_time: activation(EthereumHardfork::Shanghai),
+        cancun_time: activation(EthereumHardfork::Cancun),
+        prague_time: activation(EthereumHardfork::Prague),
+        osaka_time: activation(EthereumHardfork::Osaka),
+        clique: config.poa_mode.then_some(CliqueConfig {
+            period: Some(config.block_period),
+            epoch: Some(config.epoch),
+        }),
+        blob_schedule,
+        ..Default::default()
+    }
+}
+
+/// Helper to serialize genesis to JSON (for use with other tools)
+///
+/// The whole file is built in memory, see [`write_genesis_file_streaming`] for large allocs.
+pub fn genesis_to_json(genesis: &Genesis) -> String {
+    serde_json::to_string_pretty(genesis).expect("genesis serialization should not fail")
+}
+
+/// Write `genesis` to `path` without ever leaving a partial file behind
+///
+/// The JSON goes to a temporary file in the same directory, which is synced and then renamed to
+/// `path`. An existing file is an [`io::ErrorKind::AlreadyExists`] error unless `overwrite` is set,
+/// in which case it is kept as [`genesis_backup_path`] before being replaced.
+pub fn write_genesis_file(genesis: &Genesis, path: &Path, overwrite: bool) -> io::Result<()> {
+    if !overwrite && path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists, refusing to overwrite it", path.display()),
+        ))
+    }
+    replace_file(path, |file| file.write_all(genesis_to_json(genesis).as_bytes()))
+}
+
+/// Write `genesis` to `path` like [`write_genesis_file`] with `overwrite` set, serializing it
+/// straight into the file instead of into a string first
+///
+/// Memory use stays flat however large the alloc is, so this is the way to write the genesis of
+/// an airdrop to millions of accounts. Without `pretty` the JSON goes on a single line, which also
+/// makes the file about half the size.
+pub fn write_genesis_file_streaming(
+    genesis: &Genesis,
+    path: &Path,
+    pretty: bool,
+) -> io::Result<()> {
+    replace_file(path, |file| {
+        let mut writer = io::BufWriter::new(file);
+        if pretty {
+            serde_json::to_writer_pretty(&mut writer, genesis)?;
+        } else {
+            serde_json::to_writer(&mut writer, genesis)?;
+        }
+        writer.flush()
+    })
+}
+
+/// Replace `path` with the content `write` puts into a temporary file next to it, keeping the
+/// previous file as [`genesis_backup_path`]
+fn replace_file(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> io::Result<()>,
+) -> io::Result<()> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a file", path.display()))
+    })?;
+    let temp_path =
+        path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), std::process::id()));
+
+    let written = write_synced(&temp_path, write).and_then(|()| {
+        if path.exists() {
+            // Copied rather than moved, so that `path` is never missing
+            fs::copy(path, genesis_backup_path(path))?;
+        }
+        fs::rename(&temp_path, path)
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written?;
+    sync_parent_dir(path)
+}
+
+/// Where [`write_genesis_file`] keeps the file it replaces: `path` with `.bak` appended
+pub fn genesis_backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    backup.into()
+}
+
+/// Create `path`, fill it with `write` and flush it to disk
+fn write_synced(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+    write(&mut file)?;
+    file.sync_all()
+}
+
+/// Flush the directory entry of `path` to disk, so a rename survives a crash
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::File::open(dir)?.sync_all(),
+        _ => fs::File::open(".")?.sync_all(),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Check that `genesis` is the first block of a chain run under `poa_config`
+///
+/// The extra data must list the configured signers (in any order) followed by an all zero seal,
+/// the difficulty must be 1 and a `clique` section, if there is one, must agree on period and
+/// epoch. A chain that switches to POA at a later block has no POA genesis to check.
+pub fn validate_genesis(
+    genesis: &Genesis,
+    poa_config: &PoaConfig,
+) -> Result<(), GenesisValidationError> {
+    if poa_config.poa_activation_block.is_some_and(|block| block > 0) {
+        return Ok(())
+    }
+
+    let extra_data = &genesis.extra_data;
+    let mut signers = genesis_signers(extra_data)
+        .map_err(|_| GenesisValidationError::InvalidExtraData(extra_data.len()))?;
+    signers.sort();
+    let mut config = poa_config.signers.clone();
+    config.sort();
+    if signers != config {
+        return Err(GenesisValidationError::SignerMismatch { genesis: signers, config })
+    }
+    if extra_data[extra_data.len() - EXTRA_SEAL_LENGTH..].iter().any(|byte| *byte != 0) {
+        return Err(GenesisValidationError::NonZeroSeal)
+    }
+    if genesis.difficulty != U256::from(1) {
+        return Err(GenesisValidationError::InvalidDifficulty(genesis.difficulty))
+    }
+
+    if let Some(clique) = &genesis.config.clique {
+        if let Some(period) = clique.period.filter(|period| *period != poa_config.period) {
+            return Err(GenesisValidationError::PeriodMismatch {
+                genesis: period,
+                config: poa_config.period,
+            })
+        }
+        if let Some(epoch) = clique.epoch.filter(|epoch| *epoch != poa_config.epoch) {
+            return Err(GenesisValidationError::EpochMismatch {
+                genesis: epoch,
+                config: poa_config.epoch,
+            })
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a genesis file written by [`write_genesis_file`], or edited by hand
+pub fn read_genesis_file(path: &Path) -> Result<Genesis, GenesisError> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Root of the state trie the genesis alloc makes up, the `stateRoot` of the genesis header
+///
+/// Each account is committed to with its nonce, balance, code hash and the root of its storage
+/// trie, leaving out zero storage slots as the EVM would never have written them.
+pub fn compute_state_root(genesis: &Genesis) -> B256 {
+    let accounts = genesis.alloc.iter().map(|(address, account)| {
+        let storage = account.storage.iter().flatten().filter(|(_, value)| !value.is_zero());
+        let account = TrieAccount {
+            nonce: account.nonce.unwrap_or_default(),
+            balance: account.balance,
+            storage_root: storage_root_unhashed(
+                storage.map(|(slot, value)| (*slot, U256::from_be_bytes(value.0))),
+            ),
+            code_hash: account.code.as_ref().map_or(KECCAK_EMPTY, keccak256),
+        };
+        (*address, account)
+    });
+    state_root_unhashed(accounts)
+}
+
+/// Chain id, genesis hash, state root and the extra data split into vanity, signers and seal
+pub fn genesis_summary(chain: &PoaChainSpec) -> String {
+    let header = chain.genesis_header();
+    let extra_data = &header.extra_data;
+    let mut summary = format!(
+        "Chain id:     {}\nGenesis hash: {}\nState root:   {}\nExtra data:   {} bytes\n",
+        chain.chain().id(),
+        chain.genesis_hash(),
+        header.state_root,
+        extra_data.len()
+    );
+    match genesis_signers(extra_data) {
+        Ok(signers) => {
+            let vanity = &extra_data[..EXTRA_VANITY_LENGTH];
+            let seal = &extra_data[extra_data.len() - EXTRA_SEAL_LENGTH..];
+            let _ = writeln!(summary, "  Vanity:     {}", hex::encode(vanity));
+            let _ = writeln!(summary, "  Signers ({}):", signers.len());
+            for signer in signers {
+                let _ = writeln!(summary, "    {signer}");
+            }
+            let _ = writeln!(summary, "  Seal:       {}", hex::encode(seal));
+        }
+        Err(_) => {
+            let _ = writeln!(summary, "  Raw:        {}", hex::encode(extra_data));
+        }
+    }
+    summary
+}
+
+/// Print the [`genesis_summary`] of `chain` to stdout
+pub fn print_genesis_summary(chain: &PoaChainSpec) {
+    print!("{}", genesis_summary(chain));
+}
+
+/// How many holders [`AllocationReport`] lists
+pub const LARGEST_HOLDERS: usize = 10;
+
+/// Sum of the balances of all genesis accounts, `None` if it exceeds 2^256 - 1
+pub fn total_allocated(genesis: &Genesis) -> Option<U256> {
+    genesis.alloc.values().try_fold(U256::ZERO, |total, account| total.checked_add(account.balance))
+}
+
+/// Who holds the premine of a genesis, see [`allocation_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllocationReport {
+    /// Number of accounts in the alloc
+    pub accounts: usize,
+    /// Sum of all balances, `None` if it overflows
+    pub total: Option<U256>,
+    /// The [`LARGEST_HOLDERS`] largest balances, largest first
+    pub largest_holders: Vec<(Address, U256)>,
+    /// The signers in the extra data that hold a balance
+    pub funded_signers: Vec<Address>,
+    /// Sum of the balances of the signers
+    pub signer_balance: U256,
+}
+
+impl AllocationReport {
+    /// Share of the total the signers hold, in basis points
+    pub fn signer_share_bps(&self) -> Option<U256> {
+        let total = self.total.filter(|total| !total.is_zero())?;
+        let bps = U256::from(10_000);
+        // Balances close to 2^256 can't be scaled up first, so scale the total down instead
+        Some(match self.signer_balance.checked_mul(bps) {
+            Some(scaled) => scaled / total,
+            None => self.signer_balance / (total / bps),
+        })
+    }
+}
+
+impl fmt::Display for AllocationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Accounts:     {}", self.accounts)?;
+        match self.total {
+            Some(total) => writeln!(f, "Total:        {} ETH", format_ether(total))?,
+            None => writeln!(f, "Total:        overflows 256 bits")?,
+        }
+        write!(
+            f,
+            "Signers:      {} ETH across {} accounts",
+            format_ether(self.signer_balance),
+            self.funded_signers.len()
+        )?;
+        match self.signer_share_bps() {
+            Some(bps) => writeln!(f, " ({}.{:02}%)", bps / U256::from(100), bps % U256::from(100))?,
+            None => writeln!(f)?,
+        }
+        writeln!(f, "Largest holders:")?;
+        for (address, balance) in &self.largest_holders {
+            let signer = if self.funded_signers.contains(address) { " (signer)" } else { "" };
+            writeln!(f, "  {address} {} ETH{signer}", format_ether(*balance))?;
+        }
+        Ok(())
+    }
+}
+
+/// Account count, total, largest holders and the signers' share of the alloc of `genesis`
+pub fn allocation_report(genesis: &Genesis) -> AllocationReport {
+    let mut holders: Vec<_> =
+        genesis.alloc.iter().map(|(address, account)| (*address, account.balance)).collect();
+    // Largest first, ties in address order
+    holders.sort_by(|(a, a_balance), (b, b_balance)| b_balance.cmp(a_balance).then(a.cmp(b)));
+    holders.truncate(LARGEST_HOLDERS);
+
+    let signers = genesis_signers(&genesis.extra_data).unwrap_or_default();
+    let funded_signers: Vec<_> = signers
+        .into_iter()
+        .filter(|signer| {
+            genesis.alloc.get(signer).is_some_and(|account| !account.balance.is_zero())
+        })
+        .collect();
+    let signer_balance = funded_signers
+        .iter()
+        .fold(U256::ZERO, |total, signer| total.saturating_add(genesis.alloc[signer].balance));
+
+    AllocationReport {
+        accounts: genesis.alloc.len(),
+        total: total_allocated(genesis),
+        largest_holders: holders,
+        funded_signers,
+        signer_balance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::{BlobCounts, PoaChainSpecError, PoaConfigError},
+        consensus::PoaConsensus,
+    };
+    use alloy_primitives::b256;
+
+    #[test]
+    fn test_dev_genesis_creation() {
+        let genesis = create_dev_genesis();
+
+        // Verify chain ID
+        assert_eq!(genesis.config.chain_id, 31337);
+
+        // Verify accounts are prefunded
+        assert!(!genesis.alloc.is_empty());
+        assert_eq!(genesis.alloc.len(), 20); // 20 dev accounts
+
+        // Verify extra data contains signers
+        assert!(genesis.extra_data.len() >= 32 + 65); // At least vanity + seal
+    }
+
+    /// The POA config a chain built from `config` runs under
+    fn template_poa_config(config: &GenesisConfig) -> PoaConfig {
+        PoaConfig {
+            period: config.block_period,
+            epoch: config.epoch,
+            signers: config.signers.clone(),
+            zero_base_fee: config.initial_base_fee == Some(0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_templates() {
+        let ether = U256::from(10u64).pow(U256::from(18u64));
+        let signers: Vec<_> = (1..=3).map(Address::with_last_byte).collect();
+        let faucet = Address::with_last_byte(0xfa);
+        let treasury = Address::with_last_byte(0x7e);
+        let testnet = GenesisTemplate::PrivateTestnet { signers: signers.clone(), faucet };
+        let consortium = |zero_fees| GenesisTemplate::Consortium {
+            signers: signers.clone(),
+            treasury,
+            zero_fees,
+        };
+
+        for template in
+            [GenesisTemplate::LocalDev, testnet.clone(), consortium(true), consortium(false)]
+        {
+            let config = GenesisConfig::template(template.clone());
+            let poa_config = template_poa_config(&config);
+            let genesis = create_genesis(config);
+            validate_genesis(&genesis, &poa_config).unwrap();
+            for predeploy in presets::STANDARD_PREDEPLOYS {
+                assert!(genesis.alloc[&predeploy.address].code.is_some(), "{template:?}");
+            }
+            PoaChainSpec::try_new(genesis, poa_config).unwrap();
+        }
+
+        let local = GenesisConfig::template(GenesisTemplate::LocalDev);
+        assert_eq!((local.chain_id, local.block_period), (DEV_CHAIN_ID, 2));
+        assert_eq!(local.signers, dev_signers());
+        assert_eq!(local.prefunded_accounts.len(), dev_accounts().len());
+
+        let config = GenesisConfig::template(testnet.clone());
+        assert_eq!((config.chain_id, config.block_period), (PRIVATE_TESTNET_CHAIN_ID, 5));
+        let genesis = create_genesis(config);
+        assert_eq!(genesis.alloc[&faucet].balance, U256::from(1_000_000u64) * ether);
+        for signer in &signers {
+            assert_eq!(genesis.alloc[signer].balance, U256::from(100u64) * ether);
+        }
+
+        let config = GenesisConfig::template(consortium(true));
+        assert_eq!((config.chain_id, config.block_period), (CONSORTIUM_CHAIN_ID, 3));
+        assert_eq!(config.gas_limit, 60_000_000);
+        let poa_config = template_poa_config(&config);
+        let genesis = create_genesis(config);
+        assert_eq!(genesis.base_fee_per_gas, Some(0));
+        assert_eq!(genesis.alloc[&treasury].balance, U256::from(100_000_000u64) * ether);
+        assert!(PoaChainSpec::try_new(genesis, poa_config).unwrap().is_zero_base_fee());
+        let genesis = create_genesis(GenesisConfig::template(consortium(false)));
+        assert_eq!(genesis.base_fee_per_gas, Some(u128::from(DEFAULT_INITIAL_BASE_FEE)));
+
+        // The builders still apply on top
+        let config = GenesisConfig::template(testnet).with_chain_id(31338).with_block_period(1);
+        assert_eq!((config.chain_id, config.block_period), (31338, 1));
+        assert_eq!(config.prefunded_accounts[&faucet], U256::from(1_000_000u64) * ether);
+    }
+
+    #[test]
+    fn test_storage_builder() {
+        let holder = dev_accounts()[0];
+        let supply = U256::from(1000);
+        let storage = StorageBuilder::new()
+            .slot(2)
+            .value_u256(supply)
+            .mapping_slot(holder, 3)
+            .value_u256(supply)
+            .value_address(holder)
+            .build();
+
+        // `balanceOf[holder]` of WETH9, which declares `balanceOf` at slot 3
+        let balance_slot =
+            b256!("c651ee22c6951bb8b5bd29e8210fb394645a94315fe10eff2cc73de1aa75c137");
+        let next_slot = B256::from(U256::from_be_bytes(balance_slot.0) + U256::from(1));
+        assert_eq!(
+            storage,
+            BTreeMap::from([
+                (B256::with_last_byte(2), B256::from(supply)),
+                (balance_slot, B256::from(supply)),
+                (next_slot, holder.into_word()),
+            ])
+        );
+        let zero_key = StorageBuilder::new().mapping_slot(Address::ZERO, 0).value_u256(supply);
+        assert_eq!(
+            zero_key.build().into_keys().collect::<Vec<_>>(),
+            [b256!("ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5")]
+        );
+    }
+
+    #[test]
+    fn test_dev_accounts_with_keys() {
+        let accounts = dev_accounts_with_keys();
+        assert_eq!(accounts.len(), 20);
+        let addresses: Vec<_> = accounts.iter().map(|(address, _)| *address).collect();
+        assert_eq!(addresses, dev_accounts());
+        let derived =
+            dev::keys_from_mnemonic(dev::DEV_MNEMONIC, dev::DEV_DERIVATION_PATH, 20).unwrap();
+        for ((address, key), signer) in accounts.iter().zip(derived) {
+            assert_eq!((signer.address(), signer.to_bytes()), (*address, *key));
+        }
+    }
+
+    #[test]
+    fn test_write_dev_accounts_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("accounts.json");
+        write_dev_accounts_file(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        let accounts: Vec<DevAccount> = serde_json::from_str(&json).unwrap();
+        assert_eq!(accounts.len(), 20);
+        for (account, (address, key)) in accounts.iter().zip(dev_accounts_with_keys()) {
+            assert_eq!((account.address, account.private_key), (address, key));
+            assert_eq!(account.balance, default_prefund_balance());
+        }
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json[0]["address"], "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+        assert_eq!(
+            json[0]["privateKey"],
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+        );
+
+        // A chain of its own signed by a dev account gives the keys away
+        let signer = dev_accounts()[5];
+        let poa_config = PoaConfig { signers: vec![signer], ..Default::default() };
+        let genesis = create_genesis(
+            GenesisConfig::default().with_chain_id(4242).with_poa_config(&poa_config),
+        );
+        let chain = ("prod".to_string(), genesis.clone(), poa_config.clone());
+        let err = check_dev_accounts_unused([chain]).unwrap_err();
+        assert!(matches!(
+            err,
+            DevAccountsError::UsedOutsideDev { chain_id: 4242, address, .. } if address == signer
+        ));
+        assert!(err.to_string().starts_with("preset `prod` (chain id 4242)"), "{err}");
+        let mut dev_chain = genesis;
+        dev_chain.config.chain_id = DEV_CHAIN_ID;
+        check_dev_accounts_unused([("dev".to_string(), dev_chain, poa_config)]).unwrap();
+    }
+
+    #[test]
+    fn test_accounts_from_mnemonic() {
+        let derived =
+            accounts_from_mnemonic(dev::DEV_MNEMONIC, dev::DEV_DERIVATION_PATH, 20).unwrap();
+        assert_eq!(derived, dev_accounts());
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                        abandon abandon about";
+        let custom = accounts_from_mnemonic(mnemonic, dev::DEV_DERIVATION_PATH, 3).unwrap();
+        assert_eq!(custom[0], address!("9858EfFD232B4033E47d90003D41EC34EcaEda94"));
+        assert_eq!(custom, accounts_from_mnemonic(mnemonic, dev::DEV_DERIVATION_PATH, 3).unwrap());
+        assert!(custom.iter().all(|account| !derived.contains(account)));
+        // Another path gives other accounts
+        let other_path = accounts_from_mnemonic(mnemonic, "m/44'/60'/1'/0/", 3).unwrap();
+        assert!(other_path.iter().all(|account| !custom.contains(account)));
+
+        let config = GenesisConfig::dev_with_accounts(25);
+        let prefunded: Vec<_> = config.prefunded_accounts.keys().copied().collect();
+        let mut expected = dev_accounts_up_to(25);
+        assert_eq!(expected[..20], derived);
+        expected.sort();
+        assert_eq!(prefunded, expected);
+        assert_eq!(GenesisConfig::dev_with_accounts(2).prefunded_accounts.len(), 2);
+    }
+
+    #[test]
+    fn test_custom_genesis() {
+        let signer = address!("0000000000000000000000000000000000000001");
+        let funded = address!("0000000000000000000000000000000000000002");
+
+        let config = GenesisConfig::default()
+            .with_chain_id(12345)
+            .with_signers(vec![signer])
+            .with_prefunded_account(funded, U256::from(1000));
+
+        let genesis = create_genesis(config);
+
+        assert_eq!(genesis.config.chain_id, 12345);
+        assert!(genesis.alloc.contains_key(&funded));
+        assert_eq!(genesis.alloc.get(&funded).unwrap().balance, U256::from(1000));
+    }
+
+    #[test]
+    fn test_epoch_and_gas_limit_round_trip() {
+        let signer = dev_accounts()[0];
+        let config = GenesisConfig::dev()
+            .with_signers(vec![signer])
+            .with_epoch(100)
+            .with_gas_limit(60_000_000);
+        let genesis = create_genesis(config);
+        assert_eq!(genesis.gas_limit, 60_000_000);
+
+        let chain = std::sync::Arc::new(PoaChainSpec::from_genesis(genesis).unwrap());
+        assert_eq!(chain.epoch(), 100);
+        let consensus = PoaConsensus::new(chain);
+        assert!(consensus.is_epoch_block(100));
+        assert!(!consensus.is_epoch_block(150));
+    }
+
+    #[test]
+    #[should_panic(expected = "epoch must be at least one block")]
+    fn test_zero_epoch_panics() {
+        let _ = GenesisConfig::dev().with_epoch(0);
+    }
+
+    #[test]
+    fn test_genesis_json_serialization() {
+        let genesis = create_dev_genesis();
+        let json = genesis_to_json(&genesis);
+
+        // Verify it's valid JSON
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed.is_object());
+    }
+
+    #[test]
+    fn test_validator_set_contract_predeploy() {
+        let contract = address!("0000000000000000000000000000000000001000");
+        let signers = dev_signers();
+        let genesis = create_genesis(
+            GenesisConfig::default()
+                .with_signers(signers.clone())
+                .with_validator_set_contract(contract),
+        );
+
+        let account = &genesis.alloc[&contract];
+        assert_eq!(account.code.as_ref().map(|code| &code[..]), Some(VALIDATOR_SET_CONTRACT_CODE));
+        let storage = account.storage.as_ref().unwrap();
+        assert_eq!(storage[&B256::ZERO], B256::from(U256::from(3)));
+        // Stored in ascending order, like the extra data
+        let highest = signers.iter().max().unwrap();
+        assert_eq!(storage[&B256::from(U256::from(3))], highest.into_word());
+    }
+
+    /// Hash of the dev genesis; changes here change every dev chain
+    const DEV_GENESIS_HASH: B256 =
+        b256!("f2a46df57cdb90eee274b86d0c7e3218b29206a17081bd5d86b829c8aee3998f");
+
+    #[test]
+    fn test_dev_genesis_hash() {
+        assert_eq!(GenesisConfig::dev().genesis_hash(), DEV_GENESIS_HASH);
+        assert_eq!(PoaChainSpec::dev_chain().genesis_hash(), DEV_GENESIS_HASH);
+
+        // The chain id isn't part of the header, the state is
+        let config = GenesisConfig::dev().with_chain_id(4242);
+        assert_eq!(config.genesis_hash(), DEV_GENESIS_HASH);
+        let config = config.with_prefunded_account(Address::repeat_byte(0x42), U256::from(1));
+        let poa_config = PoaConfig { period: 2, signers: dev_signers(), ..Default::default() };
+        let chain = PoaChainSpec::new(create_genesis(config.clone()), poa_config);
+        assert_eq!(config.genesis_hash(), chain.genesis_hash());
+        assert_ne!(config.genesis_hash(), DEV_GENESIS_HASH);
+    }
+
+    /// State root of the dev genesis; changes when the dev accounts or their balances do
+    const DEV_STATE_ROOT: B256 =
+        b256!("829ff6f5b5116bc96a7415872fed27f243e557a92cb82f69f89f20e8f8087874");
+
+    #[test]
+    fn test_compute_state_root() {
+        let chain = PoaChainSpec::dev_chain();
+        assert_eq!(compute_state_root(chain.genesis()), DEV_STATE_ROOT);
+        assert_eq!(chain.genesis_header().state_root, DEV_STATE_ROOT);
+
+        let goerli: Genesis =
+            serde_json::from_str(include_str!("../../testdata/goerli_genesis.json")).unwrap();
+        assert_eq!(
+            compute_state_root(&goerli),
+            b256!("5d6cded585e73c4e322c30c2f782a336316f17dd85a4863b9d838d2d4b8b3008")
+        );
+
+        // Code and storage count, storage slots set to zero don't
+        let contract = Address::repeat_byte(0x42);
+        let storage = BTreeMap::from([(B256::ZERO, B256::with_last_byte(1))]);
+        let config = GenesisConfig::dev().with_predeploy(
+            contract,
+            Bytes::from_static(&[0x60, 0x00]),
+            storage,
+            U256::ZERO,
+            1,
+        );
+        let genesis = create_genesis(config);
+        assert_ne!(compute_state_root(&genesis), DEV_STATE_ROOT);
+        let mut zero_slot = genesis.clone();
+        let storage = zero_slot.alloc.get_mut(&contract).unwrap().storage.as_mut().unwrap();
+        storage.insert(B256::with_last_byte(1), B256::ZERO);
+        assert_eq!(compute_state_root(&zero_slot), compute_state_root(&genesis));
+        let poa_config = PoaConfig { period: 2, signers: dev_signers(), ..Default::default() };
+        let chain = PoaChainSpec::new(genesis.clone(), poa_config);
+        assert_eq!(chain.genesis_header().state_root, compute_state_root(&genesis));
+    }
+
+    #[test]
+    fn test_validate_genesis() {
+        let chain = PoaChainSpec::dev_chain();
+        let (genesis, poa_config) = (chain.genesis(), chain.poa_config());
+        assert_eq!(validate_genesis(genesis, poa_config), Ok(()));
+
+        // The order of the configured signers doesn't matter, the set does
+        let reversed = poa_config.signers.iter().rev().copied().collect();
+        let config = PoaConfig { signers: reversed, ..poa_config.clone() };
+        assert_eq!(validate_genesis(genesis, &config), Ok(()));
+        let mut signers = poa_config.signers.clone();
+        signers[0] = dev_accounts()[5];
+        let config = PoaConfig { signers: signers.clone(), ..poa_config.clone() };
+        signers.sort();
+        assert_eq!(
+            validate_genesis(genesis, &config),
+            Err(GenesisValidationError::SignerMismatch {
+                genesis: poa_config.signers.clone(),
+                config: signers,
+            })
+        );
+        assert!(matches!(
+            PoaChainSpec::try_new(genesis.clone(), config),
+            Err(PoaConfigError::Genesis(GenesisValidationError::SignerMismatch { .. }))
+        ));
+
+        // A seal one byte short leaves a signer list that isn't a whole number of addresses
+        let mut short_seal = genesis.clone();
+        short_seal.extra_data = genesis.extra_data[..genesis.extra_data.len() - 1].to_vec().into();
+        assert_eq!(
+            validate_genesis(&short_seal, poa_config),
+            Err(GenesisValidationError::InvalidExtraData(genesis.extra_data.len() - 1))
+        );
+
+        let mut sealed = genesis.clone();
+        let mut extra_data = genesis.extra_data.to_vec();
+        *extra_data.last_mut().unwrap() = 1;
+        sealed.extra_data = extra_data.into();
+        assert_eq!(validate_genesis(&sealed, poa_config), Err(GenesisValidationError::NonZeroSeal));
+
+        let difficult = Genesis { difficulty: U256::from(2), ..genesis.clone() };
+        assert_eq!(
+            validate_genesis(&difficult, poa_config),
+            Err(GenesisValidationError::InvalidDifficulty(U256::from(2)))
+        );
+
+        let slower = PoaConfig { period: 5, ..poa_config.clone() };
+        assert_eq!(
+            validate_genesis(genesis, &slower),
+            Err(GenesisValidationError::PeriodMismatch { genesis: 2, config: 5 })
+        );
+        let shorter = PoaConfig { epoch: 100, ..poa_config.clone() };
+        assert_eq!(
+            validate_genesis(genesis, &shorter),
+            Err(GenesisValidationError::EpochMismatch { genesis: 30000, config: 100 })
+        );
+
+        // Before POA activates the genesis is not a POA block
+        let later = PoaConfig { poa_activation_block: Some(10), ..slower };
+        assert_eq!(validate_genesis(&difficult, &later), Ok(()));
+    }
+
+    #[test]
+    fn test_genesis_summary() {
+        let chain = PoaChainSpec::dev_chain();
+        let summary = genesis_summary(&chain);
+        assert!(summary.starts_with("Chain id:     31337\n"), "{summary}");
+        assert!(summary.contains(&format!("Genesis hash: {DEV_GENESIS_HASH}")), "{summary}");
+        assert!(summary.contains("Extra data:   157 bytes"), "{summary}");
+        assert!(summary.contains("  Signers (3):"), "{summary}");
+        for signer in dev_signers() {
+            assert!(summary.contains(&format!("    {signer}\n")), "{summary}");
+        }
+    }
+
+    #[test]
+    fn test_genesis_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genesis.json");
+        let mut signers = dev_signers();
+        signers.reverse();
+        let config = GenesisConfig::dev()
+            .with_signers(signers)
+            .with_chain_id(4242)
+            .with_vanity([0x11; 32])
+            .with_timestamp(1_700_000_000)
+            .with_nonce(42)
+            .with_mix_hash(B256::repeat_byte(0x22))
+            .with_coinbase(address!("0000000000000000000000000000000000c0ffee"))
+            .with_validator_set_contract(address!("0000000000000000000000000000000000001000"))
+            .with_deposit_contract(address!("4242424242424242424242424242424242424242"));
+        write_genesis_file(&create_genesis(config.clone()), &path, false).unwrap();
+
+        let read = GenesisConfig::from_genesis(&read_genesis_file(&path).unwrap()).unwrap();
+        let mut expected = config;
+        expected.signers.sort();
+        assert_eq!(read, expected);
+    }
+
+    #[test]
+    fn test_genesis_file_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genesis.json");
+        assert!(matches!(read_genesis_file(&path), Err(GenesisError::Io(_))));
+        std::fs::write(&path, r#"{"config": {"chainId": "none"}}"#).unwrap();
+        assert!(matches!(read_genesis_file(&path), Err(GenesisError::Json(_))));
+
+        let genesis = create_dev_genesis();
+        let mut no_epoch = genesis.clone();
+        no_epoch.config.clique.as_mut().unwrap().epoch = None;
+        let err = GenesisConfig::from_genesis(&no_epoch).unwrap_err();
+        assert_eq!(err.to_string(), "genesis is missing `config.clique.epoch`");
+
+        let mut truncated = genesis.clone();
+        truncated.extra_data = genesis.extra_data[..96].to_vec().into();
+        assert!(matches!(
+            GenesisConfig::from_genesis(&truncated),
+            Err(GenesisError::InvalidExtraData(96))
+        ));
+
+        let mut storage_only = genesis;
+        let account = dev_accounts()[0];
+        storage_only.alloc.get_mut(&account).unwrap().storage =
+            Some(BTreeMap::from([(B256::ZERO, B256::with_last_byte(1))]));
+        assert!(matches!(
+            GenesisConfig::from_genesis(&storage_only),
+            Err(GenesisError::UnsupportedAccount(address)) if address == account
+        ));
+    }
+
+    #[test]
+    fn test_write_genesis_file_keeps_existing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("genesis.json");
+        let old = create_dev_genesis();
+        let new = create_genesis(GenesisConfig::dev().with_chain_id(4242));
+        write_genesis_file(&old, &path, false).unwrap();
+
+        // Refused by default, leaving the file alone
+        let err = write_genesis_file(&new, &path, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(read_genesis_file(&path).unwrap(), old);
+        assert!(!genesis_backup_path(&path).exists());
+
+        // Replaced with a backup when overwriting
+        write_genesis_file(&new, &path, true).unwrap();
+        let backup = dir.path().join("genesis.json.bak");
+        assert_eq!(genesis_backup_path(&path), backup);
+        assert_eq!(read_genesis_file(&path).unwrap(), new);
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), genesis_to_json(&old));
+
+        // No temporary files are left behind
+        let mut files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["genesis.json", "genesis.json.bak"]);
+    }
+
+    /// Fields of geth's `core.Genesis` and `params.ChainConfig`, as their JSON tags spell them
+    const GETH_GENESIS_FIELDS: &[&str] = &[
+        "config",
+        "nonce",
+        "timestamp",
+        "extraData",
+        "gasLimit",
+        "difficulty",
+        "mixHash",
+        "coinbase",
+        "alloc",
+        "number",
+        "gasUsed",
+        "parentHash",
+        "baseFeePerGas",
+        "excessBlobGas",
+        "blobGasUsed",
+    ];
+    const GETH_CHAIN_CONFIG_FIELDS: &[&str] = &[
+        "chainId",
+        "homesteadBlock",
+        "daoForkBlock",
+        "daoForkSupport",
+        "eip150Block",
+        "eip155Block",
+        "eip158Block",
+        "byzantiumBlock",
+        "constantinopleBlock",
+        "petersburgBlock",
+        "istanbulBlock",
+        "muirGlacierBlock",
+        "berlinBlock",
+        "londonBlock",
+        "arrowGlacierBlock",
+        "grayGlacierBlock",
+        "mergeNetsplitBlock",
+        "shanghaiTime",
+        "cancunTime",
+        "pragueTime",
+        "osakaTime",
+        "terminalTotalDifficulty",
+        // Dropped by geth 1.14, which ignores it, but still read by older releases
+        "terminalTotalDifficultyPassed",
+        "depositContractAddress",
+        "clique",
+        "blobSchedule",
+    ];
+
+    #[test]
+    fn test_write_genesis_file_streaming() {
+        let dir = tempfile::tempdir().unwrap();
+        let genesis = create_dev_genesis();
+        let pretty = dir.path().join("pretty.json");
+        write_genesis_file_streaming(&genesis, &pretty, true).unwrap();
+        assert_eq!(fs::read_to_string(&pretty).unwrap(), genesis_to_json(&genesis));
+
+        let compact = dir.path().join("compact.json");
+        write_genesis_file_streaming(&genesis, &compact, false).unwrap();
+        let json = fs::read_to_string(&compact).unwrap();
+        assert_eq!(json.lines().count(), 1);
+        assert_eq!(read_genesis_file(&compact).unwrap(), genesis);
+
+        // An existing file is kept as the backup
+        let new = create_genesis(GenesisConfig::dev().with_chain_id(4242));
+        write_genesis_file_streaming(&new, &compact, false).unwrap();
+        assert_eq!(read_genesis_file(&compact).unwrap(), new);
+        assert_eq!(fs::read_to_string(genesis_backup_path(&compact)).unwrap(), json);
+    }
+
+    #[test]
+    fn test_geth_genesis_format() {
+        let genesis = create_dev_genesis();
+        let json: serde_json::Value = serde_json::from_str(&genesis_to_json(&genesis)).unwrap();
+        let golden: serde_json::Value =
+            serde_json::from_str(include_str!("../../testdata/dev_genesis.json")).unwrap();
+        assert_eq!(json, golden);
+
+        let fields = |value: &serde_json::Value| {
+            value.as_object().unwrap().keys().cloned().collect::<Vec<_>>()
+        };
+        for field in fields(&json) {
+            assert!(GETH_GENESIS_FIELDS.contains(&field.as_str()), "{field}");
+        }
+        let config = &json["config"];
+        for field in fields(config) {
+            assert!(GETH_CHAIN_CONFIG_FIELDS.contains(&field.as_str()), "config.{field}");
+        }
+
+        // geth reads header quantities as hex strings and fork activations as numbers
+        for field in ["nonce", "timestamp", "gasLimit", "difficulty", "baseFeePerGas"] {
+            assert!(json[field].as_str().unwrap().starts_with("0x"), "{field}");
+        }
+        for field in ["chainId", "londonBlock", "terminalTotalDifficulty", "pragueTime"] {
+            assert!(config[field].is_u64(), "config.{field}");
+        }
+        assert_eq!(config["clique"], serde_json::json!({"period": 2, "epoch": 30000}));
+        // geth refuses forks with blobs that have no blob schedule entry
+        for fork in ["cancun", "prague"] {
+            assert_eq!(
+                fields(&config["blobSchedule"][fork]),
+                ["baseFeeUpdateFraction", "max", "target"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_blob_schedule() {
+        let blob_params = BlobScheduleOverride {
+            cancun: Some(BlobCounts { target: 2, max: 4 }),
+            prague: Some(BlobCounts { target: 8, max: 12 }),
+        };
+        let genesis = create_genesis(GenesisConfig::dev().with_blob_params(blob_params));
+        let json: serde_json::Value = serde_json::from_str(&genesis_to_json(&genesis)).unwrap();
+        let golden: serde_json::Value =
+            serde_json::from_str(include_str!("../../testdata/blob_schedule_genesis.json"))
+                .unwrap();
+        assert_eq!(json, golden);
+
+        assert_eq!(GenesisConfig::from_genesis(&genesis).unwrap().blob_params, Some(blob_params));
+        assert_eq!(GenesisConfig::from_genesis(&create_dev_genesis()).unwrap().blob_params, None);
+        let chain = PoaChainSpec::from_genesis(genesis).unwrap();
+        assert_eq!(chain.poa_config().blob_params, Some(blob_params));
+        let params = chain.blob_params_at_timestamp(0).unwrap();
+        assert_eq!((params.target_blob_count, params.max_blob_count), (8, 12));
+    }
+
+    #[test]
+    fn test_genesis_header_fields_follow_hardforks() {
+        let default = create_dev_genesis();
+        assert_eq!(default.base_fee_per_gas, Some(u128::from(DEFAULT_INITIAL_BASE_FEE)));
+        assert_eq!((default.excess_blob_gas, default.blob_gas_used), (Some(0), Some(0)));
+
+        let gasless =
+            PoaConfig { zero_base_fee: true, ..PoaChainSpec::dev_chain().poa_config().clone() };
+        let genesis = create_dev_genesis_for(&gasless);
+        assert_eq!(genesis.base_fee_per_gas, Some(0));
+        let chain = PoaChainSpec::new(genesis.clone(), gasless.clone());
+        assert_eq!(chain.genesis_header().base_fee_per_gas, Some(0));
+        assert_eq!(chain.genesis_hash(), PoaChainSpec::compute_genesis_hash(&genesis, &gasless));
+
+        let hardforks = HardforkSchedule::default()
+            .with_fork(EthereumHardfork::Cancun, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Never);
+        let shanghai = GenesisConfig::dev().with_hardforks(hardforks.clone());
+        let json: serde_json::Value =
+            serde_json::from_str(&genesis_to_json(&create_genesis(shanghai.clone()))).unwrap();
+        for field in ["excessBlobGas", "blobGasUsed"] {
+            assert!(json.get(field).is_none(), "{field}");
+        }
+        for field in ["cancunTime", "pragueTime", "blobSchedule"] {
+            assert!(json["config"].get(field).is_none(), "config.{field}");
+        }
+        assert_eq!(json["config"]["shanghaiTime"], 0);
+        let poa_config = PoaConfig { hardforks, ..PoaChainSpec::dev_chain().poa_config().clone() };
+        let chain = PoaChainSpec::new(create_genesis(shanghai.clone()), poa_config);
+        assert_eq!(chain.genesis_header().excess_blob_gas, None);
+        assert_eq!(chain.genesis_hash(), shanghai.genesis_hash());
+
+        // Without London there is no base fee to configure
+        let hardforks = HardforkSchedule::default()
+            .with_fork(EthereumHardfork::London, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Paris, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Shanghai, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Cancun, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Never);
+        let berlin = GenesisConfig::dev().with_hardforks(hardforks).with_initial_base_fee(Some(7));
+        assert_eq!(create_genesis(berlin).base_fee_per_gas, None);
+    }
+
+    /// Hash geth computes for the Görli genesis in `testdata/goerli_genesis.json`
+    const GOERLI_GENESIS_HASH: B256 =
+        b256!("bf7e331f7f7c1dd2e05159666b3bf8bc7a8a3a9eb1d518969eab529dd9b88c1a");
+
+    /// Görli's hardforks as far as they matter for the genesis block
+    fn goerli_hardforks() -> HardforkSchedule {
+        HardforkSchedule::default()
+            .with_fork(EthereumHardfork::Istanbul, ForkCondition::Block(1_561_651))
+            .with_fork(EthereumHardfork::Berlin, ForkCondition::Block(4_460_644))
+            .with_fork(EthereumHardfork::London, ForkCondition::Block(5_062_605))
+            .with_fork(EthereumHardfork::Paris, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Shanghai, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Cancun, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Never)
+    }
+
+    #[test]
+    fn test_genesis_hash_matches_geth() {
+        let genesis: Genesis =
+            serde_json::from_str(include_str!("../../testdata/goerli_genesis.json")).unwrap();
+        let poa_config = PoaConfig { hardforks: goerli_hardforks(), ..Default::default() };
+        assert_eq!(PoaChainSpec::compute_genesis_hash(&genesis, &poa_config), GOERLI_GENESIS_HASH);
+
+        // Reading the file back and writing it again doesn't change the hash
+        let written: Genesis = serde_json::from_str(&genesis_to_json(&genesis)).unwrap();
+        assert_eq!(PoaChainSpec::compute_genesis_hash(&written, &poa_config), GOERLI_GENESIS_HASH);
+    }
+
+    #[test]
+    fn test_from_geth_clique_json_keeps_goerli() {
+        let json = include_str!("../../testdata/goerli_genesis.json");
+        let config = GenesisConfig::from_geth_clique_json(json).unwrap();
+        assert_eq!(
+            config.hardforks.fork(EthereumHardfork::London),
+            ForkCondition::Block(5_062_605)
+        );
+        assert_eq!(config.hardforks.fork(EthereumHardfork::Paris), ForkCondition::Never);
+        assert_eq!(config.vanity_as_string(), "\"Flexi is a thing\" - Afri");
+        assert_eq!(config.genesis_hash(), GOERLI_GENESIS_HASH);
+
+        // The same genesis, except for the zero block number and parent hash geth spells out
+        let genesis = create_genesis(config);
+        let geth: Genesis = serde_json::from_str(json).unwrap();
+        assert_eq!((geth.number, geth.parent_hash), (Some(0), Some(B256::ZERO)));
+        assert_eq!(genesis, Genesis { number: None, parent_hash: None, ..geth });
+        let written = GenesisConfig::from_geth_clique_json(&genesis_to_json(&genesis)).unwrap();
+        assert_eq!(written.genesis_hash(), GOERLI_GENESIS_HASH);
+    }
+
+    #[test]
+    fn test_from_geth_clique_json_spellings() {
+        let signer = dev_signers()[0];
+        let contract = address!("0000000000000000000000000000000000001234");
+        let funded = address!("00000000000000000000000000000000000000aa");
+        // Decimal numbers and balances, no `0x` on the extra data and no nonce, mix hash or
+        // coinbase, all of which geth accepts
+        let json = format!(
+            r#"{{
+                "config": {{
+                    "chainId": 1337,
+                    "homesteadBlock": 0,
+                    "eip150Block": 0,
+                    "eip155Block": 0,
+                    "eip158Block": 0,
+                    "byzantiumBlock": 0,
+                    "constantinopleBlock": 0,
+                    "petersburgBlock": 0,
+                    "istanbulBlock": 0,
+                    "berlinBlock": 0,
+                    "londonBlock": 0,
+                    "clique": {{ "period": 5, "epoch": 30000 }}
+                }},
+                "timestamp": "1700000000",
+                "gasLimit": "30000000",
+                "difficulty": "1",
+                "extraData": "{}{}{}",
+                "alloc": {{
+                    "{}": {{ "balance": "1000000000000000000000" }},
+                    "{}": {{
+                        "balance": "0x0",
+                        "nonce": "0x1",
+                        "code": "0x6000",
+                        "storage": {{
+                            "0x0000000000000000000000000000000000000000000000000000000000000000":
+                            "0x0000000000000000000000000000000000000000000000000000000000000001"
+                        }}
+                    }}
+                }}
+            }}"#,
+            "00".repeat(EXTRA_VANITY_LENGTH),
+            hex::encode(signer),
+            "00".repeat(EXTRA_SEAL_LENGTH),
+            funded,
+            contract,
+        );
+        let config = GenesisConfig::from_geth_clique_json(&json).unwrap();
+        assert_eq!((config.chain_id, config.block_period, config.epoch), (1337, 5, 30000));
+        assert_eq!((config.timestamp, config.gas_limit), (1_700_000_000, 30_000_000));
+        assert_eq!(
+            (config.nonce, config.mix_hash, config.coinbase),
+            (0, B256::ZERO, Address::ZERO)
+        );
+        assert_eq!(config.signers, vec![signer]);
+        assert_eq!(config.initial_base_fee, None);
+        assert_eq!(
+            config.prefunded_accounts[&funded],
+            U256::from(1000) * U256::from(10).pow(U256::from(18))
+        );
+        let predeploy = &config.predeploys[&contract];
+        assert_eq!(predeploy.code, Some(Bytes::from_static(&[0x60, 0x00])));
+        assert_eq!(predeploy.nonce, Some(1));
+        assert_eq!(config.hardforks.fork(EthereumHardfork::London), ForkCondition::Block(0));
+        assert_eq!(config.hardforks.fork(EthereumHardfork::Shanghai), ForkCondition::Never);
+
+        // The genesis written back hashes like the one read
+        let geth: Genesis = serde_json::from_str(&json).unwrap();
+        let poa_config = PoaConfig { hardforks: config.hardforks.clone(), ..Default::default() };
+        let expected = PoaChainSpec::compute_genesis_hash(&geth, &poa_config);
+        let genesis = create_genesis(config.clone());
+        assert_eq!(PoaChainSpec::compute_genesis_hash(&genesis, &poa_config), expected);
+        assert_eq!(config.genesis_hash(), expected);
+        assert_eq!(
+            GenesisConfig::from_geth_clique_json(&genesis_to_json(&genesis)).unwrap(),
+            config
+        );
+
+        assert!(matches!(
+            GenesisConfig::from_geth_clique_json(r#"{"config": {"chainId": 1}}"#),
+            Err(GenesisError::MissingField("config.clique"))
+        ));
+        assert!(matches!(GenesisConfig::from_geth_clique_json("{"), Err(GenesisError::Json(_))));
+    }
+
+    #[test]
+    fn test_with_vanity_str() {
+        let config = GenesisConfig::dev().with_vanity_str("my chain").unwrap();
+        assert_eq!(config.vanity[..8], *b"my chain");
+        assert_eq!(config.vanity_as_string(), "my chain");
+        let genesis = create_genesis(config);
+        assert_eq!(genesis.extra_data[..8], *b"my chain");
+        assert!(genesis.extra_data[8..EXTRA_VANITY_LENGTH].iter().all(|byte| *byte == 0));
+
+        let err = GenesisConfig::dev().with_vanity_str(&"é".repeat(17)).unwrap_err();
+        assert!(matches!(err, GenesisError::VanityTooLong(34)));
+        assert_eq!(err.to_string(), "vanity: 34 bytes exceed the 32 byte field");
+    }
+
+    #[test]
+    fn test_reproduce_goerli_genesis() {
+        // The values of Görli's published genesis.json
+        let balance = |hex: &str| hex.parse::<U256>().unwrap();
+        let config = (0..=255u8).fold(GenesisConfig::default(), |config, byte| {
+            config.with_prefunded_account(Address::with_last_byte(byte), U256::from(1))
+        });
+        let config = config
+            .with_chain_id(5)
+            .with_gas_limit(0xa00000)
+            .with_timestamp(0x5c51a607)
+            .with_vanity_str("\"Flexi is a thing\" - Afri")
+            .unwrap()
+            .with_signers(vec![address!("e0a2bd4258d2768837baa26a28fe71dc079f84c7")])
+            .with_block_period(15)
+            .with_hardforks(goerli_hardforks())
+            .with_nonce(0)
+            .with_mix_hash(B256::ZERO)
+            .with_coinbase(Address::ZERO)
+            .with_prefunded_account(
+                address!("4c2ae482593505f0163cdefc073e81c63cda4107"),
+                balance("0x152d02c7e14af6800000"),
+            )
+            .with_prefunded_account(
+                address!("a8e8f14732658e4b51e8711931053a8a69baf2b1"),
+                balance("0x152d02c7e14af6800000"),
+            )
+            .with_prefunded_account(
+                address!("d9a5179f091d85051d3c982785efd1455cec8699"),
+                balance("0x84595161401484a000000"),
+            )
+            .with_prefunded_account(
+                address!("e0a2bd4258d2768837baa26a28fe71dc079f84c7"),
+                balance("0x4a47e3c12448f4ad000000"),
+            );
+        assert_eq!(config.genesis_hash(), GOERLI_GENESIS_HASH);
+
+        // Any of the header fields changes the hash
+        for changed in [
+            config.clone().with_nonce(1),
+            config.clone().with_mix_hash(B256::with_last_byte(1)),
+            config.with_coinbase(Address::with_last_byte(1)),
+        ] {
+            assert_ne!(changed.genesis_hash(), GOERLI_GENESIS_HASH);
+        }
+    }
+
+    #[test]
+    fn test_allocation_report() {
+        let genesis = create_dev_genesis();
+        let balance = default_prefund_balance();
+        assert_eq!(total_allocated(&genesis), Some(balance * U256::from(20)));
+
+        let report = allocation_report(&genesis);
+        assert_eq!(report.accounts, 20);
+        assert_eq!(report.total, Some(U256::from(200_000) * U256::from(10).pow(U256::from(18))));
+        assert_eq!(report.largest_holders.len(), LARGEST_HOLDERS);
+        assert!(report.largest_holders.iter().all(|(_, holding)| *holding == balance));
+        let mut signers = dev_signers();
+        signers.sort();
+        assert_eq!(report.funded_signers, signers);
+        assert_eq!(report.signer_balance, balance * U256::from(3));
+        assert_eq!(report.signer_share_bps(), Some(U256::from(1_500)));
+
+        let text = report.to_string();
+        assert!(text.starts_with("Accounts:     20\nTotal:        200000.000000000000000000 ETH\n"));
+        assert!(text.contains("across 3 accounts (15.00%)"), "{text}");
+        let listed_signers =
+            report.largest_holders.iter().filter(|(holder, _)| signers.contains(holder)).count();
+        assert_eq!(text.matches("(signer)").count(), listed_signers, "{text}");
+
+        let mut overflowing = genesis;
+        for account in overflowing.alloc.values_mut().take(2) {
+            account.balance = U256::MAX;
+        }
+        assert_eq!(total_allocated(&overflowing), None);
+        assert!(allocation_report(&overflowing).to_string().contains("overflows"));
+
+        // Signer balances too large to scale by 10_000 still give their true share
+        let huge = AllocationReport {
+            total: Some(U256::MAX),
+            signer_balance: U256::MAX / U256::from(2),
+            ..report
+        };
+        assert_eq!(huge.signer_share_bps(), Some(U256::from(5_000)));
+        let all = AllocationReport { signer_balance: U256::MAX, ..huge };
+        assert_eq!(all.signer_share_bps(), Some(U256::from(10_000)));
+    }
+
+    #[test]
+    fn test_with_prefunded_account_eth() {
+        let (first, second) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let config = GenesisConfig::default()
+            .with_prefunded_account_eth(first, "10000.5")
+            .and_then(|config| config.with_prefunded_account_eth(second, "25 gwei"))
+            .unwrap();
+        let ether = U256::from(10).pow(U256::from(18));
+        assert_eq!(config.prefunded_accounts[&first], U256::from(100_005) * ether / U256::from(10));
+        assert_eq!(config.prefunded_accounts[&second], U256::from(25_000_000_000u64));
+
+        let err = GenesisConfig::default().with_prefunded_account_eth(first, "-3").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("alloc.{first}: invalid amount `-3`: amounts can't be negative")
+        );
+        assert!(matches!(
+            GenesisConfig::default().with_prefunded_account_eth(first, "0.1234567890123456789"),
+            Err(GenesisError::InvalidAmount { reason: AmountError::TooPrecise { .. }, .. })
+        ));
+    }
+
+    #[test]
+    fn test_with_alloc_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alloc.csv");
+        let (funded, new) = (dev_accounts()[0], Address::repeat_byte(0x42));
+        std::fs::write(&path, format!("address,balance\n{funded},1 ETH\n{new},0x10\n")).unwrap();
+
+        let config = GenesisConfig::dev().with_alloc_file(&path).unwrap();
+        assert_eq!(config.prefunded_accounts.len(), dev_accounts().len() + 1);
+        assert_eq!(config.prefunded_accounts[&funded], U256::from(10).pow(U256::from(18)));
+        assert_eq!(config.prefunded_accounts[&new], U256::from(16));
+        let genesis = create_genesis(config.clone());
+        let supply: U256 = genesis.alloc.values().map(|account| account.balance).sum();
+        assert_eq!(config.total_supply(), supply);
+    }
+
+    #[test]
+    fn test_signer_prefund_tops_up() {
+        let [poor, unlisted, rich] = [1, 2, 3].map(Address::with_last_byte);
+        let prefund = U256::from(1_000);
+        let config = GenesisConfig::default()
+            .with_signers(vec![poor, unlisted, rich])
+            .with_prefunded_account(poor, U256::from(10))
+            .with_prefunded_account(rich, U256::from(5_000))
+            .with_signer_prefund(prefund);
+        let genesis = create_genesis(config);
+
+        assert_eq!(genesis.alloc[&poor].balance, prefund);
+        assert_eq!(genesis.alloc[&unlisted].balance, prefund);
+        assert_eq!(genesis.alloc[&rich].balance, U256::from(5_000));
+        assert_eq!(genesis.alloc.len(), 3);
+    }
+
+    #[test]
+    fn test_signer_prefund_already_funded() {
+        let config = GenesisConfig::dev();
+        let funded = create_genesis(config.clone().with_signer_prefund(U256::from(1)));
+        assert_eq!(funded, create_genesis(config));
+
+        // Without the option unfunded signers stay out of the alloc
+        let signer = Address::repeat_byte(0x42);
+        let genesis = create_genesis(GenesisConfig::dev().with_signers(vec![signer]));
+        assert!(!genesis.alloc.contains_key(&signer));
+    }
+
+    #[test]
+    fn test_predeploy() {
+        let contract = address!("0000000000000000000000000000000000c0ffee");
+        // Returns slot 0 + slot 1
+        let code = Bytes::from_static(&hex!("60015460005401" "60005260206000f3"));
+        let storage = BTreeMap::from([
+            (B256::ZERO, B256::with_last_byte(0x2a)),
+            (B256::with_last_byte(1), B256::with_last_byte(0x07)),
+        ]);
+        let config = GenesisConfig::dev().with_predeploy(
+            contract,
+            code.clone(),
+            storage.clone(),
+            U256::from(5),
+            1,
+        );
+        let genesis = create_genesis(config.clone());
+
+        let account = &genesis.alloc[&contract];
+        assert_eq!(account.code.as_ref(), Some(&code));
+        assert_eq!(account.storage.as_ref(), Some(&storage));
+        assert_eq!((account.balance, account.nonce), (U256::from(5), Some(1)));
+        let read = GenesisConfig::from_genesis(&genesis).unwrap();
+        assert_eq!(read.predeploys, config.predeploys);
+
+        // A prefunded account at the same address becomes part of the contract
+        let funded = dev_accounts()[0];
+        let merged = create_genesis(GenesisConfig::dev().with_predeploy(
+            funded,
+            code.clone(),
+            BTreeMap::new(),
+            U256::from(5),
+            0,
+        ));
+        assert_eq!(merged.alloc[&funded].code.as_ref(), Some(&code));
+        assert_eq!(merged.alloc[&funded].balance, default_prefund_balance() + U256::from(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "has storage but no code")]
+    fn test_predeploy_storage_without_code() {
+        let storage = BTreeMap::from([(B256::ZERO, B256::with_last_byte(1))]);
+        let _ = GenesisConfig::dev().with_predeploy(
+            Address::repeat_byte(1),
+            Bytes::new(),
+            storage,
+            U256::ZERO,
+            0,
+        );
+    }
+
+    #[test]
+    fn test_plain_genesis() {
+        let poa = create_genesis(GenesisConfig::dev());
+        assert_eq!(poa.extra_data.len(), 32 + 3 * 20 + 65);
+        assert!(poa.config.clique.is_some());
+
+        let plain = create_genesis(GenesisConfig::dev().poa_mode(false));
+        assert!(plain.extra_data.is_empty());
+        assert_eq!(plain.config.clique, None);
+        assert_eq!(plain.alloc, poa.alloc);
+        assert_eq!(plain.config.cancun_time, poa.config.cancun_time);
+        let json: serde_json::Value = serde_json::from_str(&genesis_to_json(&plain)).unwrap();
+        assert_eq!(json["extraData"], "0x");
+        assert!(json["config"].get("clique").is_none());
+
+        let err = PoaChainSpec::from_genesis(plain).unwrap_err();
+        assert!(matches!(err, PoaChainSpecError::MissingClique));
+        assert_eq!(
+            err.to_string(),
+            "genesis config has no clique section, so it is not a POA genesis"
+        );
+    }
+
+    #[test]
+    fn test_extra_data_format() {
+        let signers = vec![
+            address!("0000000000000000000000000000000000000001"),
+            address!("0000000000000000000000000000000000000002"),
+        ];
+
+        let config = GenesisConfig::default().with_signers(signers);
+        let genesis = create_genesis(config);
+
+        // Extra data should be: 32 (vanity) + 2*20 (signers) + 65 (seal) = 137 bytes
+        assert_eq!(genesis.extra_data.len(), 32 + 40 + 65);
+    }
+}
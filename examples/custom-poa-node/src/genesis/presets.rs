@@ -0,0 +1,336 @@
+//! Standard predeploys
+//!
+//! Contracts that tooling expects at the same address on every chain, with the runtime code of
+//! their canonical deployments so that hardcoded addresses (forge scripts, viem's multicall) work
+//! from the first block.
+//! [`GenesisConfig::with_standard_predeploys`](super::GenesisConfig::with_standard_predeploys)
+//! installs all of them.
+//!
+//! WETH9 is not among them: it has no address shared across chains, so tooling looks it up per
+//! chain, and a chain that wants it deploys it like any other contract.
+
+use alloy_primitives::{address, hex, Address, B256};
+
+/// A contract installed at genesis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Predeploy {
+    /// Name of the contract, for logs and docs
+    pub name: &'static str,
+    /// Address of the canonical deployment
+    pub address: Address,
+    /// Runtime code of the canonical deployment
+    pub code: &'static [u8],
+    /// Storage the contract needs to work, if any
+    pub storage: &'static [(B256, B256)],
+}
+
+/// Arachnid's deterministic deployment proxy, the `CREATE2` factory forge deploys through
+///
+/// Called with a 32 byte salt followed by init code, it deploys the contract with `CREATE2` and
+/// returns its address.
+pub const DETERMINISTIC_DEPLOYER: Predeploy = Predeploy {
+    name: "Deterministic Deployment Proxy",
+    address: address!("4e59b44847b379578588920ca78fbf26c0b4956c"),
+    code: &hex!(
+        "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035"
+        "828234f58015156039578182fd5b8082525050506014600cf3"
+    ),
+    storage: &[],
+};
+
+/// Multicall3, which batches calls into one
+pub const MULTICALL3: Predeploy = Predeploy {
+    name: "Multicall3",
+    address: address!("cA11bde05977b3631167028862bE2a173976CA11"),
+    code: &hex!(
+    "6080604052600436106100f35760003560e01c80634d2301cc1161008a578063a8b0574e11610059578063a8b057"
+    "4e1461025a578063bce38bd714610275578063c3077fa914610288578063ee82ac5e1461029b57600080fd5b8063"
+    "4d2301cc146101ec57806372425d9d1461022157806382ad56cb1461023457806386d516e81461024757600080fd"
+    "5b80633408e470116100c65780633408e47014610191578063399542e9146101a45780633e64a696146101c65780"
+    "6342cbb15c146101d957600080fd5b80630f28c97d146100f8578063174dea711461011a578063252dba42146101"
+    "3a57806327e86d6e1461015b575b600080fd5b34801561010457600080fd5b50425b6040519081526020015b6040"
+    "5180910390f35b61012d610128366004610a85565b6102ba565b6040516101119190610bbe565b61014d61014836"
+    "6004610a85565b6104ef565b604051610111929190610bd8565b34801561016757600080fd5b50437fffffffffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffff0140610107565b34801561019d57600080fd5b"
+    "5046610107565b6101b76101b2366004610c60565b610690565b60405161011193929190610cba565b3480156101"
+    "d257600080fd5b5048610107565b3480156101e557600080fd5b5043610107565b3480156101f857600080fd5b50"
+    "610107610207366004610ce2565b73ffffffffffffffffffffffffffffffffffffffff163190565b34801561022d"
+    "57600080fd5b5044610107565b61012d610242366004610a85565b6106ab565b34801561025357600080fd5b5045"
+    "610107565b34801561026657600080fd5b50604051418152602001610111565b61012d610283366004610c60565b"
+    "61085a565b6101b7610296366004610a85565b610a1a565b3480156102a757600080fd5b506101076102b6366004"
+    "610d18565b4090565b60606000828067ffffffffffffffff8111156102d8576102d8610d31565b60405190808252"
+    "806020026020018201604052801561031e57816020015b6040805180820190915260008152606060208201528152"
+    "602001906001900390816102f65790505b5092503660005b82811015610477576000858281518110610341576103"
+    "41610d60565b6020026020010151905087878381811061035d5761035d610d60565b905060200281019061036f91"
+    "90610d8f565b6040810135958601959093506103886020850185610ce2565b73ffffffffffffffffffffffffffff"
+    "ffffffffffff16816103ac6060870187610dcd565b6040516103ba929190610e32565b6000604051808303818587"
+    "5af1925050503d80600081146103f7576040519150601f19603f3d011682016040523d82523d6000602084013e61"
+    "03fc565b606091505b50602080850191909152901515808452908501351761046d577f08c379a000000000000000"
+    "000000000000000000000000000000000000000000600052602060045260176024527f4d756c746963616c6c333a"
+    "2063616c6c206661696c656400000000000000000060445260846000fd5b5050600101610325565b508234146104"
+    "e6576040517f08c379a0000000000000000000000000000000000000000000000000000000008152602060048201"
+    "52601a60248201527f4d756c746963616c6c333a2076616c7565206d69736d617463680000000000006044820152"
+    "6064015b60405180910390fd5b50505092915050565b436060828067ffffffffffffffff81111561050c5761050c"
+    "610d31565b60405190808252806020026020018201604052801561053f57816020015b6060815260200190600190"
+    "03908161052a5790505b5091503660005b8281101561068657600087878381811061056257610562610d60565b90"
+    "506020028101906105749190610e42565b92506105836020840184610ce2565b73ffffffffffffffffffffffffff"
+    "ffffffffffffff166105a66020850185610dcd565b6040516105b4929190610e32565b6000604051808303816000"
+    "865af19150503d80600081146105f1576040519150601f19603f3d011682016040523d82523d6000602084013e61"
+    "05f6565b606091505b5086848151811061060957610609610d60565b602090810291909101015290508061067d57"
+    "6040517f08c379a00000000000000000000000000000000000000000000000000000000081526020600482015260"
+    "1760248201527f4d756c746963616c6c333a2063616c6c206661696c656400000000000000000060448201526064"
+    "016104dd565b50600101610546565b5050509250929050565b43804060606106a086868661085a565b9050935093"
+    "50939050565b6060818067ffffffffffffffff8111156106c7576106c7610d31565b604051908082528060200260"
+    "20018201604052801561070d57816020015b60408051808201909152600081526060602082015281526020019060"
+    "01900390816106e55790505b5091503660005b828110156104e657600084828151811061073057610730610d6056"
+    "5b6020026020010151905086868381811061074c5761074c610d60565b905060200281019061075e9190610e7656"
+    "5b925061076d6020840184610ce2565b73ffffffffffffffffffffffffffffffffffffffff166107906040850185"
+    "610dcd565b60405161079e929190610e32565b6000604051808303816000865af19150503d80600081146107db57"
+    "6040519150601f19603f3d011682016040523d82523d6000602084013e6107e0565b606091505b50602080840191"
+    "9091529015158083529084013517610851577f08c379a00000000000000000000000000000000000000000000000"
+    "0000000000600052602060045260176024527f4d756c746963616c6c333a2063616c6c206661696c656400000000"
+    "000000000060445260646000fd5b50600101610714565b6060818067ffffffffffffffff81111561087657610876"
+    "610d31565b6040519080825280602002602001820160405280156108bc57816020015b6040805180820190915260"
+    "008152606060208201528152602001906001900390816108945790505b5091503660005b82811015610a10576000"
+    "8482815181106108df576108df610d60565b602002602001015190508686838181106108fb576108fb610d60565b"
+    "905060200281019061090d9190610e42565b925061091c6020840184610ce2565b73ffffffffffffffffffffffff"
+    "ffffffffffffffff1661093f6020850185610dcd565b60405161094d929190610e32565b60006040518083038160"
+    "00865af19150503d806000811461098a576040519150601f19603f3d011682016040523d82523d6000602084013e"
+    "61098f565b606091505b506020830152151581528715610a07578051610a07576040517f08c379a0000000000000"
+    "00000000000000000000000000000000000000000000815260206004820152601760248201527f4d756c74696361"
+    "6c6c333a2063616c6c206661696c656400000000000000000060448201526064016104dd565b506001016108c356"
+    "5b5050509392505050565b6000806060610a2b60018686610690565b919790965090945092505050565b60008083"
+    "601f840112610a4b57600080fd5b50813567ffffffffffffffff811115610a6357600080fd5b6020830191508360"
+    "208260051b8501011115610a7e57600080fd5b9250929050565b60008060208385031215610a9857600080fd5b82"
+    "3567ffffffffffffffff811115610aaf57600080fd5b610abb85828601610a39565b90969095509350505050565b"
+    "6000815180845260005b81811015610aed57602081850181015186830182015201610ad1565b81811115610aff57"
+    "6000602083870101525b50601f017fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    "e0169290920160200192915050565b600082825180855260208086019550808260051b84010181860160005b8481"
+    "1015610bb1578583037fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe001895281"
+    "518051151584528401516040858501819052610b9d81860183610ac7565b9a86019a945050509083019060010161"
+    "0b4f565b5090979650505050505050565b602081526000610bd16020830184610b32565b9392505050565b600060"
+    "408201848352602060408185015281855180845260608601915060608160051b870101935082870160005b828110"
+    "15610c52577fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffa0888703018452610c"
+    "40868351610ac7565b95509284019290840190600101610c06565b509398975050505050505050565b6000806000"
+    "60408486031215610c7557600080fd5b83358015158114610c8557600080fd5b9250602084013567ffffffffffff"
+    "ffff811115610ca157600080fd5b610cad86828701610a39565b9497909650939450505050565b83815282602082"
+    "0152606060408201526000610cd96060830184610b32565b95945050505050565b600060208284031215610cf457"
+    "600080fd5b813573ffffffffffffffffffffffffffffffffffffffff81168114610bd157600080fd5b6000602082"
+    "84031215610d2a57600080fd5b5035919050565b7f4e487b71000000000000000000000000000000000000000000"
+    "00000000000000600052604160045260246000fd5b7f4e487b710000000000000000000000000000000000000000"
+    "0000000000000000600052603260045260246000fd5b600082357fffffffffffffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffff81833603018112610dc357600080fd5b9190910192915050565b60008083357fffff"
+    "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe1843603018112610e0257600080fd5b83"
+    "018035915067ffffffffffffffff821115610e1d57600080fd5b602001915036819003821315610a7e57600080fd"
+    "5b8183823760009101908152919050565b600082357fffffffffffffffffffffffffffffffffffffffffffffffff"
+    "ffffffffffffffc1833603018112610dc357600080fd5b600082357fffffffffffffffffffffffffffffffffffff"
+    "ffffffffffffffffffffffffffa1833603018112610dc357600080fdfea2646970667358221220bb2b5c71a32803"
+    "2f97c676ae39a1ec2148d3e5d6f73d95e9b17910152d61f16264736f6c634300080c0033"
+    ),
+    storage: &[],
+};
+
+/// The predeploys
+/// [`GenesisConfig::with_standard_predeploys`](super::GenesisConfig::with_standard_predeploys)
+/// installs
+pub const STANDARD_PREDEPLOYS: [Predeploy; 2] = [DETERMINISTIC_DEPLOYER, MULTICALL3];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::PoaChainSpec,
+        config::RpcSettings,
+        consensus::test_utils::genesis_state,
+        genesis::{
+            create_genesis, default_prefund_balance, dev_accounts, GenesisConfig, StorageBuilder,
+        },
+        network::PoaNetworkBuilder,
+    };
+    use alloy_eips::BlockId;
+    use alloy_primitives::{b256, keccak256, Bytes, TxKind, U256};
+    use reth_chainspec::EthChainSpec;
+    use reth_ethereum::{
+        node::{
+            builder::{NodeBuilder, NodeHandle},
+            node::EthereumAddOns,
+            EthereumNode,
+        },
+        rpc::{
+            api::eth::helpers::EthCall,
+            eth::primitives::{state::EvmOverrides, TransactionInput, TransactionRequest},
+        },
+        tasks::TaskManager,
+    };
+    use reth_evm::{ConfigureEvm, Evm};
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_revm::context::result::ExecutionResult;
+    use std::sync::Arc;
+
+    /// Dev chain with the standard predeploys
+    fn chain() -> PoaChainSpec {
+        let genesis = create_genesis(GenesisConfig::dev().with_standard_predeploys());
+        PoaChainSpec::new(genesis, PoaChainSpec::dev_chain().poa_config().clone())
+    }
+
+    /// Output of calling `contract` with `input` on top of the genesis, like `eth_call` would
+    fn call(chain: &PoaChainSpec, contract: Address, input: Vec<u8>) -> Bytes {
+        let evm_config = EthEvmConfig::new(chain.inner().clone());
+        let Ok(mut evm) = evm_config.evm_for_block(genesis_state(chain), chain.genesis_header());
+        match evm.transact_system_call(Address::ZERO, contract, input.into()).unwrap().result {
+            ExecutionResult::Success { output, .. } => output.into_data(),
+            other => panic!("call to {contract} failed: {other:?}"),
+        }
+    }
+
+    /// ABI word holding `value`
+    fn word(value: usize) -> [u8; 32] {
+        U256::from(value).to_be_bytes()
+    }
+
+    /// Read the ABI word at byte `offset` as an offset or length
+    fn read_word(data: &[u8], offset: usize) -> usize {
+        U256::from_be_slice(&data[offset..offset + 32]).to()
+    }
+
+    /// Block number and return data (as words) of the output of `aggregate`
+    fn decode_aggregate(output: &[u8]) -> (usize, Vec<U256>) {
+        // Returns (uint256 blockNumber, bytes[] returnData)
+        let results = read_word(output, 32);
+        let return_data = (0..read_word(output, results))
+            .map(|index| {
+                let offset = results + 32 + read_word(output, results + 32 + index * 32);
+                let len = read_word(output, offset);
+                U256::from_be_slice(&output[offset + 32..offset + 32 + len])
+            })
+            .collect();
+        (read_word(output, 0), return_data)
+    }
+
+    /// ABI encoding of `aggregate((address,bytes)[])`
+    fn encode_aggregate(calls: &[(Address, Vec<u8>)]) -> Vec<u8> {
+        let mut input = keccak256("aggregate((address,bytes)[])")[..4].to_vec();
+        input.extend(word(0x20));
+        input.extend(word(calls.len()));
+        let mut tuples = Vec::new();
+        for (target, data) in calls {
+            input.extend(word(calls.len() * 32 + tuples.len()));
+            tuples.extend(target.into_word());
+            tuples.extend(word(0x40));
+            tuples.extend(word(data.len()));
+            tuples.extend(data);
+            tuples.resize(tuples.len().next_multiple_of(32), 0);
+        }
+        input.extend(tuples);
+        input
+    }
+
+    #[test]
+    fn test_standard_predeploys_in_genesis() {
+        let genesis = chain().inner().genesis.clone();
+        for predeploy in STANDARD_PREDEPLOYS {
+            let account = &genesis.alloc[&predeploy.address];
+            assert_eq!(
+                account.code.as_ref().map(|code| &code[..]),
+                Some(predeploy.code),
+                "{}",
+                predeploy.name
+            );
+        }
+        assert_eq!(
+            keccak256(DETERMINISTIC_DEPLOYER.code),
+            b256!("2fa86add0aed31f33a762c9d88e807c475bd51d0f52bd0955754b2608f7e4989")
+        );
+    }
+
+    #[test]
+    fn test_deterministic_deployer() {
+        let chain = chain();
+        let salt = B256::with_last_byte(1);
+        // Deploys a contract returning 42
+        let init_code = hex!("600a600c600039600a6000f3" "602a60005260206000f3");
+        let mut input = salt.to_vec();
+        input.extend_from_slice(&init_code);
+
+        let output = call(&chain, DETERMINISTIC_DEPLOYER.address, input);
+        assert_eq!(
+            Address::from_slice(&output),
+            DETERMINISTIC_DEPLOYER.address.create2_from_code(salt, init_code)
+        );
+    }
+
+    #[test]
+    fn test_storage_builder_mapping_layout() {
+        // What solc emits for `return balanceOf[account]` with `balanceOf` at slot 3, the account
+        // taking up the whole calldata
+        let code = hex!("600035600052600360205260406000205460005260206000f3");
+        let contract = Address::with_last_byte(0xc0);
+        let account = dev_accounts()[0];
+        let balance = U256::from(1234);
+        let storage = StorageBuilder::new().mapping_slot(account, 3).value_u256(balance).build();
+        let genesis = create_genesis(GenesisConfig::dev().with_predeploy(
+            contract,
+            Bytes::copy_from_slice(&code),
+            storage,
+            U256::ZERO,
+            1,
+        ));
+        let chain = PoaChainSpec::new(genesis, PoaChainSpec::dev_chain().poa_config().clone());
+
+        let output = call(&chain, contract, account.into_word().to_vec());
+        assert_eq!(U256::from_be_slice(&output), balance);
+        let output = call(&chain, contract, dev_accounts()[1].into_word().to_vec());
+        assert_eq!(U256::from_be_slice(&output), U256::ZERO);
+    }
+
+    #[test]
+    fn test_multicall3_aggregate() {
+        let chain = chain();
+        let account = dev_accounts()[0];
+        let mut get_balance = keccak256("getEthBalance(address)")[..4].to_vec();
+        get_balance.extend(account.into_word());
+        let calls = [
+            (MULTICALL3.address, keccak256("getChainId()")[..4].to_vec()),
+            (MULTICALL3.address, get_balance),
+        ];
+
+        let output = call(&chain, MULTICALL3.address, encode_aggregate(&calls));
+        assert_eq!(
+            decode_aggregate(&output),
+            (0, vec![U256::from(chain.chain().id()), default_prefund_balance()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multicall3_aggregate_on_node() {
+        let chain = Arc::new(chain());
+        let mut rpc = RpcSettings::default();
+        rpc.http = false;
+        let tasks = TaskManager::current();
+        let components = EthereumNode::components().network(PoaNetworkBuilder::new(chain.clone()));
+        let NodeHandle { node, node_exit_future: _ } =
+            NodeBuilder::new(crate::node_config(&chain, &rpc))
+                .testing_node(tasks.executor())
+                .with_types::<EthereumNode>()
+                .with_components(components)
+                .with_add_ons(EthereumAddOns::default())
+                .launch_with_debug_capabilities()
+                .await
+                .unwrap();
+
+        let calls = [(MULTICALL3.address, keccak256("getChainId()")[..4].to_vec())];
+        let request = TransactionRequest {
+            to: Some(TxKind::Call(MULTICALL3.address)),
+            input: TransactionInput::new(encode_aggregate(&calls).into()),
+            ..Default::default()
+        };
+        let output = node
+            .rpc_registry
+            .eth_api()
+            .call(request, Some(BlockId::latest()), EvmOverrides::default())
+            .await
+            .unwrap();
+        let (_, return_data) = decode_aggregate(&output);
+        assert_eq!(return_data, [U256::from(chain.chain().id())]);
+    }
+}
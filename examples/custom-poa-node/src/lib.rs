@@ -0,0 +1,17 @@
+//! Library of the custom POA node
+//!
+//! The binary compiles these modules itself and runs their unit tests. The library only exists
+//! so that integration tests, which build into binaries of their own, can reach them too.
+
+pub mod chainspec;
+pub mod cli;
+pub mod config;
+pub mod consensus;
+pub mod deposit;
+pub mod diff;
+pub mod genesis;
+pub mod network;
+pub mod pool;
+pub mod presets;
+pub mod sealing;
+pub mod signer;
@@ -31,6 +31,12 @@
 //! └─────────────────────────────────────────────────────────────────┘
 //! ```
 //!
+//! ## Status
+//!
+//! The POA rules live in [`consensus`] and [`signer`] as library code, covered by tests. This
+//! binary doesn't install them yet: it runs reth's [`EthereumNode`] with dev interval mining,
+//! so blocks are neither sealed by a signer nor checked against the signer set.
+//!
 //! ## Features
 //!
 //! - **Multi-signer POA consensus**: Configure multiple authorized signers
@@ -46,52 +52,138 @@
 //! cargo run -p example-custom-poa-node
 //!
 //! # The node produces blocks every 2 seconds automatically
+//!
+//! # Run against a chain definition on disk (a geth clique genesis, optionally with a `poa`
+//! # object holding the POA config)
+//! cargo run -p example-custom-poa-node -- --chain ./mychain.json
+//!
+//! # ... or against a named preset, see [`presets`]. `CHAIN_FILE` works in place of `--chain`
+//! CHAIN_FILE=local-fast cargo run -p example-custom-poa-node
+//!
+//! # Any of these under another chain id (or `--chain-id`)
+//! POA_CHAIN_ID=4242 cargo run -p example-custom-poa-node
+//!
+//! # Generate the signer key of a new authority node, encrypted with the password in the file
+//! cargo run -p example-custom-poa-node -- --new-key --keystore ./keystore --password-file ./pw
 //! ```
+//!
+//! Node settings (period, epoch, signers, datadir, RPC) are read from `poa.toml` in the working
+//! directory if it exists, see [`config`].
 
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
 pub mod chainspec;
+pub mod cli;
+pub mod config;
 pub mod consensus;
+pub mod deposit;
+pub mod diff;
 pub mod genesis;
+pub mod network;
+pub mod pool;
+pub mod presets;
+pub mod sealing;
 pub mod signer;
 
-use crate::chainspec::PoaChainSpec;
+use crate::{
+    chainspec::PoaChainSpec,
+    cli::PoaChainSpecParser,
+    config::{PoaNodeConfigFile, RpcSettings, DEFAULT_CONFIG_FILE},
+    consensus::{
+        provider::{HeaderStateProvider, SnapshotProvider},
+        store::SnapshotStore,
+    },
+    diff::DivergenceKind,
+    genesis::GenesisConfig,
+    network::PoaNetworkBuilder,
+    signer::{PasswordSource, SignerManager},
+};
 use alloy_consensus::BlockHeader;
-use alloy_primitives::U256;
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, U256};
+use clap::Parser;
 use futures_util::StreamExt;
+use reth_chainspec::{ChainSpec, EthChainSpec};
+use reth_cli::chainspec::ChainSpecParser;
 use reth_ethereum::{
     node::{
         builder::{NodeBuilder, NodeHandle},
         core::{
-            args::{DevArgs, RpcServerArgs},
+            args::{DevArgs, RpcServerArgs, TxPoolArgs},
             node_config::NodeConfig,
         },
+        node::EthereumAddOns,
         EthereumNode,
     },
-    provider::CanonStateSubscriptions,
+    provider::{CanonStateNotification, CanonStateSubscriptions},
     rpc::api::eth::helpers::EthState,
     tasks::TaskManager,
 };
-use std::{path::PathBuf, time::Duration};
+use reth_network_peers::TrustedPeer;
+use reth_tracing::tracing::warn;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+// The modules are compiled right here, the library target only serves the integration tests
+use example_custom_poa_node as _;
 
-/// Main entry point for the POA node
-#[tokio::main]
-async fn main() -> eyre::Result<()> {
-    // Initialize tracing for debug output
-    reth_tracing::init_test_tracing();
+/// Command line arguments of the POA node
+#[derive(Debug, Parser)]
+struct Args {
+    /// The chain to run, see [`PoaChainSpecParser`]
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        env = "CHAIN_FILE",
+        long_help = PoaChainSpecParser::help_message(),
+        value_parser = PoaChainSpecParser::parser(),
+    )]
+    chain: Option<Arc<PoaChainSpec>>,
 
-    // Create the POA chain specification
-    let poa_chain = PoaChainSpec::dev_chain();
+    /// Run the chain under this chain id instead of the one in its genesis
+    #[arg(long, value_name = "CHAIN_ID", env = "POA_CHAIN_ID")]
+    chain_id: Option<u64>,
 
-    println!("Starting POA node with chain ID: {}", poa_chain.inner().chain.id());
-    println!("Authorized signers: {:?}", poa_chain.signers());
-    println!("Block period: {} seconds", poa_chain.block_period());
+    /// Generate a signer key in the `--keystore` directory, print its address and exit
+    #[arg(long, requires = "keystore")]
+    new_key: bool,
 
-    // Set up data directory in the current working directory
-    let datadir = PathBuf::from("custompoanode");
+    /// Directory of geth style keystore files
+    #[arg(long, value_name = "DIR", env = "POA_KEYSTORE")]
+    keystore: Option<PathBuf>,
 
-    // Configure dev args with interval-based block production (POA style)
-    // This makes the node produce blocks at regular intervals, not just when transactions arrive
+    /// File of keystore passwords, one per line, which are otherwise read from
+    /// `POA_KEYSTORE_PASSWORD`
+    ///
+    /// A line `# <address>` labels the password on the next line. `--new-key` uses the first
+    /// unlabeled password.
+    #[arg(long, value_name = "FILE")]
+    password_file: Option<PathBuf>,
+}
+
+/// Generate a signer key in the `--keystore` directory, encrypted with the password of
+/// `--password-file` or `POA_KEYSTORE_PASSWORD`
+async fn new_key(args: &Args) -> eyre::Result<Address> {
+    let keystore = args.keystore.as_deref().expect("clap requires --keystore");
+    let passwords = match &args.password_file {
+        Some(file) => PasswordSource::File(file.clone()),
+        None => PasswordSource::Env("POA_KEYSTORE_PASSWORD".to_string()),
+    };
+    // An empty password would leave the new key effectively unencrypted
+    let password = passwords
+        .new_key_password(keystore)?
+        .filter(|password| !password.is_empty())
+        .ok_or_else(|| eyre::eyre!("no keystore password provided"))?;
+    Ok(SignerManager::new().generate_signer(keystore, &password).await?)
+}
+
+/// Node settings to run `poa_chain` with, serving RPC as `rpc` asks
+fn node_config(poa_chain: &PoaChainSpec, rpc: &RpcSettings) -> NodeConfig<ChainSpec> {
+    // Configure dev args with interval-based block production
+    // This makes the node produce blocks at regular intervals, not just when transactions arrive.
+    // The blocks carry no POA seal, see the crate docs
     let dev_args = DevArgs {
         dev: true,
         block_time: Some(Duration::from_secs(poa_chain.block_period())),
@@ -99,11 +191,91 @@ async fn main() -> eyre::Result<()> {
         ..Default::default()
     };
 
+    let txpool = pool::txpool_args(poa_chain, TxPoolArgs::default());
+
     // Build node configuration with interval-based mining for POA
-    let node_config = NodeConfig::test()
+    let mut node_config = NodeConfig::test()
         .with_dev(dev_args)
-        .with_rpc(RpcServerArgs::default().with_http())
+        .with_txpool(txpool)
+        .with_rpc(RpcServerArgs {
+            http: rpc.http,
+            http_addr: rpc.http_addr,
+            http_port: rpc.http_port,
+            ..Default::default()
+        })
         .with_chain(poa_chain.inner().clone());
+    // Have the payload builder move the gas limit towards the chain's target
+    node_config.builder.gas_limit = Some(poa_chain.block_gas_limit());
+    // The inner chain spec knows no bootnodes, so hand the configured ones to the network
+    node_config.network.bootnodes = poa_chain
+        .bootnodes()
+        .map(|bootnodes| bootnodes.into_iter().map(TrustedPeer::from).collect());
+    node_config
+}
+
+/// Main entry point for the POA node
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+
+    // Initialize tracing for debug output
+    reth_tracing::init_test_tracing();
+
+    if args.new_key {
+        let address = new_key(&args).await?;
+        let keystore = args.keystore.as_deref().expect("clap requires --keystore");
+        println!("New signer {address}, stored in {}", keystore.display());
+        return Ok(());
+    }
+
+    // Operator settings from `poa.toml` in the working directory, if present
+    let config_path = Path::new(DEFAULT_CONFIG_FILE);
+    let config =
+        if config_path.exists() { Some(PoaNodeConfigFile::load(config_path)?) } else { None };
+
+    // Create the POA chain specification, from the preset or file named by `--chain` if set
+    let poa_chain = match (args.chain, &config) {
+        (Some(chain), _) => Arc::unwrap_or_clone(chain),
+        (None, Some(config)) => {
+            // The dev genesis, sealed for the period, epoch and signers of the file
+            let settings = &config.poa;
+            let mut dev =
+                GenesisConfig::dev().with_block_period(settings.period).with_epoch(settings.epoch);
+            if !settings.signers.is_empty() {
+                dev = dev.with_signers(settings.signers.clone());
+            }
+            PoaChainSpec::from_config(config, genesis::create_genesis(dev))?
+        }
+        (None, None) => PoaChainSpec::dev_chain(),
+    };
+    let poa_chain = match args.chain_id {
+        Some(chain_id) => poa_chain.with_chain_id(chain_id),
+        None => poa_chain,
+    };
+
+    println!("Starting {poa_chain}");
+    if poa_chain.is_evm_equivalent_to_mainnet() {
+        println!("EVM: every mainnet hardfork is active");
+    } else {
+        println!("EVM: differs from mainnet");
+        for divergence in poa_chain.mainnet_divergence() {
+            if divergence.kind != DivergenceKind::Earlier {
+                println!("  {divergence}");
+            }
+        }
+    }
+    for bootnode in poa_chain.bootnodes().iter().flatten() {
+        println!("Bootnode: {bootnode}");
+    }
+    print!("Genesis allocation:\n{}", genesis::allocation_report(poa_chain.genesis()));
+
+    // Set up data directory in the current working directory
+    let datadir = config
+        .as_ref()
+        .map_or_else(|| PathBuf::from("custompoanode"), |config| config.datadir.clone());
+    let rpc = config.as_ref().map(|config| config.rpc.clone()).unwrap_or_default();
+
+    let node_config = node_config(&poa_chain, &rpc);
 
     println!("Dev mode enabled: {}", node_config.dev.dev);
     println!(
@@ -115,9 +287,14 @@ async fn main() -> eyre::Result<()> {
     // Dropping the TaskManager fires the shutdown signal, which stops all spawned tasks.
     let tasks = TaskManager::current();
 
+    // Ethereum components, except for a network that announces the fork id of the POA chain
+    let poa_chain = Arc::new(poa_chain);
+    let components = EthereumNode::components().network(PoaNetworkBuilder::new(poa_chain.clone()));
     let NodeHandle { node, node_exit_future } = NodeBuilder::new(node_config)
         .testing_node_with_datadir(tasks.executor(), datadir.clone())
-        .node(EthereumNode::default())
+        .with_types::<EthereumNode>()
+        .with_components(components)
+        .with_add_ons(EthereumAddOns::default())
         .launch_with_debug_capabilities()
         .await?;
 
@@ -135,12 +312,39 @@ async fn main() -> eyre::Result<()> {
         println!("  {}. {} - Balance: {} ETH", i + 1, account, balance / U256::from(10u64.pow(18)));
     }
 
+    // Keep the snapshot head on the canonical tip, so `latest` follows the winner of a reorg
+    let store = SnapshotStore::open(&datadir)?;
+    let snapshots = Arc::new(
+        SnapshotProvider::new(
+            poa_chain.clone(),
+            Arc::new(HeaderStateProvider::new(node.provider.clone())),
+        )
+        .with_store(store.clone()),
+    );
+    let mut canonical = node.provider.canonical_state_stream();
+    let tip_snapshots = snapshots.clone();
+    tasks.executor().spawn(async move {
+        let snapshots = tip_snapshots;
+        while let Some(notification) = canonical.next().await {
+            match notification {
+                CanonStateNotification::Commit { new } => {
+                    snapshots.cache().on_commit(new.tip().hash())
+                }
+                CanonStateNotification::Reorg { old, new } => {
+                    // A plain revert leaves the chain at the fork point
+                    let tip = if new.is_empty() { old.fork_block().hash } else { new.tip().hash() };
+                    snapshots.cache().on_reorg(tip)
+                }
+            }
+        }
+    });
+
     // Subscribe to new blocks
     let mut notifications = node.provider.canonical_state_stream();
 
     println!("\n📖 Chain data is stored in: {:?}", datadir);
     println!(
-        "\n🚀 Blocks are produced every {} seconds (POA interval mining).",
+        "\n🚀 Blocks are produced every {} seconds (interval mining).",
         poa_chain.block_period()
     );
 
@@ -168,5 +372,128 @@ async fn main() -> eyre::Result<()> {
     println!("Press Ctrl+C to stop the node...\n");
 
     // Keep the node running until exit signal
-    node_exit_future.await
-}
\ No newline at end of file
+    tokio::select! {
+        exit = node_exit_future => exit?,
+        signal = tokio::signal::ctrl_c() => signal?,
+    }
+
+    // A clean shutdown persists the snapshot of the tip, so a restart replays no headers
+    match save_tip_snapshot(&snapshots, &store) {
+        Ok(number) => println!("Saved the snapshot of block #{number}"),
+        Err(err) => warn!(target: "poa::snapshot", %err, "Failed to save the snapshot of the tip"),
+    }
+    Ok(())
+}
+
+/// Persist the snapshot of the canonical tip in `store`, returning its block number
+fn save_tip_snapshot(snapshots: &SnapshotProvider, store: &SnapshotStore) -> eyre::Result<u64> {
+    let snapshot = snapshots.snapshot_at_block(BlockId::latest())?;
+    store.save(&snapshot)?;
+    Ok(snapshot.number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chainspec::PoaConfig, consensus::test_utils::ChainHarness, signer::dev};
+    use alloy_consensus::{SignableTransaction, TxEip1559};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::TxKind;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+    use reth_ethereum::{rpc::api::eth::helpers::EthTransactions, TransactionSigned};
+    use std::fs;
+    use zeroize::Zeroizing;
+
+    #[tokio::test]
+    async fn test_new_key_with_password_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = dir.path().join("keystore");
+        let file = dir.path().join("passwords");
+        let args = Args::try_parse_from([
+            "custom-poa-node",
+            "--new-key",
+            "--keystore",
+            keystore.to_str().unwrap(),
+            "--password-file",
+            file.to_str().unwrap(),
+        ])
+        .unwrap();
+
+        // Several passwords, as used to unlock a keystore directory
+        let other = Address::with_last_byte(1);
+        fs::write(&file, format!("# {other}\nlabeled\nfirst\nsecond\n")).unwrap();
+        let address = new_key(&args).await.unwrap();
+
+        let manager = SignerManager::new();
+        let first = PasswordSource::Static(Zeroizing::new("first".to_string()));
+        assert_eq!(manager.load_keystore_dir(&keystore, &first).await.unwrap(), [address]);
+
+        // Without a password no key is written
+        fs::write(&file, "").unwrap();
+        let err = new_key(&args).await.unwrap_err();
+        assert!(err.to_string().contains("no keystore password"), "{err}");
+        assert_eq!(fs::read_dir(&keystore).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_save_tip_snapshot() {
+        let chain = Arc::new(PoaChainSpec::dev_chain());
+        let mut harness = ChainHarness::new(chain.clone(), dev::setup_dev_signers().await);
+        for _ in 0..3 {
+            let header = harness.builder().build().await;
+            harness.extend(header).unwrap();
+        }
+        let snapshots = SnapshotProvider::new(chain, harness.provider.clone());
+        snapshots.cache().on_commit(harness.parent.hash());
+
+        let datadir = tempfile::tempdir().unwrap();
+        let store = SnapshotStore::open(datadir.path()).unwrap();
+        assert_eq!(save_tip_snapshot(&snapshots, &store).unwrap(), 3);
+        assert_eq!(store.load(3), Some(harness.snapshot));
+    }
+
+    #[tokio::test]
+    async fn test_zero_base_fee_node_mines_free_transactions() {
+        let dev = PoaChainSpec::dev_chain();
+        let config = PoaConfig { period: 1, zero_base_fee: true, ..dev.poa_config().clone() };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis(), config));
+        let mut rpc = RpcSettings::default();
+        rpc.http = false;
+
+        let tasks = TaskManager::current();
+        let components = EthereumNode::components().network(PoaNetworkBuilder::new(chain.clone()));
+        let NodeHandle { node, node_exit_future: _ } = NodeBuilder::new(node_config(&chain, &rpc))
+            .testing_node(tasks.executor())
+            .with_types::<EthereumNode>()
+            .with_components(components)
+            .with_add_ons(EthereumAddOns::default())
+            .launch_with_debug_capabilities()
+            .await
+            .unwrap();
+        let mut blocks = node.provider.canonical_state_stream();
+
+        // `maxFeePerGas = 0`, from an account without any balance
+        let key = PrivateKeySigner::random();
+        let tx = TxEip1559 {
+            chain_id: chain.inner().chain.id(),
+            gas_limit: 21_000,
+            max_fee_per_gas: 0,
+            to: TxKind::Call(Address::repeat_byte(0x42)),
+            ..Default::default()
+        };
+        let signature = key.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let tx = TransactionSigned::from(tx.into_signed(signature));
+        let eth_api = node.rpc_registry.eth_api();
+        let hash = eth_api.send_raw_transaction(tx.encoded_2718().into()).await.unwrap();
+
+        loop {
+            let notification = blocks.next().await.unwrap();
+            let block = notification.tip();
+            assert_eq!(block.header().base_fee_per_gas(), Some(0));
+            if block.body().transactions().any(|tx| *tx.tx_hash() == hash) {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! P2P network of the node
+//!
+//! The node runs on the inner reth [`ChainSpec`](reth_chainspec::ChainSpec) of the
+//! [`PoaChainSpec`], whose [`ForkId`](reth_ethereum_forks::ForkId) lacks the POA parameters.
+//! [`PoaNetworkBuilder`] builds reth's network with the [`ForkFilter`] of the [`PoaChainSpec`]
+//! instead, so peers running other POA parameters are turned away at the handshake.
+
+use crate::chainspec::PoaChainSpec;
+use reth_chainspec::Hardforks;
+use reth_ethereum::{
+    network::{
+        primitives::BasicNetworkPrimitives, NetworkConfig, NetworkHandle, NetworkManager,
+        NetworkPrimitives, PeersInfo,
+    },
+    node::{
+        api::{FullNodeTypes, NodeTypes, PrimitivesTy, TxTy},
+        builder::{components::NetworkBuilder, BuilderContext},
+    },
+    pool::{PoolPooledTx, PoolTransaction, TransactionPool},
+};
+use reth_ethereum_forks::ForkFilter;
+use reth_tracing::tracing::info;
+use std::sync::Arc;
+
+/// Builds reth's network, announcing the [`ForkFilter`] of the [`PoaChainSpec`]
+#[derive(Debug, Clone)]
+pub struct PoaNetworkBuilder {
+    chain_spec: Arc<PoaChainSpec>,
+}
+
+impl PoaNetworkBuilder {
+    /// Network builder for the given chain
+    pub const fn new(chain_spec: Arc<PoaChainSpec>) -> Self {
+        Self { chain_spec }
+    }
+}
+
+impl<Node, Pool> NetworkBuilder<Node, Pool> for PoaNetworkBuilder
+where
+    Node: FullNodeTypes<Types: NodeTypes<ChainSpec: Hardforks>>,
+    Pool: TransactionPool<Transaction: PoolTransaction<Consensus = TxTy<Node::Types>>>
+        + Unpin
+        + 'static,
+{
+    type Network =
+        NetworkHandle<BasicNetworkPrimitives<PrimitivesTy<Node::Types>, PoolPooledTx<Pool>>>;
+
+    async fn build_network(
+        self,
+        ctx: &BuilderContext<Node>,
+        pool: Pool,
+    ) -> eyre::Result<Self::Network> {
+        let mut config = ctx.network_config()?;
+        set_fork_filter(&mut config, self.chain_spec.fork_filter(ctx.head()));
+        let fork_id = config.status.forkid;
+        let network = NetworkManager::builder(config).await?;
+        let handle = ctx.start_network(network, pool);
+        info!(
+            target: "reth::cli",
+            enode=%handle.local_node_record(),
+            ?fork_id,
+            "P2P networking initialized"
+        );
+        Ok(handle)
+    }
+}
+
+/// Replace the fork filter of `config`, and the fork id of the status it sends to peers
+///
+/// The session manager keeps moving the filter to new heads, see
+/// [`ForkFilter::set_head`], so this only has to happen at launch.
+fn set_fork_filter<C, N: NetworkPrimitives>(config: &mut NetworkConfig<C, N>, filter: ForkFilter) {
+    config.status.forkid = filter.current();
+    config.fork_filter = filter;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use reth_chainspec::EthChainSpec;
+    use reth_ethereum::{
+        network::{
+            config::rng_secret_key, EthNetworkPrimitives, NetworkConfigBuilder, NetworkEvent,
+            NetworkEventListenerProvider, Peers,
+        },
+        node::{
+            builder::{NodeBuilder, NodeHandle},
+            core::node_config::NodeConfig,
+            node::EthereumAddOns,
+            EthereumNode,
+        },
+        tasks::TaskManager,
+    };
+    use reth_ethereum_forks::Head;
+
+    #[tokio::test]
+    async fn test_node_advertises_poa_fork_id() {
+        let chain = Arc::new(PoaChainSpec::dev_chain());
+        let tasks = TaskManager::current();
+        let NodeHandle { node, node_exit_future: _ } =
+            NodeBuilder::new(NodeConfig::test().with_chain(chain.inner().clone()))
+                .testing_node(tasks.executor())
+                .with_types::<EthereumNode>()
+                .with_components(
+                    EthereumNode::components().network(PoaNetworkBuilder::new(chain.clone())),
+                )
+                .with_add_ons(EthereumAddOns::default())
+                .launch()
+                .await
+                .unwrap();
+
+        // A peer running the POA chain spec itself, which only accepts the POA fork id
+        let config = NetworkConfigBuilder::<EthNetworkPrimitives>::eth(rng_secret_key())
+            .disable_discovery()
+            .with_unused_ports()
+            .build_with_noop_provider(chain.clone());
+        let peer = NetworkManager::new(config).await.unwrap();
+        let handle = peer.handle().clone();
+        let mut events = handle.event_listener();
+        tokio::spawn(peer);
+
+        let record = node.network.local_node_record();
+        handle.add_peer(record.id, record.tcp_addr());
+        let status = loop {
+            if let NetworkEvent::ActivePeerSession { info, .. } = events.next().await.unwrap() {
+                break info.status;
+            }
+        };
+
+        let head = Head { timestamp: chain.genesis().timestamp, ..Default::default() };
+        assert_eq!(status.forkid, chain.fork_id(&head));
+        assert_ne!(status.forkid, chain.inner().fork_id(&head));
+    }
+}
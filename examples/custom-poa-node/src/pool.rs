@@ -0,0 +1,103 @@
+//! Transaction pool settings
+//!
+//! The pool is reth's, configured through [`TxPoolArgs`]; [`txpool_args`] adjusts them to what the
+//! chain allows.
+
+use crate::chainspec::PoaChainSpec;
+use reth_ethereum::node::core::args::TxPoolArgs;
+
+/// Adjust `args` to the chain
+///
+/// Gasless chains need the pool to accept transactions that pay no fee at all, and chains that
+/// never activate Cancun reject blob transactions at the pool instead of at block building.
+pub fn txpool_args(chain: &PoaChainSpec, mut args: TxPoolArgs) -> TxPoolArgs {
+    if chain.is_zero_base_fee() {
+        args.minimal_protocol_basefee = 0;
+    }
+    if !chain.supports_blob_transactions() {
+        args.disable_blobs_support = true;
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chainspec::{HardforkSchedule, PoaConfig};
+    use alloy_consensus::{SignableTransaction, TxEip4844};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::{Address, B256};
+    use alloy_signer::SignerSync;
+    use reth_chainspec::{EthChainSpec, ForkCondition};
+    use reth_ethereum::{
+        pool::{
+            blobstore::InMemoryBlobStore, error::InvalidPoolTransactionError,
+            validate::EthTransactionValidatorBuilder, EthPooledTransaction, TransactionOrigin,
+            TransactionValidationOutcome,
+        },
+        provider::test_utils::MockEthProvider,
+        TransactionSigned,
+    };
+    use reth_ethereum_forks::EthereumHardfork;
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_primitives_traits::{transaction::error::InvalidTransactionError, Recovered};
+
+    /// Validate a blob transaction the way the node's pool does
+    fn validate_blob_transaction(
+        chain: &PoaChainSpec,
+    ) -> TransactionValidationOutcome<EthPooledTransaction> {
+        let args = txpool_args(chain, TxPoolArgs::default());
+        let provider = MockEthProvider::default()
+            .with_chain_spec((**chain.inner()).clone())
+            .with_genesis_block();
+        let validator =
+            EthTransactionValidatorBuilder::new(provider, EthEvmConfig::new(chain.inner().clone()))
+                .set_eip4844(!args.disable_blobs_support)
+                .build(InMemoryBlobStore::default());
+
+        let key = alloy_signer_local::PrivateKeySigner::random();
+        let tx = TxEip4844 {
+            chain_id: chain.chain().id(),
+            gas_limit: 21_000,
+            max_fee_per_gas: 1_000_000_000,
+            to: Address::repeat_byte(0x42),
+            blob_versioned_hashes: vec![B256::repeat_byte(0x01)],
+            max_fee_per_blob_gas: 1,
+            ..Default::default()
+        };
+        let signature = key.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let tx: TransactionSigned = tx.into_signed(signature).into();
+        let encoded_length = tx.encode_2718_len();
+        let tx =
+            EthPooledTransaction::new(Recovered::new_unchecked(tx, key.address()), encoded_length);
+
+        validator.validate_one(TransactionOrigin::External, tx)
+    }
+
+    #[test]
+    fn test_shanghai_chain_rejects_blob_transactions() {
+        let dev_chain = PoaChainSpec::dev_chain();
+        let hardforks = HardforkSchedule::default()
+            .with_fork(EthereumHardfork::Cancun, ForkCondition::Never)
+            .with_fork(EthereumHardfork::Prague, ForkCondition::Never);
+        let shanghai = PoaChainSpec::new(
+            dev_chain.genesis().clone(),
+            PoaConfig { hardforks, ..dev_chain.poa_config().clone() },
+        );
+        assert!(txpool_args(&shanghai, TxPoolArgs::default()).disable_blobs_support);
+        assert!(matches!(
+            validate_blob_transaction(&shanghai).as_invalid(),
+            Some(InvalidPoolTransactionError::Consensus(InvalidTransactionError::Eip4844Disabled))
+        ));
+
+        // With Cancun the transaction type is fine, whatever else the pool has to say about it
+        assert!(!txpool_args(&dev_chain, TxPoolArgs::default()).disable_blobs_support);
+        assert!(!matches!(
+            validate_blob_transaction(&dev_chain).as_invalid(),
+            Some(InvalidPoolTransactionError::Consensus(
+                InvalidTransactionError::Eip4844Disabled |
+                    InvalidTransactionError::TxTypeNotSupported
+            ))
+        ));
+    }
+}
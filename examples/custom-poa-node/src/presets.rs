@@ -0,0 +1,113 @@
+//! Named chain presets
+//!
+//! Common chain shapes are available by name through
+//! [`PoaChainSpec::named`](crate::chainspec::PoaChainSpec::named), and the node accepts the name in
+//! place of a chain definition file. Downstream crates can add their own with [`register_preset`].
+
+use crate::{
+    chainspec::{DevChainOptions, PoaChainSpec, PoaConfig},
+    genesis::{self, GenesisConfig},
+};
+use alloy_genesis::Genesis;
+use alloy_primitives::{address, Address};
+use parking_lot::RwLock;
+use reth_chainspec::EthChainSpec;
+use std::{collections::BTreeMap, sync::LazyLock};
+
+/// Builds the genesis and POA config of a preset
+pub type PresetFn = fn() -> (Genesis, PoaConfig);
+
+/// Three dev signers with 2 second blocks, the same chain as
+/// [`PoaChainSpec::dev_chain`]
+pub const DEV: &str = "dev";
+
+/// A single dev signer with 1 second blocks
+pub const LOCAL_FAST: &str = "local-fast";
+
+/// 12 second blocks and [`CONSORTIUM_PLACEHOLDER_SIGNERS`], which must be replaced by the
+/// consortium's own signers before launch
+pub const CONSORTIUM_TEMPLATE: &str = "consortium-template";
+
+/// Signers of the [`CONSORTIUM_TEMPLATE`] preset, which nobody holds the keys for
+pub const CONSORTIUM_PLACEHOLDER_SIGNERS: [Address; 3] = [
+    address!("0000000000000000000000000000000000000001"),
+    address!("0000000000000000000000000000000000000002"),
+    address!("0000000000000000000000000000000000000003"),
+];
+
+static PRESETS: LazyLock<RwLock<BTreeMap<String, PresetFn>>> = LazyLock::new(|| {
+    let builtin: [(&str, PresetFn); 3] =
+        [(DEV, dev), (LOCAL_FAST, local_fast), (CONSORTIUM_TEMPLATE, consortium_template)];
+    RwLock::new(builtin.into_iter().map(|(name, preset)| (name.to_string(), preset)).collect())
+});
+
+/// Register `preset` under `name`, returning the preset it replaces, if any
+pub fn register_preset(name: impl Into<String>, preset: PresetFn) -> Option<PresetFn> {
+    PRESETS.write().insert(name.into(), preset)
+}
+
+/// The preset registered under `name`
+pub fn preset(name: &str) -> Option<PresetFn> {
+    PRESETS.read().get(name).copied()
+}
+
+/// Names of all registered presets, in alphabetical order
+pub fn preset_names() -> Vec<String> {
+    PRESETS.read().keys().cloned().collect()
+}
+
+fn dev() -> (Genesis, PoaConfig) {
+    let chain = PoaChainSpec::dev_chain();
+    (chain.genesis().clone(), chain.poa_config().clone())
+}
+
+fn local_fast() -> (Genesis, PoaConfig) {
+    let chain = PoaChainSpec::dev_chain_with(DevChainOptions {
+        block_period: 1,
+        signers: 1,
+        ..Default::default()
+    });
+    (chain.genesis().clone(), chain.poa_config().clone())
+}
+
+fn consortium_template() -> (Genesis, PoaConfig) {
+    let signers = CONSORTIUM_PLACEHOLDER_SIGNERS.to_vec();
+    let genesis = genesis::create_genesis(GenesisConfig::mainnet_compatible(
+        genesis::CONSORTIUM_CHAIN_ID,
+        signers.clone(),
+    ));
+    (genesis, PoaConfig { period: 12, epoch: 30000, signers, ..Default::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_presets_are_valid() {
+        fn single_signer() -> (Genesis, PoaConfig) {
+            let config = PoaConfig { period: 3, ..local_fast().1 };
+            (genesis::create_dev_genesis_for(&config), config)
+        }
+        assert!(register_preset("test-single-signer", single_signer).is_none());
+
+        let names = preset_names();
+        for name in [DEV, LOCAL_FAST, CONSORTIUM_TEMPLATE, "test-single-signer"] {
+            assert!(names.iter().any(|registered| registered == name), "{name}");
+        }
+        for name in &names {
+            let (genesis, config) = preset(name).unwrap()();
+            PoaChainSpec::try_new(genesis, config).unwrap();
+        }
+
+        let dev = PoaChainSpec::named(DEV).unwrap();
+        assert_eq!(dev.genesis_hash(), PoaChainSpec::dev_chain().genesis_hash());
+        let fast = PoaChainSpec::named(LOCAL_FAST).unwrap();
+        assert_eq!((fast.block_period(), fast.signers().len()), (1, 1));
+        let consortium = PoaChainSpec::named(CONSORTIUM_TEMPLATE).unwrap();
+        assert_eq!(consortium.signers(), CONSORTIUM_PLACEHOLDER_SIGNERS);
+        assert_eq!(consortium.block_period(), 12);
+        assert_eq!(PoaChainSpec::named("test-single-signer").unwrap().block_period(), 3);
+        assert!(PoaChainSpec::named("mainnet").is_none());
+    }
+}
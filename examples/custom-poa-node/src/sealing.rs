@@ -0,0 +1,91 @@
+//! Header sealing layout
+//!
+//! The extra data of a POA header is `[vanity][signers, on checkpoints only][seal]`. The seal is
+//! a signature over [`seal_hash`], the header hashed without the seal. Sealing
+//! ([`BlockSealer`](crate::signer::BlockSealer)) and validation
+//! ([`PoaConsensus`](crate::consensus::PoaConsensus)) both use this module, so they can't drift
+//! apart.
+
+use alloy_consensus::Header;
+use alloy_primitives::{keccak256, B256};
+
+/// Extra data structure for POA blocks
+/// Format: [vanity (32 bytes)][signers list (N*20 bytes, only in epoch blocks)][signature (65
+/// bytes)]
+pub const EXTRA_VANITY_LENGTH: usize = 32;
+/// Signature length in extra data (65 bytes: r=32, s=32, v=1)
+pub const EXTRA_SEAL_LENGTH: usize = 65;
+/// Ethereum address length (20 bytes)
+pub const ADDRESS_LENGTH: usize = 20;
+
+/// Calculate the hash used for sealing (excludes the signature from extra data)
+pub fn seal_hash(header: &Header) -> B256 {
+    // Create a copy of the header with signature stripped from extra data
+    let mut header_for_hash = header.clone();
+
+    let extra_data = &header.extra_data;
+    if extra_data.len() >= EXTRA_SEAL_LENGTH {
+        let without_seal = &extra_data[..extra_data.len() - EXTRA_SEAL_LENGTH];
+        header_for_hash.extra_data = without_seal.to_vec().into();
+    }
+
+    // Hash the modified header
+    keccak256(alloy_rlp::encode(&header_for_hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::{PoaChainSpec, PoaConfig},
+        consensus::PoaConsensus,
+        genesis,
+        signer::{dev, BlockSealer},
+    };
+    use alloy_primitives::U256;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_sealer_and_consensus_agree() {
+        let config = PoaConfig { epoch: 4, signers: genesis::dev_signers(), ..Default::default() };
+        let chain = Arc::new(PoaChainSpec::new(genesis::create_dev_genesis_for(&config), config));
+        let consensus = PoaConsensus::new(chain);
+        let manager = dev::setup_dev_signers().await;
+        let signer = genesis::dev_signers()[1];
+
+        let pre_blob = Header {
+            gas_limit: 30_000_000,
+            timestamp: 1_700_000_000,
+            base_fee_per_gas: Some(7),
+            difficulty: U256::from(2),
+            ..Default::default()
+        };
+        let post_blob = Header {
+            withdrawals_root: Some(B256::repeat_byte(1)),
+            blob_gas_used: Some(131_072),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(B256::repeat_byte(2)),
+            requests_hash: Some(B256::repeat_byte(3)),
+            ..pre_blob.clone()
+        };
+
+        let mut signers = genesis::dev_signers();
+        signers.sort();
+        for shape in [pre_blob, post_blob] {
+            for (number, epoch_signers) in [(4, Some(signers.as_slice())), (5, None)] {
+                let extra_data = BlockSealer::build_extra_data(&[9u8; 32], epoch_signers);
+                let header = Header { number, extra_data, ..shape.clone() };
+                let sealer = BlockSealer::new(manager.clone());
+                let sealed = sealer.seal_header(header.clone(), &signer).await.unwrap();
+
+                assert_eq!(seal_hash(&sealed), seal_hash(&header));
+                assert_eq!(BlockSealer::seal_hash(&sealed), consensus.seal_hash(&sealed));
+                assert_eq!(consensus.recover_signer(&sealed).unwrap(), signer, "block {number}");
+                if epoch_signers.is_some() {
+                    let extracted = consensus.extract_signers_from_epoch_block(&sealed).unwrap();
+                    assert_eq!(extracted, signers);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Signing audit log
+//!
+//! [`AuditLog`] appends a JSON line for every header [`BlockSealer`](super::BlockSealer) signs,
+//! so operators can account for each signature the node produced. Full files are rotated, never
+//! truncated: `signing-audit.jsonl` becomes `signing-audit.jsonl.1`, then `.2` and so on.
+
+use super::SignerError;
+use alloy_primitives::{Address, B256};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    ops::RangeBounds,
+    path::{Path, PathBuf},
+};
+
+/// File in the datadir holding the [`AuditLog`]
+pub const AUDIT_LOG_FILE: &str = "signing-audit.jsonl";
+
+/// Size at which the audit log is rotated by default, see [`AuditLog::with_max_size`]
+pub const DEFAULT_AUDIT_LOG_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// A signature in the [`AuditLog`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// Unix time of the signature in seconds
+    pub timestamp: u64,
+    /// Block number of the signed header
+    pub number: u64,
+    /// Seal hash of the signed header
+    pub seal_hash: B256,
+    /// Signer that produced the signature
+    pub signer: Address,
+    /// Whether the header was sealed in turn
+    pub in_turn: bool,
+}
+
+/// Append-only JSON lines log of the signatures a node produced
+#[derive(Debug)]
+pub struct AuditLog {
+    /// File entries are appended to
+    path: PathBuf,
+    /// Size beyond which the file is rotated
+    max_size: u64,
+    /// Whether a failed write fails the seal or only logs a warning
+    fail_on_error: bool,
+    /// The open file
+    state: Mutex<AuditFile>,
+}
+
+/// The file currently appended to
+#[derive(Debug)]
+struct AuditFile {
+    file: File,
+    /// Bytes in the file
+    size: u64,
+    /// Number of rotated files
+    rotated: u32,
+}
+
+impl AuditLog {
+    /// Append to the log at `path`, usually [`AUDIT_LOG_FILE`] in the datadir
+    ///
+    /// Writes fail the seal and the file is rotated at [`DEFAULT_AUDIT_LOG_MAX_SIZE`].
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, SignerError> {
+        let path = path.into();
+        let mut rotated = 0;
+        while rotated_path(&path, rotated + 1).exists() {
+            rotated += 1;
+        }
+        let (file, size) = open_append(&path).map_err(SignerError::AuditLogIo)?;
+        Ok(Self {
+            path,
+            max_size: DEFAULT_AUDIT_LOG_MAX_SIZE,
+            fail_on_error: true,
+            state: Mutex::new(AuditFile { file, size, rotated }),
+        })
+    }
+
+    /// Rotate the file once appending would grow it beyond `max_size` bytes
+    pub const fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Whether a failed write fails the seal, the default, or only logs a warning
+    pub const fn with_fail_on_error(mut self, fail_on_error: bool) -> Self {
+        self.fail_on_error = fail_on_error;
+        self
+    }
+
+    /// Whether a failed write fails the seal
+    pub const fn fail_on_error(&self) -> bool {
+        self.fail_on_error
+    }
+
+    /// File entries are appended to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `entry`, synced to disk
+    pub fn append(&self, entry: &AuditEntry) -> Result<(), SignerError> {
+        let mut line = serde_json::to_vec(entry)
+            .map_err(|err| SignerError::AuditLogIo(io::Error::other(err)))?;
+        line.push(b'\n');
+
+        let mut state = self.state.lock();
+        if state.size > 0 && state.size + line.len() as u64 > self.max_size {
+            self.rotate(&mut state).map_err(SignerError::AuditLogIo)?;
+        }
+        state
+            .file
+            .write_all(&line)
+            .and_then(|()| state.file.sync_data())
+            .map_err(SignerError::AuditLogIo)?;
+        state.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Entries for the block numbers in `range`, oldest first, including rotated files
+    pub fn query(&self, range: impl RangeBounds<u64>) -> Result<Vec<AuditEntry>, SignerError> {
+        // Keep rotation out while reading
+        let state = self.state.lock();
+        let files = (1..=state.rotated)
+            .map(|index| rotated_path(&self.path, index))
+            .chain([self.path.clone()]);
+
+        let mut entries = Vec::new();
+        for path in files {
+            let log = fs::read_to_string(&path).map_err(SignerError::AuditLogIo)?;
+            for (line, entry) in log.lines().enumerate() {
+                let entry: AuditEntry = serde_json::from_str(entry).map_err(|err| {
+                    let line = line + 1;
+                    SignerError::MalformedAuditLog(format!("{}:{line}: {err}", path.display()))
+                })?;
+                if range.contains(&entry.number) {
+                    entries.push(entry);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Move the full file aside and start a new one
+    fn rotate(&self, state: &mut AuditFile) -> io::Result<()> {
+        fs::rename(&self.path, rotated_path(&self.path, state.rotated + 1))?;
+        state.rotated += 1;
+        (state.file, state.size) = open_append(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Path of the `index`th rotated file of the log at `path`
+fn rotated_path(path: &Path, index: u32) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{index}"));
+    rotated.into()
+}
+
+/// Open `path` for appending, with its current size
+fn open_append(path: &Path) -> io::Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::{dev, BlockSealer, SignerManager};
+    use alloy_consensus::Header;
+    use alloy_primitives::{Bytes, U256};
+    use std::sync::Arc;
+
+    fn header(number: u64) -> Header {
+        Header {
+            number,
+            difficulty: U256::from(if number % 2 == 0 { 1 } else { 2 }),
+            extra_data: Bytes::from(vec![0u8; 32 + 65]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        let manager = Arc::new(SignerManager::new());
+        let signer = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        let sealer =
+            BlockSealer::new(manager).with_audit_log(Arc::new(AuditLog::open(&path).unwrap()));
+
+        for number in 1..=3 {
+            sealer.seal_header(header(number), &signer).await.unwrap();
+        }
+
+        let log = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = log.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["number"], 1);
+        assert_eq!(first["inTurn"], false);
+        assert_eq!(first["signer"].as_str().unwrap().parse::<Address>().unwrap(), signer);
+        assert_eq!(
+            first["sealHash"].as_str().unwrap().parse::<B256>().unwrap(),
+            BlockSealer::seal_hash(&header(1))
+        );
+        assert!(first["timestamp"].as_u64().unwrap() > 0);
+
+        let audit = sealer.audit_log().unwrap();
+        let entries = audit.query(2..).unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.number).collect::<Vec<_>>(), [2, 3]);
+        assert!(entries[0].in_turn);
+    }
+
+    #[test]
+    fn test_audit_log_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        let entry = |number| AuditEntry {
+            timestamp: 1_700_000_000,
+            number,
+            seal_hash: B256::repeat_byte(number as u8),
+            signer: Address::with_last_byte(1),
+            in_turn: true,
+        };
+        let line_len = serde_json::to_vec(&entry(0)).unwrap().len() as u64 + 1;
+
+        // Room for two entries per file
+        let audit = AuditLog::open(&path).unwrap().with_max_size(2 * line_len);
+        for number in 0..5 {
+            audit.append(&entry(number)).unwrap();
+        }
+        assert_eq!(fs::metadata(&path).unwrap().len(), line_len);
+        for index in [1, 2] {
+            assert_eq!(fs::metadata(rotated_path(&path, index)).unwrap().len(), 2 * line_len);
+        }
+        assert!(!rotated_path(&path, 3).exists());
+
+        // Reopened, rotated files are still found and appended after
+        drop(audit);
+        let audit = AuditLog::open(&path).unwrap().with_max_size(2 * line_len);
+        audit.append(&entry(5)).unwrap();
+        audit.append(&entry(6)).unwrap();
+        assert!(rotated_path(&path, 3).exists());
+        let numbers: Vec<_> = audit.query(..).unwrap().iter().map(|entry| entry.number).collect();
+        assert_eq!(numbers, (0..7).collect::<Vec<_>>());
+        assert_eq!(audit.query(3..=4).unwrap(), [entry(3), entry(4)]);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_write_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        let manager = Arc::new(SignerManager::new());
+        let signer = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+
+        // Rotating fails once the directory is gone
+        let audit = AuditLog::open(&path).unwrap().with_max_size(1);
+        let entry =
+            AuditEntry { timestamp: 0, number: 0, seal_hash: B256::ZERO, signer, in_turn: true };
+        audit.append(&entry).unwrap();
+        fs::remove_dir_all(dir.path()).unwrap();
+
+        let audit = Arc::new(audit);
+        let sealer = BlockSealer::new(manager.clone()).with_audit_log(audit.clone());
+        let err = sealer.seal_header(header(1), &signer).await.unwrap_err();
+        assert!(matches!(err, SignerError::AuditLogIo(_)), "{err}");
+        drop(sealer);
+
+        let audit = Arc::into_inner(audit).unwrap().with_fail_on_error(false);
+        let sealer = BlockSealer::new(manager).with_audit_log(Arc::new(audit));
+        let sealed = sealer.seal_header(header(1), &signer).await.unwrap();
+        assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), signer);
+    }
+}
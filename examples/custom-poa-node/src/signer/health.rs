@@ -0,0 +1,108 @@
+//! Signer health
+//!
+//! [`SignerManager::health_check`] tells whether every signer can actually sign, without sealing
+//! a block: each signs a throwaway hash, which for a remote or KMS backend doubles as a ping.
+
+use super::{sign_with, DynSigner, SignerError, SignerManager, SignerSource};
+use alloy_primitives::{keccak256, Address};
+use futures_util::future::join_all;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long [`SignerManager::health_check`] reuses the result of a check
+pub const HEALTH_CHECK_TTL: Duration = Duration::from_secs(10);
+
+/// How long a signer may take to sign the health check hash
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Preimage of the hash signed by health checks, which no header hashes to
+const HEALTH_CHECK_PREIMAGE: &[u8] = b"poa signer health check";
+
+/// Whether a signer could sign when last checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerHealth {
+    /// Address of the signer
+    pub address: Address,
+    /// Where the key of the signer comes from
+    pub source: SignerSource,
+    /// Whether the last check signed successfully
+    pub unlocked: bool,
+    /// Why the last check failed
+    pub last_error: Option<String>,
+    /// Unix time in seconds of the last successful check
+    pub last_success_at: Option<u64>,
+    /// How long the last check took
+    pub latency: Duration,
+}
+
+impl SignerManager {
+    /// Check that every signer can sign, sorted by address
+    ///
+    /// Signers checked within the last [`HEALTH_CHECK_TTL`] aren't asked again, so repeated RPC
+    /// calls don't hammer remote backends. The others are checked concurrently, each within
+    /// [`HEALTH_CHECK_TIMEOUT`].
+    pub async fn health_check(&self) -> Vec<SignerHealth> {
+        let signers: Vec<_> = self
+            .signers
+            .read()
+            .await
+            .iter()
+            .map(|(address, entry)| (*address, entry.signer.clone(), entry.source.clone()))
+            .collect();
+
+        let now = Instant::now();
+        let mut report = Vec::new();
+        let mut stale = Vec::new();
+        {
+            let mut cache = self.health.lock();
+            cache.retain(|address, _| signers.iter().any(|(signer, ..)| signer == address));
+            for (address, signer, source) in signers {
+                match cache.get(&address) {
+                    Some((checked, health)) if now.duration_since(*checked) < HEALTH_CHECK_TTL => {
+                        report.push(health.clone())
+                    }
+                    _ => stale.push(check(address, signer, source)),
+                }
+            }
+        }
+
+        let checked = join_all(stale).await;
+        let mut cache = self.health.lock();
+        for mut health in checked {
+            if !health.unlocked {
+                health.last_success_at =
+                    cache.get(&health.address).and_then(|(_, previous)| previous.last_success_at);
+            }
+            cache.insert(health.address, (Instant::now(), health.clone()));
+            report.push(health);
+        }
+
+        report.sort_unstable_by_key(|health| health.address);
+        report
+    }
+}
+
+/// Have `signer` sign the health check hash
+async fn check(address: Address, signer: DynSigner, source: SignerSource) -> SignerHealth {
+    let hash = keccak256(HEALTH_CHECK_PREIMAGE);
+    let started = Instant::now();
+    let result = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, sign_with(&signer, hash))
+        .await
+        .unwrap_or(Err(SignerError::Timeout(address)))
+        .and_then(|signature| match signature.recover_address_from_prehash(&hash) {
+            Ok(recovered) if recovered == address => Ok(()),
+            _ => Err(SignerError::Permanent(format!("signature doesn't recover to {address}"))),
+        });
+    let latency = started.elapsed();
+
+    let last_success_at = result.is_ok().then(|| {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs()
+    });
+    SignerHealth {
+        address,
+        source,
+        unlocked: result.is_ok(),
+        last_error: result.err().map(|err| err.to_string()),
+        last_success_at,
+        latency,
+    }
+}
@@ -0,0 +1,301 @@
+//! Signer manifest
+//!
+//! [`SignerManager::save_manifest`] records each signer's address and where its key can be found
+//! again: a keystore file, a mnemonic account or a signing daemon. Key material never goes into
+//! the manifest. After a restart [`SignerManager::load_manifest`] brings the signers back.
+
+use super::{
+    check_remote_url, decrypt_keystore, dev, DynSigner, PasswordSource, RemoteSigner, SignerError,
+    SignerManager,
+};
+use alloy_primitives::Address;
+use alloy_signer::Signer;
+use reth_tracing::tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Where the key of a signer comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SignerSource {
+    /// An encrypted keystore file
+    Keystore {
+        /// Path of the keystore file
+        path: PathBuf,
+    },
+    /// An account derived from a mnemonic, which the manifest doesn't store
+    Mnemonic {
+        /// Derivation path without the account index
+        derivation_path: String,
+        /// Account index
+        index: u32,
+    },
+    /// A signing daemon, see [`RemoteSigner`]
+    Remote {
+        /// JSON-RPC endpoint of the daemon
+        url: String,
+        /// Timeout of a single request in milliseconds
+        timeout_ms: u64,
+        /// Retries after the first attempt
+        retries: u32,
+    },
+    /// A raw private key, which has to be added again after a restart
+    Key,
+    /// Another [`Signer`](alloy_signer::Signer) implementation, e.g. a hardware wallet, which has
+    /// to be added again after a restart
+    External,
+}
+
+/// Outcome of [`SignerManager::load_manifest`]
+#[derive(Debug, Default)]
+pub struct ManifestLoad {
+    /// Signers restored from their sources
+    pub loaded: Vec<Address>,
+    /// Entries that weren't restored, with the address if the entry names one
+    pub failed: Vec<(Option<Address>, SignerError)>,
+}
+
+/// The manifest file
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest<T> {
+    signers: Vec<T>,
+}
+
+/// A signer in the manifest file
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    address: Address,
+    source: SignerSource,
+}
+
+impl SignerManager {
+    /// Write the address and [`SignerSource`] of every signer to `path` as JSON
+    ///
+    /// The file is replaced atomically, so a crash leaves either the old or the new manifest.
+    pub async fn save_manifest(&self, path: &Path) -> Result<(), SignerError> {
+        let mut signers: Vec<_> = self
+            .signers
+            .read()
+            .await
+            .iter()
+            .map(|(address, entry)| ManifestEntry {
+                address: *address,
+                source: entry.source.clone(),
+            })
+            .collect();
+        signers.sort_unstable_by_key(|entry| entry.address);
+
+        let manifest = serde_json::to_string_pretty(&Manifest { signers })
+            .map_err(|err| SignerError::MalformedManifest(err.to_string()))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, manifest)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(SignerError::ManifestIo)?;
+        Ok(())
+    }
+
+    /// Restore the signers of the manifest at `path`
+    ///
+    /// Keystore passwords come from `passwords`; an entry whose password prompt returns `None` is
+    /// skipped. Mnemonic accounts are derived from `mnemonic`. Signers already registered, e.g.
+    /// raw keys added again from the node configuration, are left alone.
+    ///
+    /// Fails only if the manifest can't be read at all. Entries that can't be restored are
+    /// reported in [`ManifestLoad::failed`] and the others loaded regardless.
+    pub async fn load_manifest(
+        &self,
+        path: &Path,
+        passwords: &PasswordSource,
+        mnemonic: Option<&str>,
+    ) -> Result<ManifestLoad, SignerError> {
+        let manifest = fs::read_to_string(path).map_err(SignerError::ManifestIo)?;
+        let manifest: Manifest<serde_json::Value> = serde_json::from_str(&manifest)
+            .map_err(|err| SignerError::MalformedManifest(err.to_string()))?;
+
+        let mut load = ManifestLoad::default();
+        for value in manifest.signers {
+            let entry = match ManifestEntry::deserialize(&value) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    let address =
+                        value["address"].as_str().and_then(|address| address.parse().ok());
+                    load.failed.push((address, SignerError::MalformedManifest(err.to_string())));
+                    continue
+                }
+            };
+            if self.has_signer(&entry.address).await {
+                continue
+            }
+            match self.restore(&entry, passwords, mnemonic).await {
+                Ok(true) => load.loaded.push(entry.address),
+                Ok(false) => {
+                    warn!(target: "poa::signer", address = %entry.address, "Skipping signer")
+                }
+                Err(err) => load.failed.push((Some(entry.address), err)),
+            }
+        }
+
+        for (address, err) in &load.failed {
+            warn!(target: "poa::signer", ?address, %err, "Failed to restore signer");
+        }
+        info!(
+            target: "poa::signer",
+            path = %path.display(),
+            signers = ?load.loaded,
+            "Loaded signer manifest"
+        );
+        Ok(load)
+    }
+
+    /// Register the signer of `entry` from its source, `false` if its password prompt skipped it
+    async fn restore(
+        &self,
+        entry: &ManifestEntry,
+        passwords: &PasswordSource,
+        mnemonic: Option<&str>,
+    ) -> Result<bool, SignerError> {
+        let signer: DynSigner = match &entry.source {
+            SignerSource::Keystore { path } => {
+                let Some(password) = passwords.resolve(path).await? else { return Ok(false) };
+                Arc::new(decrypt_keystore(path, &password)?)
+            }
+            SignerSource::Mnemonic { derivation_path, index } => {
+                let mnemonic = mnemonic.ok_or_else(|| {
+                    SignerError::InvalidMnemonic("no mnemonic to derive the signer from".into())
+                })?;
+                Arc::new(dev::key_from_mnemonic(mnemonic, derivation_path, *index)?)
+            }
+            SignerSource::Remote { url, timeout_ms, retries } => {
+                check_remote_url(url)?;
+                Arc::new(
+                    RemoteSigner::new(entry.address, url.as_str())
+                        .with_timeout(Duration::from_millis(*timeout_ms))
+                        .with_retries(*retries),
+                )
+            }
+            SignerSource::Key | SignerSource::External => {
+                return Err(SignerError::NotRestorable(entry.address))
+            }
+        };
+
+        let found = signer.address();
+        if found != entry.address {
+            return Err(SignerError::AddressMismatch { expected: entry.address, found })
+        }
+        self.insert(signer, entry.source.clone()).await;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use alloy_signer_local::PrivateKeySigner;
+    use zeroize::Zeroizing;
+
+    /// The scrypt keystore fixture of the signer tests
+    const KEYSTORE: &str =
+        "testdata/keystore/UTC--2026-10-17T09-30-00.000000000Z--0a60a644910141059e9c79a716f9dad643cb092c";
+
+    /// Address of [`KEYSTORE`]
+    const KEYSTORE_ADDRESS: Address = address!("0a60a644910141059e9c79a716f9dad643cb092c");
+
+    fn passwords() -> PasswordSource {
+        PasswordSource::Static(Zeroizing::new("poa-keystore-password".to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_manifest_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signers.json");
+
+        let manager = SignerManager::new();
+        let keystore = Path::new(env!("CARGO_MANIFEST_DIR")).join(KEYSTORE);
+        manager.add_signer_from_keystore(&keystore, &passwords()).await.unwrap();
+        let hex_signer = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        let remote = RemoteSigner::new(Address::with_last_byte(1), "http://127.0.0.1:8550")
+            .with_timeout(Duration::from_millis(250));
+        manager.add_remote(remote).await.unwrap();
+        manager.save_manifest(&path).await.unwrap();
+
+        let manifest = fs::read_to_string(&path).unwrap();
+        assert!(!manifest.contains(dev::DEV_PRIVATE_KEYS[0]), "{manifest}");
+        assert!(manifest.contains("\"timeoutMs\": 250"), "{manifest}");
+        let mut expected = manager.signer_addresses().await;
+        expected.sort();
+
+        // Raw keys come back from the configuration, the rest from the manifest
+        let restarted = SignerManager::new();
+        restarted.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        let load = restarted.load_manifest(&path, &passwords(), None).await.unwrap();
+        assert!(load.failed.is_empty(), "{:?}", load.failed);
+        assert_eq!(load.loaded, vec![Address::with_last_byte(1), KEYSTORE_ADDRESS]);
+        let mut addresses = restarted.signer_addresses().await;
+        addresses.sort();
+        assert_eq!(addresses, expected);
+
+        // Saving again records the same sources
+        let resaved = dir.path().join("resaved.json");
+        restarted.save_manifest(&resaved).await.unwrap();
+        assert_eq!(fs::read_to_string(resaved).unwrap(), manifest);
+
+        // Without the raw key its entry is reported
+        let restarted = SignerManager::new();
+        let load = restarted.load_manifest(&path, &passwords(), None).await.unwrap();
+        assert_eq!(load.loaded.len(), 2);
+        assert!(matches!(
+            load.failed.as_slice(),
+            [(Some(address), SignerError::NotRestorable(_))] if *address == hex_signer
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_manifest_partial_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signers.json");
+        let keystore = Path::new(env!("CARGO_MANIFEST_DIR")).join(KEYSTORE);
+        let dev2 = dev::DEV_PRIVATE_KEYS[2].parse::<PrivateKeySigner>().unwrap().address();
+        let manifest = serde_json::json!({ "signers": [
+            { "address": dev2, "source": {
+                "kind": "mnemonic", "derivationPath": dev::DEV_DERIVATION_PATH, "index": 2 } },
+            { "address": Address::with_last_byte(2), "source": {
+                "kind": "keystore", "path": keystore } },
+            { "address": Address::with_last_byte(3), "source": { "kind": "ledger" } },
+            { "address": Address::with_last_byte(4), "source": {
+                "kind": "keystore", "path": dir.path().join("missing") } },
+            { "source": { "kind": "key" } },
+        ]});
+        fs::write(&path, manifest.to_string()).unwrap();
+
+        let manager = SignerManager::new();
+        let load =
+            manager.load_manifest(&path, &passwords(), Some(dev::DEV_MNEMONIC)).await.unwrap();
+        assert_eq!(load.loaded, vec![dev2]);
+        assert_eq!(manager.signer_addresses().await, vec![dev2]);
+        let [mismatch, unknown, missing, anonymous] = load.failed.as_slice() else {
+            panic!("{:?}", load.failed)
+        };
+        assert!(matches!(
+            mismatch,
+            (Some(_), SignerError::AddressMismatch { found, .. }) if *found == KEYSTORE_ADDRESS
+        ));
+        assert!(matches!(unknown, (Some(_), SignerError::MalformedManifest(_))));
+        assert!(matches!(missing, (Some(_), SignerError::KeystoreIo(_))));
+        assert!(matches!(anonymous, (None, SignerError::MalformedManifest(_))));
+
+        // Mnemonic accounts need the mnemonic
+        let load = SignerManager::new().load_manifest(&path, &passwords(), None).await.unwrap();
+        assert!(load.loaded.is_empty());
+        assert!(matches!(load.failed[0], (Some(_), SignerError::InvalidMnemonic(_))));
+
+        fs::write(&path, "{\"signers\": [").unwrap();
+        let err = manager.load_manifest(&path, &passwords(), None).await.unwrap_err();
+        assert!(matches!(err, SignerError::MalformedManifest(_)), "{err}");
+    }
+}
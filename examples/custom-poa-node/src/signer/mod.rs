@@ -0,0 +1,2038 @@
+//! Block Signer Implementation
+//!
+//! This module provides utilities for signing POA blocks, including:
+//! - Key management for authorized signers
+//! - Block sealing (signing)
+//! - Signature verification
+//! - Signer votes proposed by the local operator
+//! - Keys held by a signing daemon, see [`remote`]
+//! - Remembering where keys came from across restarts, see [`manifest`]
+//! - Refusing to sign two headers at the same height, see [`record`]
+
+use crate::{
+    consensus::snapshot::{Snapshot, NONCE_AUTH_VOTE, NONCE_DROP_VOTE},
+    sealing::{self, EXTRA_SEAL_LENGTH, EXTRA_VANITY_LENGTH},
+};
+use alloy_consensus::Header;
+use alloy_primitives::{hex, Address, Bytes, Signature, B256, U256};
+use alloy_signer::Signer;
+use alloy_signer_local::{
+    coins_bip39::English, LocalSignerError, MnemonicBuilder, PrivateKeySigner,
+};
+use eth_keystore::KeystoreError;
+use parking_lot::Mutex;
+use rand_08::rngs::OsRng;
+use reth_primitives_traits::{Block, SealedBlock};
+use reth_tracing::tracing::{info, warn};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use thiserror::Error;
+use tokio::sync::{broadcast, RwLock};
+use zeroize::Zeroizing;
+
+pub mod audit;
+pub mod health;
+pub mod manifest;
+pub mod password;
+pub mod record;
+pub mod remote;
+pub mod watcher;
+
+pub use self::{
+    audit::{AuditEntry, AuditLog, AUDIT_LOG_FILE},
+    health::SignerHealth,
+    manifest::{ManifestLoad, SignerSource},
+    password::{Password, PasswordSource},
+    record::{SignRecord, SIGN_RECORD_FILE},
+    remote::RemoteSigner,
+    watcher::KeystoreWatcher,
+};
+
+/// Errors that can occur during signing operations
+#[derive(Debug, Error)]
+pub enum SignerError {
+    /// No signing key available for the specified address
+    #[error("No signer available for address {0}")]
+    NoSignerForAddress(Address),
+
+    /// Signing operation failed
+    #[error("Signing failed: {0}")]
+    SigningFailed(String),
+
+    /// Invalid private key format
+    #[error("Invalid private key")]
+    InvalidPrivateKey,
+
+    /// Invalid mnemonic phrase
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+
+    /// Malformed BIP-32 derivation path
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+
+    /// Vanity text longer than the vanity field
+    #[error("Vanity of {0} bytes exceeds {EXTRA_VANITY_LENGTH} bytes")]
+    VanityTooLong(usize),
+
+    /// Extra data not shaped like a header between checkpoints
+    #[error("Extra data of {got} bytes, expected {expected} bytes")]
+    InvalidExtraData {
+        /// Vanity plus seal length
+        expected: usize,
+        /// Actual length
+        got: usize,
+    },
+
+    /// Checkpoint header without signers
+    #[error("Checkpoint without signers")]
+    EmptyCheckpointSigners,
+
+    /// The header of a block doesn't commit to the transactions of its body
+    #[error("Transactions root {header} of the header doesn't match the body's {body}")]
+    TransactionsRootMismatch {
+        /// Transactions root in the header
+        header: B256,
+        /// Transactions root computed from the body
+        body: B256,
+    },
+
+    /// None of the local keys is an authorized signer
+    #[error("No local key is an authorized signer")]
+    NoAuthorizedSigner,
+
+    /// A local key is authorized but not in turn, and out-of-turn sealing is disabled
+    #[error("Block {number} is not the turn of a local signer")]
+    NotInTurn {
+        /// Block number of the header
+        number: u64,
+        /// Signer whose turn it is
+        in_turn: Option<Address>,
+    },
+
+    /// Every authorized local key sealed one of the blocks right before the header
+    #[error("All local signers signed too recently to seal block {0}")]
+    RecentlySigned(u64),
+
+    /// Keystore file could not be read
+    #[error("Failed to read keystore: {0}")]
+    KeystoreIo(#[from] io::Error),
+
+    /// Keystore file is not a web3 secret storage JSON
+    #[error("Malformed keystore: {0}")]
+    MalformedKeystore(String),
+
+    /// Keystore encrypted with a key derivation function other than scrypt or pbkdf2
+    #[error("Unsupported keystore KDF: {0}")]
+    UnsupportedKdf(String),
+
+    /// The password doesn't decrypt the keystore
+    #[error("Wrong keystore password")]
+    WrongPassword,
+
+    /// The environment variable holding the keystore password is not set
+    #[error("Keystore password variable {0} is not set")]
+    MissingPassword(String),
+
+    /// The password source has no password for the keystore
+    #[error("No password for keystore {0}")]
+    NoPassword(PathBuf),
+
+    /// The password of the keystore can't be asked for without a terminal
+    #[error("Cannot ask for the password of {0} without a terminal")]
+    NoTerminal(PathBuf),
+
+    /// The signer for the address didn't answer in time
+    #[error("Signer for {0} timed out")]
+    Timeout(Address),
+
+    /// Signing failed for a reason that may go away, e.g. an unreachable backend
+    #[error("Signing failed, may succeed on retry: {0}")]
+    Transient(String),
+
+    /// Signing failed and retrying won't help
+    #[error("Signing failed permanently: {0}")]
+    Permanent(String),
+
+    /// Signer manifest could not be read or written
+    #[error("Failed to access signer manifest: {0}")]
+    ManifestIo(io::Error),
+
+    /// Signer manifest is not valid JSON
+    #[error("Malformed signer manifest: {0}")]
+    MalformedManifest(String),
+
+    /// The manifest doesn't record where the key of the signer can be found again
+    #[error("Signer {0} can't be restored from its source, add it again")]
+    NotRestorable(Address),
+
+    /// The signer already signed a different header at or above the height
+    #[error("Signer {signer} already signed block {signed}, refusing to sign block {number}")]
+    AlreadySignedHeight {
+        /// Signer asked to sign
+        signer: Address,
+        /// Block number of the header to sign
+        number: u64,
+        /// Highest block number the signer signed
+        signed: u64,
+    },
+
+    /// The record of signed heights could not be read or written
+    #[error("Failed to access signed heights: {0}")]
+    SignRecordIo(io::Error),
+
+    /// The record of signed heights is not valid JSON
+    #[error("Malformed signed heights: {0}")]
+    MalformedSignRecord(String),
+
+    /// The signing audit log could not be read or written
+    #[error("Failed to access signing audit log: {0}")]
+    AuditLogIo(io::Error),
+
+    /// The signing audit log has a line that isn't an entry
+    #[error("Malformed signing audit log: {0}")]
+    MalformedAuditLog(String),
+
+    /// A manifest source yields another address than recorded
+    #[error("Signer source for {expected} yields {found}")]
+    AddressMismatch {
+        /// Address recorded in the manifest
+        expected: Address,
+        /// Address the source yields
+        found: Address,
+    },
+
+    /// A remote signer URL that isn't an absolute http(s) URL
+    #[error("Invalid remote signer URL {0}")]
+    InvalidRemoteUrl(String),
+
+    /// A signer for the address is already registered
+    #[error("A signer for {0} is already registered")]
+    DuplicateSigner(Address),
+}
+
+/// A signing backend held by [`SignerManager`]: a key in memory, a [`RemoteSigner`], or anything
+/// else implementing [`Signer`]
+pub type DynSigner = Arc<dyn Signer + Send + Sync>;
+
+/// How long signing may take by default, half the default block period
+pub const DEFAULT_SIGNING_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Capacity of the [`SignerManager::subscribe`] channel, beyond which slow subscribers lag
+pub const SIGNER_EVENTS_CAPACITY: usize = 64;
+
+/// Change to the signers of a [`SignerManager`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerEvent {
+    /// A signer for the address was registered
+    Added(Address),
+    /// The signer for the address was removed
+    Removed(Address),
+}
+
+/// Which local signer [`BlockSealer::seal_selected`] seals a block with
+///
+/// Whatever the policy, a signer that sealed one of the last
+/// [`signer_limit`](Snapshot::signer_limit) blocks is never picked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignerSelection {
+    /// This signer whenever it may seal, otherwise the in-turn or another local signer, e.g. the
+    /// new key during a key rotation
+    Preferred(Address),
+    /// The local signers in turns, by address, regardless of whose turn the block is
+    RoundRobinLocal,
+    /// Only the in-turn signer, if it is held locally
+    #[default]
+    InTurnOnly,
+}
+
+/// A registered signer
+struct SignerEntry {
+    signer: DynSigner,
+    /// Where the signer came from, for [`SignerManager::save_manifest`]
+    source: SignerSource,
+}
+
+/// Manages the signing keys that seal POA headers
+///
+/// Keys held in memory are zeroized when dropped, and [`Debug`](fmt::Debug) shows only the
+/// addresses signed for.
+pub struct SignerManager {
+    /// Map of address to signer
+    signers: RwLock<HashMap<Address, SignerEntry>>,
+    /// Sender of [`SignerEvent`]s, sent while `signers` is locked so they arrive in order
+    events: broadcast::Sender<SignerEvent>,
+    /// Last [health check](Self::health_check) per signer, with the time it was done
+    health: Mutex<HashMap<Address, (Instant, SignerHealth)>>,
+    /// Held for reading while a [`BlockSealer`] seals a block, so signers are removed between
+    /// blocks only
+    sealing: RwLock<()>,
+}
+
+impl fmt::Debug for SignerManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("SignerManager");
+        let Ok(signers) = self.signers.try_read() else { return debug.finish_non_exhaustive() };
+        let mut addresses: Vec<_> = signers.keys().collect();
+        addresses.sort_unstable();
+        debug.field("count", &addresses.len()).field("addresses", &addresses).finish()
+    }
+}
+
+impl SignerManager {
+    /// Create a new signer manager
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(SIGNER_EVENTS_CAPACITY);
+        Self {
+            signers: RwLock::new(HashMap::new()),
+            events,
+            health: Mutex::default(),
+            sealing: RwLock::new(()),
+        }
+    }
+
+    /// A manager holding `count` signers with random keys, returned with their addresses in
+    /// ascending order
+    ///
+    /// The keys are never written anywhere: when the manager is dropped, whatever the signers
+    /// control is lost. Meant for throwaway chains in tests and demos, see
+    /// [`PoaChainSpec::ephemeral_chain`](crate::chainspec::PoaChainSpec::ephemeral_chain).
+    pub fn ephemeral(count: usize) -> (Arc<Self>, Vec<Address>) {
+        let signers: HashMap<_, _> = (0..count)
+            .map(|_| {
+                let signer = PrivateKeySigner::random_with(&mut OsRng);
+                let entry = SignerEntry { signer: Arc::new(signer), source: SignerSource::Key };
+                (entry.signer.address(), entry)
+            })
+            .collect();
+        let mut addresses: Vec<_> = signers.keys().copied().collect();
+        addresses.sort_unstable();
+
+        warn!(
+            target: "poa::signer",
+            signers = ?addresses,
+            "Ephemeral signer keys are held in memory only and lost when the node stops; \
+             don't send anything of value to these addresses"
+        );
+        (Arc::new(Self { signers: RwLock::new(signers), ..Self::new() }), addresses)
+    }
+
+    /// Receive a [`SignerEvent`] for every signer added or removed from now on
+    ///
+    /// Replacing the signer of a registered address sends nothing. Events are never waited for:
+    /// a subscriber more than [`SIGNER_EVENTS_CAPACITY`] events behind misses the oldest ones and
+    /// gets [`RecvError::Lagged`](broadcast::error::RecvError::Lagged).
+    pub fn subscribe(&self) -> broadcast::Receiver<SignerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Add a signer from a private key hex string
+    ///
+    /// No copy of the key outlives the call; zeroizing `private_key_hex` is up to the caller.
+    pub async fn add_signer_from_hex(&self, private_key_hex: &str) -> Result<Address, SignerError> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        hex::decode_to_slice(private_key_hex, key.as_mut_slice())
+            .map_err(|_| SignerError::InvalidPrivateKey)?;
+        let signer = PrivateKeySigner::from_slice(key.as_slice())
+            .map_err(|_| SignerError::InvalidPrivateKey)?;
+
+        Ok(self.insert(Arc::new(signer), SignerSource::Key).await)
+    }
+
+    /// Add a signer from a geth style encrypted keystore file (web3 secret storage v3), with the
+    /// key derived by scrypt or pbkdf2
+    ///
+    /// Decrypting takes a moment by design. The key stays out of errors and `Debug` output. Fails
+    /// with [`SignerError::NoPassword`] if `passwords` skips the file.
+    pub async fn add_signer_from_keystore(
+        &self,
+        path: &Path,
+        passwords: &PasswordSource,
+    ) -> Result<Address, SignerError> {
+        let password = passwords
+            .resolve(path)
+            .await?
+            .ok_or_else(|| SignerError::NoPassword(path.to_path_buf()))?;
+        let signer = decrypt_keystore(path, &password)?;
+        Ok(self.insert(Arc::new(signer), SignerSource::Keystore { path: path.to_path_buf() }).await)
+    }
+
+    /// Add a signer for every `UTC--*` keystore file in `dir`, geth's `--keystore` layout, in
+    /// file name order
+    ///
+    /// Files that can't be decrypted are skipped with a warning, as are files whose password
+    /// prompt returns `None`. An address found in several files is loaded once. Returns the
+    /// addresses loaded, which may be none.
+    pub async fn load_keystore_dir(
+        &self,
+        dir: &Path,
+        passwords: &PasswordSource,
+    ) -> Result<Vec<Address>, SignerError> {
+        let paths = keystore_paths(dir)?;
+        let mut loaded = Vec::new();
+        let mut skipped = Vec::new();
+        for path in paths {
+            let Some(password) = passwords.resolve(&path).await? else {
+                skipped.push(path.display().to_string());
+                continue
+            };
+            match decrypt_keystore(&path, &password) {
+                Ok(signer) if loaded.contains(&signer.address()) => {}
+                Ok(signer) => {
+                    let source = SignerSource::Keystore { path: path.clone() };
+                    loaded.push(self.insert(Arc::new(signer), source).await);
+                }
+                Err(err) => {
+                    warn!(target: "poa::signer", path = %path.display(), %err, "Skipping keystore");
+                    skipped.push(path.display().to_string());
+                }
+            }
+        }
+
+        if !skipped.is_empty() {
+            warn!(target: "poa::signer", ?skipped, "Some keystores were not loaded");
+        }
+        info!(target: "poa::signer", dir = %dir.display(), signers = ?loaded, "Loaded keystores");
+        Ok(loaded)
+    }
+
+    /// Generate a signer key from OS randomness, store it in `keystore_dir` encrypted with
+    /// `password` and add it
+    ///
+    /// The file is named `UTC--<timestamp>--<address>` like geth names its keystores, so geth and
+    /// [`load_keystore_dir`](Self::load_keystore_dir) pick it up. The directory is created if
+    /// missing; on unix only the owner can read the file.
+    pub async fn generate_signer(
+        &self,
+        keystore_dir: &Path,
+        password: &str,
+    ) -> Result<Address, SignerError> {
+        fs::create_dir_all(keystore_dir)?;
+        let signer = PrivateKeySigner::random_with(&mut OsRng);
+        let address = signer.address();
+
+        let timestamp = humantime::format_rfc3339_nanos(SystemTime::now()).to_string();
+        let name = format!("UTC--{}--{}", timestamp.replace(':', "-"), hex::encode(address));
+        let key = Zeroizing::new(signer.to_bytes().0);
+        PrivateKeySigner::encrypt_keystore(
+            keystore_dir,
+            &mut OsRng,
+            key.as_slice(),
+            password,
+            Some(&name),
+        )
+        .map_err(|err| SignerError::KeystoreIo(io::Error::other(err.to_string())))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(keystore_dir.join(&name), fs::Permissions::from_mode(0o600))?;
+        }
+
+        info!(target: "poa::signer", %address, file = %name, "Generated signer key");
+        let source = SignerSource::Keystore { path: keystore_dir.join(name) };
+        Ok(self.insert(Arc::new(signer), source).await)
+    }
+
+    /// Add the first `count` signers derived from `mnemonic`, the account index appended to
+    /// `derivation_path`
+    ///
+    /// The usual Ethereum accounts, which the dev accounts are, live at
+    /// [`DEV_DERIVATION_PATH`](dev::DEV_DERIVATION_PATH) (`m/44'/60'/0'/0/{index}`). Nothing is
+    /// added if the mnemonic or path is invalid.
+    pub async fn add_signers_from_mnemonic(
+        &self,
+        mnemonic: &str,
+        derivation_path: &str,
+        count: usize,
+    ) -> Result<Vec<Address>, SignerError> {
+        let keys = dev::keys_from_mnemonic(mnemonic, derivation_path, count)?;
+        let mut signers = self.signers.write().await;
+        Ok(keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let source = SignerSource::Mnemonic {
+                    derivation_path: derivation_path.to_string(),
+                    index: index as u32,
+                };
+                self.register(&mut signers, Arc::new(key), source)
+            })
+            .collect())
+    }
+
+    /// Add a signer directly
+    pub async fn add_signer(&self, signer: PrivateKeySigner) -> Address {
+        self.insert(Arc::new(signer), SignerSource::Key).await
+    }
+
+    /// Add a signer backed by any [`Signer`] implementation, e.g. a hardware wallet or KMS key
+    pub async fn add_boxed_signer(&self, signer: DynSigner) -> Address {
+        self.insert(signer, SignerSource::External).await
+    }
+
+    /// Sign for `address` through the signing daemon at `url`, with the default timeout and
+    /// retries of [`RemoteSigner::new`]
+    ///
+    /// Fails if `url` is not an http(s) URL or a signer for `address` is already registered.
+    pub async fn add_remote_signer(&self, address: Address, url: &str) -> Result<(), SignerError> {
+        self.add_remote(RemoteSigner::new(address, url)).await.map(|_| ())
+    }
+
+    /// Sign for the address of `signer` through its signing daemon
+    ///
+    /// Fails like [`add_remote_signer`](Self::add_remote_signer) on a bad URL or an address that
+    /// already has a signer.
+    pub async fn add_remote(&self, signer: RemoteSigner) -> Result<Address, SignerError> {
+        check_remote_url(signer.url())?;
+        let address = signer.address();
+        let source = remote_source(&signer);
+        let mut signers = self.signers.write().await;
+        if signers.contains_key(&address) {
+            return Err(SignerError::DuplicateSigner(address))
+        }
+        Ok(self.register(&mut signers, Arc::new(signer), source))
+    }
+
+    /// Register `signer`, replacing any signer for the same address
+    async fn insert(&self, signer: DynSigner, source: SignerSource) -> Address {
+        self.register(&mut *self.signers.write().await, signer, source)
+    }
+
+    /// Register `signer` in the locked `signers`, announcing it if its address is new
+    fn register(
+        &self,
+        signers: &mut HashMap<Address, SignerEntry>,
+        signer: DynSigner,
+        source: SignerSource,
+    ) -> Address {
+        let address = signer.address();
+        if signers.insert(address, SignerEntry { signer, source }).is_none() {
+            // Nobody listening is fine
+            let _ = self.events.send(SignerEvent::Added(address));
+        }
+        address
+    }
+
+    /// Check if we have a signer for the given address
+    pub async fn has_signer(&self, address: &Address) -> bool {
+        self.signers.read().await.contains_key(address)
+    }
+
+    /// Get all registered signer addresses
+    pub async fn signer_addresses(&self) -> Vec<Address> {
+        self.signers.read().await.keys().copied().collect()
+    }
+
+    /// Sign a message hash with the specified signer, within [`DEFAULT_SIGNING_TIMEOUT`]
+    ///
+    /// See [`sign_hash_within`](Self::sign_hash_within).
+    pub async fn sign_hash(&self, address: &Address, hash: B256) -> Result<Signature, SignerError> {
+        self.sign_hash_within(address, hash, DEFAULT_SIGNING_TIMEOUT).await
+    }
+
+    /// Sign a message hash with the specified signer, giving up after `timeout`
+    ///
+    /// A [`SignerError::Transient`] failure is retried once within the timeout. Failures are
+    /// classified so a caller can tell a slot to skip, [`SignerError::Timeout`] or
+    /// [`SignerError::Transient`], from a signer to alert about, [`SignerError::Permanent`].
+    pub async fn sign_hash_within(
+        &self,
+        address: &Address,
+        hash: B256,
+        timeout: Duration,
+    ) -> Result<Signature, SignerError> {
+        // Don't hold the lock while a remote backend is signing
+        let signer = self
+            .signers
+            .read()
+            .await
+            .get(address)
+            .map(|entry| entry.signer.clone())
+            .ok_or(SignerError::NoSignerForAddress(*address))?;
+
+        let sign = async {
+            match sign_with(&signer, hash).await {
+                Err(SignerError::Transient(reason)) => {
+                    warn!(target: "poa::signer", %address, %reason, "Signing failed, retrying");
+                    sign_with(&signer, hash).await
+                }
+                result => result,
+            }
+        };
+        tokio::time::timeout(timeout, sign).await.map_err(|_| SignerError::Timeout(*address))?
+    }
+
+    /// Remove a signer, once the blocks being sealed are
+    pub async fn remove_signer(&self, address: &Address) -> bool {
+        let _sealing = self.sealing.write().await;
+        let mut signers = self.signers.write().await;
+        let removed = signers.remove(address).is_some();
+        if removed {
+            let _ = self.events.send(SignerEvent::Removed(*address));
+        }
+        removed
+    }
+}
+
+impl Default for SignerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Signer votes the local operator wants to see cast (geth's `clique_propose`).
+///
+/// Maps each proposed address to whether it should be authorized (`true`) or dropped (`false`).
+/// A [`BlockSealer`] holding the map embeds one still pending proposal per header it seals,
+/// cycling through the map so that every proposal gets its turn.
+#[derive(Debug, Default)]
+pub struct ProposalsMap {
+    inner: Mutex<ProposalsInner>,
+}
+
+#[derive(Debug, Default)]
+struct ProposalsInner {
+    /// Proposed address and whether to authorize it
+    proposals: BTreeMap<Address, bool>,
+    /// Address of the last embedded vote, where the next search resumes
+    last: Option<Address>,
+}
+
+impl ProposalsMap {
+    /// Create an empty proposals map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Propose to authorize or drop `address`, replacing any earlier proposal for it
+    pub fn propose(&self, address: Address, authorize: bool) {
+        self.inner.lock().proposals.insert(address, authorize);
+    }
+
+    /// Stop voting on `address`. Returns `false` if there was no such proposal.
+    pub fn discard(&self, address: &Address) -> bool {
+        self.inner.lock().proposals.remove(address).is_some()
+    }
+
+    /// All current proposals
+    pub fn proposals(&self) -> BTreeMap<Address, bool> {
+        self.inner.lock().proposals.clone()
+    }
+
+    /// Pick the vote `signer` should embed in the block after `snapshot`.
+    ///
+    /// Skips proposals that are already settled in the snapshot and ones `signer` has already
+    /// voted for, resuming after the previously picked proposal.
+    pub fn next_vote(&self, snapshot: &Snapshot, signer: &Address) -> Option<(Address, bool)> {
+        let mut inner = self.inner.lock();
+        let pending: Vec<_> = inner
+            .proposals
+            .iter()
+            .map(|(address, authorize)| (*address, *authorize))
+            .filter(|(address, authorize)| snapshot.is_vote_pending(signer, address, *authorize))
+            .collect();
+        let vote = pending
+            .iter()
+            .find(|(address, _)| inner.last.is_none_or(|last| *address > last))
+            .or_else(|| pending.first())
+            .copied()?;
+        inner.last = Some(vote.0);
+        Some(vote)
+    }
+}
+
+/// Sign `hash` with `signer`, classifying its errors
+async fn sign_with(signer: &DynSigner, hash: B256) -> Result<Signature, SignerError> {
+    signer.sign_hash(&hash).await.map_err(|err| match err {
+        // Backends of this crate classify their own errors
+        alloy_signer::Error::Other(err) => match err.downcast::<SignerError>() {
+            Ok(err) => *err,
+            // Other backends, e.g. a KMS, fail like this when their service is unavailable
+            Err(err) => SignerError::Transient(err.to_string()),
+        },
+        err => SignerError::Permanent(err.to_string()),
+    })
+}
+
+/// Block sealing utilities for POA
+pub struct BlockSealer {
+    signer_manager: Arc<SignerManager>,
+    proposals: Arc<ProposalsMap>,
+    sign_record: Arc<SignRecord>,
+    /// Whether [`sign_in_turn`](Self::sign_in_turn) may seal out of turn
+    allow_out_of_turn: bool,
+    /// Policy of [`seal_selected`](Self::seal_selected)
+    selection: SignerSelection,
+    /// Signer last picked by [`select_signer`](Self::select_signer)
+    selected: Mutex<Option<Address>>,
+    /// Log of the signatures produced
+    audit_log: Option<Arc<AuditLog>>,
+    /// How long signing a header may take
+    timeout: Duration,
+}
+
+impl fmt::Debug for BlockSealer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockSealer")
+            .field("signer_manager", &self.signer_manager)
+            .field("proposals", &self.proposals)
+            .field("sign_record", &self.sign_record)
+            .field("allow_out_of_turn", &self.allow_out_of_turn)
+            .field("selection", &self.selection)
+            .field("selected", &*self.selected.lock())
+            .field("audit_log", &self.audit_log)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl BlockSealer {
+    /// Create a new block sealer, remembering the heights it signed in memory only
+    pub fn new(signer_manager: Arc<SignerManager>) -> Self {
+        Self {
+            signer_manager,
+            proposals: Arc::default(),
+            sign_record: Arc::default(),
+            allow_out_of_turn: false,
+            selection: SignerSelection::default(),
+            selected: Mutex::new(None),
+            audit_log: None,
+            timeout: DEFAULT_SIGNING_TIMEOUT,
+        }
+    }
+
+    /// Embed votes from the given proposals map in sealed blocks
+    pub fn with_proposals(mut self, proposals: Arc<ProposalsMap>) -> Self {
+        self.proposals = proposals;
+        self
+    }
+
+    /// Check and record signed heights in `sign_record`, typically
+    /// [opened](SignRecord::open) from the datadir so a restarted node remembers them
+    pub fn with_sign_record(mut self, sign_record: Arc<SignRecord>) -> Self {
+        self.sign_record = sign_record;
+        self
+    }
+
+    /// Heights this sealer signed
+    pub const fn sign_record(&self) -> &Arc<SignRecord> {
+        &self.sign_record
+    }
+
+    /// Give up signing a header after `timeout`, typically half the block period
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Append every signature to `audit_log`
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Log of the signatures this sealer produced, if any
+    pub const fn audit_log(&self) -> Option<&Arc<AuditLog>> {
+        self.audit_log.as_ref()
+    }
+
+    /// Let [`sign_in_turn`](Self::sign_in_turn) seal with another local signer when the in-turn
+    /// one isn't held locally
+    pub const fn with_out_of_turn(mut self, allow_out_of_turn: bool) -> Self {
+        self.allow_out_of_turn = allow_out_of_turn;
+        self
+    }
+
+    /// Seal `header` with the local signer whose turn it is under `snapshot`, setting the
+    /// difficulty to match
+    ///
+    /// If the in-turn key isn't held locally and out-of-turn sealing is
+    /// [enabled](Self::with_out_of_turn), the first local authorized signer that may seal the
+    /// block under the recent-signer limit seals it out of turn. Returns the sealed header and
+    /// the signer.
+    pub async fn sign_in_turn(
+        &self,
+        mut header: Header,
+        snapshot: &Snapshot,
+    ) -> Result<(Header, Address), SignerError> {
+        let local = self.local_signers(snapshot).await?;
+        let number = header.number;
+        let in_turn = snapshot.inturn_signer(number);
+        let signer = match in_turn {
+            Some(signer) if local.contains(&signer) => {
+                header.difficulty = U256::from(1);
+                signer
+            }
+            _ if self.allow_out_of_turn => {
+                header.difficulty = U256::from(2);
+                local
+                    .into_iter()
+                    .find(|signer| !snapshot.is_recently_signed_at(number, signer))
+                    .ok_or(SignerError::RecentlySigned(number))?
+            }
+            _ => return Err(SignerError::NotInTurn { number, in_turn }),
+        };
+
+        let sealed = self.seal_header(header, &signer).await?;
+        Ok((sealed, signer))
+    }
+
+    /// Pick the sealing signer with `selection`
+    pub const fn with_selection(mut self, selection: SignerSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Policy picking the sealing signer
+    pub const fn selection(&self) -> SignerSelection {
+        self.selection
+    }
+
+    /// Signer last picked to seal a block
+    pub fn selected_signer(&self) -> Option<Address> {
+        *self.selected.lock()
+    }
+
+    /// Pick the local signer to seal block `number` with, according to the
+    /// [selection policy](Self::with_selection)
+    ///
+    /// Fails with [`SignerError::NotInTurn`] if only in-turn sealing is allowed and the in-turn
+    /// signer isn't local, and with [`SignerError::RecentlySigned`] if every candidate sealed too
+    /// recently.
+    pub async fn select_signer(
+        &self,
+        number: u64,
+        snapshot: &Snapshot,
+    ) -> Result<Address, SignerError> {
+        let local = self.local_signers(snapshot).await?;
+        let in_turn = snapshot.inturn_signer(number).filter(|signer| local.contains(signer));
+        let mut candidates =
+            local.iter().copied().filter(|signer| !snapshot.is_recently_signed_at(number, signer));
+
+        let mut selected = self.selected.lock();
+        let signer = match self.selection {
+            SignerSelection::InTurnOnly => {
+                let Some(signer) = in_turn else {
+                    return Err(SignerError::NotInTurn {
+                        number,
+                        in_turn: snapshot.inturn_signer(number),
+                    })
+                };
+                candidates.find(|candidate| *candidate == signer)
+            }
+            SignerSelection::Preferred(preferred) => {
+                let candidates: Vec<_> = candidates.collect();
+                [Some(preferred), in_turn]
+                    .into_iter()
+                    .flatten()
+                    .find(|signer| candidates.contains(signer))
+                    .or_else(|| candidates.first().copied())
+            }
+            SignerSelection::RoundRobinLocal => {
+                let candidates: Vec<_> = candidates.collect();
+                candidates
+                    .iter()
+                    .find(|signer| Some(**signer) > *selected)
+                    .or_else(|| candidates.first())
+                    .copied()
+            }
+        }
+        .ok_or(SignerError::RecentlySigned(number))?;
+
+        *selected = Some(signer);
+        Ok(signer)
+    }
+
+    /// Seal `header` with the signer [picked](Self::select_signer) for it, setting the
+    /// difficulty to match
+    ///
+    /// Returns the sealed header and the signer.
+    pub async fn seal_selected(
+        &self,
+        mut header: Header,
+        snapshot: &Snapshot,
+    ) -> Result<(Header, Address), SignerError> {
+        let signer = self.select_signer(header.number, snapshot).await?;
+        let in_turn = snapshot.inturn(header.number, &signer);
+        header.difficulty = U256::from(if in_turn { 1 } else { 2 });
+        let sealed = self.seal_header(header, &signer).await?;
+        Ok((sealed, signer))
+    }
+
+    /// Local signers authorized in `snapshot`, sorted by address
+    async fn local_signers(&self, snapshot: &Snapshot) -> Result<Vec<Address>, SignerError> {
+        let mut local = self.signer_manager.signer_addresses().await;
+        local.retain(|address| snapshot.signers.contains(address));
+        local.sort_unstable();
+        if local.is_empty() {
+            return Err(SignerError::NoAuthorizedSigner)
+        }
+        Ok(local)
+    }
+
+    /// Proposals this sealer votes on
+    pub const fn proposals(&self) -> &Arc<ProposalsMap> {
+        &self.proposals
+    }
+
+    /// Set the header's beneficiary and nonce to the next pending proposal of `signer`.
+    ///
+    /// Epoch blocks never carry a vote. Returns the embedded vote, if any.
+    pub fn prepare_vote(
+        &self,
+        header: &mut Header,
+        snapshot: &Snapshot,
+        signer: &Address,
+    ) -> Option<(Address, bool)> {
+        if header.number.is_multiple_of(snapshot.epoch) {
+            return None;
+        }
+        let (address, authorize) = self.proposals.next_vote(snapshot, signer)?;
+        header.beneficiary = address;
+        header.nonce = if authorize { NONCE_AUTH_VOTE } else { NONCE_DROP_VOTE };
+        Some((address, authorize))
+    }
+
+    /// Encode `text` as UTF-8 into a vanity field, padded with zeros on the right.
+    ///
+    /// Text longer than the field is rejected rather than cut, which could split a character.
+    pub fn vanity_from_str(text: &str) -> Result<[u8; EXTRA_VANITY_LENGTH], SignerError> {
+        let bytes = text.as_bytes();
+        if bytes.len() > EXTRA_VANITY_LENGTH {
+            return Err(SignerError::VanityTooLong(bytes.len()));
+        }
+        let mut vanity = [0u8; EXTRA_VANITY_LENGTH];
+        vanity[..bytes.len()].copy_from_slice(bytes);
+        Ok(vanity)
+    }
+
+    /// The vanity field as text, without the zero padding. Invalid UTF-8 is replaced.
+    pub fn vanity_to_string(vanity: &[u8; EXTRA_VANITY_LENGTH]) -> String {
+        let len = vanity.iter().rposition(|byte| *byte != 0).map_or(0, |last| last + 1);
+        String::from_utf8_lossy(&vanity[..len]).into_owned()
+    }
+
+    /// Build the unsealed extra data of a new block.
+    ///
+    /// Epoch blocks pass the signer set of the current snapshot, which is written in ascending
+    /// order between the vanity and the seal; other blocks pass `None` and get exactly vanity and
+    /// seal. The seal is left zeroed for [`seal_header`](Self::seal_header) to fill in.
+    pub fn build_extra_data(
+        vanity: &[u8; EXTRA_VANITY_LENGTH],
+        epoch_signers: Option<&[Address]>,
+    ) -> Bytes {
+        let mut signers = epoch_signers.unwrap_or_default().to_vec();
+        signers.sort_unstable();
+
+        let mut extra = Vec::with_capacity(
+            EXTRA_VANITY_LENGTH + signers.len() * Address::len_bytes() + EXTRA_SEAL_LENGTH,
+        );
+        extra.extend_from_slice(vanity);
+        for signer in &signers {
+            extra.extend_from_slice(signer.as_slice());
+        }
+        extra.extend_from_slice(&[0u8; EXTRA_SEAL_LENGTH]);
+        extra.into()
+    }
+
+    /// Calculate the seal hash for a header (hash without signature)
+    pub fn seal_hash(header: &Header) -> B256 {
+        sealing::seal_hash(header)
+    }
+
+    /// [Seal](Self::seal_header) the header of `block` and reattach its body
+    ///
+    /// The block hash covers the seal, so the returned block is hashed again. Nothing is signed
+    /// if the header doesn't commit to the transactions of the body.
+    pub async fn seal_block<B>(
+        &self,
+        block: SealedBlock<B>,
+        signer_address: &Address,
+    ) -> Result<SealedBlock<B>, SignerError>
+    where
+        B: Block<Header = Header>,
+    {
+        block.ensure_transaction_root_valid().map_err(|mismatch| {
+            SignerError::TransactionsRootMismatch { header: mismatch.expected, body: mismatch.got }
+        })?;
+        let (header, body) = block.split_header_body();
+        let header = self.seal_header(header, signer_address).await?;
+        Ok(SealedBlock::seal_parts(header, body))
+    }
+
+    /// [Seal](Self::seal_header) a block header, or `None` if the signer timed out or failed
+    /// transiently
+    ///
+    /// A signer that doesn't answer in time costs the caller its slot, not the node: the caller
+    /// gives up on this block and leaves it to another signer. Permanent failures are returned
+    /// for the operator to look into.
+    pub async fn try_seal_header(
+        &self,
+        header: Header,
+        signer_address: &Address,
+    ) -> Result<Option<Header>, SignerError> {
+        match self.seal_header(header, signer_address).await {
+            Ok(header) => Ok(Some(header)),
+            Err(SignerError::Timeout(address)) => {
+                warn!(target: "poa::signer", %address, "Signer timed out, skipping the slot");
+                Ok(None)
+            }
+            Err(SignerError::Transient(reason)) => {
+                warn!(target: "poa::signer", %reason, "Signing failed, skipping the slot");
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Seal an epoch checkpoint header, replacing its extra data with `vanity`, `signers` in
+    /// ascending order as the consensus expects them, and the seal
+    pub async fn seal_checkpoint_header(
+        &self,
+        mut header: Header,
+        signer_address: &Address,
+        signers: &[Address],
+        vanity: &[u8; EXTRA_VANITY_LENGTH],
+    ) -> Result<Header, SignerError> {
+        if signers.is_empty() {
+            return Err(SignerError::EmptyCheckpointSigners);
+        }
+        header.extra_data = Self::build_extra_data(vanity, Some(signers));
+        self.seal_header(header, signer_address).await
+    }
+
+    /// Seal a header between checkpoints, whose extra data must hold the vanity and room for the
+    /// seal only, as [`build_extra_data`](Self::build_extra_data) lays it out without signers
+    pub async fn seal_regular_header(
+        &self,
+        header: Header,
+        signer_address: &Address,
+    ) -> Result<Header, SignerError> {
+        let expected = EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH;
+        if header.extra_data.len() != expected {
+            return Err(SignerError::InvalidExtraData { expected, got: header.extra_data.len() });
+        }
+        self.seal_header(header, signer_address).await
+    }
+
+    /// Seal a block header with a signature
+    ///
+    /// Fails with [`SignerError::AlreadySignedHeight`] if the signer signed a different header at
+    /// the same or a higher block number, see [`SignRecord::reserve`].
+    pub async fn seal_header(
+        &self,
+        header: Header,
+        signer_address: &Address,
+    ) -> Result<Header, SignerError> {
+        self.seal(header, signer_address, false).await
+    }
+
+    /// [Seal](Self::seal_header) a block header even if the signer signed a different header at
+    /// its height, e.g. to recover from a fork by hand
+    pub async fn force_seal_header(
+        &self,
+        header: Header,
+        signer_address: &Address,
+    ) -> Result<Header, SignerError> {
+        self.seal(header, signer_address, true).await
+    }
+
+    /// Seal `header`, signing over a recorded higher or conflicting height if `force`
+    async fn seal(
+        &self,
+        mut header: Header,
+        signer_address: &Address,
+        force: bool,
+    ) -> Result<Header, SignerError> {
+        // Keep the signer from being removed until the block is sealed
+        let _sealing = self.signer_manager.sealing.read().await;
+
+        // Calculate seal hash
+        let seal_hash = Self::seal_hash(&header);
+
+        // Record the height before a signature for it can exist
+        self.sign_record.reserve(*signer_address, header.number, seal_hash, force)?;
+
+        // Sign the hash
+        let signature =
+            self.signer_manager.sign_hash_within(signer_address, seal_hash, self.timeout).await?;
+        self.audit(&header, seal_hash, *signer_address)?;
+
+        // Encode signature as bytes (r, s, v)
+        let sig_bytes = signature_to_bytes(&signature);
+
+        // Update extra data with signature
+        let mut extra_data = header.extra_data.to_vec();
+
+        // Remove existing signature if present
+        if extra_data.len() >= EXTRA_SEAL_LENGTH {
+            extra_data.truncate(extra_data.len() - EXTRA_SEAL_LENGTH);
+        }
+
+        // Append new signature
+        extra_data.extend_from_slice(&sig_bytes);
+        header.extra_data = extra_data.into();
+
+        Ok(header)
+    }
+
+    /// Append the signature of `header` to the audit log, if any
+    fn audit(&self, header: &Header, seal_hash: B256, signer: Address) -> Result<(), SignerError> {
+        let Some(audit_log) = &self.audit_log else { return Ok(()) };
+        let entry = AuditEntry {
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            number: header.number,
+            seal_hash,
+            signer,
+            in_turn: header.difficulty == U256::from(1),
+        };
+        match audit_log.append(&entry) {
+            Err(err) if !audit_log.fail_on_error() => {
+                warn!(
+                    target: "poa::signer",
+                    number = header.number,
+                    %err,
+                    "Failed to audit signature"
+                );
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Verify a block's signature
+    pub fn verify_signature(header: &Header) -> Result<Address, SignerError> {
+        let seal_hash = Self::seal_hash(header);
+
+        let extra_data = &header.extra_data;
+
+        if extra_data.len() < EXTRA_SEAL_LENGTH {
+            return Err(SignerError::SigningFailed("Extra data too short".into()));
+        }
+
+        let sig_bytes = &extra_data[extra_data.len() - EXTRA_SEAL_LENGTH..];
+        let signature =
+            bytes_to_signature(sig_bytes).map_err(|e| SignerError::SigningFailed(e))?;
+
+        signature
+            .recover_address_from_prehash(&seal_hash)
+            .map_err(|e| SignerError::SigningFailed(e.to_string()))
+    }
+}
+
+/// Fail unless `url` is an absolute http(s) URL, the only kind a [`RemoteSigner`] can reach
+fn check_remote_url(url: &str) -> Result<(), SignerError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|err| SignerError::InvalidRemoteUrl(format!("{url}: {err}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") || !parsed.has_host() {
+        return Err(SignerError::InvalidRemoteUrl(url.to_string()))
+    }
+    Ok(())
+}
+
+/// Where to find `signer` again, for the manifest
+fn remote_source(signer: &RemoteSigner) -> SignerSource {
+    SignerSource::Remote {
+        url: signer.url().to_string(),
+        timeout_ms: signer.timeout().as_millis() as u64,
+        retries: signer.retries(),
+    }
+}
+
+/// The `UTC--*` keystore files in `dir`, sorted by name
+fn keystore_paths(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with("UTC--") && entry.path().is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Decrypt the keystore file at `path` with `password`
+fn decrypt_keystore(path: &Path, password: &str) -> Result<PrivateKeySigner, SignerError> {
+    // Check the format first, which the decryption reports as one generic JSON error
+    let keystore: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| SignerError::MalformedKeystore(err.to_string()))?;
+    let kdf = keystore["crypto"]["kdf"]
+        .as_str()
+        .ok_or_else(|| SignerError::MalformedKeystore("missing crypto.kdf".to_string()))?;
+    if !matches!(kdf, "scrypt" | "pbkdf2") {
+        return Err(SignerError::UnsupportedKdf(kdf.to_string()));
+    }
+
+    PrivateKeySigner::decrypt_keystore(path, password).map_err(|err| match err {
+        LocalSignerError::EthKeystoreError(KeystoreError::MacMismatch) => {
+            SignerError::WrongPassword
+        }
+        err => SignerError::MalformedKeystore(err.to_string()),
+    })
+}
+
+/// Convert a signature to bytes (r || s || v)
+pub(crate) fn signature_to_bytes(sig: &Signature) -> [u8; 65] {
+    let mut bytes = [0u8; 65];
+    bytes[..32].copy_from_slice(&sig.r().to_be_bytes::<32>());
+    bytes[32..64].copy_from_slice(&sig.s().to_be_bytes::<32>());
+    bytes[64] = sig.v() as u8;
+    bytes
+}
+
+/// Convert bytes to a signature
+pub(crate) fn bytes_to_signature(bytes: &[u8]) -> Result<Signature, String> {
+    if bytes.len() != 65 {
+        return Err(format!("Invalid signature length: expected 65, got {}", bytes.len()));
+    }
+
+    Signature::try_from(bytes).map_err(|e| format!("Invalid signature: {}", e))
+}
+
+/// Development signer setup with known test keys
+pub mod dev {
+    use super::*;
+
+    /// Private keys for the dev accounts (from "test test..." mnemonic), in the order of
+    /// [`dev_accounts`](crate::genesis::dev_accounts)
+    pub const DEV_PRIVATE_KEYS: &[&str] = &[
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+        "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a",
+        "7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6",
+        "47e179ec197488593b187f80a00eb0da91f1b9d0b13f8733639f19c30a34926a",
+        "8b3a350cf5c34c9194ca85829a2df0ec3153be0318b5e2d3348e872092edffba",
+        "92db14e403b83dfe3df233f83dfa3a0d7096f21ca9b0d6d6b8d88b2b4ec1564e",
+        "4bbbf85ce3377467afe5d46f804f221813b2bb87f24d81f60f1fcdbf7cbf4356",
+        "dbda1821b80551c9d65939329250298aa3472ba22feea921c0cf5d620ea67b97",
+        "2a871d0798f97d79848a013d4936a73bf4cc922c825d33c1cf7073dff6d409c6",
+        "f214f2b2cd398c806f84e317254e0f0b801d0643303237d97a22a48e01628897",
+        "701b615bbdfb9de65240bc28bd21bbc0d996645a3dd57e7b12bc2bdf6f192c82",
+        "a267530f49f8280200edf313ee7af6b827f2a8bce2897751d06a843f644967b1",
+        "47c99abed3324a2707c28affff1267e45918ec8c3f20b8aa892e8b065d2942dd",
+        "c526ee95bf44d8fc405a158bb884d9d1238d99f0612e9f33d006bb0789009aaa",
+        "8166f546bab6da521a8369cab06c5d2b9e46670292d85c875ee9ec20e84ffb61",
+        "ea6c44ac03bff858b476bba40716402b03e41b8e97e276d1baec7c37d42484a0",
+        "689af8efa8c651a91ad287602527f3af2fe9f6501a7ac4b061667b5a93e037fd",
+        "de9be858da4a475276426320d5e9262ecfc3ba460bfac56360bfa6c4c28b4ee0",
+        "df57089febbacf7ba0bc227dafbffa9fc08a93fdc68e1e42411a14efcf23656e",
+    ];
+
+    /// The mnemonic the dev accounts are derived from
+    pub const DEV_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    /// Derivation path of the dev accounts, without the account index
+    pub const DEV_DERIVATION_PATH: &str = "m/44'/60'/0'/0/";
+
+    /// The first `count` keys derived from `mnemonic`, the account index appended to `path`
+    pub fn keys_from_mnemonic(
+        mnemonic: &str,
+        path: &str,
+        count: usize,
+    ) -> Result<Vec<PrivateKeySigner>, SignerError> {
+        (0..count as u32).map(|index| key_from_mnemonic(mnemonic, path, index)).collect()
+    }
+
+    /// The key derived from `mnemonic` at `index` appended to `path`
+    pub fn key_from_mnemonic(
+        mnemonic: &str,
+        path: &str,
+        index: u32,
+    ) -> Result<PrivateKeySigner, SignerError> {
+        let path = format!("{path}{index}");
+        MnemonicBuilder::<English>::default()
+            .phrase(mnemonic)
+            .derivation_path(&path)
+            .map_err(|err| SignerError::InvalidDerivationPath(format!("{path}: {err}")))?
+            .build()
+            .map_err(|err| SignerError::InvalidMnemonic(err.to_string()))
+    }
+
+    /// Set up the signer manager with dev keys
+    pub async fn setup_dev_signers() -> Arc<SignerManager> {
+        let manager = Arc::new(SignerManager::new());
+        // The first 3 dev accounts are the default signers
+        manager
+            .add_signers_from_mnemonic(DEV_MNEMONIC, DEV_DERIVATION_PATH, 3)
+            .await
+            .expect("dev mnemonic is valid");
+        manager
+    }
+
+    /// Get the first dev signer for testing
+    pub fn first_dev_signer() -> PrivateKeySigner {
+        DEV_PRIVATE_KEYS[0]
+            .parse()
+            .expect("First dev key should be valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chainspec::{PoaChainSpec, PoaConfig},
+        consensus::PoaConsensus,
+    };
+    use alloy_consensus::{SignableTransaction, TxLegacy};
+    use alloy_primitives::{address, ChainId, TxKind};
+    use alloy_signer::SignerSync;
+    use async_trait::async_trait;
+    use reth_ethereum::{Block as EthBlock, BlockBody as EthBlockBody, TransactionSigned};
+    use reth_primitives_traits::BlockBody as _;
+
+    /// Keystore fixture encrypted with [`KEYSTORE_PASSWORD`] using scrypt
+    const KEYSTORE: &str =
+        "testdata/keystore/UTC--2026-10-17T09-30-00.000000000Z--0a60a644910141059e9c79a716f9dad643cb092c";
+
+    /// Password of [`KEYSTORE`]
+    const KEYSTORE_PASSWORD: &str = "poa-keystore-password";
+
+    /// Key in [`KEYSTORE`]
+    const KEYSTORE_KEY: &str = "65bde5f292dbf03fa596a426879c404f8f9c882da9b2e6a37e2d55ec56467f74";
+
+    /// Second keystore fixture, encrypted with [`KEYSTORE_PASSWORD`] using pbkdf2
+    const PBKDF2_KEYSTORE: &str =
+        "testdata/keystore/UTC--2026-10-17T09-31-00.000000000Z--e8ec52e9d2a18c0a3b040aac8509cb4a5f06a10b";
+
+    fn keystore_password() -> PasswordSource {
+        PasswordSource::Static(Zeroizing::new(KEYSTORE_PASSWORD.to_string()))
+    }
+
+    fn keystore_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(KEYSTORE)
+    }
+
+    /// A keystore directory holding both fixtures, a copy of the first, a corrupted file and a
+    /// file keystores are not named like
+    fn keystore_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for fixture in [KEYSTORE, PBKDF2_KEYSTORE] {
+            let path = fixtures.join(fixture);
+            fs::copy(&path, dir.path().join(path.file_name().unwrap())).unwrap();
+        }
+        fs::copy(keystore_path(), dir.path().join("UTC--2026-10-17T09-32-00.000000000Z--copy"))
+            .unwrap();
+        fs::write(dir.path().join("UTC--2026-10-17T09-33-00.000000000Z--corrupted"), "{\"crypto\"")
+            .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a keystore").unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_signer_manager() {
+        let manager = SignerManager::new();
+
+        // Add a dev signer
+        let address = manager
+            .add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0])
+            .await
+            .unwrap();
+
+        assert!(manager.has_signer(&address).await);
+        assert_eq!(manager.signer_addresses().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_signer_events() {
+        let manager = SignerManager::new();
+        let mut events = manager.subscribe();
+
+        let first = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        // Replacing a signer is not announced
+        manager.add_signer(dev::first_dev_signer()).await;
+        let keystore =
+            manager.add_signer_from_keystore(&keystore_path(), &keystore_password()).await.unwrap();
+        let mnemonic = manager
+            .add_signers_from_mnemonic(dev::DEV_MNEMONIC, dev::DEV_DERIVATION_PATH, 2)
+            .await
+            .unwrap();
+        assert!(manager.remove_signer(&first).await);
+        assert!(!manager.remove_signer(&first).await);
+        let dir = keystore_dir();
+        let loaded = manager.load_keystore_dir(dir.path(), &keystore_password()).await.unwrap();
+
+        let expected = [
+            SignerEvent::Added(first),
+            SignerEvent::Added(keystore),
+            SignerEvent::Added(mnemonic[1]),
+            SignerEvent::Removed(first),
+            SignerEvent::Added(loaded[1]),
+        ];
+        for event in expected {
+            assert_eq!(events.recv().await.unwrap(), event);
+        }
+        assert!(events.try_recv().is_err());
+
+        // A subscriber that never reads holds up nobody
+        let mut lagging = manager.subscribe();
+        for _ in 0..SIGNER_EVENTS_CAPACITY {
+            manager.add_signer(dev::first_dev_signer()).await;
+            manager.remove_signer(&first).await;
+        }
+        assert!(matches!(
+            lagging.recv().await,
+            Err(broadcast::error::RecvError::Lagged(n)) if n == SIGNER_EVENTS_CAPACITY as u64
+        ));
+        assert_eq!(lagging.recv().await.unwrap(), SignerEvent::Added(first));
+    }
+
+    #[tokio::test]
+    async fn test_debug_redacts_keys() {
+        let key = dev::DEV_PRIVATE_KEYS[0];
+        let manager = Arc::new(SignerManager::new());
+        let address = manager.add_signer_from_hex(key).await.unwrap();
+        assert!(manager.add_signer_from_hex("0x1234").await.is_err());
+
+        let sealer = BlockSealer::new(manager.clone());
+        for debug in [format!("{manager:?}"), format!("{sealer:#?}")] {
+            assert!(debug.contains(&format!("{address:?}")), "{debug}");
+            assert!(debug.contains("count: 1"), "{debug}");
+            // No 64 hex character run, let alone the key
+            let longest_hex_run = debug
+                .split(|c: char| !c.is_ascii_hexdigit())
+                .map(str::len)
+                .max()
+                .unwrap_or_default();
+            assert!(longest_hex_run < 64, "{debug}");
+            assert!(!debug.to_lowercase().contains(key), "{debug}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_signer_from_keystore() {
+        let manager = SignerManager::new();
+        let address =
+            manager.add_signer_from_keystore(&keystore_path(), &keystore_password()).await.unwrap();
+        assert_eq!(address, address!("0a60a644910141059e9c79a716f9dad643cb092c"));
+        assert_eq!(address, KEYSTORE_KEY.parse::<PrivateKeySigner>().unwrap().address());
+        assert!(manager.has_signer(&address).await);
+
+        let debug = format!("{manager:?}");
+        assert!(debug.contains(&format!("{address:?}")), "{debug}");
+        assert!(!debug.to_lowercase().contains(KEYSTORE_KEY), "{debug}");
+    }
+
+    #[tokio::test]
+    async fn test_add_signer_from_keystore_errors() {
+        let manager = SignerManager::new();
+        let wrong = PasswordSource::Static(Zeroizing::new("wrong".to_string()));
+        let err = manager.add_signer_from_keystore(&keystore_path(), &wrong).await.unwrap_err();
+        assert!(matches!(err, SignerError::WrongPassword), "{err}");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore.json");
+        let keystore = fs::read_to_string(keystore_path()).unwrap();
+
+        fs::write(&path, &keystore[..keystore.len() / 2]).unwrap();
+        let err = manager.add_signer_from_keystore(&path, &keystore_password()).await.unwrap_err();
+        assert!(matches!(err, SignerError::MalformedKeystore(_)), "{err}");
+
+        fs::write(&path, keystore.replace("\"scrypt\"", "\"argon2id\"")).unwrap();
+        let err = manager.add_signer_from_keystore(&path, &keystore_password()).await.unwrap_err();
+        assert!(matches!(&err, SignerError::UnsupportedKdf(kdf) if kdf == "argon2id"), "{err}");
+
+        let err = manager
+            .add_signer_from_keystore(&dir.path().join("missing"), &keystore_password())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SignerError::KeystoreIo(_)), "{err}");
+        assert!(manager.signer_addresses().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_keystore_dir() {
+        let dir = keystore_dir();
+        let password_file = dir.path().join("password");
+        fs::write(&password_file, format!("{KEYSTORE_PASSWORD}\n")).unwrap();
+
+        let manager = SignerManager::new();
+        let loaded = manager
+            .load_keystore_dir(dir.path(), &PasswordSource::File(password_file))
+            .await
+            .unwrap();
+        let expected = [
+            address!("0a60a644910141059e9c79a716f9dad643cb092c"),
+            address!("e8ec52e9d2a18c0a3b040aac8509cb4a5f06a10b"),
+        ];
+        assert_eq!(loaded, expected);
+        assert_eq!(manager.signer_addresses().await.len(), 2);
+
+        // Each keystore file is asked for, skipping the first leaves the copy of it
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let prompted = Arc::clone(&asked);
+        let prompt = PasswordSource::Callback(Box::new(move |path| {
+            let mut asked = prompted.lock();
+            asked.push(path.to_path_buf());
+            (asked.len() > 1).then(|| KEYSTORE_PASSWORD.to_string())
+        }));
+        let manager = SignerManager::new();
+        let loaded = manager.load_keystore_dir(dir.path(), &prompt).await.unwrap();
+        assert_eq!(loaded, [expected[1], expected[0]]);
+        assert_eq!(asked.lock().len(), 4);
+
+        let missing = PasswordSource::Env("POA_TEST_UNSET_KEYSTORE_PASSWORD".to_string());
+        let err = manager.load_keystore_dir(dir.path(), &missing).await.unwrap_err();
+        assert!(matches!(err, SignerError::MissingPassword(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_generate_signer() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = dir.path().join("keystore");
+        let manager = SignerManager::new();
+        let address = manager.generate_signer(&keystore, KEYSTORE_PASSWORD).await.unwrap();
+        assert!(manager.has_signer(&address).await);
+
+        let files: Vec<_> = fs::read_dir(&keystore).unwrap().map(|entry| entry.unwrap()).collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().into_string().unwrap();
+        assert!(name.starts_with("UTC--"), "{name}");
+        assert!(name.ends_with(&format!("Z--{}", hex::encode(address))), "{name}");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(files[0].metadata().unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        let reloaded = SignerManager::new();
+        let path = files[0].path();
+        assert_eq!(
+            reloaded.add_signer_from_keystore(&path, &keystore_password()).await.unwrap(),
+            address
+        );
+        let second = manager.generate_signer(&keystore, KEYSTORE_PASSWORD).await.unwrap();
+        assert_ne!(second, address);
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify() {
+        let manager = Arc::new(SignerManager::new());
+        let address = manager
+            .add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0])
+            .await
+            .unwrap();
+
+        let sealer = BlockSealer::new(manager);
+
+        // Create a test header
+        let header = Header {
+            number: 1,
+            gas_limit: 30_000_000,
+            timestamp: 12345,
+            extra_data: vec![0u8; 32 + 65].into(), // Vanity + space for seal
+            ..Default::default()
+        };
+
+        // Seal the header
+        let sealed = sealer.seal_header(header, &address).await.unwrap();
+
+        // Verify the signature
+        let recovered = BlockSealer::verify_signature(&sealed).unwrap();
+        assert_eq!(recovered, address);
+    }
+
+    /// Signs with a key in memory and records every hash it was asked to sign
+    struct RecordingSigner {
+        key: PrivateKeySigner,
+        hashes: Mutex<Vec<B256>>,
+    }
+
+    #[async_trait]
+    impl Signer for RecordingSigner {
+        async fn sign_hash(&self, hash: &B256) -> alloy_signer::Result<Signature> {
+            self.hashes.lock().push(*hash);
+            self.key.sign_hash(hash).await
+        }
+
+        fn address(&self) -> Address {
+            self.key.address()
+        }
+
+        fn chain_id(&self) -> Option<ChainId> {
+            None
+        }
+
+        fn set_chain_id(&mut self, _chain_id: Option<ChainId>) {}
+    }
+
+    /// Takes `delay` to sign, after failing its first `failures` attempts
+    struct UnreliableSigner {
+        key: PrivateKeySigner,
+        delay: Duration,
+        failures: Mutex<usize>,
+        attempts: Mutex<usize>,
+    }
+
+    impl UnreliableSigner {
+        fn new(delay: Duration, failures: usize) -> Arc<Self> {
+            Arc::new(Self {
+                key: dev::first_dev_signer(),
+                delay,
+                failures: Mutex::new(failures),
+                attempts: Mutex::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Signer for UnreliableSigner {
+        async fn sign_hash(&self, hash: &B256) -> alloy_signer::Result<Signature> {
+            *self.attempts.lock() += 1;
+            tokio::time::sleep(self.delay).await;
+            {
+                let mut failures = self.failures.lock();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err(alloy_signer::Error::other(io::Error::other("unavailable")))
+                }
+            }
+            self.key.sign_hash(hash).await
+        }
+
+        fn address(&self) -> Address {
+            self.key.address()
+        }
+
+        fn chain_id(&self) -> Option<ChainId> {
+            None
+        }
+
+        fn set_chain_id(&mut self, _chain_id: Option<ChainId>) {}
+    }
+
+    #[tokio::test]
+    async fn test_signing_timeout() {
+        let manager = Arc::new(SignerManager::new());
+        let address =
+            manager.add_boxed_signer(UnreliableSigner::new(Duration::from_secs(10), 0)).await;
+        let sealer = BlockSealer::new(manager).with_timeout(Duration::from_millis(50));
+        let header =
+            Header { number: 1, extra_data: vec![0u8; 32 + 65].into(), ..Default::default() };
+
+        let err = sealer.seal_header(header.clone(), &address).await.unwrap_err();
+        assert!(matches!(err, SignerError::Timeout(a) if a == address), "{err}");
+        assert_eq!(sealer.try_seal_header(header, &address).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_signing_retries_transient_failure() {
+        let hash = B256::repeat_byte(1);
+        let signer = UnreliableSigner::new(Duration::ZERO, 1);
+        let manager = SignerManager::new();
+        let address = manager.add_boxed_signer(signer.clone()).await;
+        let signature = manager.sign_hash(&address, hash).await.unwrap();
+        assert_eq!(signature.recover_address_from_prehash(&hash).unwrap(), address);
+        assert_eq!(*signer.attempts.lock(), 2);
+
+        // Retried only once
+        let signer = UnreliableSigner::new(Duration::ZERO, 2);
+        let manager = SignerManager::new();
+        let address = manager.add_boxed_signer(signer.clone()).await;
+        let err = manager.sign_hash(&address, hash).await.unwrap_err();
+        assert!(matches!(err, SignerError::Transient(_)), "{err}");
+        assert_eq!(*signer.attempts.lock(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let manager = SignerManager::new();
+        let local = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[1]).await.unwrap();
+        let failing = UnreliableSigner::new(Duration::ZERO, usize::MAX);
+        let remote = manager.add_boxed_signer(failing.clone()).await;
+
+        let report = manager.health_check().await;
+        let [first, second] = report.as_slice() else { panic!("{report:?}") };
+        let (healthy, broken) =
+            if first.address == local { (first, second) } else { (second, first) };
+        assert_eq!(healthy.source, SignerSource::Key);
+        assert!(healthy.unlocked);
+        assert_eq!(healthy.last_error, None);
+        assert!(healthy.last_success_at.is_some());
+
+        assert_eq!(broken.address, remote);
+        assert_eq!(broken.source, SignerSource::External);
+        assert!(!broken.unlocked);
+        assert!(broken.last_error.as_ref().unwrap().contains("unavailable"));
+        assert_eq!(broken.last_success_at, None);
+        assert_eq!(*failing.attempts.lock(), 1);
+
+        // Served from the cache
+        assert_eq!(manager.health_check().await, report);
+        assert_eq!(*failing.attempts.lock(), 1);
+
+        manager.remove_signer(&remote).await;
+        let report = manager.health_check().await;
+        assert_eq!(report.iter().map(|health| health.address).collect::<Vec<_>>(), [local]);
+    }
+
+    #[tokio::test]
+    async fn test_seal_block() {
+        let key = dev::first_dev_signer();
+        let transactions: Vec<TransactionSigned> = (0..2)
+            .map(|nonce| {
+                let tx = TxLegacy {
+                    chain_id: Some(1337),
+                    nonce,
+                    gas_price: 1_000_000_000,
+                    gas_limit: 21_000,
+                    to: TxKind::Call(Address::repeat_byte(0x42)),
+                    ..Default::default()
+                };
+                let signature = key.sign_hash_sync(&tx.signature_hash()).unwrap();
+                tx.into_signed(signature).into()
+            })
+            .collect();
+        let body = EthBlockBody { transactions, ommers: Vec::new(), withdrawals: None };
+        let header = Header {
+            number: 1,
+            difficulty: U256::from(2),
+            extra_data: BlockSealer::build_extra_data(&[0u8; 32], None),
+            transactions_root: body.calculate_tx_root(),
+            ..Default::default()
+        };
+        let unsigned = SealedBlock::<EthBlock>::seal_parts(header, body);
+
+        let sealer = BlockSealer::new(dev::setup_dev_signers().await);
+        let sealed = sealer.seal_block(unsigned.clone(), &key.address()).await.unwrap();
+        assert_ne!(sealed.hash(), unsigned.hash());
+        assert_eq!(sealed.hash(), sealed.header().hash_slow());
+        assert_eq!(sealed.body(), unsigned.body());
+
+        let config = PoaConfig { signers: crate::genesis::dev_signers(), ..Default::default() };
+        let genesis = crate::genesis::create_dev_genesis_for(&config);
+        let consensus = PoaConsensus::new(Arc::new(PoaChainSpec::new(genesis, config)));
+        assert_eq!(consensus.recover_signer(sealed.header()).unwrap(), key.address());
+
+        // A body the header doesn't commit to isn't signed
+        let mut block = unsigned.into_block();
+        block.body.transactions.pop();
+        let err = sealer.seal_block(SealedBlock::seal_slow(block), &key.address()).await;
+        assert!(matches!(err, Err(SignerError::TransactionsRootMismatch { .. })), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_add_boxed_signer() {
+        let recorder = Arc::new(RecordingSigner {
+            key: dev::first_dev_signer(),
+            hashes: Mutex::new(Vec::new()),
+        });
+        let manager = Arc::new(SignerManager::new());
+        let address = manager.add_boxed_signer(recorder.clone()).await;
+        assert_eq!(address, dev::first_dev_signer().address());
+        assert!(manager.has_signer(&address).await);
+
+        let sealer = BlockSealer::new(manager);
+        let header =
+            Header { number: 1, extra_data: vec![0u8; 32 + 65].into(), ..Default::default() };
+        let sealed = sealer.seal_header(header.clone(), &address).await.unwrap();
+        assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), address);
+        assert_eq!(*recorder.hashes.lock(), vec![BlockSealer::seal_hash(&header)]);
+    }
+
+    #[tokio::test]
+    async fn test_seal_regular_header() {
+        let manager = Arc::new(SignerManager::new());
+        let address = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        let sealer = BlockSealer::new(manager);
+
+        let extra_data = BlockSealer::build_extra_data(&[7u8; 32], None);
+        let header = Header { number: 1, extra_data, ..Default::default() };
+        let sealed = sealer.seal_regular_header(header, &address).await.unwrap();
+        assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), address);
+        assert_eq!(sealed.extra_data[..32], [7u8; 32]);
+
+        let checkpoint_data = BlockSealer::build_extra_data(&[0u8; 32], Some(&[address]));
+        for extra_data in [checkpoint_data, Bytes::from(vec![0u8; 32])] {
+            let len = extra_data.len();
+            let header = Header { number: 2, extra_data, ..Default::default() };
+            let err = sealer.seal_regular_header(header, &address).await.unwrap_err();
+            assert!(
+                matches!(err, SignerError::InvalidExtraData { expected: 97, got } if got == len),
+                "{err}"
+            );
+        }
+        let err = sealer
+            .seal_checkpoint_header(Header::default(), &address, &[], &[0u8; 32])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SignerError::EmptyCheckpointSigners), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_turn() {
+        let mut signers = crate::genesis::dev_signers();
+        signers.sort();
+        let mut snapshot = Snapshot::new(30_000, 0, B256::ZERO, signers.clone());
+        let header = |number| Header {
+            number,
+            extra_data: BlockSealer::build_extra_data(&[0u8; 32], None),
+            ..Default::default()
+        };
+
+        // Only the second signer's key is held here, in turn for block 1
+        let manager = Arc::new(SignerManager::new());
+        manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[1]).await.unwrap();
+        let local = manager.signer_addresses().await[0];
+        assert_eq!(snapshot.inturn_signer(1), Some(local));
+
+        let sealer = BlockSealer::new(manager.clone());
+        let (sealed, signer) = sealer.sign_in_turn(header(1), &snapshot).await.unwrap();
+        assert_eq!(signer, local);
+        assert_eq!(sealed.difficulty, U256::from(1));
+        assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), local);
+
+        let err = sealer.sign_in_turn(header(2), &snapshot).await.unwrap_err();
+        assert!(
+            matches!(err, SignerError::NotInTurn { number: 2, in_turn } if in_turn == Some(signers[2])),
+            "{err}"
+        );
+
+        let sealer = BlockSealer::new(manager.clone()).with_out_of_turn(true);
+        let (sealed, signer) = sealer.sign_in_turn(header(2), &snapshot).await.unwrap();
+        assert_eq!(signer, local);
+        assert_eq!(sealed.difficulty, U256::from(2));
+        assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), local);
+
+        // Having sealed block 1, the key may not seal block 2 out of turn
+        snapshot.recents.insert(1, local);
+        let err = sealer.sign_in_turn(header(2), &snapshot).await.unwrap_err();
+        assert!(matches!(err, SignerError::RecentlySigned(2)), "{err}");
+
+        let outsider = Arc::new(SignerManager::new());
+        outsider.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[5]).await.unwrap();
+        let sealer = BlockSealer::new(outsider).with_out_of_turn(true);
+        let err = sealer.sign_in_turn(header(1), &snapshot).await.unwrap_err();
+        assert!(matches!(err, SignerError::NoAuthorizedSigner), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_signer_selection() {
+        // Two of four authority keys, with a recent-signer limit of 3
+        let authorities: Vec<_> = crate::genesis::dev_accounts().into_iter().take(4).collect();
+        let manager = Arc::new(SignerManager::new());
+        let k0 = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        let k1 = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[1]).await.unwrap();
+        // Sorted, k1 is the second authority and k0 the fourth
+        assert!(k1 < k0);
+
+        let produce = |selection| {
+            let manager = manager.clone();
+            let authorities = authorities.clone();
+            async move {
+                let sealer = BlockSealer::new(manager).with_selection(selection);
+                let mut snapshot = Snapshot::new(30_000, 0, B256::ZERO, authorities);
+                let mut produced = Vec::new();
+                for number in 1..=8 {
+                    let header = Header {
+                        number,
+                        extra_data: BlockSealer::build_extra_data(&[0u8; 32], None),
+                        ..Default::default()
+                    };
+                    match sealer.seal_selected(header, &snapshot).await {
+                        Ok((sealed, signer)) => {
+                            assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), signer);
+                            let in_turn = snapshot.inturn(number, &signer);
+                            assert_eq!(sealed.difficulty, U256::from(if in_turn { 1 } else { 2 }));
+                            assert_eq!(sealer.selected_signer(), Some(signer));
+                            snapshot.recents.insert(number, signer);
+                            produced.push(Some(signer));
+                        }
+                        Err(SignerError::NotInTurn { .. } | SignerError::RecentlySigned(_)) => {
+                            produced.push(None)
+                        }
+                        Err(err) => panic!("{err}"),
+                    }
+                }
+                produced
+            }
+        };
+
+        assert_eq!(
+            produce(SignerSelection::InTurnOnly).await,
+            [Some(k1), None, Some(k0), None, Some(k1), None, Some(k0), None]
+        );
+        assert_eq!(
+            produce(SignerSelection::Preferred(k0)).await,
+            [Some(k0), Some(k1), None, Some(k0), Some(k1), None, Some(k0), Some(k1)]
+        );
+        assert_eq!(
+            produce(SignerSelection::RoundRobinLocal).await,
+            [Some(k1), Some(k0), None, Some(k1), Some(k0), None, Some(k1), Some(k0)]
+        );
+    }
+
+    #[test]
+    fn test_build_extra_data() {
+        let vanity = [7u8; 32];
+        let regular = BlockSealer::build_extra_data(&vanity, None);
+        assert_eq!(regular.len(), EXTRA_VANITY_LENGTH + EXTRA_SEAL_LENGTH);
+        assert_eq!(regular[..32], vanity);
+
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let checkpoint = BlockSealer::build_extra_data(&vanity, Some(&[b, a]));
+        assert_eq!(checkpoint.len(), EXTRA_VANITY_LENGTH + 40 + EXTRA_SEAL_LENGTH);
+        assert_eq!(checkpoint[32..52], a);
+        assert_eq!(checkpoint[52..72], b);
+        assert!(checkpoint[72..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_vanity_from_str() {
+        let exact = "x".repeat(EXTRA_VANITY_LENGTH);
+        let vanity = BlockSealer::vanity_from_str(&exact).unwrap();
+        assert_eq!(vanity, [b'x'; EXTRA_VANITY_LENGTH]);
+        assert_eq!(BlockSealer::vanity_to_string(&vanity), exact);
+
+        let short = BlockSealer::vanity_from_str("poa").unwrap();
+        assert_eq!(short[..3], *b"poa");
+        assert!(short[3..].iter().all(|byte| *byte == 0));
+        assert_eq!(BlockSealer::vanity_to_string(&short), "poa");
+        assert_eq!(BlockSealer::vanity_to_string(&[0; EXTRA_VANITY_LENGTH]), "");
+
+        // 31 ASCII bytes leave a single byte for the two byte `é`, which is not cut in half
+        let crossing = format!("{}é", "x".repeat(EXTRA_VANITY_LENGTH - 1));
+        assert!(matches!(
+            BlockSealer::vanity_from_str(&crossing),
+            Err(SignerError::VanityTooLong(33))
+        ));
+        let fitting = format!("{}é", "x".repeat(EXTRA_VANITY_LENGTH - 2));
+        let vanity = BlockSealer::vanity_from_str(&fitting).unwrap();
+        assert_eq!(BlockSealer::vanity_to_string(&vanity), fitting);
+
+        let err = BlockSealer::vanity_from_str(&"x".repeat(40)).unwrap_err();
+        assert_eq!(err.to_string(), "Vanity of 40 bytes exceeds 32 bytes");
+    }
+
+    #[test]
+    fn test_proposals_round_robin_over_pending() {
+        let signer = Address::repeat_byte(0xaa);
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let snapshot = Snapshot::new(30_000, 0, B256::ZERO, [signer, c]);
+
+        let proposals = ProposalsMap::new();
+        proposals.propose(a, true);
+        proposals.propose(b, true);
+        // Already a signer, so authorizing it again is settled
+        proposals.propose(c, true);
+
+        let picks: Vec<_> = (0..4).map(|_| proposals.next_vote(&snapshot, &signer)).collect();
+        assert_eq!(picks, vec![Some((a, true)), Some((b, true)), Some((a, true)), Some((b, true))]);
+
+        assert!(proposals.discard(&a));
+        assert!(!proposals.discard(&a));
+        assert_eq!(proposals.next_vote(&snapshot, &signer), Some((b, true)));
+        assert_eq!(proposals.proposals().len(), 2);
+    }
+
+    #[test]
+    fn test_proposals_skip_votes_already_cast() {
+        let signer = Address::repeat_byte(0xaa);
+        let target = Address::repeat_byte(1);
+        let mut snapshot = Snapshot::new(30_000, 0, B256::ZERO, [signer, Address::repeat_byte(2)]);
+        snapshot.votes.push(crate::consensus::snapshot::Vote {
+            signer,
+            block: 0,
+            address: target,
+            authorize: true,
+        });
+
+        let proposals = ProposalsMap::new();
+        proposals.propose(target, true);
+        assert_eq!(proposals.next_vote(&snapshot, &signer), None);
+
+        // Another signer still has to vote, and flipping the proposal makes it pending again
+        assert_eq!(proposals.next_vote(&snapshot, &Address::repeat_byte(2)), Some((target, true)));
+        proposals.propose(target, false);
+        assert_eq!(proposals.next_vote(&snapshot, &signer), None);
+        snapshot.signers.insert(target);
+        assert_eq!(proposals.next_vote(&snapshot, &signer), Some((target, false)));
+    }
+
+    #[test]
+    fn test_prepare_vote_sets_beneficiary_and_nonce() {
+        let signer = Address::repeat_byte(0xaa);
+        let target = Address::repeat_byte(1);
+        let snapshot = Snapshot::new(4, 0, B256::ZERO, [signer]);
+        let proposals = Arc::new(ProposalsMap::new());
+        proposals.propose(target, true);
+        let sealer = BlockSealer::new(Arc::new(SignerManager::new())).with_proposals(proposals);
+
+        let mut header = Header { number: 1, ..Default::default() };
+        assert_eq!(sealer.prepare_vote(&mut header, &snapshot, &signer), Some((target, true)));
+        assert_eq!((header.beneficiary, header.nonce), (target, NONCE_AUTH_VOTE));
+
+        // Epoch blocks carry the signer list instead of a vote
+        let mut header = Header { number: 4, ..Default::default() };
+        assert_eq!(sealer.prepare_vote(&mut header, &snapshot, &signer), None);
+        assert_eq!(header.beneficiary, Address::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_dev_signers_setup() {
+        let manager = dev::setup_dev_signers().await;
+        let addresses = manager.signer_addresses().await;
+
+        assert_eq!(addresses.len(), 3);
+
+        // Verify addresses match expected dev accounts
+        let expected_first = crate::genesis::dev_accounts()[0];
+        assert!(addresses.contains(&expected_first));
+    }
+
+    #[test]
+    fn test_keys_from_mnemonic() {
+        let count = dev::DEV_PRIVATE_KEYS.len();
+        let keys =
+            dev::keys_from_mnemonic(dev::DEV_MNEMONIC, dev::DEV_DERIVATION_PATH, count).unwrap();
+        assert_eq!(count, 20);
+        for (key, expected) in keys.iter().zip(dev::DEV_PRIVATE_KEYS) {
+            assert_eq!(key.to_bytes(), expected.parse::<PrivateKeySigner>().unwrap().to_bytes());
+        }
+        assert!(matches!(
+            dev::keys_from_mnemonic("not a mnemonic", dev::DEV_DERIVATION_PATH, 1),
+            Err(SignerError::InvalidMnemonic(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_signers_from_mnemonic() {
+        let manager = SignerManager::new();
+        let signers = manager
+            .add_signers_from_mnemonic(dev::DEV_MNEMONIC, dev::DEV_DERIVATION_PATH, 3)
+            .await
+            .unwrap();
+        assert_eq!(signers, crate::genesis::dev_signers());
+
+        // The dev setup derives the same signers
+        let mut dev_signers = dev::setup_dev_signers().await.signer_addresses().await;
+        dev_signers.sort();
+        let mut expected = signers.clone();
+        expected.sort();
+        assert_eq!(dev_signers, expected);
+
+        let manager = SignerManager::new();
+        let err = manager
+            .add_signers_from_mnemonic("not a mnemonic", dev::DEV_DERIVATION_PATH, 3)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SignerError::InvalidMnemonic(_)), "{err}");
+        let err = manager
+            .add_signers_from_mnemonic(dev::DEV_MNEMONIC, "m/44'/sixty'/0'/0/", 3)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SignerError::InvalidDerivationPath(_)), "{err}");
+        assert!(err.to_string().contains("m/44'/sixty'/0'/0/0"), "{err}");
+        assert!(manager.signer_addresses().await.is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+//! Keystore passwords
+//!
+//! Passwords don't belong in code or on the command line, where `ps` shows them. A
+//! [`PasswordSource`] reads them from a file, an environment variable or the terminal instead.
+//! They are zeroized when dropped and never logged.
+
+use super::{keystore_paths, SignerError};
+use alloy_primitives::Address;
+use std::{
+    env, fmt, fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+};
+use zeroize::Zeroizing;
+
+/// A keystore password, zeroized when dropped
+pub type Password = Zeroizing<String>;
+
+/// Where the passwords of keystore files come from
+pub enum PasswordSource {
+    /// A file with one password per line, like geth's `--password`
+    ///
+    /// A line `# <address>` names the keystore the password on the next line is for. The other
+    /// passwords go to the remaining keystores of a directory in file name order, or all to the
+    /// same one if there is a single password. Passwords can't start with `#`.
+    File(PathBuf),
+    /// An environment variable
+    Env(String),
+    /// Ask on the terminal without echoing the input; fails if there is no terminal
+    Prompt,
+    /// A callback asked for the password of each keystore file; `None` skips the file
+    Callback(Box<dyn Fn(&Path) -> Option<String> + Send + Sync>),
+    /// The same password for every keystore, for tests
+    Static(Password),
+}
+
+impl PasswordSource {
+    /// The password for the keystore at `path`, `None` to skip it
+    ///
+    /// A [`Prompt`](Self::Prompt) blocks until the operator answers; async code goes through
+    /// [`resolve`](Self::resolve) instead.
+    pub fn password(&self, path: &Path) -> Result<Option<Password>, SignerError> {
+        match self {
+            Self::File(file) => {
+                let passwords = Zeroizing::new(fs::read_to_string(file)?);
+                Ok(file_password(&passwords, path))
+            }
+            Self::Env(var) => env::var(var)
+                .map(|password| Some(Zeroizing::new(password)))
+                .map_err(|_| SignerError::MissingPassword(var.clone())),
+            Self::Prompt => prompt(path),
+            Self::Callback(callback) => Ok(callback(path).map(Zeroizing::new)),
+            Self::Static(password) => Ok(Some(password.clone())),
+        }
+    }
+
+    /// [`password`](Self::password) for async callers, with terminal prompts moved to the
+    /// blocking thread pool so waiting for the operator doesn't stall the runtime
+    pub async fn resolve(&self, path: &Path) -> Result<Option<Password>, SignerError> {
+        match self {
+            Self::Prompt => {
+                let path = path.to_path_buf();
+                tokio::task::spawn_blocking(move || prompt(&path))
+                    .await
+                    .map_err(io::Error::other)?
+            }
+            _ => self.password(path),
+        }
+    }
+
+    /// The password to encrypt a key generated in `keystore_dir` with, `None` if there is none
+    ///
+    /// The key has no keystore file yet, so a [`File`](Self::File) can't match it by address or
+    /// position. Like geth's `account new --password`, the first unlabeled password is used.
+    pub fn new_key_password(&self, keystore_dir: &Path) -> Result<Option<Password>, SignerError> {
+        match self {
+            Self::File(file) => {
+                let passwords = Zeroizing::new(fs::read_to_string(file)?);
+                let (_, unlabeled) = parse_password_file(&passwords);
+                Ok(unlabeled.first().map(|password| Zeroizing::new(password.to_string())))
+            }
+            _ => self.password(keystore_dir),
+        }
+    }
+}
+
+impl fmt::Debug for PasswordSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(file) => f.debug_tuple("File").field(file).finish(),
+            Self::Env(var) => f.debug_tuple("Env").field(var).finish(),
+            Self::Prompt => f.write_str("Prompt"),
+            Self::Callback(_) => f.write_str("Callback"),
+            Self::Static(_) => f.write_str("Static"),
+        }
+    }
+}
+
+/// Ask for the password of the keystore at `path` on the terminal, without echoing it
+fn prompt(path: &Path) -> Result<Option<Password>, SignerError> {
+    if !io::stdin().is_terminal() {
+        return Err(SignerError::NoTerminal(path.to_path_buf()))
+    }
+    let prompt = format!("Password for {}: ", path.display());
+    Ok(Some(Zeroizing::new(rpassword::prompt_password(prompt)?)))
+}
+
+/// The passwords of a password file, labeled with their keystore's address or not
+fn parse_password_file(passwords: &str) -> (Vec<(Address, &str)>, Vec<&str>) {
+    let mut labeled = Vec::new();
+    let mut unlabeled = Vec::new();
+    let mut label = None;
+    for line in passwords.lines() {
+        if let Some(comment) = line.strip_prefix('#') {
+            label = comment.trim().parse::<Address>().ok();
+            continue
+        }
+        match label.take() {
+            Some(address) => labeled.push((address, line)),
+            None => unlabeled.push(line),
+        }
+    }
+    (labeled, unlabeled)
+}
+
+/// The password for the keystore at `path` in `passwords`, the content of a password file
+fn file_password(passwords: &str, path: &Path) -> Option<Password> {
+    let (labeled, unlabeled) = parse_password_file(passwords);
+    let address = keystore_address(path);
+    if let Some((_, password)) = labeled.iter().find(|(labeled, _)| Some(*labeled) == address) {
+        return Some(Zeroizing::new(password.to_string()))
+    }
+    let password = match unlabeled.as_slice() {
+        [] => return None,
+        [password] => password,
+        passwords => {
+            let keystores = keystore_paths(path.parent()?).ok()?;
+            let index = keystores
+                .iter()
+                .filter(|keystore| {
+                    keystore_address(keystore).is_none_or(|address| {
+                        labeled.iter().all(|(labeled, _)| *labeled != address)
+                    })
+                })
+                .position(|keystore| keystore == path)?;
+            passwords.get(index)?
+        }
+    };
+    Some(Zeroizing::new(password.to_string()))
+}
+
+/// Address of the keystore at `path` from its file name, `UTC--<timestamp>--<address>`
+fn keystore_address(path: &Path) -> Option<Address> {
+    path.file_name()?.to_str()?.rsplit("--").next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_file_passwords() {
+        let dir = tempfile::tempdir().unwrap();
+        let labeled = address!("0a60a644910141059e9c79a716f9dad643cb092c");
+        let keystores: Vec<_> = [
+            format!("UTC--1--{}", Address::with_last_byte(1)),
+            format!("UTC--2--{labeled}"),
+            format!("UTC--3--{}", Address::with_last_byte(3)),
+        ]
+        .iter()
+        .map(|name| {
+            let path = dir.path().join(name);
+            fs::write(&path, "{}").unwrap();
+            path
+        })
+        .collect();
+
+        let file = dir.path().join("passwords");
+        fs::write(&file, format!("first\n# {labeled}\nlabeled\nsecond\n")).unwrap();
+        let source = PasswordSource::File(file.clone());
+        let password = |path| source.password(path).unwrap().map(|password| password.to_string());
+        let passwords: Vec<_> = keystores.iter().map(password).collect();
+        assert_eq!(passwords, ["first", "labeled", "second"].map(|p| Some(p.to_string())));
+
+        // A single password is for every keystore
+        fs::write(&file, "only\n").unwrap();
+        for path in &keystores {
+            assert_eq!(password(path).as_deref(), Some("only"));
+        }
+
+        fs::write(&file, format!("# {labeled}\nlabeled\n")).unwrap();
+        assert_eq!(password(&keystores[0]), None);
+        assert!(format!("{source:?}").contains("passwords"));
+
+        // A new key takes the first unlabeled password
+        fs::write(&file, format!("# {labeled}\nlabeled\nfirst\nsecond\n")).unwrap();
+        let new_key = source.new_key_password(dir.path()).unwrap().unwrap();
+        assert_eq!(new_key.as_str(), "first");
+        fs::write(&file, format!("# {labeled}\nlabeled\n")).unwrap();
+        assert_eq!(source.new_key_password(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_env_password() {
+        let path = Path::new("UTC--keystore");
+        // Set by cargo for the tests
+        let source = PasswordSource::Env("CARGO_PKG_NAME".to_string());
+        let password = source.password(path).unwrap().unwrap();
+        assert_eq!(password.as_str(), env!("CARGO_PKG_NAME"));
+
+        let missing = PasswordSource::Env("POA_TEST_UNSET_KEYSTORE_PASSWORD".to_string());
+        let err = missing.password(path).unwrap_err();
+        assert!(matches!(err, SignerError::MissingPassword(_)), "{err}");
+
+        let secret = PasswordSource::Static(Zeroizing::new("hunter2".to_string()));
+        assert_eq!(format!("{secret:?}"), "Static");
+    }
+}
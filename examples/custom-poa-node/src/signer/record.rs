@@ -0,0 +1,192 @@
+//! Double-sign protection
+//!
+//! Signing two different headers at the same height forks a POA chain, which is what happens when
+//! a node is accidentally run twice. [`SignRecord`] keeps the highest header each signer signed so
+//! that [`BlockSealer`](super::BlockSealer) refuses to sign another one at or below it.
+
+use super::SignerError;
+use alloy_primitives::{Address, B256};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// File in the datadir holding the [`SignRecord`]
+pub const SIGN_RECORD_FILE: &str = "signed-heights.json";
+
+/// Highest header a signer signed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedHeight {
+    /// Block number of the header
+    pub number: u64,
+    /// Seal hash of the header
+    pub seal_hash: B256,
+}
+
+/// The highest header signed per signer, persisted to a file or, by default, kept in memory
+#[derive(Debug, Default)]
+pub struct SignRecord {
+    /// File the record is persisted to
+    path: Option<PathBuf>,
+    /// Highest header signed per signer
+    heights: Mutex<BTreeMap<Address, SignedHeight>>,
+}
+
+impl SignRecord {
+    /// The record persisted at `path`, usually [`SIGN_RECORD_FILE`] in the datadir, starting from
+    /// the file's content if it exists
+    ///
+    /// An unreadable file is an error rather than an empty record: signing without it is exactly
+    /// what the record is there to prevent.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, SignerError> {
+        let path = path.into();
+        let heights = match fs::read_to_string(&path) {
+            Ok(record) => serde_json::from_str(&record).map_err(|err| {
+                SignerError::MalformedSignRecord(format!("{}: {err}", path.display()))
+            })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(SignerError::SignRecordIo(err)),
+        };
+        Ok(Self { path: Some(path), heights: Mutex::new(heights) })
+    }
+
+    /// Highest header `signer` signed
+    pub fn signed(&self, signer: &Address) -> Option<SignedHeight> {
+        self.heights.lock().get(signer).copied()
+    }
+
+    /// Record that `signer` is about to sign the header with `seal_hash` at `number`
+    ///
+    /// Returns once the record is persisted, so a signature never exists without its record.
+    /// Fails with [`SignerError::AlreadySignedHeight`] if `signer` signed a different header at
+    /// this height or any header above it, unless `force`. Signing the recorded header again is
+    /// fine. A forced signature below the recorded height leaves the record as it is.
+    pub fn reserve(
+        &self,
+        signer: Address,
+        number: u64,
+        seal_hash: B256,
+        force: bool,
+    ) -> Result<(), SignerError> {
+        let mut heights = self.heights.lock();
+        let next = SignedHeight { number, seal_hash };
+        let previous = heights.get(&signer).copied();
+        match previous {
+            Some(signed) if signed == next => return Ok(()),
+            Some(signed) if signed.number >= number && !force => {
+                return Err(SignerError::AlreadySignedHeight {
+                    signer,
+                    number,
+                    signed: signed.number,
+                })
+            }
+            Some(signed) if signed.number > number => return Ok(()),
+            _ => {}
+        }
+
+        heights.insert(signer, next);
+        if let Err(err) = self.persist(&heights) {
+            // Keep memory and file in step
+            match previous {
+                Some(signed) => heights.insert(signer, signed),
+                None => heights.remove(&signer),
+            };
+            return Err(SignerError::SignRecordIo(err))
+        }
+        Ok(())
+    }
+
+    /// Replace the file with `heights`, synced to disk
+    fn persist(&self, heights: &BTreeMap<Address, SignedHeight>) -> io::Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec_pretty(heights)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        // The rename only survives a crash once the directory entry is on disk too
+        #[cfg(unix)]
+        {
+            let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+            fs::File::open(dir.unwrap_or(std::path::Path::new(".")))?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::{dev, BlockSealer, SignerManager};
+    use alloy_consensus::Header;
+    use alloy_primitives::Bytes;
+    use std::sync::Arc;
+
+    fn header(number: u64, timestamp: u64) -> Header {
+        Header {
+            number,
+            timestamp,
+            extra_data: Bytes::from(vec![0u8; 32 + 65]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refuses_double_sign() {
+        let manager = Arc::new(SignerManager::new());
+        let signer = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        let sealer = BlockSealer::new(manager);
+
+        let sealed = sealer.seal_header(header(5, 1), &signer).await.unwrap();
+        // The same header again is fine
+        assert_eq!(sealer.seal_header(header(5, 1), &signer).await.unwrap(), sealed);
+
+        for conflicting in [header(5, 2), header(4, 1)] {
+            let err = sealer.seal_header(conflicting, &signer).await.unwrap_err();
+            assert!(matches!(err, SignerError::AlreadySignedHeight { signed: 5, .. }), "{err}");
+        }
+
+        sealer.seal_header(header(6, 1), &signer).await.unwrap();
+        sealer.force_seal_header(header(5, 2), &signer).await.unwrap();
+        let record = sealer.sign_record().signed(&signer).unwrap();
+        assert_eq!(record.number, 6);
+    }
+
+    #[tokio::test]
+    async fn test_record_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SIGN_RECORD_FILE);
+        let signer = dev::first_dev_signer().address();
+
+        // Crash between persisting and returning the signature, simulated by a manager without
+        // the key: the record is written but no signature is returned
+        let sealer = BlockSealer::new(Arc::new(SignerManager::new()))
+            .with_sign_record(Arc::new(SignRecord::open(&path).unwrap()));
+        let err = sealer.seal_header(header(7, 1), &signer).await.unwrap_err();
+        assert!(matches!(err, SignerError::NoSignerForAddress(_)), "{err}");
+        assert!(path.exists());
+
+        let manager = Arc::new(SignerManager::new());
+        manager.add_signer(dev::first_dev_signer()).await;
+        let sealer =
+            BlockSealer::new(manager).with_sign_record(Arc::new(SignRecord::open(&path).unwrap()));
+        assert_eq!(
+            sealer.sign_record().signed(&signer).unwrap().seal_hash,
+            BlockSealer::seal_hash(&header(7, 1))
+        );
+        // The header may still be signed after the restart, a different one may not
+        let sealed = sealer.seal_header(header(7, 1), &signer).await.unwrap();
+        assert_eq!(BlockSealer::verify_signature(&sealed).unwrap(), signer);
+        let err = sealer.seal_header(header(7, 2), &signer).await.unwrap_err();
+        assert!(matches!(err, SignerError::AlreadySignedHeight { number: 7, .. }), "{err}");
+
+        fs::write(&path, "{\"0x").unwrap();
+        let err = SignRecord::open(&path).unwrap_err();
+        assert!(matches!(err, SignerError::MalformedSignRecord(_)), "{err}");
+    }
+}
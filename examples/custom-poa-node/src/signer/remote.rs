@@ -0,0 +1,381 @@
+//! Remote signer backend
+//!
+//! Keeps signing keys out of the node process: a clef-style daemon holds them and the node sends
+//! it every seal hash as an `account_signHash` JSON-RPC call with the params `[address, hash]`.
+//! The daemon answers with the 65-byte `r || s || v` signature, hex encoded.
+
+use super::{bytes_to_signature, SignerError};
+use alloy_primitives::{Address, Bytes, ChainId, Signature, B256};
+use alloy_signer::Signer;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// How long a signing request may take, see [`RemoteSigner::with_timeout`]
+pub const DEFAULT_REMOTE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How often a failed signing request is retried, see [`RemoteSigner::with_retries`]
+pub const DEFAULT_REMOTE_RETRIES: u32 = 2;
+
+/// Pause before the first retry, doubled for every further one
+const RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Signs for one address through a signing daemon
+///
+/// Timeouts, transport errors and `5xx` answers are retried; a JSON-RPC error or a signature that
+/// doesn't recover to the address is not.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    /// Address the daemon signs for
+    address: Address,
+    /// JSON-RPC endpoint of the daemon
+    url: String,
+    /// HTTP client, shared between clones
+    client: reqwest::Client,
+    /// Timeout of a single request
+    timeout: Duration,
+    /// Retries after the first attempt
+    retries: u32,
+    /// Chain id reported through [`Signer::chain_id`]
+    chain_id: Option<ChainId>,
+}
+
+impl RemoteSigner {
+    /// Sign for `address` through the daemon at `url`
+    pub fn new(address: Address, url: impl Into<String>) -> Self {
+        Self {
+            address,
+            url: url.into(),
+            client: reqwest::Client::new(),
+            timeout: DEFAULT_REMOTE_TIMEOUT,
+            retries: DEFAULT_REMOTE_RETRIES,
+            chain_id: None,
+        }
+    }
+
+    /// Set the timeout of a single request
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set how often a failed request is retried
+    pub const fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Address the daemon signs for
+    pub const fn address(&self) -> Address {
+        self.address
+    }
+
+    /// JSON-RPC endpoint of the daemon
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Timeout of a single request
+    pub const fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Retries after the first attempt
+    pub const fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Have the daemon sign `hash`
+    ///
+    /// Fails with [`SignerError::Timeout`] if the last attempt timed out, and with
+    /// [`SignerError::Transient`] or [`SignerError::Permanent`] depending on why it failed
+    /// otherwise.
+    pub async fn sign_hash(&self, hash: B256) -> Result<Signature, SignerError> {
+        let mut backoff = RETRY_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            let failure = match self.request(attempt, hash).await {
+                Ok(signature) => return Ok(signature),
+                Err(failure) => failure,
+            };
+            if attempt == self.retries || matches!(failure, Failure::Fatal(_)) {
+                return Err(match failure {
+                    Failure::Timeout => SignerError::Timeout(self.address),
+                    Failure::Transient(reason) => SignerError::Transient(reason),
+                    Failure::Fatal(reason) => SignerError::Permanent(reason),
+                })
+            }
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+
+    /// One signing request
+    async fn request(&self, id: u32, hash: B256) -> Result<Signature, Failure> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "account_signHash",
+            "params": [self.address, hash],
+        });
+        let response = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| self.transport_failure(err))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let reason = format!("remote signer {} answered HTTP {status}", self.url);
+            return Err(if status.is_server_error() {
+                Failure::Transient(reason)
+            } else {
+                Failure::Fatal(reason)
+            })
+        }
+
+        let body = response.bytes().await.map_err(|err| self.transport_failure(err))?;
+        let response: RpcResponse = serde_json::from_slice(&body).map_err(|err| {
+            Failure::Fatal(format!("remote signer {} sent an invalid response: {err}", self.url))
+        })?;
+        if let Some(error) = response.error {
+            return Err(Failure::Fatal(format!(
+                "remote signer {} refused to sign ({}): {}",
+                self.url, error.code, error.message
+            )))
+        }
+        let bytes = response.result.ok_or_else(|| {
+            Failure::Fatal(format!("remote signer {} sent no signature", self.url))
+        })?;
+
+        let signature = bytes_to_signature(&bytes).map_err(Failure::Fatal)?;
+        match signature.recover_address_from_prehash(&hash) {
+            Ok(signer) if signer == self.address => Ok(signature),
+            _ => Err(Failure::Fatal(format!(
+                "remote signer {} signed for another address than {}",
+                self.url, self.address
+            ))),
+        }
+    }
+
+    /// Classify an error of the HTTP client
+    fn transport_failure(&self, err: reqwest::Error) -> Failure {
+        if err.is_timeout() {
+            Failure::Timeout
+        } else {
+            Failure::Transient(format!("remote signer {} unreachable: {err}", self.url))
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn sign_hash(&self, hash: &B256) -> alloy_signer::Result<Signature> {
+        Self::sign_hash(self, *hash).await.map_err(alloy_signer::Error::other)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id
+    }
+
+    fn set_chain_id(&mut self, chain_id: Option<ChainId>) {
+        self.chain_id = chain_id;
+    }
+}
+
+/// Why a signing request failed
+#[derive(Debug)]
+enum Failure {
+    /// The daemon didn't answer in time
+    Timeout,
+    /// Worth another attempt
+    Transient(String),
+    /// Retrying won't help
+    Fatal(String),
+}
+
+/// JSON-RPC response of the daemon
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<Bytes>,
+    error: Option<RpcError>,
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::{dev, signature_to_bytes, BlockSealer, SignerManager};
+    use alloy_consensus::Header;
+    use alloy_primitives::keccak256;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    /// Read one HTTP request and return its body
+    async fn read_request(stream: &mut TcpStream) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed mid-request");
+            buf.extend_from_slice(&chunk[..n]);
+            let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") else { continue };
+            let head = String::from_utf8_lossy(&buf[..end]).to_lowercase();
+            let len = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map_or(0, |len| len.trim().parse().unwrap());
+            if buf.len() >= end + 4 + len {
+                return buf[end + 4..end + 4 + len].to_vec()
+            }
+        }
+    }
+
+    /// Answer every request with what `respond` makes of its JSON body
+    async fn mock_daemon(
+        respond: impl Fn(serde_json::Value) -> (u16, String) + Send + Sync + 'static,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let request = serde_json::from_slice(&read_request(&mut stream).await).unwrap();
+                let (status, body) = respond(request);
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\n\
+                     content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_remote_sign_hash() {
+        let key: PrivateKeySigner = dev::DEV_PRIVATE_KEYS[0].parse().unwrap();
+        let address = key.address();
+        let url = mock_daemon(move |request| {
+            assert_eq!(request["method"], "account_signHash");
+            assert_eq!(request["params"][0].as_str().unwrap().parse::<Address>().unwrap(), address);
+            let hash: B256 = request["params"][1].as_str().unwrap().parse().unwrap();
+            let signature = signature_to_bytes(&key.sign_hash_sync(&hash).unwrap());
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": Bytes::copy_from_slice(&signature),
+            });
+            (200, body.to_string())
+        })
+        .await;
+
+        let manager = SignerManager::new();
+        manager.add_remote_signer(address, &url).await.unwrap();
+        let hash = keccak256(b"remote");
+        let signature = manager.sign_hash(&address, hash).await.unwrap();
+        assert_eq!(signature.recover_address_from_prehash(&hash).unwrap(), address);
+
+        // A daemon signing with the wrong key is caught
+        let other = dev::DEV_PRIVATE_KEYS[1].parse::<PrivateKeySigner>().unwrap().address();
+        let err = RemoteSigner::new(other, url.clone()).sign_hash(hash).await.unwrap_err();
+        assert!(matches!(err, SignerError::Permanent(_)), "{err}");
+
+        // Duplicates and malformed URLs are refused
+        let err = manager.add_remote_signer(address, &url).await.unwrap_err();
+        assert!(matches!(err, SignerError::DuplicateSigner(_)), "{err}");
+        for url in ["not a url", "ftp://127.0.0.1:8550", "unix:/tmp/signer.sock"] {
+            let err = manager.add_remote_signer(other, url).await.unwrap_err();
+            assert!(matches!(err, SignerError::InvalidRemoteUrl(_)), "{err}");
+            let err = manager.add_remote(RemoteSigner::new(other, url)).await.unwrap_err();
+            assert!(matches!(err, SignerError::InvalidRemoteUrl(_)), "{err}");
+        }
+        assert!(!manager.has_signer(&other).await);
+        let err = manager.add_remote(RemoteSigner::new(address, url)).await.unwrap_err();
+        assert!(matches!(err, SignerError::DuplicateSigner(_)), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_remote_errors() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let url = mock_daemon(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (500, String::new())
+        })
+        .await;
+        let signer = RemoteSigner::new(Address::ZERO, url).with_retries(2);
+        let err = signer.sign_hash(B256::ZERO).await.unwrap_err();
+        assert!(matches!(&err, SignerError::Transient(reason) if reason.contains("500")), "{err}");
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // A refusal isn't retried
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let url = mock_daemon(move |request| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": -32000, "message": "request denied" },
+            });
+            (200, body.to_string())
+        })
+        .await;
+        let err = RemoteSigner::new(Address::ZERO, url).sign_hash(B256::ZERO).await.unwrap_err();
+        assert!(
+            matches!(&err, SignerError::Permanent(reason) if reason.contains("denied")),
+            "{err}"
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_remote_timeout_skips_slot() {
+        // Accepts connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let address = Address::with_last_byte(1);
+        let manager = Arc::new(SignerManager::new());
+        manager
+            .add_remote(
+                RemoteSigner::new(address, url)
+                    .with_timeout(Duration::from_millis(100))
+                    .with_retries(0),
+            )
+            .await
+            .unwrap();
+
+        let err = manager.sign_hash(&address, B256::ZERO).await.unwrap_err();
+        assert!(matches!(err, SignerError::Timeout(a) if a == address), "{err}");
+
+        let header = Header { extra_data: Bytes::from(vec![0u8; 32 + 65]), ..Default::default() };
+        let sealer = BlockSealer::new(manager);
+        assert_eq!(sealer.try_seal_header(header, &address).await.unwrap(), None);
+        drop(listener);
+    }
+}
@@ -0,0 +1,262 @@
+//! Keystore hot reload
+//!
+//! [`KeystoreWatcher`] polls a keystore directory so keys can be rotated without restarting the
+//! node: keystores dropped into the directory are added to the [`SignerManager`], and signers
+//! whose file is deleted are removed once the block being sealed is done. Both show up as
+//! [`SignerEvent`](super::SignerEvent)s.
+
+use super::{keystore_paths, PasswordSource, SignerError, SignerManager, SignerSource};
+use alloy_primitives::Address;
+use reth_tracing::tracing::{info, warn};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::task::JoinHandle;
+
+/// How often a [`KeystoreWatcher`] looks at its directory by default
+pub const DEFAULT_KEYSTORE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Adds and removes the signers of a keystore directory as its `UTC--*` files come and go
+#[derive(Debug)]
+pub struct KeystoreWatcher {
+    /// Directory watched
+    dir: PathBuf,
+    /// Signers the keystores are added to
+    manager: Arc<SignerManager>,
+    /// Passwords of new keystores
+    passwords: PasswordSource,
+    /// Time between two polls
+    interval: Duration,
+    /// Keystore files seen, as they were then, with the address loaded from each; `None` if it
+    /// wasn't loaded, e.g. because it was still being written, and is retried once the file
+    /// changes
+    known: HashMap<PathBuf, (FileStamp, Option<Address>)>,
+}
+
+/// Size and modification time of a file, which change when it is written or replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    /// Stamp of the file at `path`, `None` if it is gone
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self { len: metadata.len(), modified: metadata.modified().ok() })
+    }
+}
+
+impl KeystoreWatcher {
+    /// Watch `dir`, decrypting new keystores with `passwords`
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        manager: Arc<SignerManager>,
+        passwords: PasswordSource,
+    ) -> Self {
+        Self {
+            dir: dir.into(),
+            manager,
+            passwords,
+            interval: DEFAULT_KEYSTORE_POLL_INTERVAL,
+            known: HashMap::new(),
+        }
+    }
+
+    /// Poll the directory every `interval`
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Look at the directory once, adding the signers of new keystores and removing those whose
+    /// keystore is gone
+    ///
+    /// A keystore written or replaced since the last poll is loaded again. Keystores already
+    /// registered from the same file, e.g. by
+    /// [`load_keystore_dir`](SignerManager::load_keystore_dir), are taken over. Signers added
+    /// some other way are never removed.
+    pub async fn poll(&mut self) -> Result<(), SignerError> {
+        let paths: Vec<_> = keystore_paths(&self.dir)?
+            .into_iter()
+            .filter_map(|path| Some((FileStamp::of(&path)?, path)))
+            .collect();
+
+        // Files deleted or changed since the last poll are forgotten, the changed ones reloaded
+        let stale: Vec<_> = self
+            .known
+            .iter()
+            .filter(|(path, (stamp, _))| {
+                !paths.iter().any(|(current, known)| known == *path && current == stamp)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut gone = Vec::new();
+        for path in stale {
+            if let Some((_, Some(address))) = self.known.remove(&path) {
+                gone.push((path, address));
+            }
+        }
+
+        for (stamp, path) in paths {
+            if self.known.contains_key(&path) {
+                continue
+            }
+            match self.manager.add_signer_from_keystore(&path, &self.passwords).await {
+                Ok(address) => {
+                    info!(target: "poa::signer", ?path, %address, "Added keystore");
+                    self.known.insert(path, (stamp, Some(address)));
+                }
+                Err(err) => {
+                    warn!(target: "poa::signer", ?path, %err, "Skipping keystore");
+                    self.known.insert(path, (stamp, None));
+                }
+            }
+        }
+
+        for (path, address) in gone {
+            // Still loaded from another file, or from the same one rewritten
+            if self.known.values().any(|(_, other)| *other == Some(address)) {
+                continue
+            }
+            let source = SignerSource::Keystore { path: path.clone() };
+            let registered =
+                self.manager.signers.read().await.get(&address).map(|entry| entry.source.clone());
+            if registered == Some(source) && self.manager.remove_signer(&address).await {
+                info!(target: "poa::signer", ?path, %address, "Removed keystore");
+            }
+        }
+        Ok(())
+    }
+
+    /// Poll in the background until the task is aborted
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                if let Err(err) = self.poll().await {
+                    let dir = self.dir.display();
+                    warn!(target: "poa::signer", %dir, %err, "Failed to poll keystores");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::{dev, BlockSealer, SignerEvent};
+    use alloy_consensus::Header;
+    use alloy_primitives::address;
+    use zeroize::Zeroizing;
+
+    /// The scrypt keystore fixture of the signer tests
+    const KEYSTORE: &str =
+        "testdata/keystore/UTC--2026-10-17T09-30-00.000000000Z--0a60a644910141059e9c79a716f9dad643cb092c";
+
+    /// Address of [`KEYSTORE`]
+    const KEYSTORE_ADDRESS: Address = address!("0a60a644910141059e9c79a716f9dad643cb092c");
+
+    /// Move a copy of the keystore fixture into `dir` at once, as a half written file would be
+    /// picked up too early
+    fn install_keystore(dir: &Path) -> PathBuf {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join(KEYSTORE);
+        let path = dir.join(fixture.file_name().unwrap());
+        let tmp = dir.join(".keystore.tmp");
+        fs::copy(&fixture, &tmp).unwrap();
+        fs::rename(&tmp, &path).unwrap();
+        path
+    }
+
+    /// Wait until `manager` holds a signer for `address`, or doesn't
+    async fn wait_for(manager: &SignerManager, address: Address, present: bool) {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while manager.has_signer(&address).await != present {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("signer {address} still present: {}", !present));
+    }
+
+    #[tokio::test]
+    async fn test_keystore_hot_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = Arc::new(SignerManager::new());
+        let hex_signer = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+        let mut events = manager.subscribe();
+
+        let passwords = PasswordSource::Static(Zeroizing::new("poa-keystore-password".into()));
+        let watcher = KeystoreWatcher::new(dir.path(), manager.clone(), passwords)
+            .with_interval(Duration::from_millis(50))
+            .spawn();
+
+        let path = install_keystore(dir.path());
+        wait_for(&manager, KEYSTORE_ADDRESS, true).await;
+        assert_eq!(events.recv().await.unwrap(), SignerEvent::Added(KEYSTORE_ADDRESS));
+
+        fs::remove_file(&path).unwrap();
+        wait_for(&manager, KEYSTORE_ADDRESS, false).await;
+        assert_eq!(events.recv().await.unwrap(), SignerEvent::Removed(KEYSTORE_ADDRESS));
+        assert_eq!(manager.signer_addresses().await, [hex_signer]);
+        watcher.abort();
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_keystore_is_retried_once_rewritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = Arc::new(SignerManager::new());
+        let passwords = PasswordSource::Static(Zeroizing::new("poa-keystore-password".into()));
+        let mut watcher = KeystoreWatcher::new(dir.path(), manager.clone(), passwords);
+
+        // Caught half written
+        let path = install_keystore(dir.path());
+        let keystore = fs::read(&path).unwrap();
+        fs::write(&path, &keystore[..keystore.len() / 2]).unwrap();
+        watcher.poll().await.unwrap();
+        assert!(!manager.has_signer(&KEYSTORE_ADDRESS).await);
+
+        // Not retried while unchanged, then loaded once complete
+        watcher.poll().await.unwrap();
+        assert!(!manager.has_signer(&KEYSTORE_ADDRESS).await);
+        fs::write(&path, &keystore).unwrap();
+        watcher.poll().await.unwrap();
+        assert!(manager.has_signer(&KEYSTORE_ADDRESS).await);
+
+        // Rewriting the same key keeps the signer
+        let mut events = manager.subscribe();
+        fs::write(&path, [keystore.as_slice(), b"\n".as_slice()].concat()).unwrap();
+        watcher.poll().await.unwrap();
+        assert!(manager.has_signer(&KEYSTORE_ADDRESS).await);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_removal_waits_for_sealing() {
+        let manager = Arc::new(SignerManager::new());
+        let signer = manager.add_signer_from_hex(dev::DEV_PRIVATE_KEYS[0]).await.unwrap();
+
+        // A block being sealed
+        let sealing = manager.sealing.read().await;
+        let remove = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.remove_signer(&signer).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!remove.is_finished());
+        assert!(manager.has_signer(&signer).await);
+
+        drop(sealing);
+        assert!(remove.await.unwrap());
+        let header = Header { extra_data: vec![0u8; 32 + 65].into(), ..Default::default() };
+        let err = BlockSealer::new(manager).seal_header(header, &signer).await.unwrap_err();
+        assert!(matches!(err, SignerError::NoSignerForAddress(_)), "{err}");
+    }
+}
@@ -0,0 +1,114 @@
+//! Memory use of large genesis files
+//!
+//! Measuring needs a counting `#[global_allocator]`, which applies to the whole test binary, so
+//! these tests get a binary of their own.
+
+#![allow(missing_docs)]
+
+use alloy_primitives::{hex, Address, U256};
+use example_custom_poa_node::genesis::{
+    create_genesis, write_genesis_file_streaming, GenesisConfig,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+};
+
+/// Tracks the heap use of the thread running [`peak_allocation`], so that tests running
+/// alongside don't count
+struct CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+thread_local! {
+    /// Bytes allocated since measuring started and the most allocated at once, if measuring
+    static ALLOCATED: Cell<Option<(isize, isize)>> = const { Cell::new(None) };
+}
+
+impl CountingAllocator {
+    fn track(delta: isize) {
+        let _ = ALLOCATED.try_with(|allocated| {
+            if let Some((current, peak)) = allocated.get() {
+                let current = current + delta;
+                allocated.set(Some((current, peak.max(current))));
+            }
+        });
+    }
+}
+
+// SAFETY: every call is forwarded to the system allocator
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::track(layout.size() as isize);
+        // SAFETY: the caller upholds the contract of `GlobalAlloc::alloc`
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Self::track(-(layout.size() as isize));
+        // SAFETY: `ptr` was allocated by `System` with `layout`
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::track(new_size as isize - layout.size() as isize);
+        // SAFETY: `ptr` was allocated by `System` with `layout`
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Result of `f` and the most memory it had allocated at once, in bytes
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    ALLOCATED.set(Some((0, 0)));
+    let result = f();
+    let (_, peak) = ALLOCATED.take().expect("measuring");
+    (result, peak as usize)
+}
+
+/// Accounts of the airdrop in [`test_million_account_genesis_in_bounded_memory`]
+const AIRDROP_ACCOUNTS: u64 = 1_000_000;
+
+#[test]
+#[ignore = "writes a million account genesis"]
+fn test_million_account_genesis_in_bounded_memory() {
+    let dir = tempfile::tempdir().unwrap();
+    let account = |i: u64| Address::from_word(U256::from(i).into());
+    let alloc_path = dir.path().join("airdrop.csv");
+    let mut file = io::BufWriter::new(fs::File::create(&alloc_path).unwrap());
+    for i in 1..=AIRDROP_ACCOUNTS {
+        writeln!(file, "0x{},{i}", hex::encode(account(i))).unwrap();
+    }
+    file.flush().unwrap();
+    drop(file);
+    let alloc_size = fs::metadata(&alloc_path).unwrap().len() as usize;
+
+    // Importing holds the accounts, but never the file
+    let (accounts, accounts_peak) = peak_allocation(|| {
+        let mut accounts = BTreeMap::new();
+        for i in 1..=AIRDROP_ACCOUNTS {
+            accounts.insert(account(i), U256::from(i));
+        }
+        accounts
+    });
+    let (config, import_peak) =
+        peak_allocation(|| GenesisConfig::default().with_alloc_file(&alloc_path).unwrap());
+    assert_eq!(config.prefunded_accounts, accounts);
+    assert!(
+        import_peak < accounts_peak + alloc_size / 4,
+        "{import_peak} bytes to import {accounts_peak} bytes of accounts"
+    );
+    drop(accounts);
+
+    // Writing holds nothing but a buffer
+    let genesis = create_genesis(config);
+    let path = dir.path().join("genesis.json");
+    let ((), write_peak) =
+        peak_allocation(|| write_genesis_file_streaming(&genesis, &path, false).unwrap());
+    let size = fs::metadata(&path).unwrap().len() as usize;
+    assert!(size > AIRDROP_ACCOUNTS as usize * 50, "{size}");
+    assert!(write_peak < 1 << 20, "{write_peak} bytes to write {size}");
+}